@@ -0,0 +1,71 @@
+//! SDP relaxation of max-cut on the Petersen graph
+//!
+//! The relaxation
+//! \\[
+//! \\begin{array}{ll}
+//! {\\rm maximize} & \\sum_{(i, j) \\in E} {1 - X_{ij} \\over 2} \\\\
+//! {\\rm subject \\ to} & X \\succeq 0, \\ X_{ii} = 1
+//! \\end{array}
+//! \\]
+//! bounds the maximum cut from above.
+//! \\( X = I + \\sum_{i < j} x_{ij} (E_{ij} + E_{ji}) \\) is parametrized by its off-diagonal elements,
+//! so that the constraint is \\( -X \\preceq 0 \\) and \\( x = 0 \\) is strictly feasible.
+
+use totsu::prelude::*;
+use totsu::verify::verify_sdp;
+use totsu::examples_util::{petersen_graph, graph_laplacian};
+
+// n / 4 times the largest Laplacian eigenvalue 5, which is tight for the edge-transitive graph
+const REF_OBJECTIVE: FP = 12.5;
+
+pub fn main()
+{
+    let n = 10;
+    let edges = petersen_graph();
+    let lap = graph_laplacian(n, &edges);
+
+    let pairs: Vec<(usize, usize)> = (0 .. n).flat_map(|i| (i + 1 .. n).map(move |j| (i, j))).collect();
+    let nx = pairs.len();
+
+    // constant part of the cut, |E| / 2, is left out of the objective
+    let vec_c = Mat::new_vec(nx).set_by(|k, _| if lap[pairs[k]] != 0. {-0.5} else {0.});
+    let mut mat_f: Vec<Mat> = pairs.iter().map(|&(i, j)| {
+        Mat::new(n, n).set_by(|r, c| if ((r, c) == (i, j)) || ((r, c) == (j, i)) {-1.} else {0.})
+    }).collect();
+    mat_f.push(-Mat::new(n, n).set_eye());
+    let mat_a = Mat::new(0, nx);
+    let vec_b = Mat::new_vec(0);
+
+    let prob = SDPProblem::new(vec_c.clone(), mat_f.clone(), mat_a.clone(), vec_b.clone())
+                          .with_sense(Sense::Maximize);
+    let param = PDIPMParam {
+        eps: 1e-4, // solve_sdp() is not so accurate
+        .. Default::default()
+    };
+    let rslt = prob.solve(&param).unwrap();
+    let relaxed = edges.len() as FP / 2. + rslt.primal_objective;
+    println!("relaxed cut = {:.9}, iterations = {}", relaxed, rslt.iterations);
+
+    let tol = 1e-3;
+    let report = verify_sdp(&rslt.x, &vec_c, &mat_f, &mat_a, &vec_b, tol);
+    assert!(report.is_feasible(), "{:?}", report);
+    assert!((relaxed - REF_OBJECTIVE).abs() < tol);
+
+    // complementary slackness with the dual matrix
+    let mut mat_x = Mat::new(n, n).set_eye();
+    for (k, &(i, j)) in pairs.iter().enumerate() {
+        mat_x[(i, j)] = rslt.x[(k, 0)];
+        mat_x[(j, i)] = rslt.x[(k, 0)];
+    }
+    let compl = (&rslt.lmd * &mat_x).tr();
+    println!("tr(Z X) = {:.3e}", compl);
+    assert!(compl.abs() < tol);
+
+    // exhaustive search of the maximum cut, which the relaxation bounds
+    let best = (0_u32 .. 1 << n).map(|set| {
+        edges.iter().filter(|&&(i, j)| ((set >> i) ^ (set >> j)) & 1 == 1).count()
+    }).max().unwrap();
+    println!("maximum cut = {}", best);
+    assert_eq!(best, 12);
+    assert!(best as FP <= relaxed + tol);
+}
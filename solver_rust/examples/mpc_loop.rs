@@ -0,0 +1,54 @@
+//! Receding-horizon control of a double integrator
+//!
+//! At each sampling time an `MPCProblem` is solved from the current state
+//! with a bounded acceleration, and only the first input is applied.
+
+use totsu::prelude::*;
+use totsu::mpc::{MPCProblem, MPCMode};
+use totsu::examples_util::double_integrator;
+
+const REF_COST: FP = 4.253_946_548;
+
+pub fn main()
+{
+    let dt: FP = 0.1;
+    let (mat_a, mat_b) = double_integrator(dt);
+    let mat_q = Mat::new(2, 2).set_iter(&[
+        1., 0.,
+        0., 0.1
+    ]);
+    let mat_r = Mat::new(1, 1).set_all(0.01);
+    let u_max: FP = 1.;
+    let horizon = 20;
+    let steps = 40;
+
+    let param = PDIPMParam::default();
+    let mut x = Mat::new_vec(2).set_iter(&[1., 0.]);
+    let mut cost: FP = 0.;
+
+    for k in 0 .. steps {
+        let prob = MPCProblem::new_lti(x.clone(), &mat_a, &mat_b, &mat_q, &mat_r, horizon)
+                              .with_input_bounds(Mat::new_vec(1).set_all(-u_max), Mat::new_vec(1).set_all(u_max))
+                              .with_state_bounds(Mat::new_vec(2).set_iter(&[FP::NEG_INFINITY, -0.6]),
+                                                 Mat::new_vec(2).set_iter(&[FP::INFINITY, 0.6]));
+        let sol = prob.solve(&param, MPCMode::Structured).unwrap();
+        assert_eq!(sol.status, SolverStatus::Converged);
+
+        // the condensed path agrees on the applied input
+        if k == 0 {
+            let sol_c = prob.solve(&param, MPCMode::Condensed).unwrap();
+            assert!((&sol.u[0] - &sol_c.u[0]).norm_p2() < 1e-6);
+            assert!((sol.objective - sol_c.objective).abs() < 1e-6);
+        }
+
+        let u = &sol.u[0];
+        assert!(u[(0, 0)].abs() <= u_max);
+        x = &mat_a * &x + &mat_b * u;
+        cost += x.prod(&(&mat_q * &x)) / 2. + u.prod(&(&mat_r * u)) / 2.;
+        assert!(x[(1, 0)].abs() <= 0.6 + 1e-6);
+    }
+
+    println!("closed-loop cost = {:.9}, final state = {}", cost, x);
+    assert!(x.norm_p2() < 1e-2);
+    assert!((cost - REF_COST).abs() < 1e-6);
+}
@@ -0,0 +1,56 @@
+//! Markowitz portfolio optimization
+//! \\[
+//! \\begin{array}{ll}
+//! {\\rm minimize} & {\\gamma \\over 2} w^T \\Sigma w - \\mu^T w \\\\
+//! {\\rm subject \\ to} & w \\succeq 0 \\\\
+//! & 1^T w = 1,
+//! \\end{array}
+//! \\]
+//! trading off the risk \\( w^T \\Sigma w \\) and the expected return \\( \\mu^T w \\)
+//! of long-only weights \\( w \\).
+
+use totsu::prelude::*;
+use totsu::verify::verify_qp_kkt;
+use totsu::examples_util::portfolio_data;
+
+const REF_OBJECTIVE: FP = -0.049_884_798;
+
+pub fn main()
+{
+    let (mean, cov) = portfolio_data();
+    let (n, _) = mean.size();
+    let gamma: FP = 10.;
+
+    let mat_p = &cov * gamma;
+    let vec_q = -&mean;
+    let mat_g = -Mat::new(n, n).set_eye();
+    let vec_h = Mat::new_vec(n);
+    let mat_a = Mat::new(1, n).set_all(1.);
+    let vec_b = Mat::new_vec(1).set_all(1.);
+
+    let prob = QPProblem::new(mat_p.clone(), vec_q.clone(), mat_g.clone(), vec_h.clone(), mat_a.clone(), vec_b.clone());
+    let param = PDIPMParam::default();
+    let rslt = prob.solve(&param).unwrap();
+    println!("objective = {:.9}, iterations = {}", rslt.primal_objective, rslt.iterations);
+    println!("weights = {}", rslt.x);
+
+    let report = verify_qp_kkt(&rslt, &mat_p, &vec_q, &mat_g, &vec_h, &mat_a, &vec_b, 1e-6);
+    assert!(report.is_optimal(), "{:?}", report);
+    assert!((rslt.primal_objective - REF_OBJECTIVE).abs() < 1e-6);
+
+    // risk and return from the objective breakdown
+    let parts = rslt.objective_breakdown().unwrap();
+    let risk = parts.quadratic * 2. / gamma;
+    let ret = -parts.linear;
+    println!("risk = {:.6}, return = {:.6}", risk, ret);
+    assert!((risk - rslt.x.prod(&(&cov * &rslt.x))).abs() < 1e-9);
+    assert!((ret - mean.prod(&rslt.x)).abs() < 1e-9);
+
+    // marginal utility of the budget: raising it by db changes the objective by -nu db
+    let db: FP = 1e-3;
+    let prob_db = QPProblem::new(mat_p, vec_q, mat_g, vec_h, mat_a, Mat::new_vec(1).set_all(1. + db));
+    let rslt_db = prob_db.solve(&param).unwrap();
+    let predicted = -rslt.nu[(0, 0)] * db;
+    println!("objective change = {:.3e}, predicted = {:.3e}", rslt_db.primal_objective - rslt.primal_objective, predicted);
+    assert!((rslt_db.primal_objective - rslt.primal_objective - predicted).abs() < 1e-5);
+}
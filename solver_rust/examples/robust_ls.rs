@@ -0,0 +1,67 @@
+//! Robust least squares by SOCP
+//!
+//! The worst case of \\( \\| (A + \\Delta) x - b \\|_2 \\) over \\( \\| \\Delta \\|_2 \\le \\rho \\)
+//! is \\( \\| A x - b \\|_2 + \\rho \\| x \\|_2 \\), which is minimized as
+//! \\[
+//! \\begin{array}{ll}
+//! {\\rm minimize} & t_0 + \\rho t_1 \\\\
+//! {\\rm subject \\ to} & \\| A x - b \\|_2 \\le t_0 \\\\
+//! & \\| x \\|_2 \\le t_1.
+//! \\end{array}
+//! \\]
+
+use totsu::prelude::*;
+use totsu::verify::verify_socp;
+use totsu::mat::{xor64, XOR64_INIT};
+
+const REF_OBJECTIVE: FP = 1.592_313_208;
+
+pub fn main()
+{
+    let (m, n) = (8, 3);
+    let rho: FP = 0.5;
+
+    let mut r = XOR64_INIT;
+    let mat_a = Mat::new(m, n).set_by(|_, _| xor64(&mut r) * 2. - 1.);
+    let vec_b = Mat::new_vec(m).set_by(|_, _| xor64(&mut r) * 2. - 1.);
+
+    // variables (x, t0, t1)
+    let vec_f = Mat::new_vec(n + 2).set_by(|i, _| if i == n {1.} else if i == n + 1 {rho} else {0.});
+    let mat_g = vec![
+        Mat::new(m, n + 2).set_by(|i, j| if j < n {mat_a[(i, j)]} else {0.}),
+        Mat::new(n, n + 2).set_by(|i, j| if i == j {1.} else {0.})
+    ];
+    let vec_h = vec![-&vec_b, Mat::new_vec(n)];
+    let vec_c = vec![
+        Mat::new_vec(n + 2).set_by(|i, _| if i == n {1.} else {0.}),
+        Mat::new_vec(n + 2).set_by(|i, _| if i == n + 1 {1.} else {0.})
+    ];
+    let scl_d = vec![0.; 2];
+    let mat_a_eq = Mat::new(0, n + 2);
+    let vec_b_eq = Mat::new_vec(0);
+
+    let prob = SOCPProblem::new(vec_f.clone(), mat_g.clone(), vec_h.clone(), vec_c.clone(), scl_d.clone(), mat_a_eq.clone(), vec_b_eq.clone());
+    let param = PDIPMParam::default();
+    let rslt = prob.solve(&param).unwrap();
+    println!("objective = {:.9}, iterations = {}", rslt.primal_objective, rslt.iterations);
+
+    let report = verify_socp(&rslt.x, &vec_f, &mat_g, &vec_h, &vec_c, &scl_d, &mat_a_eq, &vec_b_eq, 1e-6);
+    assert!(report.is_feasible(), "{:?}", report);
+    assert!((rslt.primal_objective - REF_OBJECTIVE).abs() < 1e-6);
+
+    // the epigraph variables are tight and the worst-case residual is stationary in x
+    let x = rslt.x.rows(0 .. n).clone_sz();
+    let resid = &mat_a * &x - &vec_b;
+    let worst = resid.norm_p2() + rho * x.norm_p2();
+    assert!((worst - rslt.primal_objective).abs() < 1e-6);
+    let grad = mat_a.t() * &resid / resid.norm_p2() + &x * (rho / x.norm_p2());
+    println!("x = {}, gradient norm = {:.3e}", x, grad.norm_p2());
+    assert!(grad.norm_p2() < 1e-5);
+
+    // robustness costs accuracy for the nominal matrix
+    let mut svd = totsu::matsvd::MatSVD::new((m, n));
+    svd.decomp(&mat_a);
+    let x_ls = svd.solve(&vec_b);
+    assert!((&mat_a * &x_ls - &vec_b).norm_p2() <= resid.norm_p2());
+    assert!(x.norm_p2() < x_ls.norm_p2());
+}
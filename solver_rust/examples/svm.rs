@@ -0,0 +1,62 @@
+//! Soft-margin linear SVM by its dual QP
+//! \\[
+//! \\begin{array}{ll}
+//! {\\rm minimize} & {1 \\over 2} \\alpha^T Q \\alpha - 1^T \\alpha \\\\
+//! {\\rm subject \\ to} & 0 \\preceq \\alpha \\preceq C \\\\
+//! & y^T \\alpha = 0,
+//! \\end{array}
+//! \\]
+//! where \\( Q_{ij} = y_i y_j \\xi_i^T \\xi_j \\).
+//! The bias of the separating hyperplane is the dual variable of the equality.
+
+use totsu::prelude::*;
+use totsu::verify::verify_qp_kkt;
+use totsu::examples_util::{two_class_dataset, svm_dual_hessian};
+
+const REF_OBJECTIVE: FP = -2.064_623;
+
+pub fn main()
+{
+    let (features, labels) = two_class_dataset(10, 1.2, 1);
+    let (n, _) = labels.size();
+    let c: FP = 1.;
+
+    let mat_p = svm_dual_hessian(&features, &labels);
+    let vec_q = Mat::new_vec(n).set_all(-1.);
+    let mat_g = Mat::new(n * 2, n).set_by(|r, c| {
+        if r == c {-1.} else if r == c + n {1.} else {0.}
+    });
+    let vec_h = Mat::new_vec(n * 2).set_by(|r, _| if r < n {0.} else {c});
+    let mat_a = labels.t().clone_sz();
+    let vec_b = Mat::new_vec(1);
+
+    let prob = QPProblem::new(mat_p.clone(), vec_q.clone(), mat_g.clone(), vec_h.clone(), mat_a.clone(), vec_b.clone());
+    let param = PDIPMParam::default();
+    let rslt = prob.solve(&param).unwrap();
+    println!("objective = {:.9}, iterations = {}", rslt.primal_objective, rslt.iterations);
+
+    let report = verify_qp_kkt(&rslt, &mat_p, &vec_q, &mat_g, &vec_h, &mat_a, &vec_b, 1e-6);
+    assert!(report.is_optimal(), "{:?}", report);
+    assert!(rslt.duality_gap.abs() < 1e-6);
+    assert!((rslt.primal_objective - REF_OBJECTIVE).abs() < 1e-6);
+
+    // w = sum_i alpha_i y_i xi_i, b = nu
+    let alpha = &rslt.x;
+    let w = features.t() * Mat::new_vec(n).set_by(|i, _| alpha[(i, 0)] * labels[(i, 0)]);
+    let b = rslt.nu[(0, 0)];
+    println!("w = {}, b = {:.6}", w, b);
+
+    // free support vectors lie on the margin
+    let mut errors = 0;
+    for i in 0 .. n {
+        let margin = labels[(i, 0)] * ((features.row(i) * &w)[(0, 0)] + b);
+        if (alpha[(i, 0)] > 1e-4) && (alpha[(i, 0)] < c - 1e-4) {
+            assert!((margin - 1.).abs() < 1e-5);
+        }
+        if margin < 0. {
+            errors += 1;
+        }
+    }
+    println!("training errors = {}", errors);
+    assert!(errors <= 2);
+}
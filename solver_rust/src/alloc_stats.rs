@@ -0,0 +1,137 @@
+/*!
+Matrix allocation statistics
+
+Enabled by the `alloc-count` feature.
+Every allocation of a [`Mat`](../mat/type.Mat.html) array is counted per thread,
+so that tests can assert the number of allocations made by a piece of code.
+
+```
+use totsu::prelude::*;
+use totsu::alloc_stats;
+
+alloc_stats::reset();
+let a = Mat::new(2, 2).set_eye();
+let _b = &a * &a;
+assert_eq!(alloc_stats::snapshot().count, 2);
+```
+*/
+
+use std::cell::Cell;
+
+/// Snapshot of allocation statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats
+{
+    /// Number of allocations.
+    pub count: usize,
+    /// Total number of allocated elements.
+    pub elems: usize
+}
+
+thread_local! {
+    static STATS: Cell<AllocStats> = Cell::new(AllocStats::default());
+}
+
+/// Resets the statistics of the current thread.
+pub fn reset()
+{
+    STATS.with(|s| s.set(AllocStats::default()));
+}
+
+/// Returns the statistics of the current thread since the last [`reset`](fn.reset.html).
+pub fn snapshot() -> AllocStats
+{
+    STATS.with(|s| s.get())
+}
+
+// used by mat module
+pub(crate) fn record(elems: usize)
+{
+    STATS.with(|s| {
+        let mut stats = s.get();
+        stats.count += 1;
+        stats.elems += elems;
+        s.set(stats);
+    });
+}
+
+#[test]
+fn test_alloc_pdipm()
+{
+    use super::prelude::*;
+
+    let n: usize = 2;
+    let m: usize = 1;
+    let p: usize = 0;
+
+    let mat_p = Mat::new(n, n).set_eye();
+    let vec_q = Mat::new_vec(n).set_iter(&[
+        1., 2.
+    ]);
+    let mat_g = Mat::new(m, n).set_iter(&[
+        -1. / 2., -1. / 3.
+    ]);
+    let vec_h = Mat::new_vec(m).set_iter(&[
+        -1.
+    ]);
+    let mat_a = Mat::new(p, n);
+    let vec_b = Mat::new_vec(p);
+
+    let solve = |solver: &mut PDIPM, max_iter: Option<usize>| {
+        let param = PDIPMParam {
+            max_iter,
+            .. PDIPMParam::default()
+        };
+        reset();
+        // the iteration limit is an error, whose allocations are the same at any limit
+        let _ = solver.solve_qp(&param, &mut std::io::sink(),
+                                &mat_p, &vec_q, &mat_g, &vec_h, &mat_a, &vec_b);
+        (snapshot().count, solver.iterations())
+    };
+
+    let mut solver = PDIPM::new();
+    let (cnt_first, iter_first) = solve(&mut solver, None);
+    let (cnt_second, iter_second) = solve(&mut solver, None);
+    println!("first: {} allocs / {} iterations", cnt_first, iter_first);
+    println!("second: {} allocs / {} iterations", cnt_second, iter_second);
+
+    // the second solve re-uses workspace matrices of the solver
+    assert_eq!(iter_first, iter_second);
+    assert!(cnt_second < cnt_first);
+
+    // allocations per iteration after warm-up, which differ by line search and correctors,
+    // are only temporaries of a bounded number
+    const MAX_ALLOCS_PER_ITER: usize = 128;
+    let (k_lo, k_hi) = (2, iter_second - 1);
+    let (cnt_lo, _) = solve(&mut solver, Some(k_lo));
+    let (cnt_hi, _) = solve(&mut solver, Some(k_hi));
+    let per_iter = (cnt_hi - cnt_lo) / (k_hi - k_lo);
+    println!("{} allocs / iteration", per_iter);
+    assert!(per_iter <= MAX_ALLOCS_PER_ITER);
+}
+
+#[test]
+fn test_alloc_solve_qp()
+{
+    use super::prelude::*;
+
+    let n: usize = 20;
+
+    let prob = QPProblem::new(Mat::new(n, n).set_eye(), Mat::new_vec(n).set_all(1.),
+                              -Mat::new(n, n).set_eye(), Mat::new_vec(n),
+                              Mat::new(1, n).set_all(1.), Mat::new_vec(1).set_all(1.));
+    let param = PDIPMParam::default();
+
+    reset();
+    prob.solve_with(&mut PDIPM::new(), &param, &mut std::io::sink()).unwrap();
+    let elems_prob = snapshot().elems;
+
+    // the convenience function borrows the inputs without copying them into a problem
+    reset();
+    PDIPM::new().solve_qp(&param, &mut std::io::sink(),
+                          &prob.mat_p, &prob.vec_q, &prob.mat_g, &prob.vec_h, &prob.mat_a, &prob.vec_b).unwrap();
+    let elems_qp = snapshot().elems;
+    println!("problem: {} elems, solve_qp: {} elems", elems_prob, elems_qp);
+
+    assert!(elems_qp <= elems_prob);
+}
@@ -0,0 +1,155 @@
+/*!
+Batch solving
+
+[`solve_qp_batch`](fn.solve_qp_batch.html) solves many independent problems,
+reusing a solver workspace across problems of identical dimensions.
+With the `parallel` feature, problems are distributed across a rayon thread pool,
+each worker having its own workspace.
+*/
+
+use super::prelude::*;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Solves QP problems in a batch.
+///
+/// Returns results in the same order as `problems`.
+/// A failure of one problem does not abort the others.
+/// Each result is identical to that of an individual [`Problem::solve`](../problem/trait.Problem.html#tymethod.solve).
+/// * `problems` are the problems to be solved.
+/// * `param` is solver parameters.
+pub fn solve_qp_batch(problems: &[QPProblem], param: &PDIPMParam) -> Vec<Result<SolverResult, SolverError>>
+{
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut solver = PDIPM::new();
+
+        problems.iter()
+                .map(|prob| prob.solve_with(&mut solver, param, &mut std::io::sink()))
+                .collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        problems.par_iter()
+                .map_init(PDIPM::new, |solver, prob| prob.solve_with(solver, param, &mut std::io::sink()))
+                .collect()
+    }
+}
+
+#[cfg(test)]
+fn gen_qps(num: usize, n: usize, m: usize) -> Vec<QPProblem>
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let mut r = XOR64_INIT;
+
+    (0 .. num).map(|_| {
+        let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r));
+        QPProblem::new(mat_b.t() * &mat_b + Mat::new(n, n).set_eye(),
+                       Mat::new_vec(n).set_by(|_, _| xor64(&mut r)),
+                       Mat::new(m, n).set_by(|_, _| xor64(&mut r)),
+                       Mat::new_vec(m).set_all(1.),
+                       Mat::new(0, n), Mat::new_vec(0))
+    }).collect()
+}
+
+#[test]
+fn test_solve_qp_batch()
+{
+    let mut probs = gen_qps(8, 3, 2);
+    // an inconsistent problem in the middle
+    probs[3].vec_q = Mat::new_vec(4);
+
+    let param = PDIPMParam::default();
+    let rslts = solve_qp_batch(&probs, &param);
+    assert_eq!(rslts.len(), probs.len());
+
+    for (prob, rslt) in probs.iter().zip(rslts.iter()) {
+        match (prob.solve(&param), rslt) {
+            (Ok(exp), Ok(rslt)) => assert_eq!(exp.x, rslt.x),
+            (Err(exp), Err(rslt)) => assert_eq!(&exp, rslt),
+            (exp, rslt) => panic!("{:?} {:?}", exp, rslt)
+        }
+    }
+    assert!(rslts[3].is_err());
+}
+
+#[test]
+fn test_reused_solver()
+{
+    let n: usize = 2;
+
+    // identical dimensions, so that the workspace is not reallocated between solves
+    let qp = |q0: FP, b0: FP| QPProblem::new(Mat::new(n, n).set_eye(), Mat::new_vec(n).set_iter(&[q0, 2.]),
+                                            Mat::new(1, n).set_iter(&[-1., 0.]), Mat::new_vec(1).set_iter(&[1.]),
+                                            Mat::new(1, n).set_iter(&[1., 1.]), Mat::new_vec(1).set_iter(&[b0]));
+    let qp_a = qp(-5., 3.);
+    let qp_b = qp(1., -1.);
+
+    let param = PDIPMParam::default();
+    let qp_b_x = qp_b.solve(&param).unwrap().x;
+
+    let mut solver = PDIPM::new();
+    qp_a.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+    let x = qp_b.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap().x;
+    assert_eq!(x, qp_b_x);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_solve_qp_batch_threads()
+{
+    let probs = gen_qps(16, 3, 2);
+    let param = PDIPMParam::default();
+
+    let solve_in = |num_threads: usize| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap();
+        pool.install(|| solve_qp_batch(&probs, &param))
+    };
+
+    let rslts1 = solve_in(1);
+    let rslts4 = solve_in(4);
+
+    for (r1, r4) in rslts1.iter().zip(rslts4.iter()) {
+        assert_eq!(r1.as_ref().unwrap().x.to_bytes(), r4.as_ref().unwrap().x.to_bytes());
+    }
+}
+
+// cargo test --release --features parallel -- --ignored --nocapture bench_solve_qp_batch
+#[test]
+#[ignore]
+fn bench_solve_qp_batch()
+{
+    use std::time::Instant;
+
+    let probs = gen_qps(1000, 8, 8);
+    let param = PDIPMParam::default();
+
+    let start = Instant::now();
+    let mut solver = PDIPM::new();
+    for prob in &probs {
+        prob.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+    }
+    let t_serial = start.elapsed();
+
+    let start = Instant::now();
+    let rslts = solve_qp_batch(&probs, &param);
+    let t_batch = start.elapsed();
+
+    assert!(rslts.iter().all(|r| r.is_ok()));
+    let speedup = t_serial.as_secs_f64() / t_batch.as_secs_f64();
+    println!("serial: {:?}, batch: {:?}, speedup: {:.2}", t_serial, t_batch, speedup);
+
+    // distributing problems must pay off where there are threads to distribute them to
+    #[cfg(feature = "parallel")]
+    {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let threads = rayon::current_num_threads().min(cores);
+        println!("threads: {}", threads);
+        if threads >= 2 {
+            assert!(speedup > 1.2, "speedup {:.2} with {} threads", speedup, threads);
+        }
+    }
+}
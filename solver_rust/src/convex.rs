@@ -0,0 +1,583 @@
+/*!
+Smooth convex terms
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+Building blocks of a smooth convex objective, each of which provides its value, gradient and Hessian
+as [`SmoothTerm`](trait.SmoothTerm.html):
+
+| term | value |
+|------|-------|
+| [`Quadratic`](struct.Quadratic.html) | \\( {1 \\over 2} x^T P x + q^T x \\) |
+| [`NegEntropy`](struct.NegEntropy.html) | \\( \\sum_j x_j \\log x_j \\) |
+| [`LogBarrier`](struct.LogBarrier.html) | \\( -\\sum_j \\log x_j \\) |
+| [`LogSumExp`](struct.LogSumExp.html) | \\( \\log \\sum_i \\exp (F x + g)_i \\) |
+| [`LogisticLoss`](struct.LogisticLoss.html) | \\( \\sum_i \\log (1 + \\exp (-y_i a_i^T x)) \\) |
+
+A sum of terms is minimized under linear constraints by [`SmoothConvex`](trait.SmoothConvex.html).
+
+```
+use totsu::prelude::*;
+use totsu::convex::*;
+
+// maximum entropy distribution of 3 outcomes with mean 1.5 over values 1, 2, 3
+let terms: Vec<Box<dyn SmoothTerm>> = vec![Box::new(NegEntropy)];
+let cons = LinearConstraints::new(
+    Mat::new(0, 3), Mat::new_vec(0),
+    Mat::new(2, 3).set_iter(&[
+        1., 1., 1.,
+        1., 2., 3.
+    ]),
+    Mat::new_vec(2).set_iter(&[
+        1., 1.5
+    ])
+);
+
+let param = PDIPMParam::default();
+let rslt = PDIPM::new().solve_smooth_convex(&param, &mut std::io::sink(),
+                                            &terms, &cons).unwrap();
+
+// geometric progression
+let r = rslt[(1, 0)] / rslt[(0, 0)];
+assert!((rslt[(2, 0)] / rslt[(1, 0)] - r).abs() < 1e-6);
+```
+*/
+
+use super::prelude::*;
+use super::mat::MatSliMu;
+use super::problem::{check_dim, check_col_vec};
+
+use std::io::Write;
+
+/// Smooth convex term of an objective function
+pub trait SmoothTerm
+{
+    /// Number of variables that the term requires, or `None` for any.
+    fn dim(&self) -> Option<usize>
+    {
+        None
+    }
+    /// Whether the domain of the term is \\( x > 0 \\).
+    fn positive_domain(&self) -> bool
+    {
+        false
+    }
+    /// Returns the value at `x`.
+    fn value(&self, x: &MatSlice) -> FP;
+    /// Adds the gradient at `x` to `df`.
+    fn add_gradient(&self, x: &MatSlice, df: &mut MatSliMu);
+    /// Adds the Hessian at `x` to `ddf`.
+    fn add_hessian(&self, x: &MatSlice, ddf: &mut MatSliMu);
+}
+
+/// Quadratic \\( {1 \\over 2} x^T P x + q^T x \\) with \\( P \\succeq 0 \\)
+#[derive(Debug, Clone)]
+pub struct Quadratic
+{
+    /// \\(P\\)
+    pub mat_p: Mat,
+    /// \\(q\\)
+    pub vec_q: Mat
+}
+
+impl SmoothTerm for Quadratic
+{
+    fn dim(&self) -> Option<usize>
+    {
+        Some(self.vec_q.size().0)
+    }
+
+    fn value(&self, x: &MatSlice) -> FP
+    {
+        x.prod(&(&self.mat_p * x)) / 2. + self.vec_q.prod(x)
+    }
+
+    fn add_gradient(&self, x: &MatSlice, df: &mut MatSliMu)
+    {
+        *df += &self.mat_p * x + &self.vec_q;
+    }
+
+    fn add_hessian(&self, _x: &MatSlice, ddf: &mut MatSliMu)
+    {
+        *ddf += &self.mat_p;
+    }
+}
+
+/// Negative entropy \\( \\sum_j x_j \\log x_j \\)
+#[derive(Debug, Clone, Copy)]
+pub struct NegEntropy;
+
+impl SmoothTerm for NegEntropy
+{
+    fn positive_domain(&self) -> bool
+    {
+        true
+    }
+
+    fn value(&self, x: &MatSlice) -> FP
+    {
+        let (n, _) = x.size();
+
+        (0 .. n).map(|j| x[(j, 0)] * x[(j, 0)].ln()).sum()
+    }
+
+    fn add_gradient(&self, x: &MatSlice, df: &mut MatSliMu)
+    {
+        let (n, _) = x.size();
+
+        for j in 0 .. n {
+            df[(j, 0)] += x[(j, 0)].ln() + 1.;
+        }
+    }
+
+    fn add_hessian(&self, x: &MatSlice, ddf: &mut MatSliMu)
+    {
+        let (n, _) = x.size();
+
+        for j in 0 .. n {
+            ddf[(j, j)] += 1. / x[(j, 0)];
+        }
+    }
+}
+
+/// Logarithmic barrier \\( -\\sum_j \\log x_j \\)
+#[derive(Debug, Clone, Copy)]
+pub struct LogBarrier;
+
+impl SmoothTerm for LogBarrier
+{
+    fn positive_domain(&self) -> bool
+    {
+        true
+    }
+
+    fn value(&self, x: &MatSlice) -> FP
+    {
+        let (n, _) = x.size();
+
+        -(0 .. n).map(|j| x[(j, 0)].ln()).sum::<FP>()
+    }
+
+    fn add_gradient(&self, x: &MatSlice, df: &mut MatSliMu)
+    {
+        let (n, _) = x.size();
+
+        for j in 0 .. n {
+            df[(j, 0)] -= 1. / x[(j, 0)];
+        }
+    }
+
+    fn add_hessian(&self, x: &MatSlice, ddf: &mut MatSliMu)
+    {
+        let (n, _) = x.size();
+
+        for j in 0 .. n {
+            ddf[(j, j)] += 1. / (x[(j, 0)] * x[(j, 0)]);
+        }
+    }
+}
+
+/// Log-sum-exp \\( \\log \\sum_i \\exp (F x + g)_i \\)
+#[derive(Debug, Clone)]
+pub struct LogSumExp
+{
+    /// \\(F\\)
+    pub mat_f: Mat,
+    /// \\(g\\)
+    pub vec_g: Mat
+}
+
+impl SmoothTerm for LogSumExp
+{
+    fn dim(&self) -> Option<usize>
+    {
+        Some(self.mat_f.size().1)
+    }
+
+    fn value(&self, x: &MatSlice) -> FP
+    {
+        lse(&self.mat_f, &self.vec_g, x).0
+    }
+
+    fn add_gradient(&self, x: &MatSlice, df: &mut MatSliMu)
+    {
+        let (_, w) = lse(&self.mat_f, &self.vec_g, x);
+
+        *df += self.mat_f.t() * w;
+    }
+
+    fn add_hessian(&self, x: &MatSlice, ddf: &mut MatSliMu)
+    {
+        let (_, w) = lse(&self.mat_f, &self.vec_g, x);
+
+        *ddf += lse_hess(&self.mat_f, &w);
+    }
+}
+
+// used by gp module:
+// log-sum-exp of (F y + g) and its softmax weights, shifted by the max for stability
+pub(crate) fn lse(mat_f: &Mat, vec_g: &Mat, y: &MatSlice) -> (FP, Mat)
+{
+    let mut z = mat_f * y + vec_g;
+    let z_max = z.max().unwrap_or(0.);
+
+    let (k, _) = z.size();
+    let mut sum = 0.;
+    for r in 0 .. k {
+        z[(r, 0)] = (z[(r, 0)] - z_max).exp();
+        sum += z[(r, 0)];
+    }
+
+    (z_max + sum.ln(), z / sum)
+}
+
+// used by gp module:
+// Hessian of log-sum-exp: F^T (diag(w) - w w^T) F
+pub(crate) fn lse_hess(mat_f: &Mat, w: &Mat) -> Mat
+{
+    mat_f.t() * (w.clone_diag() - w * w.t()) * mat_f
+}
+
+/// Logistic loss \\( \\sum_i \\log (1 + \\exp (-y_i a_i^T x)) \\)
+///
+/// \\( a_i^T \\) is the \\(i\\)-th row of \\(A\\) and \\( y_i \\in \\lbrace -1, 1 \\rbrace \\) is its label.
+#[derive(Debug, Clone)]
+pub struct LogisticLoss
+{
+    /// \\(A\\), samples in rows
+    pub mat_a: Mat,
+    /// \\(y\\), labels
+    pub vec_y: Mat
+}
+
+impl LogisticLoss
+{
+    // margins y_i a_i^T x
+    fn h_margin(&self, x: &MatSlice) -> Mat
+    {
+        let z = &self.mat_a * x;
+
+        z.clone_sz().set_by(|r, _| self.vec_y[(r, 0)] * z[(r, 0)])
+    }
+}
+
+// log(1 + exp(-z)) without overflow
+fn h_softplus_neg(z: FP) -> FP
+{
+    if z > 0. {
+        (-z).exp().ln_1p()
+    }
+    else {
+        -z + z.exp().ln_1p()
+    }
+}
+
+// 1 / (1 + exp(-z))
+fn h_sigmoid(z: FP) -> FP
+{
+    if z > 0. {
+        1. / (1. + (-z).exp())
+    }
+    else {
+        let e = z.exp();
+        e / (1. + e)
+    }
+}
+
+impl SmoothTerm for LogisticLoss
+{
+    fn dim(&self) -> Option<usize>
+    {
+        Some(self.mat_a.size().1)
+    }
+
+    fn value(&self, x: &MatSlice) -> FP
+    {
+        let z = self.h_margin(x);
+        let (k, _) = z.size();
+
+        (0 .. k).map(|r| h_softplus_neg(z[(r, 0)])).sum()
+    }
+
+    fn add_gradient(&self, x: &MatSlice, df: &mut MatSliMu)
+    {
+        let z = self.h_margin(x);
+        let w = z.clone_sz().set_by(|r, _| -self.vec_y[(r, 0)] * h_sigmoid(-z[(r, 0)]));
+
+        *df += self.mat_a.t() * w;
+    }
+
+    fn add_hessian(&self, x: &MatSlice, ddf: &mut MatSliMu)
+    {
+        let z = self.h_margin(x);
+        let w = z.clone_sz().set_by(|r, _| h_sigmoid(z[(r, 0)]) * h_sigmoid(-z[(r, 0)]));
+
+        *ddf += self.mat_a.t() * w.clone_diag() * &self.mat_a;
+    }
+}
+
+//
+
+/// Linear constraints \\( G x \\le h, A x = b \\)
+#[derive(Debug, Clone)]
+pub struct LinearConstraints
+{
+    /// \\(G\\)
+    pub mat_g: Mat,
+    /// \\(h\\)
+    pub vec_h: Mat,
+    /// \\(A\\)
+    pub mat_a: Mat,
+    /// \\(b\\)
+    pub vec_b: Mat
+}
+
+impl LinearConstraints
+{
+    /// Creates linear constraints.
+    ///
+    /// The number of columns of `mat_g` determines the number of variables \\(n\\),
+    /// even when it has no rows.
+    pub fn new(mat_g: Mat, vec_h: Mat, mat_a: Mat, vec_b: Mat) -> LinearConstraints
+    {
+        LinearConstraints {
+            mat_g, vec_h, mat_a, vec_b
+        }
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let (m, n) = self.mat_g.size();
+        let (p, _) = self.mat_a.size();
+
+        if n == 0 {return Err(SolverError::InvalidProblem("mat_g: 0 columns".into()));}
+
+        check_dim("vec_h rows", self.vec_h.size().0, "mat_g rows", m)?;
+        check_col_vec("vec_h", &self.vec_h)?;
+        check_dim("mat_a columns", self.mat_a.size().1, "mat_g columns", n)?;
+        check_dim("vec_b rows", self.vec_b.size().0, "mat_a rows", p)?;
+        check_col_vec("vec_b", &self.vec_b)?;
+
+        Ok(())
+    }
+}
+
+/// Smooth convex program
+///
+/// The problem is
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize} & \\sum_k f_k(x) \\\\
+/// {\\rm subject \\ to} & G x \\le h \\\\
+/// & A x = b,
+/// \\end{array}
+/// \\]
+/// where
+/// - variables \\( x \\in {\\bf R}^n \\)
+/// - \\( f_k \\) are [`SmoothTerm`](trait.SmoothTerm.html)s.
+///
+/// The solver starts from \\( x = 1 \\).
+/// When \\( G x \\le h \\) does not hold strictly there,
+/// a slack variable \\( s \\in {\\bf R} \\) is introduced in the same way as the other pre-defined solvers:
+/// \\( G x - h \\le s 1 \\) and \\( s = 0 \\).
+/// When any term has the domain \\( x > 0 \\), the constraints \\( -x < 0 \\) are added without the slack variable,
+/// so that all iterates stay in the domain.
+pub trait SmoothConvex {
+    fn solve_smooth_convex<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                              terms: &[Box<dyn SmoothTerm>], cons: &LinearConstraints)
+                              -> Result<Mat, SolverError>
+    where L: Write;
+}
+
+impl SmoothConvex for PDIPM
+{
+    /// Runs the solver with given parameters.
+    ///
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    /// * `terms` are \\(f_k\\).
+    /// * `cons` is \\(G\\), \\(h\\), \\(A\\) and \\(b\\).
+    fn solve_smooth_convex<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                              terms: &[Box<dyn SmoothTerm>], cons: &LinearConstraints)
+                              -> Result<Mat, SolverError>
+    where L: Write
+    {
+        // ----- parameter check
+
+        cons.validate()?;
+
+        let (m, n) = cons.mat_g.size();
+        let (p, _) = cons.mat_a.size();
+
+        if terms.is_empty() {return Err(SolverError::InvalidProblem("terms: empty".into()));}
+        for (k, t) in terms.iter().enumerate() {
+            if let Some(dim) = t.dim() {
+                check_dim(&format!("terms[{}] dimension", k), dim, "mat_g columns", n)?;
+            }
+        }
+
+        let n_pos = if terms.iter().any(|t| t.positive_domain()) {n} else {0};
+
+        // ----- initial value of a slack variable
+
+        let x0 = Mat::new_vec(n).set_all(1.);
+        let s = (&cons.mat_g * &x0 - &cons.vec_h).max().unwrap_or(-1.);
+        let n_s = if s < 0. {0} else {1};
+
+        let mut margin = param.margin;
+        let mut s_initial = s + margin;
+        while s_initial <= s {
+            margin *= 2.;
+            s_initial = s + margin;
+        }
+
+        // ----- start to solve
+
+        let rslt = self.solve(param, log,
+            n + n_s, m + n_pos, p + n_s, // 'n_s' is for a slack variable
+            |x, df_o| {
+                df_o.assign_all(0.);
+                let mut df = df_o.rows_mut(0 .. n);
+                for t in terms {
+                    t.add_gradient(&x.rows(0 .. n), &mut df);
+                }
+            },
+            |x, ddf_o| {
+                ddf_o.assign_all(0.);
+                let mut ddf = ddf_o.slice_mut(0 .. n, 0 .. n);
+                for t in terms {
+                    t.add_hessian(&x.rows(0 .. n), &mut ddf);
+                }
+            },
+            |x, f_i| {
+                let s = if n_s > 0 {x[(n, 0)]} else {0.};
+                // minus a slack variable
+                f_i.rows_mut(0 .. m).assign(&(&cons.mat_g * x.rows(0 .. n) - &cons.vec_h - s));
+                f_i.rows_mut(m .. m + n_pos).assign(&(-x.rows(0 .. n_pos)));
+            },
+            |_, df_i| {
+                df_i.assign_all(0.);
+                df_i.slice_mut(0 .. m, 0 .. n).assign(&cons.mat_g);
+                if n_s > 0 {
+                    // for a slack variable
+                    df_i.slice_mut(0 .. m, n ..= n).assign_all(-1.);
+                }
+                df_i.slice_mut(m .. m + n_pos, 0 .. n_pos).assign(&(-Mat::new(n_pos, n_pos).set_eye()));
+            },
+            |_, ddf_i, _| {
+                ddf_i.assign_all(0.);
+            },
+            |a, b| {
+                a.assign_all(0.);
+                b.assign_all(0.);
+                a.slice_mut(0 .. p, 0 .. n).assign(&cons.mat_a);
+                b.rows_mut(0 .. p).assign(&cons.vec_b);
+                if n_s > 0 {
+                    // for a slack variable
+                    a[(p, n)] = 1.;
+                }
+            },
+            |mut x| {
+                x.rows_mut(0 .. n).assign(&x0);
+                if n_s > 0 {
+                    x[(n, 0)] = s_initial;
+                }
+            }
+        )?;
+
+        let x = rslt.rows(0 .. n).clone_sz();
+        self.check_converged()?;
+
+        Ok(x)
+    }
+}
+
+#[test]
+fn test_logistic_bounds()
+{
+    // L2-regularized logistic regression without intercept, whose objective is
+    // that of scikit-learn LogisticRegression(C=1, fit_intercept=False)
+    let mat_x = Mat::new(8, 2).set_iter(&[
+        1.0, 2.0,
+        2.0, -1.0,
+        -1.5, 0.5,
+        0.5, 1.5,
+        -2.0, -1.0,
+        1.0, -2.0,
+        -0.5, -1.5,
+        3.0, 0.5
+    ]);
+    let vec_y = Mat::new_vec(8).set_iter(&[
+        1., 1., -1., 1., -1., 1., -1., 1.
+    ]);
+
+    let terms: Vec<Box<dyn SmoothTerm>> = vec![
+        Box::new(LogisticLoss {mat_a: mat_x, vec_y}),
+        Box::new(Quadratic {mat_p: Mat::new(2, 2).set_eye(), vec_q: Mat::new_vec(2)}),
+    ];
+    let param = PDIPMParam::default();
+
+    // fixed reference coefficients of the unconstrained fit, computed outside this crate
+    let cons = LinearConstraints::new(
+        Mat::new(0, 2), Mat::new_vec(0),
+        Mat::new(0, 2), Mat::new_vec(0)
+    );
+    let rslt = PDIPM::new().solve_smooth_convex(&param, &mut std::io::sink(),
+                                                &terms, &cons).unwrap();
+    println!("rslt = {}", rslt);
+
+    let exp = Mat::new_vec(2).set_iter(&[
+        1.2838831263586073, 0.2832112587808854
+    ]);
+    assert!((&rslt - exp).norm_p2() < 1e-6);
+
+    // with bounds -1 <= w <= 0.6, which the first coefficient sticks to
+    let cons = LinearConstraints::new(
+        Mat::new(4, 2).set_iter(&[
+            1., 0.,
+            0., 1.,
+            -1., 0.,
+            0., -1.
+        ]),
+        Mat::new_vec(4).set_iter(&[
+            0.6, 0.6, 1., 1.
+        ]),
+        Mat::new(0, 2), Mat::new_vec(0)
+    );
+    let rslt = PDIPM::new().solve_smooth_convex(&param, &mut std::io::sink(),
+                                                &terms, &cons).unwrap();
+    println!("rslt = {}", rslt);
+
+    let exp = Mat::new_vec(2).set_iter(&[
+        0.6, 0.2808381552148915
+    ]);
+    assert!((&rslt - &exp).norm_p2() < 1e-6);
+
+    // optimality: the gradient vanishes along the free coefficient, and pushes the other beyond its bound
+    let mut grad = Mat::new_vec(2);
+    for t in &terms {
+        t.add_gradient(&exp.as_slice(), &mut grad.as_slice_mut());
+    }
+    println!("grad = {}", grad);
+    assert!(grad[(1, 0)].abs() < 1e-12);
+    assert!(grad[(0, 0)] < 0.);
+
+    // an infeasible start for the slack variable, and terms of the domain x > 0
+    let terms: Vec<Box<dyn SmoothTerm>> = vec![
+        Box::new(NegEntropy),
+        Box::new(LogBarrier),
+        Box::new(LogSumExp {mat_f: Mat::new(2, 2).set_eye(), vec_g: Mat::new_vec(2)}),
+    ];
+    let cons = LinearConstraints::new(
+        Mat::new(1, 2).set_iter(&[1., 1.]), Mat::new_vec(1).set_all(0.5),
+        Mat::new(0, 2), Mat::new_vec(0)
+    );
+    let rslt = PDIPM::new().solve_smooth_convex(&param, &mut std::io::sink(),
+                                                &terms, &cons).unwrap();
+    println!("rslt = {}", rslt);
+    assert!(rslt.min().unwrap() > 0.);
+    assert!(rslt[(0, 0)] + rslt[(1, 0)] <= 0.5 + param.eps);
+    // symmetric
+    assert!((rslt[(0, 0)] - rslt[(1, 0)]).abs() < 1e-6);
+}
@@ -0,0 +1,99 @@
+/*!
+Support for worked examples
+
+Small deterministic data sets and model matrices used by the programs in `examples/`,
+which are also run as integration tests.
+*/
+
+use super::mat::{Mat, FP, xor64};
+
+/// Generates a two-class toy data set in the plane.
+///
+/// Returns `(features, labels)`:
+/// `features` has a sample \\( (\\xi_0, \\xi_1) \\) in each row,
+/// `labels` is \\( +1 \\) for the cluster around \\( (1, 1) \\) and \\( -1 \\) for the cluster around \\( (-1, -1) \\).
+/// * `n_per_class` is the number of samples of each class.
+/// * `spread` is the half width of the uniform noise around the cluster centers.
+/// * `seed` is a seed of the pseudo-random numbers, nonzero.
+pub fn two_class_dataset(n_per_class: usize, spread: FP, seed: u64) -> (Mat, Mat)
+{
+    let mut r = seed;
+    let n = n_per_class * 2;
+
+    let labels = Mat::new_vec(n).set_by(|i, _| if i < n_per_class {1.} else {-1.});
+    let features = Mat::new(n, 2).set_by(|i, _| labels[(i, 0)] + (xor64(&mut r) * 2. - 1.) * spread);
+
+    (features, labels)
+}
+
+/// Returns \\( Q_{ij} = y_i y_j \\xi_i^T \\xi_j \\), the Hessian of the dual problem of a linear SVM.
+/// * `features` has a sample \\( \\xi_i \\) in each row.
+/// * `labels` is \\( y \\).
+pub fn svm_dual_hessian(features: &Mat, labels: &Mat) -> Mat
+{
+    let (n, _) = labels.size();
+    let gram = features * features.t();
+
+    Mat::new(n, n).set_by(|i, j| labels[(i, 0)] * labels[(j, 0)] * gram[(i, j)])
+}
+
+/// Returns the edges of the Petersen graph, which has 10 nodes and 15 edges.
+pub fn petersen_graph() -> Vec<(usize, usize)>
+{
+    let mut edges = Vec::new();
+    for i in 0 .. 5 {
+        edges.push((i, (i + 1) % 5));     // outer cycle
+        edges.push((i, i + 5));           // spokes
+        edges.push((i + 5, (i + 2) % 5 + 5)); // inner pentagram
+    }
+    edges
+}
+
+/// Returns the Laplacian matrix \\( L = D - W \\) of an unweighted undirected graph.
+/// * `n` is the number of nodes.
+/// * `edges` are pairs of nodes.
+pub fn graph_laplacian(n: usize, edges: &[(usize, usize)]) -> Mat
+{
+    let mut lap = Mat::new(n, n);
+    for &(i, j) in edges {
+        lap[(i, i)] += 1.;
+        lap[(j, j)] += 1.;
+        lap[(i, j)] -= 1.;
+        lap[(j, i)] -= 1.;
+    }
+    lap
+}
+
+/// Returns `(mean, covariance)` of the returns of four assets,
+/// from a stock-like to a bond-like one.
+pub fn portfolio_data() -> (Mat, Mat)
+{
+    let mean = Mat::new_vec(4).set_iter(&[
+        0.12, 0.10, 0.07, 0.03
+    ]);
+    let cov = Mat::new(4, 4).set_iter(&[
+        0.0400, 0.0060, 0.0020, 0.0000,
+        0.0060, 0.0225, 0.0030, 0.0005,
+        0.0020, 0.0030, 0.0100, 0.0010,
+        0.0000, 0.0005, 0.0010, 0.0025
+    ]);
+
+    (mean, cov)
+}
+
+/// Returns `(A, B)` of a discretized double integrator,
+/// whose state is (position, velocity) and input is acceleration.
+/// * `dt` is the sampling period.
+pub fn double_integrator(dt: FP) -> (Mat, Mat)
+{
+    let mat_a = Mat::new(2, 2).set_iter(&[
+        1., dt,
+        0., 1.
+    ]);
+    let mat_b = Mat::new(2, 1).set_iter(&[
+        dt * dt / 2.,
+        dt
+    ]);
+
+    (mat_a, mat_b)
+}
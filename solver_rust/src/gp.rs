@@ -0,0 +1,402 @@
+/*!
+Geometric program
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+A geometric program is built from [`Monomial`](struct.Monomial.html) and [`Posynomial`](struct.Posynomial.html)
+of positive variables \\(x_0, \\ldots, x_{n-1}\\), which are indexed by `usize`.
+
+```
+use totsu::prelude::*;
+use totsu::gp::*;
+
+// maximize x0 x1 subject to x0 + 2 x1 <= 4
+let objective = Posynomial::from(Monomial::new(1.).pow(0, -1.).pow(1, -1.));
+let constraints = vec![
+    (Monomial::new(1.).pow(0, 1.) + Monomial::new(2.).pow(1, 1.)).le(Monomial::new(4.))
+];
+
+let param = PDIPMParam::default();
+let rslt = PDIPM::new().solve_gp(&param, &mut std::io::sink(),
+                                 &objective, &constraints).unwrap();
+
+let exp = Mat::new_vec(2).set_iter(&[
+    2., 1.
+]);
+assert!((&rslt - exp).norm_p2() < 1e-6);
+```
+*/
+
+use super::prelude::*;
+use super::convex::{lse, lse_hess};
+
+use std::io::Write;
+use std::ops::{Add, Mul, Div};
+
+/// Monomial \\( c \\prod_j x_j^{a_j} \\) with \\( c > 0 \\)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monomial
+{
+    coef: FP,
+    exps: Vec<(usize, FP)>
+}
+
+/// Posynomial, a sum of monomials
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posynomial
+{
+    terms: Vec<Monomial>
+}
+
+/// Constraint of a geometric program
+#[derive(Debug, Clone, PartialEq)]
+pub enum GPConstraint
+{
+    /// Posynomial \\( \\le 1 \\)
+    Le(Posynomial),
+    /// Monomial \\( = 1 \\)
+    Eq(Monomial)
+}
+
+impl Monomial
+{
+    /// Makes a constant monomial \\(c\\).
+    pub fn new(coef: FP) -> Monomial
+    {
+        Monomial {
+            coef,
+            exps: Vec::new()
+        }
+    }
+    /// Multiplies \\( x_{var}^{exp} \\).
+    pub fn pow(mut self, var: usize, exp: FP) -> Monomial
+    {
+        match self.exps.iter_mut().find(|(v, _)| *v == var) {
+            Some((_, e)) => *e += exp,
+            None => self.exps.push((var, exp))
+        }
+        self
+    }
+    /// Evaluates at positive `x`.
+    pub fn eval(&self, x: &Mat) -> FP
+    {
+        self.exps.iter().fold(self.coef, |acc, &(v, e)| acc * x[(v, 0)].powf(e))
+    }
+    /// Makes a constraint `self` \\( \\le \\) `rhs`.
+    pub fn le(self, rhs: Monomial) -> GPConstraint
+    {
+        Posynomial::from(self).le(rhs)
+    }
+    /// Makes a constraint `self` \\( = \\) `rhs`.
+    pub fn eq_to(self, rhs: Monomial) -> GPConstraint
+    {
+        GPConstraint::Eq(self / rhs)
+    }
+    //
+    fn h_nvars(&self) -> usize
+    {
+        self.exps.iter().map(|(v, _)| v + 1).max().unwrap_or(0)
+    }
+}
+
+impl Posynomial
+{
+    /// Evaluates at positive `x`.
+    pub fn eval(&self, x: &Mat) -> FP
+    {
+        self.terms.iter().map(|t| t.eval(x)).sum()
+    }
+    /// Makes a constraint `self` \\( \\le \\) `rhs`.
+    pub fn le(self, rhs: Monomial) -> GPConstraint
+    {
+        GPConstraint::Le(self / rhs)
+    }
+    //
+    fn h_nvars(&self) -> usize
+    {
+        self.terms.iter().map(|t| t.h_nvars()).max().unwrap_or(0)
+    }
+    // log-transformed as log-sum-exp of (F y + g), where y = log x
+    fn h_lower(&self, n: usize) -> (Mat, Mat)
+    {
+        let k = self.terms.len();
+        let mut mat_f = Mat::new(k, n);
+        let mut vec_g = Mat::new_vec(k);
+
+        for (r, t) in self.terms.iter().enumerate() {
+            for &(v, e) in &t.exps {
+                mat_f[(r, v)] += e;
+            }
+            vec_g[(r, 0)] = t.coef.ln();
+        }
+
+        (mat_f, vec_g)
+    }
+}
+
+impl From<Monomial> for Posynomial
+{
+    fn from(m: Monomial) -> Posynomial
+    {
+        Posynomial {
+            terms: vec![m]
+        }
+    }
+}
+
+impl Mul for Monomial
+{
+    type Output = Monomial;
+
+    fn mul(self, rhs: Monomial) -> Monomial
+    {
+        let coef = self.coef * rhs.coef;
+        rhs.exps.iter().fold(Monomial {coef, .. self}, |m, &(v, e)| m.pow(v, e))
+    }
+}
+
+impl Div for Monomial
+{
+    type Output = Monomial;
+
+    fn div(self, rhs: Monomial) -> Monomial
+    {
+        let coef = self.coef / rhs.coef;
+        rhs.exps.iter().fold(Monomial {coef, .. self}, |m, &(v, e)| m.pow(v, -e))
+    }
+}
+
+impl Div<Monomial> for Posynomial
+{
+    type Output = Posynomial;
+
+    fn div(self, rhs: Monomial) -> Posynomial
+    {
+        Posynomial {
+            terms: self.terms.into_iter().map(|t| t / rhs.clone()).collect()
+        }
+    }
+}
+
+impl Add<Monomial> for Monomial
+{
+    type Output = Posynomial;
+
+    fn add(self, rhs: Monomial) -> Posynomial
+    {
+        Posynomial::from(self) + rhs
+    }
+}
+
+impl Add<Monomial> for Posynomial
+{
+    type Output = Posynomial;
+
+    fn add(mut self, rhs: Monomial) -> Posynomial
+    {
+        self.terms.push(rhs);
+        self
+    }
+}
+
+impl Add<Posynomial> for Posynomial
+{
+    type Output = Posynomial;
+
+    fn add(mut self, rhs: Posynomial) -> Posynomial
+    {
+        self.terms.extend(rhs.terms);
+        self
+    }
+}
+
+//
+
+/// Geometric program
+///
+/// The problem is
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize} & f_0(x) \\\\
+/// {\\rm subject \\ to} & f_i(x) \\le 1 \\quad (i = 1, \\ldots, m) \\\\
+/// & g_j(x) = 1 \\quad (j = 1, \\ldots, p),
+/// \\end{array}
+/// \\]
+/// where
+/// - variables \\( x \\in {\\bf R}_{++}^n \\)
+/// - \\( f_0, \\ldots, f_m \\) are posynomials
+/// - \\( g_1, \\ldots, g_p \\) are monomials.
+///
+/// Internally the variables are changed into \\( y = \\log x \\),
+/// which makes a convex problem of log-sum-exp functions:
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize}_{y,s} & \\log f_0(e^y) \\\\
+/// {\\rm subject \\ to} & \\log f_i(e^y) \\le s \\quad (i = 1, \\ldots, m) \\\\
+/// & \\log g_j(e^y) = 0 \\quad (j = 1, \\ldots, p) \\\\
+/// & s = 0,
+/// \\end{array}
+/// \\]
+/// with a slack variable \\( s \\in {\\bf R} \\) for the infeasible start method.
+pub trait GP {
+    fn solve_gp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                   objective: &Posynomial, constraints: &[GPConstraint])
+                   -> Result<Mat, SolverError>
+    where L: Write;
+}
+
+impl GP for PDIPM
+{
+    /// Runs the solver with given parameters.
+    ///
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    /// * `objective` is \\(f_0\\).
+    /// * `constraints` are \\( f_i(x) \\le 1 \\) and \\( g_j(x) = 1 \\).
+    fn solve_gp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                   objective: &Posynomial, constraints: &[GPConstraint])
+                   -> Result<Mat, SolverError>
+    where L: Write
+    {
+        // ----- parameter check
+
+        let ineqs: Vec<&Posynomial> = constraints.iter().filter_map(|c| if let GPConstraint::Le(f) = c {Some(f)} else {None}).collect();
+        let eqs: Vec<&Monomial> = constraints.iter().filter_map(|c| if let GPConstraint::Eq(g) = c {Some(g)} else {None}).collect();
+
+        let n = ineqs.iter().map(|f| f.h_nvars())
+                     .chain(eqs.iter().map(|g| g.h_nvars()))
+                     .fold(objective.h_nvars(), usize::max);
+        let m = ineqs.len();
+        let p = eqs.len();
+
+        if n == 0 {return Err(SolverError::InvalidProblem("objective: no variables".into()));}
+        if objective.terms.is_empty() {return Err(SolverError::InvalidProblem("objective: no terms".into()));}
+        for (i, f) in ineqs.iter().enumerate() {
+            if f.terms.is_empty() {return Err(SolverError::InvalidProblem(format!("inequality {}: no terms", i)));}
+        }
+        let coefs = objective.terms.iter().chain(ineqs.iter().flat_map(|f| f.terms.iter())).chain(eqs.iter().copied());
+        for t in coefs {
+            if t.coef.is_nan() || t.coef <= 0. {return Err(SolverError::InvalidProblem(format!("coefficient {} is not positive", t.coef)));}
+        }
+
+        // ----- log transformation
+
+        let (mat_f0, vec_g0) = objective.h_lower(n);
+        let lse_i: Vec<(Mat, Mat)> = ineqs.iter().map(|f| f.h_lower(n)).collect();
+
+        let mut mat_a = Mat::new(p, n);
+        let mut vec_b = Mat::new_vec(p);
+        for (r, g) in eqs.iter().enumerate() {
+            let (mat_f, vec_g) = Posynomial::from((*g).clone()).h_lower(n);
+            mat_a.row_mut(r).assign(&mat_f);
+            vec_b[(r, 0)] = -vec_g[(0, 0)];
+        }
+
+        // ----- initial value of a slack variable
+
+        let y0 = Mat::new_vec(n);
+        let s = lse_i.iter().map(|(f, g)| lse(f, g, &y0.as_slice()).0).fold(0., FP::max);
+        let mut margin = param.margin;
+        let mut s_initial = s + margin;
+        while s_initial <= s {
+            margin *= 2.;
+            s_initial = s + margin;
+        }
+
+        // ----- start to solve
+
+        let rslt = self.solve(param, log,
+            n + 1, m, p + 1, // '+ 1' is for a slack variable
+            |x, df_o| {
+                let (_, w) = lse(&mat_f0, &vec_g0, &x.rows(0 .. n));
+                df_o.rows_mut(0 .. n).assign(&(mat_f0.t() * w));
+                // for a slack variable
+                df_o[(n, 0)] = 0.;
+            },
+            |x, ddf_o| {
+                let (_, w) = lse(&mat_f0, &vec_g0, &x.rows(0 .. n));
+                ddf_o.assign_all(0.);
+                ddf_o.slice_mut(0 .. n, 0 .. n).assign(&lse_hess(&mat_f0, &w));
+            },
+            |x, f_i| {
+                for (r, (mat_f, vec_g)) in lse_i.iter().enumerate() {
+                    let (v, _) = lse(mat_f, vec_g, &x.rows(0 .. n));
+                    f_i[(r, 0)] = v - x[(n, 0)]; // minus a slack variable
+                }
+            },
+            |x, df_i| {
+                for (r, (mat_f, vec_g)) in lse_i.iter().enumerate() {
+                    let (_, w) = lse(mat_f, vec_g, &x.rows(0 .. n));
+                    df_i.slice_mut(r ..= r, 0 .. n).assign(&(w.t() * mat_f));
+                    // for a slack variable
+                    df_i[(r, n)] = -1.;
+                }
+            },
+            |x, ddf_i, i| {
+                let (mat_f, vec_g) = &lse_i[i];
+                let (_, w) = lse(mat_f, vec_g, &x.rows(0 .. n));
+                ddf_i.assign_all(0.);
+                ddf_i.slice_mut(0 .. n, 0 .. n).assign(&lse_hess(mat_f, &w));
+            },
+            |a, b| {
+                a.assign_all(0.);
+                b.assign_all(0.);
+                a.slice_mut(0 .. p, 0 .. n).assign(&mat_a);
+                b.rows_mut(0 .. p).assign(&vec_b);
+                // for a slack variable
+                a[(p, n)] = 1.;
+            },
+            |mut x| {
+                x.assign_all(0.);
+                x[(n, 0)] = s_initial;
+            }
+        )?;
+
+        // ----- back to x = exp(y)
+
+        let x = rslt.rows(0 .. n).clone_sz().set_by(|r, _| rslt[(r, 0)].exp());
+        self.check_converged()?;
+
+        Ok(x)
+    }
+}
+
+#[test]
+fn test_gp_box()
+{
+    // maximize volume h w d subject to surface area 2 (h w + h d + w d) <= a_surf:
+    // a cube of side sqrt(a_surf / 6)
+    let a_surf: FP = 24.;
+    let (h, w, d) = (0, 1, 2);
+
+    let volume = Monomial::new(1.).pow(h, 1.).pow(w, 1.).pow(d, 1.);
+    let objective = Posynomial::from(Monomial::new(1.) / volume);
+    let surface = Monomial::new(2.).pow(h, 1.).pow(w, 1.)
+                + Monomial::new(2.).pow(h, 1.).pow(d, 1.)
+                + Monomial::new(2.).pow(w, 1.).pow(d, 1.);
+    let constraints = vec![surface.le(Monomial::new(a_surf))];
+
+    let param = PDIPMParam::default();
+    let rslt = PDIPM::new().solve_gp(&param, &mut std::io::sink(),
+                                     &objective, &constraints).unwrap();
+
+    let exp = Mat::new_vec(3).set_all((a_surf / 6.).sqrt());
+    println!("rslt = {}", rslt);
+    assert!((&rslt - exp).norm_p2() < 1e-6);
+
+    // with a fixed aspect ratio h = 2 w, while `eq` remains the comparison of `PartialEq`
+    assert!(Monomial::new(2.).pow(w, 1.).eq(&Monomial::new(2.).pow(w, 1.)));
+    let constraints = vec![
+        constraints[0].clone(),
+        Monomial::new(1.).pow(h, 1.).eq_to(Monomial::new(2.).pow(w, 1.))
+    ];
+    let rslt = PDIPM::new().solve_gp(&param, &mut std::io::sink(),
+                                     &objective, &constraints).unwrap();
+    println!("rslt = {}", rslt);
+    assert!((rslt[(h, 0)] - 2. * rslt[(w, 0)]).abs() < 1e-6);
+    // surface 2 (2 w^2 + 2 w d + w d) = a_surf with d = 4 w / 3 at optimum
+    let w_opt = (a_surf / 2. / (2. + 3. * 4. / 3.)).sqrt();
+    assert!((rslt[(w, 0)] - w_opt).abs() < 1e-6);
+    assert!((rslt[(d, 0)] - 4. / 3. * w_opt).abs() < 1e-6);
+}
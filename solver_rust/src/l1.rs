@@ -0,0 +1,189 @@
+/*!
+\\(\\ell_1\\)-penalized problems
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+Wrappers which reduce \\(\\ell_1\\)-type problems into [`LPProblem`](../lp/struct.LPProblem.html)
+by splitting a free variable into nonnegative positive and negative parts,
+and undo the splitting in their results.
+
+```
+use totsu::prelude::*;
+use totsu::l1::*;
+
+// median of 1, 2, 10
+let mat_x = Mat::new(3, 1).set_all(1.);
+let vec_y = Mat::new_vec(3).set_iter(&[
+    1., 2., 10.
+]);
+
+let param = PDIPMParam::default();
+let beta = solve_quantile_regression(&mat_x, &vec_y, 0.5, &param).unwrap();
+
+assert!((beta[(0, 0)] - 2.).abs() < 1e-6);
+```
+*/
+
+use super::prelude::*;
+use super::problem::{check_dim, check_col_vec};
+
+/// Solves a basis pursuit.
+///
+/// The problem is
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize} & \\| x \\|_1 \\\\
+/// {\\rm subject \\ to} & A x = b,
+/// \\end{array}
+/// \\]
+/// which is solved as an LP of \\( x = u - v \\):
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize}_{u,v} & {\\bf 1}^T u + {\\bf 1}^T v \\\\
+/// {\\rm subject \\ to} & A u - A v = b \\\\
+/// & u \\succeq 0, \\ v \\succeq 0.
+/// \\end{array}
+/// \\]
+///
+/// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+/// * `mat_a` is \\(A\\).
+/// * `vec_b` is \\(b\\).
+/// * `param` is solver parameters.
+pub fn solve_basis_pursuit(mat_a: &Mat, vec_b: &Mat, param: &PDIPMParam) -> Result<Mat, SolverError>
+{
+    let (p, n) = mat_a.size();
+
+    if n == 0 {return Err(SolverError::InvalidProblem("mat_a: 0 columns".into()));}
+    check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+    check_col_vec("vec_b", vec_b)?;
+
+    let vec_c = Mat::new_vec(2 * n).set_all(1.);
+    let mat_g = -Mat::new(2 * n, 2 * n).set_eye();
+    let vec_h = Mat::new_vec(2 * n);
+    let mut mat_a_uv = Mat::new(p, 2 * n);
+    mat_a_uv.cols_mut(0 .. n).assign(mat_a);
+    mat_a_uv.cols_mut(n .. 2 * n).assign(&-mat_a);
+
+    let x = LPProblem::new(vec_c, mat_g, vec_h, mat_a_uv, vec_b.clone()).solve(param)?.converged_x()?;
+
+    Ok(x.rows(0 .. n) - x.rows(n .. 2 * n))
+}
+
+/// Solves a quantile regression.
+///
+/// The problem is
+/// \\[
+/// {\\rm minimize}_\\beta \\ \\sum_i \\rho_\\tau (y_i - x_i^T \\beta),
+/// \\quad \\rho_\\tau(r) = \\max \\lbrace \\tau r, (\\tau - 1) r \\rbrace,
+/// \\]
+/// where \\( x_i^T \\) is the \\(i\\)-th row of \\(X\\).
+/// It is solved as an LP of residuals \\( y - X \\beta = u - v \\):
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize}_{\\beta,u,v} & \\tau {\\bf 1}^T u + (1 - \\tau) {\\bf 1}^T v \\\\
+/// {\\rm subject \\ to} & X \\beta + u - v = y \\\\
+/// & u \\succeq 0, \\ v \\succeq 0.
+/// \\end{array}
+/// \\]
+/// \\( \\tau = 0.5 \\) makes a median (least absolute deviation) regression.
+///
+/// Returns `Ok` with optimal \\(\\beta\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+/// * `mat_x` is \\(X\\), samples in rows.
+/// * `vec_y` is \\(y\\).
+/// * `tau` is \\( \\tau \\in (0, 1) \\).
+/// * `param` is solver parameters.
+pub fn solve_quantile_regression(mat_x: &Mat, vec_y: &Mat, tau: FP, param: &PDIPMParam) -> Result<Mat, SolverError>
+{
+    let (k, n) = mat_x.size();
+
+    if n == 0 {return Err(SolverError::InvalidProblem("mat_x: 0 columns".into()));}
+    check_dim("vec_y rows", vec_y.size().0, "mat_x rows", k)?;
+    check_col_vec("vec_y", vec_y)?;
+    if !(tau > 0. && tau < 1.) {return Err(SolverError::InvalidProblem(format!("tau = {} is not in (0, 1)", tau)));}
+
+    let nv = n + 2 * k;
+
+    let mut vec_c = Mat::new_vec(nv);
+    vec_c.rows_mut(n .. n + k).assign_all(tau);
+    vec_c.rows_mut(n + k .. nv).assign_all(1. - tau);
+
+    let mut mat_g = Mat::new(2 * k, nv);
+    mat_g.cols_mut(n .. nv).assign(&-Mat::new(2 * k, 2 * k).set_eye());
+    let vec_h = Mat::new_vec(2 * k);
+
+    let mut mat_a = Mat::new(k, nv);
+    mat_a.cols_mut(0 .. n).assign(mat_x);
+    mat_a.cols_mut(n .. n + k).assign_eye();
+    mat_a.cols_mut(n + k .. nv).assign(&-Mat::new(k, k).set_eye());
+
+    let x = LPProblem::new(vec_c, mat_g, vec_h, mat_a, vec_y.clone()).solve(param)?.converged_x()?;
+
+    Ok(x.rows(0 .. n).clone_sz())
+}
+
+#[test]
+fn test_basis_pursuit()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let p: usize = 6;
+    let n: usize = 12;
+
+    let mut r = XOR64_INIT;
+    let mat_a = Mat::new(p, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mut x_sparse = Mat::new_vec(n);
+    x_sparse[(3, 0)] = 1.5;
+    x_sparse[(8, 0)] = -0.7;
+    let vec_b = &mat_a * &x_sparse;
+
+    let param = PDIPMParam::default();
+    let x = solve_basis_pursuit(&mat_a, &vec_b, &param).unwrap();
+    println!("x = {}", x);
+
+    assert!((&mat_a * &x - &vec_b).norm_p2() < 1e-6);
+    assert!((&x - &x_sparse).norm_p2() < 1e-6);
+
+    assert!(solve_basis_pursuit(&mat_a, &Mat::new_vec(p + 1), &param).is_err());
+
+    let param = PDIPMParam {
+        max_iter: Some(1),
+        .. PDIPMParam::default()
+    };
+    assert!(matches!(solve_basis_pursuit(&mat_a, &vec_b, &param), Err(SolverError::NotConverged(_))));
+}
+
+#[test]
+fn test_quantile_regression()
+{
+    // points on y = 2 t - 1 except an outlier, which least absolute deviation ignores
+    let ts = [0., 1., 2., 3., 4., 5., 6.];
+    let mat_x = Mat::new(7, 2).set_by(|r, c| if c == 0 {1.} else {ts[r]});
+    let mut vec_y = Mat::new_vec(7).set_by(|r, _| 2. * ts[r] - 1.);
+    vec_y[(5, 0)] = 30.;
+
+    let param = PDIPMParam::default();
+    let beta = solve_quantile_regression(&mat_x, &vec_y, 0.5, &param).unwrap();
+    println!("beta = {}", beta);
+
+    let exp = Mat::new_vec(2).set_iter(&[
+        -1., 2.
+    ]);
+    assert!((&beta - exp).norm_p2() < 1e-6);
+
+    // an intercept only: the tau-quantile of samples
+    let mat_x = Mat::new(5, 1).set_all(1.);
+    let vec_y = Mat::new_vec(5).set_iter(&[
+        5., 1., 4., 2., 3.
+    ]);
+    let beta = solve_quantile_regression(&mat_x, &vec_y, 0.3, &param).unwrap();
+    println!("beta = {}", beta);
+    assert!((beta[(0, 0)] - 2.).abs() < 1e-6);
+
+    assert!(solve_quantile_regression(&mat_x, &vec_y, 1., &param).is_err());
+
+    let param = PDIPMParam {
+        max_iter: Some(1),
+        .. PDIPMParam::default()
+    };
+    assert!(matches!(solve_quantile_regression(&mat_x, &vec_y, 0.3, &param), Err(SolverError::NotConverged(_))));
+}
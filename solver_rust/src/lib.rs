@@ -34,15 +34,23 @@ which takes objective and constraint (derivative) functions as closures.
 Therefore solving a specific problem requires a implementation of those closures.
 You can use a pre-defined implementations (see [`predef`](predef/index.html)),
 as well as construct a user-defined tailored version for the reason of functionality and efficiency.
+For LP, QP and SOCP, the [`modeling`](modeling/index.html) layer also builds those inputs from variables and expressions.
 
-This crate has no dependencies on other crates at all.
-Necessary matrix operations are implemented in [`mat`](mat/index.html) and [`matsvd`](matsvd/index.html).
+This crate has no dependencies on other crates at all, except for optional features.
+Necessary matrix operations are implemented in [`mat`](mat/index.html), [`matsvd`](matsvd/index.html), [`matlu`](matlu/index.html), [`matchol`](matchol/index.html), [`matqr`](matqr/index.html), [`matldl`](matldl/index.html) and [`mateig`](mateig/index.html).
+
+# Determinism
+
+Solving is deterministic:
+the same inputs give bitwise-identical results across repeated runs,
+whether a [`PDIPM`](pdipm/struct.PDIPM.html) instance is fresh or reused,
+and regardless of the number of threads with the `parallel` feature.
+Accumulations are done in a fixed order, and the solve path uses neither randomness nor hash-ordered containers.
 
 # Example: QP
 
 ```
 use totsu::prelude::*;
-use totsu::predef::*;
 
 let n: usize = 2; // x0, x1
 let m: usize = 1;
@@ -88,32 +96,61 @@ You can find other test examples of pre-defined solvers in [`lib.rs`](../src/tot
 
 pub mod mat;
 pub mod matsvd;
+pub mod matlu;
+pub mod matchol;
+pub mod matqr;
+pub mod matldl;
+pub mod mateig;
+pub mod matio;
 pub mod pdipm;
+pub mod problem;
 pub mod lp;
 pub mod qp;
 pub mod qcqp;
 pub mod socp;
 pub mod sdp;
+pub mod gp;
+pub mod convex;
+pub mod l1;
+pub mod simplex;
+pub mod selfdual;
+pub mod sensitivity;
+pub mod path;
+pub mod stats;
+pub mod modeling;
+pub mod verify;
+pub mod refine;
+pub mod batch;
+pub mod mpc;
+pub mod examples_util;
+#[cfg(feature = "alloc-count")]
+pub mod alloc_stats;
 
 /// Prelude
+///
+/// `use totsu::prelude::*;` brings matrix types, the solver, pre-defined solvers and problem structs.
 pub mod prelude {
-    pub use crate::mat::{Mat, FP};
-    pub use crate::pdipm::{PDIPM, PDIPMParam};
+    pub use crate::mat::{Mat, MatSlice, FP};
+    pub use crate::pdipm::{PDIPM, PDIPMParam, Timings, Formulation, DependentRows, Factorization};
+    pub use crate::problem::{Problem, ProblemDims, SolverResult, SolverError, ConvergenceFailure, SolverStatus, Residuals, Sense, RangeConstraints, ProblemNames, ObjectiveBreakdown};
+    pub use crate::predef::*;
 }
 
 /// Pre-defined solvers
 pub mod predef {
-    pub use crate::lp::LP;
-    pub use crate::qp::QP;
-    pub use crate::qcqp::QCQP;
-    pub use crate::socp::SOCP;
-    pub use crate::sdp::SDP;
+    pub use crate::lp::{LP, LPProblem};
+    pub use crate::qp::{QP, QPProblem, SoftNorm};
+    pub use crate::qcqp::{QCQP, QCQPProblem};
+    pub use crate::socp::{SOCP, SOCPProblem};
+    pub use crate::sdp::{SDP, SDPProblem};
+    pub use crate::gp::GP;
+    pub use crate::convex::SmoothConvex;
 }
 
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
-    use crate::predef::*;
+    use crate::verify::*;
 
     #[test]
     fn test_qcqp()
@@ -159,6 +196,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_socp()
     {
         let n: usize = 2; // x0, x1
@@ -192,6 +230,12 @@ mod tests {
         ]);
         println!("rslt = {}", rslt);
         assert!((&rslt - exp).norm_p2() < param.eps);
+
+        let report = verify_socp(&rslt, &vec_f,
+                                 &mat_g, &vec_h, &vec_c, &scl_d,
+                                 &mat_a, &vec_b, param.eps);
+        println!("report = {:?}", report);
+        assert!(report.is_feasible());
     }
 
     #[test]
@@ -224,6 +268,409 @@ mod tests {
                                           &mat_a, &vec_b).unwrap_err();
     }
 
+    #[test]
+    fn test_lp_maximize()
+    {
+        let n: usize = 2;
+        let m: usize = 3;
+        let p: usize = 0;
+
+        let vec_c = Mat::new_vec(n).set_iter(&[
+            1., 2.
+        ]);
+
+        // x0 >= 0, x1 >= 0, x0 + x1 <= 1
+        let mat_g = Mat::new(m, n).set_iter(&[
+            -1., 0.,
+            0., -1.,
+            1., 1.
+        ]);
+        let vec_h = Mat::new_vec(m).set_iter(&[
+            0., 0., 1.
+        ]);
+
+        let mat_a = Mat::new(p, n);
+        let vec_b = Mat::new_vec(p);
+
+        let param = PDIPMParam::default();
+        let prob_max = LPProblem::new(vec_c.clone(),
+                                      mat_g.clone(), vec_h.clone(),
+                                      mat_a.clone(), vec_b.clone())
+                       .with_sense(Sense::Maximize);
+        let rslt_max = prob_max.solve(&param).unwrap();
+
+        let prob_min = LPProblem::new(-&vec_c,
+                                      mat_g.clone(), vec_h.clone(),
+                                      mat_a.clone(), vec_b.clone());
+        let rslt_min = prob_min.solve(&param).unwrap();
+
+        println!("max = {}, min = {}", rslt_max.primal_objective, rslt_min.primal_objective);
+        assert!((rslt_max.primal_objective - 2.).abs() < 1e-6);
+        assert!((rslt_max.primal_objective + rslt_min.primal_objective).abs() < param.eps);
+        assert!((&rslt_max.lmd + &rslt_min.lmd).norm_p2() < param.eps);
+
+        let report = verify_lp_kkt(&rslt_max, &vec_c, &mat_g, &vec_h, &mat_a, &vec_b, 1e-6);
+        println!("report = {:?}", report);
+        assert!(report.is_optimal());
+    }
+
+    #[test]
+    fn test_qp_timing()
+    {
+        use crate::mat::{xor64, XOR64_INIT};
+
+        let n: usize = 20;
+        let m: usize = 20;
+        let p: usize = 0;
+
+        let mut r = XOR64_INIT;
+        let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r));
+        let mat_p = mat_b.t() * &mat_b + Mat::new(n, n).set_eye();
+        let vec_q = Mat::new_vec(n).set_by(|_, _| xor64(&mut r));
+        let mat_g = Mat::new(m, n).set_by(|_, _| xor64(&mut r));
+        let vec_h = Mat::new_vec(m).set_all(1.);
+        let mat_a = Mat::new(p, n);
+        let vec_b = Mat::new_vec(p);
+
+        let prob = QPProblem::new(mat_p, vec_q, mat_g, vec_h, mat_a, vec_b);
+
+        let param = PDIPMParam::default();
+        assert!(prob.solve(&param).unwrap().timings.is_none());
+
+        let param = PDIPMParam {
+            timing: true,
+            .. Default::default()
+        };
+        let rslt = prob.solve(&param).unwrap();
+        let timings = rslt.timings.unwrap();
+        println!("timings = {:?}", timings);
+
+        let sum = timings.sum_phases();
+        assert!(timings.factorization > Default::default());
+        assert!(sum <= timings.total);
+        assert!(sum.as_secs_f64() >= 0.5 * timings.total.as_secs_f64());
+    }
+
+    #[test]
+    fn test_max_iter()
+    {
+        use crate::mat::{xor64, XOR64_INIT};
+
+        let n: usize = 8;
+        let m: usize = 8;
+        let p: usize = 1;
+
+        let mut r = XOR64_INIT;
+        let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r));
+        let mat_p = mat_b.t() * &mat_b + Mat::new(n, n).set_eye();
+        let vec_q = Mat::new_vec(n).set_by(|_, _| xor64(&mut r) * 4.);
+        let mat_g = Mat::new(m, n).set_by(|_, _| xor64(&mut r));
+        let vec_h = Mat::new_vec(m).set_all(0.1);
+        let mat_a = Mat::new(p, n).set_all(1.);
+        let vec_b = Mat::new_vec(p).set_all(1.);
+
+        let prob = QPProblem::new(mat_p, vec_q, mat_g, vec_h, mat_a, vec_b);
+
+        let param = PDIPMParam::default();
+        let rslt = prob.solve(&param).unwrap();
+        println!("iterations: {}", rslt.iterations);
+        assert_eq!(rslt.status, SolverStatus::Converged);
+        assert!(rslt.iterations > 10);
+
+        let param = PDIPMParam {
+            max_iter: Some(3),
+            .. Default::default()
+        };
+        let mut log = Vec::new();
+        let rslt = prob.solve_with(&mut PDIPM::new(), &param, &mut log).unwrap();
+        assert_eq!(rslt.status, SolverStatus::MaxIterReached);
+        assert_eq!(rslt.iterations, 3);
+
+        // merits of all iterates from the log
+        let log = String::from_utf8(log).unwrap();
+        let values: Vec<FP> = log.lines()
+                                 .filter(|l| l.starts_with("|| r_") || l.starts_with("   eta"))
+                                 .map(|l| l.rsplit(':').next().unwrap().trim().parse().unwrap())
+                                 .collect();
+        let merits: Vec<FP> = values.chunks(3)
+                                    .map(|v| Residuals {r_dual: v[0], r_pri: v[1], gap: v[2]}.merit())
+                                    .collect();
+        println!("merits: {:?}, returned: {:e}", merits, rslt.residuals.merit());
+        assert_eq!(merits.len(), 4);
+
+        let best = merits.iter().cloned().fold(FP::INFINITY, FP::min);
+        assert!((rslt.residuals.merit() - best).abs() <= 1e-3 * best);
+
+        // interfaces returning only x keep failing
+        let mut solver = PDIPM::new();
+        assert!(solver.solve_qp(&param, &mut std::io::sink(),
+                                &prob.mat_p, &prob.vec_q, &prob.mat_g, &prob.vec_h, &prob.mat_a, &prob.vec_b).is_err());
+    }
+
+    #[test]
+    fn test_gondzio_correctors()
+    {
+        use crate::mat::{xor64, XOR64_INIT};
+
+        let n: usize = 8;
+        let m: usize = 16;
+        let p: usize = 2;
+
+        // random QP suite
+        let mut r = XOR64_INIT;
+        let probs: Vec<QPProblem> = (0 .. 4).map(|_| {
+            let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+            let mat_p = mat_b.t() * &mat_b + Mat::new(n, n).set_eye() * 0.1;
+            let vec_q = Mat::new_vec(n).set_by(|_, _| (xor64(&mut r) - 0.5) * 4.);
+            let mat_g = Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5);
+            let vec_h = Mat::new_vec(m).set_by(|_, _| xor64(&mut r));
+            let mat_a = Mat::new(p, n).set_by(|_, _| xor64(&mut r) - 0.5);
+            let vec_b = Mat::new_vec(p);
+            QPProblem::new(mat_p, vec_q, mat_g, vec_h, mat_a, vec_b)
+        }).collect();
+
+        let mut solver = PDIPM::new();
+        let mut xs: Vec<Mat> = Vec::new();
+        let mut counts = Vec::new();
+        for max_correctors in 0 ..= 2 {
+            let param = PDIPMParam {
+                max_correctors,
+                .. Default::default()
+            };
+
+            let mut factorizations = 0;
+            let mut corrections = 0;
+            for (i, prob) in probs.iter().enumerate() {
+                let rslt = prob.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+                assert_eq!(rslt.status, SolverStatus::Converged);
+                factorizations += solver.factorizations();
+                corrections += solver.corrections();
+
+                // solutions are unchanged by correctors
+                if max_correctors == 0 {
+                    xs.push(rslt.x);
+                }
+                else {
+                    assert!((&rslt.x - &xs[i]).norm_p2() < 1e-6);
+                }
+            }
+            println!("max_correctors = {}: {} factorizations, {} corrections", max_correctors, factorizations, corrections);
+            assert_eq!(corrections == 0, max_correctors == 0);
+            counts.push(factorizations);
+        }
+        assert!(counts[2] < counts[0]);
+    }
+
+    #[test]
+    fn test_determinism()
+    {
+        let n: usize = 2;
+
+        let qp = QPProblem::new(Mat::new(n, n).set_eye(), Mat::new_vec(n).set_iter(&[1., 2.]),
+                                Mat::new(1, n).set_iter(&[-1. / 2., -1. / 3.]), Mat::new_vec(1).set_iter(&[-1.]),
+                                Mat::new(0, n), Mat::new_vec(0));
+        let socp = SOCPProblem::new(Mat::new_vec(n).set_all(1.),
+                                    vec![Mat::new(n, n).set_eye()], vec![Mat::new_vec(n)],
+                                    vec![Mat::new_vec(n)], vec![FP::sqrt(2.)],
+                                    Mat::new(0, n), Mat::new_vec(0));
+
+        let param = PDIPMParam::default();
+        let mut solver = PDIPM::new();
+
+        let qp_x = qp.solve(&param).unwrap().x;
+        let socp_x = socp.solve(&param).unwrap().x;
+
+        for _ in 0 .. 2 {
+            // a reused solver, interleaving problems
+            let x = qp.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap().x;
+            assert_eq!(x.to_bytes(), qp_x.to_bytes());
+            let x = socp.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap().x;
+            assert_eq!(x.to_bytes(), socp_x.to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_pdipm_state()
+    {
+        use crate::pdipm::{PDIPMState, StepOutcome};
+
+        let n: usize = 2;
+        let m: usize = 2;
+        let p: usize = 1;
+
+        // minimize (1/2)||x - a||^2 subject to x <= 0.5 and x0 + 2 x1 = 1
+        let vec_a = Mat::new_vec(n).set_iter(&[2., -1.]);
+        let mat_a = Mat::new(p, n).set_iter(&[1., 2.]);
+
+        let param = PDIPMParam::default();
+        let mut solver = PDIPM::new();
+        let mut log_solve = Vec::new();
+        let y_solve = solver.solve(&param, &mut log_solve, n, m, p,
+                                   |x, df_o| df_o.assign(&(x - &vec_a)),
+                                   |_, ddf_o| {ddf_o.assign_eye();},
+                                   |x, f_i| f_i.assign(&(x - 0.5)),
+                                   |_, df_i| {df_i.assign_eye();},
+                                   |_, ddf_i, _| ddf_i.assign_all(0.),
+                                   |a, b| {a.assign(&mat_a); b.assign_all(1.);},
+                                   |mut x| x.assign_all(0.)).unwrap().clone_sz();
+        let iterations = solver.iterations();
+        let residuals = solver.residuals();
+
+        let mut log_state = Vec::new();
+        let mut state = PDIPMState::init(&mut solver, &param, &mut log_state, n, m, p,
+                                         |x, df_o| df_o.assign(&(x - &vec_a)),
+                                         |_, ddf_o| {ddf_o.assign_eye();},
+                                         |x, f_i| f_i.assign(&(x - 0.5)),
+                                         |_, df_i| {df_i.assign_eye();},
+                                         |_, ddf_i, _| ddf_i.assign_all(0.),
+                                         |a, b| {a.assign(&mat_a); b.assign_all(1.);},
+                                         |mut x| x.assign_all(0.)).unwrap();
+        assert_eq!(state.kkt_cond(), None);
+
+        let mut steps = 0;
+        loop {
+            let outcome = state.step().unwrap();
+            if outcome != StepOutcome::Continue {
+                assert_eq!(outcome, StepOutcome::Converged);
+                break;
+            }
+            steps += 1;
+            println!("x = {} step = {:.3e} / {:.3e} cond = {:.3e}",
+                     state.x().t(), state.step_size(), state.step_max(), state.kkt_cond().unwrap());
+            assert_eq!(state.iterations(), steps);
+            assert!(state.slacks().min().unwrap() > 0.);
+            assert!(state.lmd().min().unwrap() > 0.);
+            assert!(state.step_size() <= state.step_max());
+            assert!(state.kkt_cond().unwrap() >= 1.);
+        }
+        assert_eq!(state.step().unwrap(), StepOutcome::Converged);
+        assert_eq!(state.residuals(), residuals);
+        let y_state = state.finish().unwrap().clone_sz();
+
+        // the one-shot solve is reproduced bit by bit
+        assert_eq!(steps, iterations);
+        assert_eq!(y_state, y_solve);
+        assert_eq!(log_state, log_solve);
+        assert_eq!(solver.status(), SolverStatus::Converged);
+    }
+
+    #[test]
+    fn test_dependent_eqs()
+    {
+        let n: usize = 3;
+
+        // minimize (1/2)||x||^2 + q^T x subject to x >= 0 and the equalities
+        let qp = |mat_a: &[FP], vec_b: &[FP]| {
+            let p = vec_b.len();
+            QPProblem::new(Mat::new(n, n).set_eye(), Mat::new_vec(n).set_iter(&[1., -2., 0.5]),
+                           -Mat::new(n, n).set_eye(), Mat::new_vec(n),
+                           Mat::new(p, n).set_iter(mat_a), Mat::new_vec(p).set_iter(vec_b))
+        };
+        let param = PDIPMParam::default();
+
+        let rslt_one = qp(&[1., 1., 1.], &[1.]).solve(&param).unwrap();
+        assert!(rslt_one.dropped_eqs.is_empty());
+
+        // a duplicated row and a combination of rows
+        let dup = qp(&[1., 1., 1.,
+                       1., -1., 0.,
+                       1., 1., 1.,
+                       3., 1., 2.], &[1., 0., 1., 2.]);
+        let rslt = dup.solve(&param).unwrap();
+        println!("x = {} nu = {}", rslt.x, rslt.nu);
+        assert_eq!(rslt.status, SolverStatus::Converged);
+        // rows of larger norms are kept
+        assert_eq!(rslt.dropped_eqs, vec![0, 2]);
+        assert_eq!(rslt.nu[(0, 0)], 0.);
+        assert_eq!(rslt.nu[(2, 0)], 0.);
+        let rslt_two = qp(&[1., 1., 1.,
+                            1., -1., 0.], &[1., 0.]).solve(&param).unwrap();
+        assert!((&rslt.x - &rslt_two.x).norm_p2() < 1e-6);
+
+        let mut log = Vec::new();
+        let rslt = qp(&[1., 1., 1.,
+                        2., 2., 2.], &[1., 2.]).solve_with(&mut PDIPM::new(), &param, &mut log).unwrap();
+        assert_eq!(rslt.dropped_eqs, vec![0]);
+        assert!((&rslt.x - &rslt_one.x).norm_p2() < 1e-6);
+        assert!(String::from_utf8(log).unwrap().contains("warning: equality rows [0]"));
+
+        // the duplicated row conflicts
+        let conflict = qp(&[1., 1., 1.,
+                            1., 1., 1.], &[1., 2.]);
+        match conflict.solve(&param) {
+            Err(SolverError::Infeasible(msg)) => {
+                println!("{}", msg);
+                assert!(msg.contains("eq[0]") && msg.contains("eq[1]"));
+            },
+            rslt => panic!("{:?}", rslt)
+        }
+
+        // regularization instead of dropping
+        let param_reg = PDIPMParam {
+            dependent_rows: DependentRows::Regularize,
+            .. Default::default()
+        };
+        let rslt = qp(&[1., 1., 1.,
+                        1., 1., 1.], &[1., 1.]).solve(&param_reg).unwrap();
+        assert!(rslt.dropped_eqs.is_empty());
+        assert!((&rslt.x - &rslt_one.x).norm_p2() < 1e-6);
+    }
+
+    #[test]
+    fn test_not_converged_diagnosis()
+    {
+        let n: usize = 2;
+
+        // x0 >= 1 and x0 + x1 <= 0 with x1 >= 0 conflict, and its LP stalls
+        let vec_c = Mat::new_vec(n).set_iter(&[1., 1.]);
+        let mat_g = Mat::new(3, n).set_iter(&[
+            -1., 0.,
+            1., 1.,
+            0., -1.
+        ]);
+        let vec_h = Mat::new_vec(3).set_iter(&[-1., 0., 0.]);
+
+        let param = PDIPMParam {
+            max_iter: Some(50),
+            .. Default::default()
+        };
+        let err = PDIPM::new().solve_lp(&param, &mut std::io::sink(),
+                                        &vec_c, &mat_g, &vec_h,
+                                        &Mat::new(0, n), &Mat::new_vec(0)).unwrap_err();
+        println!("{}", err);
+        println!("{:?}", err);
+
+        match &err {
+            SolverError::NotConverged(fail) => {
+                assert_eq!(fail.reason, "iteration: not converged");
+                let r = fail.residuals.unwrap();
+                assert!(r.r_pri.max(r.r_dual).max(r.gap) > param.eps);
+                assert!(fail.worst_complementarity.unwrap() < 3);
+                let s = fail.step_size.unwrap();
+                assert!((s > 0.) && (s <= fail.step_max.unwrap()));
+                assert!(fail.kkt_cond.unwrap() >= 1.);
+                assert!(fail.diagnosis().unwrap().contains("stalled at"));
+            },
+            _ => panic!("{:?}", err)
+        }
+        assert!(err.to_string().starts_with("not converged: iteration: not converged ("));
+
+        // estimated without the SVD
+        let param = PDIPMParam {factorization: Factorization::LU, .. param};
+        let err = PDIPM::new().solve_lp(&param, &mut std::io::sink(),
+                                        &vec_c, &mat_g, &vec_h,
+                                        &Mat::new(0, n), &Mat::new_vec(0)).unwrap_err();
+        match &err {
+            SolverError::NotConverged(fail) => assert!(fail.kkt_cond.unwrap() >= 1.),
+            _ => panic!("{:?}", err)
+        }
+
+        // failures not attributed to iterations have no diagnosis
+        let fail = ConvergenceFailure::from("status: primal infeasible");
+        assert_eq!(fail.diagnosis(), None);
+        assert_eq!(SolverError::NotConverged(fail).to_string(), "not converged: status: primal infeasible");
+    }
+
     #[test]
     fn test_sdp()
     {
@@ -266,5 +713,15 @@ mod tests {
         let eps = 1e-3; // solve_sdp() is not so accurate
         println!("rslt = {}", rslt);
         assert!((&rslt - exp).norm_p2() < eps);
+
+        let report = verify_sdp(&rslt, &vec_c, &mat_f, &mat_a, &vec_b, eps);
+        println!("report = {:?}", report);
+        assert!(report.is_feasible());
+
+        // the same problem in svec form
+        let svec_f: Vec<Mat> = mat_f.iter().map(|f| f.svec()).collect();
+        let prob = SDPProblem::new_svec(vec_c.clone(), &svec_f, k, mat_a.clone(), vec_b.clone());
+        assert_eq!(prob.mat_f, mat_f);
+        assert_eq!(prob.solve(&param).unwrap().x, rslt);
     }
 }
@@ -1,8 +1,18 @@
 //! Linear program
 
 use super::prelude::*;
+use super::problem::{Problem, ProblemDims, SolverResult, SolverError, Sense, RangeConstraints, ProblemNames};
+use super::verify::{violation_qp, ViolationReport};
+use super::problem::{check_dim, check_col_vec};
+use super::stats::ProblemStats;
+use super::selfdual;
 
 use std::io::Write;
+macro_rules! writeln_or {
+    ( $( $arg: expr ),* ) => {
+        writeln!( $( $arg ),* ).or(Err(SolverError::LogFailure))
+    };
+}
 
 /// Linear program
 /// 
@@ -34,58 +44,218 @@ use std::io::Write;
 /// 
 /// In the following, \\( d \\) does not appear since it does not matter.
 pub trait LP {
+    #[allow(clippy::too_many_arguments)]
     fn solve_lp<L>(&mut self, param: &PDIPMParam, log: &mut L,
                    vec_c: &Mat,
                    mat_g: &Mat, vec_h: &Mat,
                    mat_a: &Mat, vec_b: &Mat)
-                   -> Result<Mat, String>
+                   -> Result<Mat, SolverError>
     where L: Write;
 }
 
-fn check_param(vec_c: &Mat,
-               mat_g: &Mat, vec_h: &Mat,
-               mat_a: &Mat, vec_b: &Mat)
-               -> Result<(usize, usize, usize), String>
+/// Linear program inputs
+/// 
+/// See [`LP`](trait.LP.html) for the problem formulation.
+#[derive(Debug, Clone)]
+pub struct LPProblem
 {
-    let (n, _) = vec_c.size();
-    let (m, _) = mat_g.size();
-    let (p, _) = mat_a.size();
+    /// \\(c\\)
+    pub vec_c: Mat,
+    /// \\(G\\)
+    pub mat_g: Mat,
+    /// \\(h\\)
+    pub vec_h: Mat,
+    /// \\(A\\)
+    pub mat_a: Mat,
+    /// \\(b\\)
+    pub vec_b: Mat,
+    /// Sense of the objective, `Sense::Minimize` by default.
+    pub objective_sense: Sense,
+    /// Two-sided constraints in addition to \\( G x \\preceq h \\), none by default.
+    pub ranges: Option<RangeConstraints>,
+    /// Names of variables and constraints, none by default.
+    pub names: Option<Box<ProblemNames>>
+}
 
-    if n == 0 {return Err("vec_c: 0 rows".into());}
-    // m = 0 means NO inequality constraints
-    // p = 0 means NO equality constraints
+impl LPProblem
+{
+    /// Makes a problem.
+    pub fn new(vec_c: Mat,
+               mat_g: Mat, vec_h: Mat,
+               mat_a: Mat, vec_b: Mat) -> LPProblem
+    {
+        LPProblem {
+            vec_c,
+            mat_g, vec_h,
+            mat_a, vec_b,
+            objective_sense: Sense::default(),
+            ranges: None,
+            names: None
+        }
+    }
+    /// Sets the sense of the objective.
+    pub fn with_sense(mut self, sense: Sense) -> LPProblem
+    {
+        self.objective_sense = sense;
+        self
+    }
 
-    if vec_c.size() != (n, 1) {return Err(format!("vec_c: size {:?} must be {:?}", vec_c.size(), (n, 1)));}
-    if mat_g.size() != (m, n) {return Err(format!("mat_g: size {:?} must be {:?}", mat_g.size(), (m, n)));}
-    if vec_h.size() != (m, 1) {return Err(format!("vec_h: size {:?} must be {:?}", vec_h.size(), (m, 1)));}
-    if mat_a.size() != (p, n) {return Err(format!("mat_a: size {:?} must be {:?}", mat_a.size(), (p, n)));}
-    if vec_b.size() != (p, 1) {return Err(format!("vec_b: size {:?} must be {:?}", vec_b.size(), (p, 1)));}
+    /// Adds two-sided constraints \\( l \\preceq C x \\preceq u \\).
+    ///
+    /// See [`RangeConstraints`](../problem/struct.RangeConstraints.html), each row of which is one row of the KKT matrix.
+    /// They are not supported by `Formulation::SelfDual`.
+    /// Their dual variables are reported in
+    /// [`SolverResult::range_dual`](../problem/struct.SolverResult.html#structfield.range_dual).
+    pub fn with_ranges(mut self, mat_c: Mat, vec_l: Mat, vec_u: Mat) -> LPProblem
+    {
+        self.ranges = Some(RangeConstraints::new(mat_c, vec_l, vec_u));
+        self
+    }
 
-    Ok((n, m, p))
-}
+    /// Names variables and constraints.
+    ///
+    /// See [`ProblemNames`](../problem/struct.ProblemNames.html).
+    pub fn with_names(mut self, names: ProblemNames) -> LPProblem
+    {
+        self.names = Some(Box::new(names));
+        self
+    }
 
-impl LP for PDIPM
-{
-    /// Runs the solver with given parameters.
+    /// Returns statistics of the inputs, which may be examined before solving.
+    ///
+    /// See [`ProblemStats`](../stats/struct.ProblemStats.html).
+    pub fn analyze(&self) -> ProblemStats
+    {
+        self.h_stats(true)
+    }
+
+    fn h_stats(&self, with_cond: bool) -> ProblemStats
+    {
+        self.h_inputs().stats(with_cond)
+    }
+
+    fn h_inputs(&self) -> LPInputs<'_>
+    {
+        LPInputs {
+            vec_c: &self.vec_c,
+            mat_g: &self.mat_g, vec_h: &self.vec_h,
+            mat_a: &self.mat_a, vec_b: &self.vec_b,
+            ranges: self.ranges.as_ref()
+        }
+    }
+
+    /// Reports constraint violations of `x`, listing at most `k` worst violators.
+    ///
+    /// See [`violation_qp`](../verify/fn.violation_qp.html).
+    /// Constraints are labeled by their names if given. Two-sided constraints are not included.
+    pub fn violation(&self, x: &Mat, k: usize) -> ViolationReport
+    {
+        let report = violation_qp(x, &self.mat_g, &self.vec_h, &self.mat_a, &self.vec_b, k);
+
+        match &self.names {
+            Some(names) => {
+                let ProblemDims {m, p, ..} = self.dims();
+                report.with_names(names.constraints(m, p))
+            },
+            None => report
+        }
+    }
+
+    /// Runs the solver with given parameters, reusing a solver instance.
     /// 
-    /// Returns `Ok` with optimal \\(x\\) or `Err` with message string.
+    /// Returns `Ok` with [`SolverResult`](../problem/struct.SolverResult.html)
+    /// or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `solver` is a solver instance.
     /// * `param` is solver parameters.
     /// * `log` outputs solver progress.
-    /// * `vec_c` is \\(c\\).
-    /// * `mat_g` is \\(G\\).
-    /// * `vec_h` is \\(h\\).
-    /// * `mat_a` is \\(A\\).
-    /// * `vec_b` is \\(b\\).
-    fn solve_lp<L>(&mut self, param: &PDIPMParam, log: &mut L,
-                   vec_c: &Mat,
-                   mat_g: &Mat, vec_h: &Mat,
-                   mat_a: &Mat, vec_b: &Mat)
-                   -> Result<Mat, String>
+    pub fn solve_with<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                         -> Result<SolverResult, SolverError>
     where L: Write
     {
         // ----- parameter check
 
-        let (n, m, p) = check_param(vec_c, mat_g, vec_h, mat_a, vec_b)?;
+        let lap = Timings::start(param);
+        self.validate()?;
+        let validation = Timings::elapsed(lap);
+
+        let rslt = self.h_inputs().solve_with(self.objective_sense, solver, param, log)?;
+
+        Ok(rslt.with_validation(validation))
+    }
+}
+
+// inputs of LPProblem without its options, borrowed so that LP::solve_lp does not copy them:
+// used by solver modules
+#[derive(Clone, Copy)]
+pub(crate) struct LPInputs<'a>
+{
+    pub(crate) vec_c: &'a Mat,
+    pub(crate) mat_g: &'a Mat,
+    pub(crate) vec_h: &'a Mat,
+    pub(crate) mat_a: &'a Mat,
+    pub(crate) vec_b: &'a Mat,
+    pub(crate) ranges: Option<&'a RangeConstraints>
+}
+
+impl LPInputs<'_>
+{
+    fn dims(&self) -> ProblemDims
+    {
+        ProblemDims {
+            n: self.vec_c.size().0,
+            m: self.mat_g.size().0,
+            p: self.mat_a.size().0
+        }
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, m, p} = self.dims();
+        let vec_c = self.vec_c;
+        let (mat_g, vec_h) = (self.mat_g, self.vec_h);
+        let (mat_a, vec_b) = (self.mat_a, self.vec_b);
+
+        if n == 0 {return Err(SolverError::InvalidProblem("vec_c: 0 rows".into()));}
+        // m = 0 means NO inequality constraints
+        // p = 0 means NO equality constraints
+
+        check_col_vec("vec_c", vec_c)?;
+        check_dim("mat_g columns", mat_g.size().1, "vec_c rows", n)?;
+        check_dim("vec_h rows", vec_h.size().0, "mat_g rows", m)?;
+        check_col_vec("vec_h", vec_h)?;
+        check_dim("mat_a columns", mat_a.size().1, "vec_c rows", n)?;
+        check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+        check_col_vec("vec_b", vec_b)?;
+
+        Ok(())
+    }
+
+    fn stats(&self, with_cond: bool) -> ProblemStats
+    {
+        ProblemStats::new(self.dims(), None,
+                          vec![("vec_c", self.vec_c), ("mat_g", self.mat_g), ("vec_h", self.vec_h), ("mat_a", self.mat_a), ("vec_b", self.vec_b)],
+                          (self.mat_g, self.vec_h), self.mat_a, with_cond)
+    }
+
+    fn solve_with<L>(&self, sense: Sense, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                     -> Result<SolverResult, SolverError>
+    where L: Write
+    {
+        if param.formulation == Formulation::SelfDual {
+            if self.ranges.is_some() {
+                return Err(SolverError::InvalidProblem("ranges: not supported by Formulation::SelfDual".into()));
+            }
+            return selfdual::solve_lp(*self, sense, solver, param, log);
+        }
+
+        if param.log_stats {
+            writeln_or!(log, "problem: {}", self.stats(false))?;
+        }
+
+        let ProblemDims {n, m, p} = self.dims();
+        let vec_c = &(sense.sign() * self.vec_c);
+        let (mat_g, vec_h) = (self.mat_g, self.vec_h);
+        let (mat_a, vec_b) = (self.mat_a, self.vec_b);
 
         // ----- initial value of a slack variable
 
@@ -97,12 +267,19 @@ impl LP for PDIPM
             s_initial = s + margin;
         }
 
+        // two-sided constraints, which do not involve the slack variable
+        if let Some(ranges) = self.ranges {
+            let mut mat_c = Mat::new(ranges.mat_c.size().0, n + 1);
+            mat_c.cols_mut(0 .. n).assign(&ranges.mat_c);
+            solver.set_ranges(mat_c, ranges.vec_l.clone(), ranges.vec_u.clone());
+        }
+
         // ----- start to solve
 
-        let rslt = self.solve(param, log,
+        let rslt = solver.solve(param, log,
             n + 1, m, p + 1, // '+ 1' is for a slack variable
             |_, df_o| {
-                df_o.rows_mut(0 .. n).assign(&vec_c);
+                df_o.rows_mut(0 .. n).assign(vec_c);
                 // for a slack variable
                 df_o[(n, 0)] = 0.;
             },
@@ -116,7 +293,7 @@ impl LP for PDIPM
                 ))
             },
             |_, df_i| {
-                df_i.cols_mut(0 .. n).assign(&mat_g);
+                df_i.cols_mut(0 .. n).assign(mat_g);
                 // for a slack variable
                 df_i.col_mut(n).assign_all(-(m as FP));
             },
@@ -137,9 +314,110 @@ impl LP for PDIPM
             }
         );
 
-        match rslt {
-            Ok(y) => Ok(y.rows(0 .. n).clone_sz()),
-            Err(s) => Err(s.into())
+        rslt?;
+
+        let rslt = SolverResult::from_solver(solver, self.dims(), (n + 1, m),
+                                             sense, |x| self.vec_c.prod(x));
+        let rslt = rslt.with_gap(|x| (self.mat_g * x - self.vec_h, self.mat_a * x - self.vec_b))
+                       .with_range_dual(solver, self.ranges);
+
+        Ok(rslt)
+    }
+}
+
+
+impl Problem for LPProblem
+{
+    fn dims(&self) -> ProblemDims
+    {
+        self.h_inputs().dims()
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, ..} = self.dims();
+
+        self.h_inputs().validate()?;
+        let names = ProblemNames::of(&self.names);
+        names.validate(self.dims(), self.ranges.as_ref().map_or(0, |r| r.mat_c.size().0))?;
+        if let Some(ranges) = &self.ranges {
+            ranges.validate(n, names)?;
         }
+
+        Ok(())
+    }
+
+    fn solve(&self, param: &PDIPMParam) -> Result<SolverResult, SolverError>
+    {
+        self.solve_with(&mut PDIPM::new(), param, &mut std::io::sink())
     }
 }
+
+impl LP for PDIPM
+{
+    /// Runs the solver with given parameters.
+    /// 
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    /// * `vec_c` is \\(c\\).
+    /// * `mat_g` is \\(G\\).
+    /// * `vec_h` is \\(h\\).
+    /// * `mat_a` is \\(A\\).
+    /// * `vec_b` is \\(b\\).
+    fn solve_lp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                   vec_c: &Mat,
+                   mat_g: &Mat, vec_h: &Mat,
+                   mat_a: &Mat, vec_b: &Mat)
+                   -> Result<Mat, SolverError>
+    where L: Write
+    {
+        let inputs = LPInputs {
+            vec_c,
+            mat_g, vec_h,
+            mat_a, vec_b,
+            ranges: None
+        };
+
+        inputs.validate()
+              .and_then(|_| inputs.solve_with(Sense::Minimize, self, param, log))
+              .and_then(|rslt| rslt.converged_x())
+              .map_err(|e| self.diagnose(e))
+    }
+}
+
+#[test]
+fn test_lp_ranges()
+{
+    let param = PDIPMParam::default();
+
+    // -1 <= x0 <= 2 and x1 <= 3 with no lower side
+    let lp = |sense| {
+        LPProblem::new(Mat::new_vec(2).set_all(1.),
+                       Mat::new(0, 2), Mat::new_vec(0),
+                       Mat::new(0, 2), Mat::new_vec(0))
+                  .with_sense(sense)
+                  .with_ranges(Mat::new(2, 2).set_eye(),
+                               Mat::new_vec(2).set_iter(&[-1., FP::NEG_INFINITY]),
+                               Mat::new_vec(2).set_iter(&[2., 3.]))
+    };
+
+    let rslt = lp(Sense::Maximize).solve(&param).unwrap();
+    println!("x = {} range_dual = {}", rslt.x, rslt.range_dual);
+    assert!((rslt.x[(0, 0)] - 2.).abs() < 1e-6);
+    assert!((rslt.x[(1, 0)] - 3.).abs() < 1e-6);
+    assert_eq!(rslt.lmd.size(), (0, 1));
+    assert!((&rslt.range_dual + Mat::new_vec(2).set_all(1.)).norm_p2() < 1e-6);
+
+    // unbounded below on x1
+    let rslt = lp(Sense::Minimize).solve(&param);
+    assert!(!matches!(rslt, Ok(SolverResult {status: SolverStatus::Converged, ..})));
+
+    let param_sd = PDIPMParam {formulation: Formulation::SelfDual, .. Default::default()};
+    assert!(matches!(lp(Sense::Maximize).solve(&param_sd), Err(SolverError::InvalidProblem(_))));
+
+    let bad = lp(Sense::Minimize).with_ranges(Mat::new(1, 2).set_all(1.),
+                                              Mat::new_vec(1).set_all(1.),
+                                              Mat::new_vec(1).set_all(0.));
+    assert!(matches!(bad.validate(), Err(SolverError::InvalidProblem(_))));
+}
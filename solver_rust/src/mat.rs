@@ -7,8 +7,13 @@ See also [`MatGen`](struct.MatGen.html) for supported methods.
 
 /// Scalar floating point type
 pub type FP = f64;
-pub use std::f64::EPSILON as FP_EPSILON;
-pub use std::f64::MIN_POSITIVE as FP_MINPOS;
+pub const FP_EPSILON: FP = FP::EPSILON;
+pub const FP_MINPOS: FP = FP::MIN_POSITIVE;
+
+// tolerance of asymmetry for solve and chol_factor_or_sqrt to try Cholesky
+const SOLVE_SYM_TOL: FP = FP_EPSILON * 16.;
+// tolerance of negative eigenvalues for sqrt_psd, relative to the largest magnitude per dimension
+const PSD_TOL: FP = FP_EPSILON * 16.;
 
 /// Matrix
 pub type Mat = MatGen<Vec<FP>>;
@@ -22,6 +27,33 @@ use std::ops::{Range, RangeBounds, Bound};
 use std::ops::{Neg, Add, Mul, Sub, Div, AddAssign, SubAssign, MulAssign, DivAssign};
 use std::ops::{Index, IndexMut};
 use std::fmt;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use crate::matsvd::MatSVD;
+use crate::matlu::{MatLU, SingularError};
+use crate::matchol::MatChol;
+use crate::matqr::MatQR;
+use crate::mateig::{MatEig, EigPair, NotConverged, NotPsd};
+
+#[cfg(feature = "nalgebra")]
+mod conv_nalgebra;
+#[cfg(feature = "ndarray")]
+mod conv_ndarray;
+#[cfg(feature = "ndarray")]
+pub use conv_ndarray::MatCow;
+#[cfg(feature = "serde")]
+mod conv_serde;
+
+/// Storage order of matrix elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout
+{
+    /// Elements of each column are adjacent.
+    ColMajor,
+    /// Elements of each row are adjacent.
+    RowMajor
+}
 
 /// Ownership view of matrix array entity
 pub trait View {
@@ -112,7 +144,9 @@ pub struct MatGen<V: View>
     ncols: usize,
     //
     offset: usize,
-    stride: usize,
+    // distance between adjacent columns and rows in storage, negative if flipped
+    stride: isize,
+    rstride: isize,
     //
     transposed: bool,
     //
@@ -124,12 +158,9 @@ impl<V: View> MatGen<V>
     // private helper methods
     fn h_index(&self, index: (usize, usize)) -> usize
     {
-        if !self.transposed {
-            self.offset + self.stride * index.1 + index.0
-        }
-        else {
-            self.offset + self.stride * index.0 + index.1
-        }
+        let (r, c) = if !self.transposed {index} else {(index.1, index.0)};
+
+        (self.offset as isize + self.stride * c as isize + self.rstride * r as isize) as usize
     }
     //
     fn h_bound<RR, CR>(&self, rows: RR, cols: CR) -> (Range<usize>, Range<usize>)
@@ -175,6 +206,7 @@ impl<V: View> MatGen<V>
                 ncols: self.ncols,
                 offset: self.offset,
                 stride: self.stride,
+                rstride: self.rstride,
                 transposed: self.transposed,
                 view: self.view.get_own()
             }
@@ -184,17 +216,245 @@ impl<V: View> MatGen<V>
         }
     }
     //
+    // (row, column) of a column or row vector along its long dimension
+    fn h_vec_index(&self, i: usize) -> (usize, usize)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        let (index, len) = if l_ncols == 1 {
+            ((i, 0), l_nrows)
+        }
+        else if l_nrows == 1 {
+            ((0, i), l_ncols)
+        }
+        else {
+            panic!("not a vector, size is {}x{}", l_nrows, l_ncols);
+        };
+
+        assert!(i < len, "index {} out of range for vector of length {}", i, len);
+        index
+    }
+    //
+    // disjoint mutable views of rows or columns, which must not be interleaved in storage
+    fn h_lines_mut(&mut self, rows: bool) -> impl Iterator<Item=MatSliMu<'_>>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (count, len) = if rows {(l_nrows, l_ncols)} else {(l_ncols, l_nrows)};
+
+        // lines of a storage column are apart by stride, and those of a single element are apart by rstride
+        let (lstep, istep) = if rows == self.transposed {
+            (self.stride, self.rstride)
+        }
+        else if len <= 1 {
+            (self.rstride, 1)
+        }
+        else {
+            panic!("{} of {} layout are interleaved in storage, use {} instead",
+                   if rows {"rows"} else {"columns"},
+                   if !self.transposed {"column-major"} else {"row-major"},
+                   if rows {"row_mut"} else {"col_mut"});
+        };
+        // column vectors, or row vectors as transposed ones
+        let transposed = rows;
+
+        let lines = if (count == 0) || (len == 0) {
+            (0 .. count).map(|_| MatGen {
+                nrows: 0,
+                ncols: 1,
+                offset: 0,
+                stride: 0,
+                rstride: 1,
+                transposed,
+                view: &mut [][..]
+            }).collect()
+        }
+        else {
+            // chunked from the line lowest in storage, then ordered as logical if flipped
+            let span = istep.unsigned_abs() * (len - 1) + 1;
+            let base = self.offset as isize + lstep.min(0) * (count - 1) as isize + istep.min(0) * (len - 1) as isize;
+            let view = &mut self.view.get_mut()[base as usize ..];
+
+            let mut lines: Vec<_> = view.chunks_mut(lstep.unsigned_abs().max(1)).take(count).map(|line| MatGen {
+                nrows: len,
+                ncols: 1,
+                offset: if istep < 0 {span - 1} else {0},
+                stride: len as isize,
+                rstride: istep,
+                transposed,
+                view: &mut line[.. span]
+            }).collect();
+
+            if lstep < 0 {
+                lines.reverse();
+            }
+            lines
+        };
+
+        lines.into_iter()
+    }
+    //
+    // two disjoint mutable views split at a row or a column, which must not be interleaved in storage
+    fn h_split_mut(&mut self, at: usize, cols: bool) -> (MatSliMu<'_>, MatSliMu<'_>)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let len = if cols {l_ncols} else {l_nrows};
+        assert!(at <= len, "split at {} beyond {} {}", at, len, if cols {"columns"} else {"rows"});
+
+        let (nrows, ncols, offset, stride, rstride, transposed) = (self.nrows, self.ncols, self.offset, self.stride, self.rstride, self.transposed);
+
+        // storage columns are apart by stride, and storage rows of a single element are apart by rstride
+        let storage_cols = cols != transposed;
+        let (lstep, istep, ilen) = if storage_cols {
+            (stride, rstride, nrows)
+        }
+        else if ncols <= 1 {
+            (rstride, 1, ncols)
+        }
+        else {
+            panic!("{} of {} layout are interleaved in storage, cannot be split",
+                   if cols {"columns"} else {"rows"},
+                   if !transposed {"column-major"} else {"row-major"});
+        };
+
+        let view = self.view.get_mut();
+        let (head, tail) = if (at == 0) || (at == len) || (ilen == 0) {
+            // either has no elements
+            view.split_at_mut(if at == len {view.len()} else {0})
+        }
+        else {
+            // at the lowest element of the latter lines in storage, or the former ones if flipped
+            let line = if lstep >= 0 {at} else {at - 1};
+            let split = offset as isize + lstep * line as isize + istep.min(0) * (ilen - 1) as isize;
+            view.split_at_mut(split as usize)
+        };
+
+        let part = |lines: usize, offset: isize, view| {
+            let offset = offset as usize;
+            if storage_cols {
+                MatGen {nrows, ncols: lines, offset, stride, rstride, transposed, view}
+            }
+            else {
+                MatGen {nrows: lines, ncols, offset, stride, rstride, transposed, view}
+            }
+        };
+
+        let former_offset = offset as isize;
+        let latter_offset = offset as isize + lstep * at as isize;
+        let head_len = head.len() as isize;
+
+        if (at == 0) || (ilen == 0) {
+            (part(at, 0, head), part(len - at, former_offset, tail))
+        }
+        else if at == len {
+            (part(at, former_offset, head), part(0, 0, tail))
+        }
+        else if lstep >= 0 {
+            (part(at, former_offset, head), part(len - at, latter_offset - head_len, tail))
+        }
+        else {
+            (part(at, former_offset - head_len, tail), part(len - at, latter_offset, head))
+        }
+    }
+    //
+    // offset of a slice in storage ranges, which stays if empty not to go out of the buffer when flipped
+    fn h_slice_offset(&self, row_range: &Range<usize>, col_range: &Range<usize>) -> usize
+    {
+        if row_range.is_empty() || col_range.is_empty() {
+            self.offset
+        }
+        else {
+            (self.offset as isize + self.stride * col_range.start as isize + self.rstride * row_range.start as isize) as usize
+        }
+    }
+    //
+    // reverses rows if rows, or columns otherwise
+    fn h_flip(mut self, rows: bool) -> MatGen<V>
+    {
+        if rows != self.transposed {
+            // storage rows
+            if self.nrows > 0 {
+                self.offset = (self.offset as isize + self.rstride * (self.nrows - 1) as isize) as usize;
+            }
+            self.rstride = -self.rstride;
+        }
+        else {
+            // storage columns
+            if self.ncols > 0 {
+                self.offset = (self.offset as isize + self.stride * (self.ncols - 1) as isize) as usize;
+            }
+            self.stride = -self.stride;
+        }
+        self
+    }
+    //
+    // keeps every step-th row if rows, or column otherwise
+    fn h_step(mut self, step: usize, rows: bool) -> MatGen<V>
+    {
+        assert!(step > 0, "step must be positive");
+
+        if rows != self.transposed {
+            // storage rows
+            self.nrows = self.nrows.div_ceil(step);
+            self.rstride *= step as isize;
+        }
+        else {
+            // storage columns
+            self.ncols = self.ncols.div_ceil(step);
+            self.stride *= step as isize;
+        }
+        self
+    }
+    //
+    // offset and length of k-th diagonal, upper if k > 0 and lower if k < 0
+    fn h_diag(&self, k: isize) -> (usize, usize)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r0, c0) = if k >= 0 {(0, k as usize)} else {(k.unsigned_abs(), 0)};
+
+        let len = l_nrows.saturating_sub(r0).min(l_ncols.saturating_sub(c0));
+        let offset = if len > 0 {self.h_index((r0, c0))} else {self.offset};
+
+        (offset, len)
+    }
+    //
+    // column-major elements as a single slice if contiguous and non-transposed
+    pub(crate) fn h_col_major(&self) -> Option<&[FP]>
+    {
+        if !self.transposed && ((self.rstride == 1) || (self.nrows <= 1)) && ((self.stride == self.nrows as isize) || (self.ncols <= 1)) {
+            Some(&self.view.get_ref()[self.offset .. self.offset + self.nrows * self.ncols])
+        }
+        else {
+            None
+        }
+    }
+    //
     /// *new* - Makes a matrix.
+    ///
+    /// Panics if the number of elements overflows, see [`try_new`](#method.try_new).
     pub fn new(nrows: usize, ncols: usize) -> Mat
     {
-        MatGen {
+        match Mat::try_new(nrows, ncols) {
+            Ok(mat) => mat,
+            Err(e) => panic!("{}", e)
+        }
+    }
+    /// *new* - Makes a matrix, checking the number of elements and its size in bytes for overflow.
+    pub fn try_new(nrows: usize, ncols: usize) -> Result<Mat, MatError>
+    {
+        let len = h_len(nrows, ncols)?;
+
+        #[cfg(feature = "alloc-count")]
+        crate::alloc_stats::record(len);
+
+        Ok(MatGen {
             nrows,
             ncols,
             offset: 0,
-            stride: nrows,
+            stride: nrows as isize,
+            rstride: 1,
             transposed: false,
-            view: vec![0.0; nrows * ncols]
-        }
+            view: vec![0.0; len]
+        })
     }
     /// *new* - Makes a matrix of the same size.
     pub fn new_like<V2: View>(mat: &MatGen<V2>) -> Mat
@@ -208,134 +468,684 @@ impl<V: View> MatGen<V>
     {
         Mat::new(nrows, 1)
     }
+    /// *new* - Makes a matrix whose elements are stored in row-major order.
+    ///
+    /// It is a transposed view over a column-major buffer internally,
+    /// and works as a matrix made by [`new`](#method.new) except for its [`layout`](#method.layout).
+    pub fn new_row_major(nrows: usize, ncols: usize) -> Mat
+    {
+        Mat::new(ncols, nrows).set_t()
+    }
+    /// *new* - Makes a matrix from a vector of row-major elements, taking over the vector without copying.
+    ///
+    /// Panics if the length of `vec` is not `nrows * ncols`.
+    pub fn from_row_major_vec_noswap(nrows: usize, ncols: usize, vec: Vec<FP>) -> Mat
+    {
+        assert_eq!(vec.len(), nrows * ncols);
+
+        MatGen {
+            nrows: ncols,
+            ncols: nrows,
+            offset: 0,
+            stride: ncols as isize,
+            rstride: 1,
+            transposed: true,
+            view: vec
+        }
+    }
+    /// *new* - Makes a matrix from a vector of column-major elements, taking over the vector without copying.
+    ///
+    /// Note that elements are column-major, unlike [`set_iter`](#method.set_iter) which is row-wise.
+    /// Panics if the length of `vec` is not `nrows * ncols`.
+    ///
+    /// See also `TryFrom<(usize, usize, Vec<FP>)>` for the fallible version.
+    pub fn from_vec(nrows: usize, ncols: usize, vec: Vec<FP>) -> Mat
+    {
+        match Mat::try_from((nrows, ncols, vec)) {
+            Ok(mat) => mat,
+            Err(e) => panic!("{}", e)
+        }
+    }
+    /// *new* - Makes a square matrix with a given diagonal.
+    pub fn new_diag(diag: &[FP]) -> Mat
+    {
+        Mat::new(diag.len(), diag.len()).set_diag(diag)
+    }
+    /// *new* - Makes a square matrix with a diagonal given by iterator.
+    pub fn new_diag_iter<'b, T>(iter: T) -> Mat
+    where T: IntoIterator<Item=&'b FP>
+    {
+        let diag: Vec<FP> = iter.into_iter().copied().collect();
+
+        Mat::new_diag(&diag)
+    }
+    /// *new* - Makes a symmetric matrix from its scaled symmetric vectorization, the inverse of [`svec`](#method.svec).
+    ///
+    /// Panics if `v` is not a row or column vector of length \\(n(n+1)/2\\).
+    pub fn smat<V2: View>(v: &MatGen<V2>, n: usize) -> Mat
+    {
+        h_check_vec("smat", v.size(), n * (n + 1) / 2);
+
+        let mut mat = Mat::new(n, n);
+        let mut i = 0;
+        for c in 0 .. n {
+            mat[(c, c)] = v[i];
+            for r in c + 1 .. n {
+                let e = v[i + r - c] * FP::sqrt(0.5);
+                mat[(r, c)] = e;
+                mat[(c, r)] = e;
+            }
+            i += n - c;
+        }
+        mat
+    }
+    /// *new* - Makes a matrix picking elementwise from `a` where `mask` is nonzero and from `b` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn select<M, A, B>(mask: M, a: A, b: B) -> Mat
+    where M: MatAcc, A: MatAcc, B: MatAcc
+    {
+        let (nrows, ncols) = mask.acc_size();
+        h_check_size("select", (nrows, ncols), a.acc_size());
+        h_check_size("select", (nrows, ncols), b.acc_size());
+
+        Mat::new(nrows, ncols).set_by(|r, c| if mask.acc_get(r, c) != 0. {a.acc_get(r, c)} else {b.acc_get(r, c)})
+    }
+    /// *new* - Makes a column vector of `n` evenly spaced values from `a` to `b`, both inclusive.
+    ///
+    /// The last element is exactly `b`. It is `[a]` if `n` is 1.
+    pub fn linspace(a: FP, b: FP, n: usize) -> Mat
+    {
+        Mat::new_vec(n).set_by(|r, _| {
+            if r == 0 {
+                a
+            }
+            else if r == n - 1 {
+                b
+            }
+            else {
+                a + (b - a) * (r as FP / (n - 1) as FP)
+            }
+        })
+    }
+    /// *new* - Makes a column vector of `start + i * step` less than `stop`, or greater for a negative `step`.
+    ///
+    /// Panics if `step` is zero or not finite.
+    pub fn arange(start: FP, stop: FP, step: FP) -> Mat
+    {
+        assert!((step != 0.) && step.is_finite(), "arange: step {} is invalid", step);
+
+        // the division may round either way, so elements are checked against stop
+        let beyond = |i: usize| (start + i as FP * step - stop) * step.signum() >= 0.;
+        let mut n = ((stop - start) / step).ceil().max(0.) as usize;
+        while (n > 0) && beyond(n - 1) {
+            n -= 1;
+        }
+        while !beyond(n) {
+            n += 1;
+        }
+
+        Mat::new_vec(n).set_by(|r, _| start + r as FP * step)
+    }
+    /// *new* - Makes a column vector of `n` geometrically spaced values from `a` to `b`, both inclusive.
+    ///
+    /// The first and last elements are exactly `a` and `b`.
+    /// Panics if `a` and `b` are zero or of different signs.
+    pub fn geomspace(a: FP, b: FP, n: usize) -> Mat
+    {
+        assert!(a * b > 0., "geomspace: {} and {} must be nonzero of the same sign", a, b);
+
+        let (la, lb) = (a.abs().ln(), b.abs().ln());
+
+        Mat::new_vec(n).set_by(|r, _| {
+            if r == 0 {
+                a
+            }
+            else if r == n - 1 {
+                b
+            }
+            else {
+                a.signum() * (la + (lb - la) * (r as FP / (n - 1) as FP)).exp()
+            }
+        })
+    }
+    /// *new* - Makes a Vandermonde matrix whose rows are powers \\(x_i^0, \\ldots, x_i^{n-1}\\) of `points`,
+    /// in increasing order if `increasing` or decreasing order otherwise.
+    ///
+    /// ```
+    /// use totsu::prelude::*;
+    ///
+    /// let v = Mat::vander(&[2., 3.], 3, true);
+    /// assert_eq!(v, Mat::new(2, 3).set_iter(&[
+    ///     1., 2., 4.,
+    ///     1., 3., 9.
+    /// ]));
+    /// ```
+    pub fn vander(points: &[FP], ncols: usize, increasing: bool) -> Mat
+    {
+        let mut mat = Mat::new(points.len(), ncols);
+
+        for (r, x) in points.iter().enumerate() {
+            let mut pow = 1.;
+            for i in 0 .. ncols {
+                let c = if increasing {i} else {ncols - 1 - i};
+                mat[(r, c)] = pow;
+                pow *= x;
+            }
+        }
+
+        mat
+    }
+    /// *new* - Makes a Toeplitz matrix, constant along each diagonal, from its first column and first row.
+    ///
+    /// Panics if the first elements of `first_col` and `first_row` differ.
+    ///
+    /// ```
+    /// use totsu::prelude::*;
+    ///
+    /// let t = Mat::toeplitz(&[1., 2., 3.], &[1., 4.]);
+    /// assert_eq!(t, Mat::new(3, 2).set_iter(&[
+    ///     1., 4.,
+    ///     2., 1.,
+    ///     3., 2.
+    /// ]));
+    /// ```
+    pub fn toeplitz(first_col: &[FP], first_row: &[FP]) -> Mat
+    {
+        if let (Some(c), Some(r)) = (first_col.first(), first_row.first()) {
+            assert!(c == r, "toeplitz: corner elements {} and {} differ", c, r);
+        }
+
+        Mat::new(first_col.len(), first_row.len()).set_by(|r, c| {
+            if r >= c {first_col[r - c]} else {first_row[c - r]}
+        })
+    }
+    /// *new* - Makes a circulant matrix, each column of which is the previous one rotated down by one.
+    ///
+    /// ```
+    /// use totsu::prelude::*;
+    ///
+    /// let c = Mat::circulant(&[1., 2., 3.]);
+    /// assert_eq!(c, Mat::new(3, 3).set_iter(&[
+    ///     1., 3., 2.,
+    ///     2., 1., 3.,
+    ///     3., 2., 1.
+    /// ]));
+    /// ```
+    pub fn circulant(first_col: &[FP]) -> Mat
+    {
+        let n = first_col.len();
+
+        Mat::new(n, n).set_by(|r, c| first_col[(r + n - c) % n])
+    }
+    /// *new* - Makes a matrix from an iterator of row-major elements.
+    ///
+    /// Returns `Err` if the number of elements is not `nrows * ncols`.
+    pub fn from_row_major<T>(nrows: usize, ncols: usize, iter: T) -> Result<Mat, MatError>
+    where T: IntoIterator<Item=FP>
+    {
+        let vec = h_collect(nrows, ncols, iter)?;
+
+        Ok(Mat::new(nrows, ncols).set_by(|r, c| vec[r * ncols + c]))
+    }
+    /// *new* - Makes a matrix from an iterator of column-major elements.
+    ///
+    /// Returns `Err` if the number of elements is not `nrows * ncols`.
+    pub fn from_col_major<T>(nrows: usize, ncols: usize, iter: T) -> Result<Mat, MatError>
+    where T: IntoIterator<Item=FP>
+    {
+        let vec = h_collect(nrows, ncols, iter)?;
+
+        Ok(Mat::from_vec(nrows, ncols, vec))
+    }
+    /// *new* - Makes a matrix concatenating `blocks` horizontally.
+    ///
+    /// Panics with the offending block index if the numbers of rows differ.
+    pub fn hstack(blocks: &[&dyn MatAcc]) -> Mat
+    {
+        let nrows = blocks.first().map_or(0, |b| b.acc_size().0);
+        for (i, b) in blocks.iter().enumerate() {
+            assert!(b.acc_size().0 == nrows,
+                    "hstack: block {} has {} rows, expected {}", i, b.acc_size().0, nrows);
+        }
+
+        Mat::block(&[blocks])
+    }
+    /// *new* - Makes a matrix concatenating `blocks` vertically.
+    ///
+    /// Panics with the offending block index if the numbers of columns differ.
+    pub fn vstack(blocks: &[&dyn MatAcc]) -> Mat
+    {
+        let ncols = blocks.first().map_or(0, |b| b.acc_size().1);
+        for (i, b) in blocks.iter().enumerate() {
+            assert!(b.acc_size().1 == ncols,
+                    "vstack: block {} has {} columns, expected {}", i, b.acc_size().1, ncols);
+        }
+
+        let rows: Vec<[&dyn MatAcc; 1]> = blocks.iter().map(|&b| [b]).collect();
+        let rows: Vec<&[&dyn MatAcc]> = rows.iter().map(|r| &r[..]).collect();
+        Mat::block(&rows)
+    }
+    /// *new* - Makes a matrix of 2D block layout, where `blocks` is a list of block rows.
+    ///
+    /// Panics with the offending block index if blocks in a block row differ in the number of rows,
+    /// or block rows differ in the total number of columns.
+    pub fn block(blocks: &[&[&dyn MatAcc]]) -> Mat
+    {
+        let mut heights = Vec::with_capacity(blocks.len());
+        let mut ncols = None;
+        for (i, row) in blocks.iter().enumerate() {
+            let height = row.first().map_or(0, |b| b.acc_size().0);
+            let mut width = 0;
+            for (j, b) in row.iter().enumerate() {
+                let (b_nrows, b_ncols) = b.acc_size();
+                assert!(b_nrows == height,
+                        "block: block ({}, {}) has {} rows, expected {}", i, j, b_nrows, height);
+                width += b_ncols;
+            }
+            let ncols = *ncols.get_or_insert(width);
+            assert!(width == ncols,
+                    "block: block row {} has {} columns, expected {}", i, width, ncols);
+            heights.push(height);
+        }
+
+        let mut mat = Mat::new(heights.iter().sum(), ncols.unwrap_or(0));
+
+        let mut r0 = 0;
+        for (row, height) in blocks.iter().zip(heights) {
+            let mut c0 = 0;
+            for b in row.iter() {
+                let (b_nrows, b_ncols) = b.acc_size();
+                mat.slice_mut(r0 .. r0 + b_nrows, c0 .. c0 + b_ncols).assign_by(|r, c| Some(b.acc_get(r, c)));
+                c0 += b_ncols;
+            }
+            r0 += height;
+        }
+
+        mat
+    }
+    /// *new* - Makes a block-diagonal matrix placing `blocks` along the diagonal, zero elsewhere.
+    ///
+    /// Blocks may be rectangular, so the size is the sums of the numbers of rows and columns of the blocks.
+    pub fn block_diag(blocks: &[&dyn MatAcc]) -> Mat
+    {
+        let nrows = blocks.iter().map(|b| b.acc_size().0).sum();
+        let ncols = blocks.iter().map(|b| b.acc_size().1).sum();
+
+        let mut mat = Mat::new(nrows, ncols);
+
+        let (mut r0, mut c0) = (0, 0);
+        for b in blocks {
+            let (b_nrows, b_ncols) = b.acc_size();
+            mat.slice_mut(r0 .. r0 + b_nrows, c0 .. c0 + b_ncols).assign_by(|r, c| Some(b.acc_get(r, c)));
+            r0 += b_nrows;
+            c0 += b_ncols;
+        }
+
+        mat
+    }
     //
     /// *slice* - Slice block reference.
-    pub fn slice<RR, CR>(&self, rows: RR, cols: CR) -> MatSlice
+    pub fn slice<RR, CR>(&self, rows: RR, cols: CR) -> MatSlice<'_>
     where RR: RangeBounds<usize>,  CR: RangeBounds<usize>
     {
         let (row_range, col_range) = self.h_bound(rows, cols);
+        let offset = self.h_slice_offset(&row_range, &col_range);
 
         MatGen {
             nrows: row_range.end - row_range.start,
             ncols: col_range.end - col_range.start,
-            offset: self.offset + self.stride * col_range.start + row_range.start,
+            offset,
             stride: self.stride,
+            rstride: self.rstride,
             transposed: self.transposed,
             view: self.view.get_ref()
         }
     }
     /// *slice* - Slice block mutable reference.
-    pub fn slice_mut<'b, RR, CR>(&'b mut self, rows: RR, cols: CR) -> MatSliMu
+    pub fn slice_mut<RR, CR>(&mut self, rows: RR, cols: CR) -> MatSliMu<'_>
     where RR: RangeBounds<usize>,  CR: RangeBounds<usize>
     {
         let (row_range, col_range) = self.h_bound(rows, cols);
+        let offset = self.h_slice_offset(&row_range, &col_range);
 
         MatGen {
             nrows: row_range.end - row_range.start,
             ncols: col_range.end - col_range.start,
-            offset: self.offset + self.stride * col_range.start + row_range.start,
+            offset,
             stride: self.stride,
+            rstride: self.rstride,
             transposed: self.transposed,
             view: self.view.get_mut()
         }
     }
     /// *slice* - Row vectors reference.
-    pub fn rows<RR>(&self, rows: RR) -> MatSlice
+    pub fn rows<RR>(&self, rows: RR) -> MatSlice<'_>
     where RR: RangeBounds<usize>
     {
         self.slice(rows, ..)
     }
     /// *slice* - Column vectors reference.
-    pub fn cols<CR>(&self, cols: CR) -> MatSlice
+    pub fn cols<CR>(&self, cols: CR) -> MatSlice<'_>
     where CR: RangeBounds<usize>
     {
         self.slice(.., cols)
     }
+    /// *slice* - Every `step`-th row of row vectors reference, starting from the first of `rows`.
+    ///
+    /// Panics if `step` is zero.
+    pub fn rows_step<RR>(&self, rows: RR, step: usize) -> MatSlice<'_>
+    where RR: RangeBounds<usize>
+    {
+        self.rows(rows).h_step(step, true)
+    }
+    /// *slice* - Every `step`-th column of column vectors reference, starting from the first of `cols`.
+    ///
+    /// Panics if `step` is zero.
+    pub fn cols_step<CR>(&self, cols: CR, step: usize) -> MatSlice<'_>
+    where CR: RangeBounds<usize>
+    {
+        self.cols(cols).h_step(step, false)
+    }
     /// *slice* - A row vector reference.
-    pub fn row(&self, r: usize) -> MatSlice
+    pub fn row(&self, r: usize) -> MatSlice<'_>
     {
         self.rows(r ..= r)
     }
     /// *slice* - A column vector reference.
-    pub fn col(&self, c: usize) -> MatSlice
+    pub fn col(&self, c: usize) -> MatSlice<'_>
     {
         self.cols(c ..= c)
     }
     /// *slice* - Row vectors mutable reference.
-    pub fn rows_mut<RR>(&mut self, rows: RR) -> MatSliMu
+    pub fn rows_mut<RR>(&mut self, rows: RR) -> MatSliMu<'_>
     where RR: RangeBounds<usize>
     {
         self.slice_mut(rows, ..)
     }
     /// *slice* - Column vectors mutable reference.
-    pub fn cols_mut<CR>(&mut self, cols: CR) -> MatSliMu
+    pub fn cols_mut<CR>(&mut self, cols: CR) -> MatSliMu<'_>
     where CR: RangeBounds<usize>
     {
         self.slice_mut(.., cols)
     }
+    /// *slice* - Every `step`-th row of row vectors mutable reference, see [`rows_step`](#method.rows_step).
+    pub fn rows_step_mut<RR>(&mut self, rows: RR, step: usize) -> MatSliMu<'_>
+    where RR: RangeBounds<usize>
+    {
+        self.rows_mut(rows).h_step(step, true)
+    }
+    /// *slice* - Every `step`-th column of column vectors mutable reference, see [`cols_step`](#method.cols_step).
+    pub fn cols_step_mut<CR>(&mut self, cols: CR, step: usize) -> MatSliMu<'_>
+    where CR: RangeBounds<usize>
+    {
+        self.cols_mut(cols).h_step(step, false)
+    }
     /// *slice* - A row vector mutable reference.
-    pub fn row_mut(&mut self, r: usize) -> MatSliMu
+    pub fn row_mut(&mut self, r: usize) -> MatSliMu<'_>
     {
         self.rows_mut(r ..= r)
     }
     /// *slice* - A column vector mutable reference.
-    pub fn col_mut(&mut self, c: usize) -> MatSliMu
+    pub fn col_mut(&mut self, c: usize) -> MatSliMu<'_>
     {
         self.cols_mut(c ..= c)
     }
+    /// *slice* - Iterator of row vector references.
+    pub fn row_iter(&self) -> impl Iterator<Item=MatSlice<'_>>
+    {
+        let (l_nrows, _) = self.size();
+
+        (0 .. l_nrows).map(move |r| self.row(r))
+    }
+    /// *slice* - Iterator of column vector references.
+    pub fn col_iter(&self) -> impl Iterator<Item=MatSlice<'_>>
+    {
+        let (_, l_ncols) = self.size();
+
+        (0 .. l_ncols).map(move |c| self.col(c))
+    }
+    /// *slice* - Iterator of disjoint row vector mutable references.
+    ///
+    /// Panics if rows are interleaved in storage, that is, `self` is column-major
+    /// (see [`layout`](#method.layout)) with more than one column.
+    /// [`row_mut`](#method.row_mut) is available for such a matrix.
+    pub fn row_iter_mut(&mut self) -> impl Iterator<Item=MatSliMu<'_>>
+    {
+        self.h_lines_mut(true)
+    }
+    /// *slice* - Iterator of disjoint column vector mutable references.
+    ///
+    /// Panics if columns are interleaved in storage, that is, `self` is row-major
+    /// (see [`layout`](#method.layout)) with more than one row.
+    /// [`col_mut`](#method.col_mut) is available for such a matrix.
+    pub fn col_iter_mut(&mut self) -> impl Iterator<Item=MatSliMu<'_>>
+    {
+        self.h_lines_mut(false)
+    }
+    /// *slice* - Two disjoint mutable references of columns `.. c` and `c ..`.
+    ///
+    /// Panics if `c` is beyond the columns, or if columns are interleaved in storage, that is,
+    /// `self` is row-major (see [`layout`](#method.layout)) with more than one row.
+    pub fn split_at_col_mut(&mut self, c: usize) -> (MatSliMu<'_>, MatSliMu<'_>)
+    {
+        self.h_split_mut(c, true)
+    }
+    /// *slice* - Two disjoint mutable references of rows `.. r` and `r ..`.
+    ///
+    /// Panics if `r` is beyond the rows, or if rows are interleaved in storage, that is,
+    /// `self` is column-major (see [`layout`](#method.layout)) with more than one column.
+    pub fn split_at_row_mut(&mut self, r: usize) -> (MatSliMu<'_>, MatSliMu<'_>)
+    {
+        self.h_split_mut(r, false)
+    }
+    /// *slice* - Main diagonal reference as a column vector.
+    pub fn diag(&self) -> MatSlice<'_>
+    {
+        self.diag_k(0)
+    }
+    /// *slice* - Main diagonal mutable reference as a column vector.
+    pub fn diag_mut(&mut self) -> MatSliMu<'_>
+    {
+        self.diag_k_mut(0)
+    }
+    /// *slice* - `k`-th diagonal reference as a column vector,
+    /// which is above the main diagonal if `k > 0` and below if `k < 0`.
+    ///
+    /// The elements are \\(a_{i, i+k}\\) from the top, or empty if `k` is out of the matrix.
+    pub fn diag_k(&self, k: isize) -> MatSlice<'_>
+    {
+        let (offset, len) = self.h_diag(k);
+
+        // diagonal elements are apart by stride + rstride, as a transposed row vector of the stride
+        MatGen {
+            nrows: 1,
+            ncols: len,
+            offset,
+            stride: self.stride + self.rstride,
+            rstride: 1,
+            transposed: true,
+            view: self.view.get_ref()
+        }
+    }
+    /// *slice* - `k`-th diagonal mutable reference as a column vector, see [`diag_k`](#method.diag_k).
+    pub fn diag_k_mut(&mut self, k: isize) -> MatSliMu<'_>
+    {
+        let (offset, len) = self.h_diag(k);
+
+        MatGen {
+            nrows: 1,
+            ncols: len,
+            offset,
+            stride: self.stride + self.rstride,
+            rstride: 1,
+            transposed: true,
+            view: self.view.get_mut()
+        }
+    }
     /// *slice* - Whole reference.
-    pub fn as_slice(&self) -> MatSlice
+    pub fn as_slice(&self) -> MatSlice<'_>
     {
         self.slice(.., ..)
     }
     /// *slice* - Whole mutable reference.
-    pub fn as_slice_mut(&mut self) -> MatSliMu
+    pub fn as_slice_mut(&mut self) -> MatSliMu<'_>
     {
         self.slice_mut(.., ..)
     }
+    /// *slice* - Column vector reference of the elements in column-major order, the vectorization,
+    /// without copying.
+    ///
+    /// It is `None` unless the elements are contiguous and non-transposed, as of a plain owned matrix;
+    /// see [`vec_copy`](#method.vec_copy) for the copying version.
+    pub fn vec(&self) -> Option<MatSlice<'_>>
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        self.unvec(l_nrows * l_ncols, 1)
+    }
+    /// *slice* - `nrows` by `ncols` reference reinterpreting the elements in column-major order, the inverse of [`vec`](#method.vec),
+    /// without copying.
+    ///
+    /// It is `None` unless the elements are contiguous and non-transposed;
+    /// see [`unvec_copy`](#method.unvec_copy) for the copying version.
+    /// Panics if the number of elements differs.
+    pub fn unvec(&self, nrows: usize, ncols: usize) -> Option<MatSlice<'_>>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows * l_ncols == nrows * ncols,
+                "unvec: {} x {} into {} x {}", l_nrows, l_ncols, nrows, ncols);
+
+        self.h_col_major().map(|view| MatGen {
+            nrows,
+            ncols,
+            offset: 0,
+            stride: nrows as isize,
+            rstride: 1,
+            transposed: false,
+            view
+        })
+    }
     /// *slice* - Transopsed reference.
-    pub fn t(&self) -> MatSlice
+    pub fn t(&self) -> MatSlice<'_>
     {
         MatGen {
             nrows: self.nrows,
             ncols: self.ncols,
             offset: self.offset,
             stride: self.stride,
+            rstride: self.rstride,
             transposed: !self.transposed,
             view: self.view.get_ref()
         }
     }
     /// *slice* - Transopsed mutable reference.
-    pub fn t_mut(&mut self) -> MatSliMu
+    pub fn t_mut(&mut self) -> MatSliMu<'_>
     {
         MatGen {
             nrows: self.nrows,
             ncols: self.ncols,
             offset: self.offset,
             stride: self.stride,
+            rstride: self.rstride,
             transposed: !self.transposed,
             view: self.view.get_mut()
         }
     }
-    //
-    /// *set* - Set by closure.
-    pub fn set_by<F>(mut self, mut f: F) -> MatGen<V>
-    where F: FnMut(usize, usize) -> FP
+    /// *slice* - Reference with rows in reverse order.
+    pub fn flip_rows(&self) -> MatSlice<'_>
     {
-        let (nrows, ncols) = self.size();
-
-        for c in 0 .. ncols {
-            for r in 0 .. nrows {
-                self[(r, c)] = f(r, c);
-            }
-        }
-        self
+        self.as_slice().h_flip(true)
     }
-    /// *set* - Set by iterator.
-    pub fn set_iter<'b, T>(mut self, iter: T) -> MatGen<V>
-    where T: IntoIterator<Item=&'b FP>
+    /// *slice* - Reference with columns in reverse order.
+    pub fn flip_cols(&self) -> MatSlice<'_>
     {
-        let (nrows, ncols) = self.size();
+        self.as_slice().h_flip(false)
+    }
+    /// *slice* - Mutable reference with rows in reverse order.
+    pub fn flip_rows_mut(&mut self) -> MatSliMu<'_>
+    {
+        self.as_slice_mut().h_flip(true)
+    }
+    /// *slice* - Mutable reference with columns in reverse order.
+    pub fn flip_cols_mut(&mut self) -> MatSliMu<'_>
+    {
+        self.as_slice_mut().h_flip(false)
+    }
+    //
+    // column vector of f applied to each row if rows, or each column otherwise
+    fn h_reduce_lines<F>(&self, rows: bool, f: F) -> Mat
+    where F: Fn(MatSlice<'_>) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        if rows {
+            Mat::new_vec(l_nrows).set_by(|r, _| f(self.row(r)))
+        }
+        else {
+            Mat::new_vec(l_ncols).set_by(|c, _| f(self.col(c)))
+        }
+    }
+    //
+    // unordered singular values as a column vector
+    fn h_singular_values(&self) -> Mat
+    {
+        let (l_nrows, l_ncols) = self.size();
+        if l_nrows * l_ncols == 0 {
+            return Mat::new_vec(0);
+        }
+
+        let mut svd = MatSVD::new((l_nrows, l_ncols)).set_rank_revealing();
+        svd.decomp(&self.clone_sz());
+        svd.s().clone()
+    }
+    //
+    // the first non-NaN element in column-major order which no later element is better than
+    fn h_arg<F>(&self, better: F) -> Option<(usize, usize)>
+    where F: Fn(FP, FP) -> bool
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let mut best: Option<(usize, usize, FP)> = None;
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                let x = self[(r, c)];
+                if !x.is_nan() && best.is_none_or(|(_, _, b)| better(x, b)) {
+                    best = Some((r, c, x));
+                }
+            }
+        }
+
+        best.map(|(r, c, _)| (r, c))
+    }
+    //
+    // 0/1 mask of a comparison with rhs of the same size
+    fn h_mask<T, F>(&self, name: &str, rhs: T, f: F) -> Mat
+    where T: MatAcc, F: Fn(FP, FP) -> bool
+    {
+        h_check_size(name, self.size(), rhs.acc_size());
+
+        self.zip_map(rhs, |x, y| if f(x, y) {1.} else {0.})
+    }
+    //
+    /// *set* - Set by closure.
+    pub fn set_by<F>(mut self, mut f: F) -> MatGen<V>
+    where F: FnMut(usize, usize) -> FP
+    {
+        let (nrows, ncols) = self.size();
+
+        for c in 0 .. ncols {
+            for r in 0 .. nrows {
+                self[(r, c)] = f(r, c);
+            }
+        }
+        self
+    }
+    /// *set* - Set by iterator.
+    pub fn set_iter<'b, T>(mut self, iter: T) -> MatGen<V>
+    where T: IntoIterator<Item=&'b FP>
+    {
+        let (nrows, ncols) = self.size();
         let mut i = iter.into_iter();
 
         // NOTE: contents of iter is row-wise
@@ -346,6 +1156,16 @@ impl<V: View> MatGen<V>
         }
         self
     }
+    /// *set* - Set uniform random numbers in \\([0, 1)\\).
+    pub fn set_rand(self, rng: &mut Rng) -> MatGen<V>
+    {
+        self.set_by(|_, _| rng.uniform())
+    }
+    /// *set* - Set standard normal random numbers.
+    pub fn set_randn(self, rng: &mut Rng) -> MatGen<V>
+    {
+        self.set_by(|_, _| rng.normal())
+    }
     /// *set* - Set eye matrix.
     pub fn set_eye(self) -> MatGen<V>
     {
@@ -356,6 +1176,14 @@ impl<V: View> MatGen<V>
     {
         self.set_by(|_, _| value)
     }
+    /// *set* - Set diagonal elements, leaving the others.
+    ///
+    /// Panics if the length of `diag` is not the length of the diagonal.
+    pub fn set_diag(mut self, diag: &[FP]) -> MatGen<V>
+    {
+        self.assign_diag(diag);
+        self
+    }
     /// *set* - Set transposed.
     pub fn set_t(mut self) -> MatGen<V>
     {
@@ -370,11 +1198,15 @@ impl<V: View> MatGen<V>
         let sz = self.view.get_len();
 
         if sz == l_nrows * l_ncols {
+            #[cfg(feature = "alloc-count")]
+            crate::alloc_stats::record(sz);
+
             MatGen {
                 nrows: self.nrows,
                 ncols: self.ncols,
                 offset: self.offset,
                 stride: self.stride,
+                rstride: self.rstride,
                 transposed: self.transposed,
                 view: self.view.get_ref().to_vec()
             }
@@ -385,748 +1217,5009 @@ impl<V: View> MatGen<V>
             mat
         }
     }
-    /// *clone* - Clone into diagonal matrix.
-    pub fn clone_diag(&self) -> Mat
+    /// *clone* - Clone with rows in reverse order.
+    pub fn flipud(&self) -> Mat
     {
         let (l_nrows, l_ncols) = self.size();
-        assert_eq!(l_ncols, 1);
-
-        let mut mat = Mat::new(l_nrows, l_nrows);
-
-        for r in 0 .. l_nrows {
-            mat[(r, r)] = self[(r, 0)];
-        }
-
+        let mut mat = Mat::new(l_nrows, l_ncols);
+        mat.assign(&self.flip_rows());
         mat
     }
-    //
-    /// *assign* - Assign by closure.
-    pub fn assign_by<F>(&mut self, f: F)
-    where F: Fn(usize, usize) -> Option<FP>
+    /// *clone* - Clone with columns in reverse order.
+    pub fn fliplr(&self) -> Mat
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let mut mat = Mat::new(l_nrows, l_ncols);
+        mat.assign(&self.flip_cols());
+        mat
+    }
+    /// Converts into a vector of column-major elements.
+    ///
+    /// The buffer is returned without copying if `self` is an owned, non-transposed matrix covering its whole buffer,
+    /// such as one made by [`new`](#method.new) or [`from_vec`](#method.from_vec).
+    /// Otherwise the elements are copied, as a single slice if contiguous.
+    pub fn into_vec(self) -> Vec<FP>
     {
         let (l_nrows, l_ncols) = self.size();
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                if let Some(value) = f(r, c) {
-                    self[(r, c)] = value;
+        if self.view.is_own() && !self.transposed && (self.offset == 0) && (self.stride == l_nrows as isize) && (self.rstride == 1)
+           && (self.view.get_len() == l_nrows * l_ncols) {
+            self.view.get_own()
+        }
+        else if let Some(s) = self.h_col_major() {
+            s.to_vec()
+        }
+        else {
+            let mut vec = Vec::with_capacity(l_nrows * l_ncols);
+            for c in 0 .. l_ncols {
+                for r in 0 .. l_nrows {
+                    vec.push(self[(r, c)]);
                 }
             }
+            vec
         }
     }
-    /// *assign* - Assign by iterator.
-    pub fn assign_iter<'b, T>(&mut self, iter: T)
-    where T: IntoIterator<Item=&'b FP>
+    /// Reshapes into `nrows` by `ncols`, reinterpreting the elements in column-major order.
+    ///
+    /// The buffer is reused without copying under the same condition as [`into_vec`](#method.into_vec),
+    /// otherwise the elements are copied.
+    /// Panics if the number of elements differs.
+    pub fn reshape(self, nrows: usize, ncols: usize) -> Mat
     {
-        let (nrows, ncols) = self.size();
-        let mut i = iter.into_iter();
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows * l_ncols == nrows * ncols,
+                "reshape: {} x {} into {} x {}", l_nrows, l_ncols, nrows, ncols);
 
-        // NOTE: contents of iter is row-wise
-        for r in 0 .. nrows {
-            for c in 0 .. ncols {
-                self[(r, c)] = *i.next().unwrap_or(&0.);
-            }
-        }
+        Mat::from_vec(nrows, ncols, self.into_vec())
     }
-    /// *assign* - Assign eye matrix.
-    pub fn assign_eye(&mut self)
+    /// *clone* - Clone into a column vector of the elements in column-major order, the vectorization.
+    pub fn vec_copy(&self) -> Mat
     {
-        self.assign_by(|r, c| Some(if r == c {1.} else {0.}));
+        let (l_nrows, l_ncols) = self.size();
+
+        self.unvec_copy(l_nrows * l_ncols, 1)
     }
-    /// *assign* - Assign a value.
-    pub fn assign_all(&mut self, value: FP)
+    /// *clone* - Clone into `nrows` by `ncols` reinterpreting the elements in column-major order.
+    ///
+    /// Panics if the number of elements differs.
+    pub fn unvec_copy(&self, nrows: usize, ncols: usize) -> Mat
     {
-        self.assign_by(|_, _| Some(value));
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows * l_ncols == nrows * ncols,
+                "unvec_copy: {} x {} into {} x {}", l_nrows, l_ncols, nrows, ncols);
+
+        Mat::from_vec(nrows, ncols, self.iter().copied().collect())
     }
-    /// *assign* - Assign matrix.
-    pub fn assign<V2: View>(&mut self, rhs: &MatGen<V2>)
+    /// *clone* - Clone tiling `times_r` times vertically and `times_c` times horizontally.
+    pub fn repeat(&self, times_r: usize, times_c: usize) -> Mat
     {
         let (l_nrows, l_ncols) = self.size();
-        let (r_nrows, r_ncols) = rhs.size();
 
-        assert_eq!(l_nrows, r_nrows);
-        assert_eq!(l_ncols, r_ncols);
-        
-        self.assign_by(|r, c| Some(rhs[(r, c)]));
+        // empty if either is zero, where modulo is never evaluated
+        Mat::new(l_nrows * times_r, l_ncols * times_c).set_by(|r, c| self[(r % l_nrows, c % l_ncols)])
     }
-    //
-    /// Returns p=2 norm squared.
-    pub fn norm_p2sq(&self) -> FP
+    /// *clone* - Clone a column vector into `ncols` columns.
+    pub fn repeat_vec(&self, ncols: usize) -> Mat
     {
         let (l_nrows, l_ncols) = self.size();
+        assert_eq!(l_ncols, 1);
 
-        let mut sum = 0.;
-
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                sum += self[(r, c)] * self[(r, c)];
-            }
+        let mut mat = Mat::new(l_nrows, ncols);
+        for c in 0 .. ncols {
+            mat.col_mut(c).assign(self);
         }
-
-        sum
+        mat
     }
-    /// Returns p=2 norm.
-    pub fn norm_p2(&self) -> FP
+    /// *clone* - Clone rows chosen by `idx`, in its order and allowing repeats.
+    ///
+    /// Panics if an index is out of range.
+    pub fn select_rows(&self, idx: &[usize]) -> Mat
     {
-        FP::sqrt(self.norm_p2sq())
+        let (l_nrows, l_ncols) = self.size();
+        h_check_idx("select_rows", idx, l_nrows, "rows");
+
+        Mat::new(idx.len(), l_ncols).set_by(|r, c| self[(idx[r], c)])
     }
-    /// Returns trace.
-    pub fn tr(&self) -> FP
+    /// *clone* - Clone columns chosen by `idx`, in its order and allowing repeats.
+    ///
+    /// Panics if an index is out of range.
+    pub fn select_cols(&self, idx: &[usize]) -> Mat
     {
         let (l_nrows, l_ncols) = self.size();
+        h_check_idx("select_cols", idx, l_ncols, "columns");
 
-        let mut sum = 0.;
-
-        for i in 0 .. l_nrows.min(l_ncols) {
-            sum += self[(i, i)];
-        }
-
-        sum
+        Mat::new(l_nrows, idx.len()).set_by(|r, c| self[(r, idx[c])])
     }
-    /// Returns inner product.
-    pub fn prod<V2: View>(&self, rhs: &MatGen<V2>) -> FP
+    /// *clone* - Clone the upper triangle on and above the `k`-th diagonal, zeroing the others.
+    ///
+    /// Elements \\(a_{ij}\\) of \\(j - i \\ge k\\) are kept, as in [`diag_k`](#method.diag_k) for `k`.
+    pub fn triu(&self, k: isize) -> Mat
+    {
+        let mut mat = self.clone_sz();
+        mat.zero_tril(k);
+        mat
+    }
+    /// *clone* - Clone the lower triangle on and below the `k`-th diagonal, zeroing the others.
+    ///
+    /// Elements \\(a_{ij}\\) of \\(j - i \\le k\\) are kept.
+    pub fn tril(&self, k: isize) -> Mat
+    {
+        let mut mat = self.clone_sz();
+        mat.zero_triu(k);
+        mat
+    }
+    /// *clone* - Clone with each column replaced by its running sum from the top.
+    pub fn cumsum_cols(&self) -> Mat
+    {
+        let mut mat = self.clone_sz();
+        mat.cumsum_cols_inplace();
+        mat
+    }
+    /// *clone* - Clone with each row replaced by its running sum from the left.
+    pub fn cumsum_rows(&self) -> Mat
+    {
+        let mut mat = self.clone_sz();
+        mat.cumsum_rows_inplace();
+        mat
+    }
+    /// *clone* - Clone into the scaled symmetric vectorization, a column vector of length \\(n(n+1)/2\\).
+    ///
+    /// The lower triangle is stacked column by column, with off-diagonal elements scaled by \\(\\sqrt2\\),
+    /// so that inner products are preserved for symmetric matrices: `a.svec().prod(&b.svec())` is `a.prod(&b)`.
+    /// The upper triangle is ignored. Panics if not square.
+    pub fn svec(&self) -> Mat
     {
         let (l_nrows, l_ncols) = self.size();
-        let (r_nrows, r_ncols) = rhs.size();
-
-        assert_eq!(l_nrows, r_nrows);
-        assert_eq!(l_ncols, r_ncols);
-
-        let mut sum = 0.;
-
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                sum += self[(r, c)] * rhs[(r, c)];
+        assert!(l_nrows == l_ncols, "svec: size {}x{} is not square", l_nrows, l_ncols);
+
+        let n = l_nrows;
+        let mut vec = Vec::with_capacity(n * (n + 1) / 2);
+        for c in 0 .. n {
+            vec.push(self[(c, c)]);
+            for r in c + 1 .. n {
+                vec.push(self[(r, c)] * FP::sqrt(2.));
             }
         }
-
-        sum
+        Mat::from_vec(vec.len(), 1, vec)
     }
-    //
-    /// Finds maximum value.
-    pub fn max(&self) -> Option<FP>
+    /// *clone* - Clone with each element transformed by `f`.
+    pub fn map<F>(&self, mut f: F) -> Mat
+    where F: FnMut(FP) -> FP
     {
         let (l_nrows, l_ncols) = self.size();
-        if (l_nrows == 0) || (l_ncols == 0) {
-            return None;
-        }
 
-        let mut m = self[(0, 0)];
+        Mat::from_vec(l_nrows, l_ncols, self.iter().map(|x| f(*x)).collect())
+    }
+    /// *clone* - Clone with each element transformed by `f` of (row, column, value).
+    pub fn map_indexed<F>(&self, mut f: F) -> Mat
+    where F: FnMut(usize, usize, FP) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                if self[(r, c)] > m {
-                    m = self[(r, c)];
-                }
-            }
-        }
+        Mat::new(l_nrows, l_ncols).set_by(|r, c| f(r, c, self[(r, c)]))
+    }
+    /// *clone* - Clone combining each element with that of `rhs` by `f`.
+    ///
+    /// Panics if the sizes differ.
+    ///
+    /// ```
+    /// use totsu::prelude::*;
+    ///
+    /// // elementwise max against a lower bound broadcast over columns
+    /// let x = Mat::new(2, 3).set_iter(&[
+    ///     -1., 2., 0.,
+    ///     3., -4., 5.
+    /// ]);
+    /// let lb = Mat::new_vec(2).set_iter(&[0., 1.]);
+    /// assert_eq!(x.zip_map(&lb.repeat_vec(3), FP::max), Mat::new(2, 3).set_iter(&[
+    ///     0., 2., 0.,
+    ///     3., 1., 5.
+    /// ]));
+    /// ```
+    pub fn zip_map<T, F>(&self, rhs: T, mut f: F) -> Mat
+    where T: MatAcc, F: FnMut(FP, FP) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert_eq!((l_nrows, l_ncols), rhs.acc_size());
 
-        Some(m)
+        Mat::new(l_nrows, l_ncols).set_by(|r, c| f(self[(r, c)], rhs.acc_get(r, c)))
     }
-    /// Finds minumum value.
-    pub fn min(&self) -> Option<FP>
+    /// *clone* - Clone multiplied elementwise by `rhs`, that is, the Hadamard product.
+    ///
+    /// Panics if the sizes differ.
+    pub fn elem_mul<V2: View>(&self, rhs: &MatGen<V2>) -> Mat
+    {
+        self.zip_map(rhs, |x, y| x * y)
+    }
+    /// *clone* - Clone divided elementwise by `rhs`.
+    ///
+    /// Division by zero does not panic but follows IEEE 754, resulting in infinity or NaN.
+    /// Panics if the sizes differ.
+    pub fn elem_div<V2: View>(&self, rhs: &MatGen<V2>) -> Mat
+    {
+        self.zip_map(rhs, |x, y| x / y)
+    }
+    /// *clone* - Clone with absolute values.
+    pub fn abs(&self) -> Mat
+    {
+        self.map(FP::abs)
+    }
+    /// *clone* - Clone with signs, `1`, `-1` or NaN as [`f64::signum`].
+    pub fn signum(&self) -> Mat
+    {
+        self.map(FP::signum)
+    }
+    /// *clone* - Clone clamped into \\([lo, hi]\\) elementwise.
+    ///
+    /// Each bound is either a scalar or a matrix of the same size.
+    /// Panics if the sizes differ or a lower bound exceeds the upper one.
+    ///
+    /// ```
+    /// use totsu::prelude::*;
+    ///
+    /// // minimize (1/2)|x - a|^2 subject to l <= x <= u,
+    /// // whose solution is the candidate a projected onto the box
+    /// let n = 3;
+    /// let a = Mat::new_vec(n).set_iter(&[-2., 0.5, 3.]);
+    /// let l = Mat::new_vec(n).set_all(-1.);
+    /// let x0 = a.clamp(&l, 1.);
+    /// assert_eq!(x0, Mat::new_vec(n).set_iter(&[-1., 0.5, 1.]));
+    ///
+    /// let mat_p = Mat::new(n, n).set_eye();
+    /// let vec_q = -&a;
+    /// let mat_g = Mat::new(2 * n, n).set_by(|r, c| if r == c {1.} else if r == c + n {-1.} else {0.});
+    /// let vec_h = Mat::new_vec(2 * n).set_by(|r, _| if r < n {1.} else {-l[r - n]});
+    /// let mat_a = Mat::new(0, n);
+    /// let vec_b = Mat::new_vec(0);
+    ///
+    /// let param = PDIPMParam::default();
+    /// let x = PDIPM::new().solve_qp(&param, &mut std::io::sink(),
+    ///                               &mat_p, &vec_q,
+    ///                               &mat_g, &vec_h,
+    ///                               &mat_a, &vec_b).unwrap();
+    /// assert!((&x - &x0).norm_p2() < 1e-6);
+    /// ```
+    pub fn clamp<L, H>(&self, lo: L, hi: H) -> Mat
+    where L: MatBound, H: MatBound
+    {
+        let mut mat = self.clone_sz();
+        mat.clamp_inplace(lo, hi);
+        mat
+    }
+    /// *clone* - Clone with exponentials.
+    pub fn exp(&self) -> Mat
+    {
+        self.map(FP::exp)
+    }
+    /// *clone* - Clone with natural logarithms.
+    ///
+    /// Negative elements result in NaN and zeros in negative infinity, without panicking.
+    /// See [`try_ln`](#method.try_ln) for the checked version.
+    pub fn ln(&self) -> Mat
+    {
+        self.map(FP::ln)
+    }
+    /// *clone* - Clone with natural logarithms, checking elements.
+    ///
+    /// Returns `Err` with [`MatError::OutOfDomain`] at the first non-positive or NaN element in column-major order.
+    pub fn try_ln(&self) -> Result<Mat, MatError>
     {
         let (l_nrows, l_ncols) = self.size();
-        if (l_nrows == 0) || (l_ncols == 0) {
-            return None;
-        }
-        
-        let mut m = self[(0, 0)];
 
         for c in 0 .. l_ncols {
             for r in 0 .. l_nrows {
-                if self[(r, c)] < m {
-                    m = self[(r, c)];
+                let x = self[(r, c)];
+                if x.is_nan() || (x <= 0.) {
+                    return Err(MatError::OutOfDomain(format!("ln of {} at ({}, {})", x, r, c)));
                 }
             }
         }
 
-        Some(m)
+        Ok(self.ln())
     }
-    //
-    /// Returns number of rows and columns.
-    pub fn size(&self) -> (usize, usize)
+    /// *clone* - Clone with square roots.
+    ///
+    /// Negative elements result in NaN without panicking.
+    pub fn sqrt(&self) -> Mat
     {
-        if !self.transposed {
-            (self.nrows, self.ncols)
-        }
-        else {
-            (self.ncols, self.nrows)
-        }
+        self.map(FP::sqrt)
     }
-}
-
-//
-
-impl<V: View> Index<(usize, usize)> for MatGen<V>
-{
-    type Output = FP;
-    fn index(&self, index: (usize, usize)) -> &FP
+    /// *clone* - Clone with elements raised to the power `p`.
+    pub fn powf(&self, p: FP) -> Mat
     {
-        let i = self.h_index(index);
-
-        &self.view.get_ref()[i]
+        self.map(|x| x.powf(p))
     }
-}
-
-impl<V: View> IndexMut<(usize, usize)> for MatGen<V>
-{
-    fn index_mut(&mut self, index: (usize, usize)) -> &mut FP
+    /// *clone* - Clone into a mask, `1` where an element is less than that of `rhs` and `0` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn lt<T: MatAcc>(&self, rhs: T) -> Mat
     {
-        let i = self.h_index(index);
-
-        &mut self.view.get_mut()[i]
+        self.h_mask("lt", rhs, |x, y| x < y)
     }
-}
-
-//
-
-impl<V: View> fmt::LowerExp for MatGen<V>
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    /// *clone* - Clone into a mask, `1` where an element is less than or equal to that of `rhs` and `0` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn le<T: MatAcc>(&self, rhs: T) -> Mat
     {
-        let (l_nrows, l_ncols) = self.size();
-
-        writeln!(f, "[")?;
-        for r in 0 .. l_nrows {
-            for c in 0 .. l_ncols {
-                write!(f, "  {:.precision$e},", self[(r, c)], precision = f.precision().unwrap_or(3))?;
-            }
-            writeln!(f)?;
-        }
-        write!(f, "]")
+        self.h_mask("le", rhs, |x, y| x <= y)
     }
-}
-
-impl<V: View> fmt::Display for MatGen<V>
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    /// *clone* - Clone into a mask, `1` where an element is greater than that of `rhs` and `0` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn gt<T: MatAcc>(&self, rhs: T) -> Mat
     {
-        writeln!(f, "{:.3e}", self)
+        self.h_mask("gt", rhs, |x, y| x > y)
     }
-}
-
+    /// *clone* - Clone into a mask, `1` where an element is greater than or equal to that of `rhs` and `0` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn ge<T: MatAcc>(&self, rhs: T) -> Mat
+    {
+        self.h_mask("ge", rhs, |x, y| x >= y)
+    }
+    /// *clone* - Clone into a mask, `1` where an element is equal to that of `rhs` and `0` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn eq_elem<T: MatAcc>(&self, rhs: T) -> Mat
+    {
+        self.h_mask("eq_elem", rhs, |x, y| x == y)
+    }
+    /// *clone* - Clone into a mask, `1` where an element is not equal to that of `rhs` and `0` elsewhere.
+    ///
+    /// Panics if the sizes differ.
+    pub fn ne_elem<T: MatAcc>(&self, rhs: T) -> Mat
+    {
+        self.h_mask("ne_elem", rhs, |x, y| x != y)
+    }
+    /// *clone* - Clone into a mask, `1` where an element is less than `rhs` and `0` elsewhere.
+    pub fn lt_scalar(&self, rhs: FP) -> Mat
+    {
+        self.map(|x| if x < rhs {1.} else {0.})
+    }
+    /// *clone* - Clone into a mask, `1` where an element is less than or equal to `rhs` and `0` elsewhere.
+    pub fn le_scalar(&self, rhs: FP) -> Mat
+    {
+        self.map(|x| if x <= rhs {1.} else {0.})
+    }
+    /// *clone* - Clone into a mask, `1` where an element is greater than `rhs` and `0` elsewhere.
+    pub fn gt_scalar(&self, rhs: FP) -> Mat
+    {
+        self.map(|x| if x > rhs {1.} else {0.})
+    }
+    /// *clone* - Clone into a mask, `1` where an element is greater than or equal to `rhs` and `0` elsewhere.
+    pub fn ge_scalar(&self, rhs: FP) -> Mat
+    {
+        self.map(|x| if x >= rhs {1.} else {0.})
+    }
+    /// *clone* - Clone into a mask, `1` where an element is equal to `rhs` and `0` elsewhere.
+    pub fn eq_scalar(&self, rhs: FP) -> Mat
+    {
+        self.map(|x| if x == rhs {1.} else {0.})
+    }
+    /// *clone* - Clone into a mask, `1` where an element is not equal to `rhs` and `0` elsewhere.
+    pub fn ne_scalar(&self, rhs: FP) -> Mat
+    {
+        self.map(|x| if x != rhs {1.} else {0.})
+    }
+    /// *assign* - Assign rows of `src` to rows chosen by `idx`, the inverse of [`select_rows`](#method.select_rows).
+    ///
+    /// A row chosen repeatedly gets the last one.
+    /// Panics if an index is out of range or `src` does not have `idx.len()` rows of the same width.
+    pub fn scatter_rows<V2: View>(&mut self, idx: &[usize], src: &MatGen<V2>)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        h_check_idx("scatter_rows", idx, l_nrows, "rows");
+        assert_eq!(src.size(), (idx.len(), l_ncols));
+
+        for (r, &i) in idx.iter().enumerate() {
+            self.row_mut(i).assign(&src.row(r));
+        }
+    }
+    /// *assign* - Swap rows `i` and `j`.
+    pub fn swap_rows(&mut self, i: usize, j: usize)
+    {
+        let (_, l_ncols) = self.size();
+
+        if i != j {
+            for c in 0 .. l_ncols {
+                let e = self[(i, c)];
+                self[(i, c)] = self[(j, c)];
+                self[(j, c)] = e;
+            }
+        }
+    }
+    /// *assign* - Swap columns `i` and `j`.
+    pub fn swap_cols(&mut self, i: usize, j: usize)
+    {
+        let (l_nrows, _) = self.size();
+
+        if i != j {
+            for r in 0 .. l_nrows {
+                let e = self[(r, i)];
+                self[(r, i)] = self[(r, j)];
+                self[(r, j)] = e;
+            }
+        }
+    }
+    /// *assign* - Permute rows so that `i`-th row becomes what was `perm[i]`-th row,
+    /// the same as [`select_rows`](#method.select_rows) with a permutation but in place.
+    ///
+    /// Rows are moved by swaps following each cycle of the permutation, without a buffer of a row.
+    /// Returns `Err` if `perm` is not a permutation of the row indices.
+    pub fn permute_rows(&mut self, perm: &[usize]) -> Result<(), MatError>
+    {
+        let (l_nrows, _) = self.size();
+
+        h_permute(perm, l_nrows, |i, j| self.swap_rows(i, j))
+    }
+    /// *assign* - Permute columns so that `i`-th column becomes what was `perm[i]`-th column,
+    /// see [`permute_rows`](#method.permute_rows).
+    pub fn permute_cols(&mut self, perm: &[usize]) -> Result<(), MatError>
+    {
+        let (_, l_ncols) = self.size();
+
+        h_permute(perm, l_ncols, |i, j| self.swap_cols(i, j))
+    }
+    /// Returns Kronecker product \\(A \\otimes B\\) of `self` \\(A\\) and `rhs` \\(B\\).
+    pub fn kron<V2: View>(&self, rhs: &MatGen<V2>) -> Mat
+    {
+        let (m, n) = self.size();
+        let (p, q) = rhs.size();
+
+        let mut mat = Mat::new(m * p, n * q);
+
+        // down each column of the result
+        for ja in 0 .. n {
+            for jb in 0 .. q {
+                let c = ja * q + jb;
+                for ia in 0 .. m {
+                    let a = self[(ia, ja)];
+                    for ib in 0 .. p {
+                        mat[(ia * p + ib, c)] = a * rhs[(ib, jb)];
+                    }
+                }
+            }
+        }
+
+        mat
+    }
+    /// *clone* - Clone into diagonal matrix.
+    pub fn clone_diag(&self) -> Mat
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert_eq!(l_ncols, 1);
+
+        let mut mat = Mat::new(l_nrows, l_nrows);
+
+        for r in 0 .. l_nrows {
+            mat[(r, r)] = self[(r, 0)];
+        }
+
+        mat
+    }
+    //
+    /// *assign* - Assign by closure.
+    pub fn assign_by<F>(&mut self, f: F)
+    where F: Fn(usize, usize) -> Option<FP>
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        // in the storage order
+        if !self.transposed {
+            for c in 0 .. l_ncols {
+                for r in 0 .. l_nrows {
+                    if let Some(value) = f(r, c) {
+                        self[(r, c)] = value;
+                    }
+                }
+            }
+        }
+        else {
+            for r in 0 .. l_nrows {
+                for c in 0 .. l_ncols {
+                    if let Some(value) = f(r, c) {
+                        self[(r, c)] = value;
+                    }
+                }
+            }
+        }
+    }
+    /// *assign* - Assign by iterator.
+    pub fn assign_iter<'b, T>(&mut self, iter: T)
+    where T: IntoIterator<Item=&'b FP>
+    {
+        let (nrows, ncols) = self.size();
+        let mut i = iter.into_iter();
+
+        // NOTE: contents of iter is row-wise
+        for r in 0 .. nrows {
+            for c in 0 .. ncols {
+                self[(r, c)] = *i.next().unwrap_or(&0.);
+            }
+        }
+    }
+    /// *assign* - Assign eye matrix.
+    pub fn assign_eye(&mut self)
+    {
+        self.assign_by(|r, c| Some(if r == c {1.} else {0.}));
+    }
+    /// *assign* - Assign a value.
+    pub fn assign_all(&mut self, value: FP)
+    {
+        self.assign_by(|_, _| Some(value));
+    }
+    /// *assign* - Assign diagonal elements, leaving the others.
+    ///
+    /// Panics if the length of `diag` is not the length of the diagonal.
+    pub fn assign_diag(&mut self, diag: &[FP])
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let n = l_nrows.min(l_ncols);
+        assert!(diag.len() == n,
+                "assign_diag: length {} does not match diagonal length {}", diag.len(), n);
+
+        for (i, d) in diag.iter().enumerate() {
+            self[(i, i)] = *d;
+        }
+    }
+    /// *assign* - Replace each column with its running sum from the top in place.
+    pub fn cumsum_cols_inplace(&mut self)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        for c in 0 .. l_ncols {
+            for r in 1 .. l_nrows {
+                self[(r, c)] += self[(r - 1, c)];
+            }
+        }
+    }
+    /// *assign* - Replace each row with its running sum from the left in place.
+    pub fn cumsum_rows_inplace(&mut self)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        for c in 1 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] += self[(r, c - 1)];
+            }
+        }
+    }
+    /// *assign* - Zero elements below the `k`-th diagonal, leaving [`triu`](#method.triu) of `k`.
+    pub fn zero_tril(&mut self, k: isize)
+    {
+        self.assign_by(|r, c| if (c as isize - r as isize) < k {Some(0.)} else {None});
+    }
+    /// *assign* - Zero elements above the `k`-th diagonal, leaving [`tril`](#method.tril) of `k`.
+    pub fn zero_triu(&mut self, k: isize)
+    {
+        self.assign_by(|r, c| if (c as isize - r as isize) > k {Some(0.)} else {None});
+    }
+    /// *assign* - Replace with the symmetric part \\((A + A^T) / 2\\) in place.
+    ///
+    /// Panics if not square.
+    pub fn symmetrize(&mut self)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows == l_ncols, "symmetrize: size {}x{} is not square", l_nrows, l_ncols);
+
+        for c in 0 .. l_ncols {
+            for r in c + 1 .. l_nrows {
+                let v = (self[(r, c)] + self[(c, r)]) / 2.;
+                self[(r, c)] = v;
+                self[(c, r)] = v;
+            }
+        }
+    }
+    /// *assign* - Copy the strictly upper triangle to the lower one, making a symmetric matrix from the upper triangle.
+    ///
+    /// Panics if not square.
+    pub fn copy_triu_to_tril(&mut self)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows == l_ncols, "copy_triu_to_tril: size {}x{} is not square", l_nrows, l_ncols);
+
+        for c in 0 .. l_ncols {
+            for r in c + 1 .. l_nrows {
+                self[(r, c)] = self[(c, r)];
+            }
+        }
+    }
+    /// *assign* - Add `value` to diagonal elements, leaving the others.
+    ///
+    /// A non-square matrix has its diagonal of length `min(nrows, ncols)` from the top-left.
+    pub fn add_diag(&mut self, value: FP)
+    {
+        self.diag_mut().map_inplace(|x| x + value);
+    }
+    /// *assign* - Add each element of a vector `d` to the corresponding diagonal element, leaving the others.
+    ///
+    /// A non-square matrix has its diagonal of length `min(nrows, ncols)` from the top-left.
+    /// Panics if `d` is not a row or column vector of the length of the diagonal.
+    pub fn add_diag_vec<V2: View>(&mut self, d: &MatGen<V2>)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let n = l_nrows.min(l_ncols);
+        h_check_vec("add_diag_vec", d.size(), n);
+
+        for i in 0 .. n {
+            self[(i, i)] += d[i];
+        }
+    }
+    /// *assign* - Assign matrix.
+    pub fn assign<V2: View>(&mut self, rhs: &MatGen<V2>)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = rhs.size();
+
+        assert_eq!(l_nrows, r_nrows);
+        assert_eq!(l_ncols, r_ncols);
+        
+        self.assign_by(|r, c| Some(rhs[(r, c)]));
+    }
+    //
+    /// *assign* - Transform each element by `f` in place.
+    pub fn map_inplace<F>(&mut self, mut f: F)
+    where F: FnMut(FP) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        if self.h_col_major().is_some() {
+            let offset = self.offset;
+            for x in &mut self.view.get_mut()[offset .. offset + l_nrows * l_ncols] {
+                *x = f(*x);
+            }
+        }
+        else {
+            for c in 0 .. l_ncols {
+                for r in 0 .. l_nrows {
+                    self[(r, c)] = f(self[(r, c)]);
+                }
+            }
+        }
+    }
+    /// *assign* - Combine each element with that of `rhs` by `f` in place.
+    ///
+    /// Panics if the sizes differ.
+    pub fn zip_map_inplace<T, F>(&mut self, rhs: T, mut f: F)
+    where T: MatAcc, F: FnMut(FP, FP) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert_eq!((l_nrows, l_ncols), rhs.acc_size());
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] = f(self[(r, c)], rhs.acc_get(r, c));
+            }
+        }
+    }
+    /// *assign* - Multiply elementwise by `rhs`.
+    ///
+    /// Panics if the sizes differ.
+    pub fn elem_mul_assign<V2: View>(&mut self, rhs: &MatGen<V2>)
+    {
+        self.zip_map_inplace(rhs, |x, y| x * y);
+    }
+    /// *assign* - Divide elementwise by `rhs`.
+    ///
+    /// Division by zero does not panic but follows IEEE 754, resulting in infinity or NaN.
+    /// Panics if the sizes differ.
+    pub fn elem_div_assign<V2: View>(&mut self, rhs: &MatGen<V2>)
+    {
+        self.zip_map_inplace(rhs, |x, y| x / y);
+    }
+    /// *assign* - Take absolute values in place.
+    pub fn abs_inplace(&mut self)
+    {
+        self.map_inplace(FP::abs);
+    }
+    /// *assign* - Take signs in place, as [`signum`](#method.signum).
+    pub fn signum_inplace(&mut self)
+    {
+        self.map_inplace(FP::signum);
+    }
+    /// *assign* - Clamp into \\([lo, hi]\\) elementwise in place, as [`clamp`](#method.clamp).
+    pub fn clamp_inplace<L, H>(&mut self, lo: L, hi: H)
+    where L: MatBound, H: MatBound
+    {
+        let (l_nrows, l_ncols) = self.size();
+        for (name, size) in [("lower", lo.bound_size()), ("upper", hi.bound_size())] {
+            if let Some(size) = size {
+                assert!(size == (l_nrows, l_ncols),
+                        "clamp: {} bound size {}x{} does not match {}x{}", name, size.0, size.1, l_nrows, l_ncols);
+            }
+        }
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                let (l, h) = (lo.bound_get(r, c), hi.bound_get(r, c));
+                assert!(l <= h, "clamp: lower bound {} exceeds upper bound {} at ({}, {})", l, h, r, c);
+
+                self[(r, c)] = self[(r, c)].max(l).min(h);
+            }
+        }
+    }
+    /// *assign* - Multiply each row by the corresponding element of a vector `d`,
+    /// equivalently to \\({\\rm diag}(d) A\\) without the matrix multiplication.
+    ///
+    /// Panics if `d` is not a row or column vector of the number of rows.
+    pub fn scale_rows<V2: View>(&mut self, d: &MatGen<V2>)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        h_check_vec("scale_rows", d.size(), l_nrows);
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] *= d[r];
+            }
+        }
+    }
+    /// *assign* - Multiply each column by the corresponding element of a vector `d`,
+    /// equivalently to \\(A {\\rm diag}(d)\\) without the matrix multiplication.
+    ///
+    /// Panics if `d` is not a row or column vector of the number of columns.
+    pub fn scale_cols<V2: View>(&mut self, d: &MatGen<V2>)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        h_check_vec("scale_cols", d.size(), l_ncols);
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] *= d[c];
+            }
+        }
+    }
+    /// *assign* - Divide each column by its p=2 norm.
+    ///
+    /// Returns a column vector of the norms, by which [`scale_cols`](#method.scale_cols) undoes the scaling.
+    /// Columns of zero norm are left untouched, which are reported as zeros of the returned norms.
+    pub fn normalize_cols(&mut self) -> Mat
+    {
+        let norms = self.col_norms_p2();
+
+        for (c, n) in norms.iter().enumerate() {
+            if *n > 0. {
+                self.col_mut(c).map_inplace(|x| x / n);
+            }
+        }
+        norms
+    }
+    /// *assign* - Take exponentials in place.
+    pub fn exp_inplace(&mut self)
+    {
+        self.map_inplace(FP::exp);
+    }
+    /// *assign* - Take natural logarithms in place, as [`ln`](#method.ln).
+    pub fn ln_inplace(&mut self)
+    {
+        self.map_inplace(FP::ln);
+    }
+    /// *assign* - Take square roots in place, as [`sqrt`](#method.sqrt).
+    pub fn sqrt_inplace(&mut self)
+    {
+        self.map_inplace(FP::sqrt);
+    }
+    /// *assign* - Raise elements to the power `p` in place.
+    pub fn powf_inplace(&mut self, p: FP)
+    {
+        self.map_inplace(|x| x.powf(p));
+    }
+    /// Returns p=2 norm squared.
+    pub fn norm_p2sq(&self) -> FP
+    {
+        self.iter().fold(0., |sum, e| sum + e * e)
+    }
+    /// Returns p=2 norm.
+    ///
+    /// Squares are accumulated scaled by the running maximum absolute value, as LAPACK `dnrm2`,
+    /// so that it neither overflows nor underflows unless the norm itself does,
+    /// unlike the square root of [`norm_p2sq`](#method.norm_p2sq).
+    pub fn norm_p2(&self) -> FP
+    {
+        let mut scale: FP = 0.;
+        let mut ssq: FP = 1.;
+
+        for e in self.iter() {
+            if *e != 0. {
+                let a = e.abs();
+                if scale < a {
+                    ssq = 1. + ssq * (scale / a) * (scale / a);
+                    scale = a;
+                }
+                else {
+                    ssq += (a / scale) * (a / scale);
+                }
+            }
+        }
+
+        scale * FP::sqrt(ssq)
+    }
+    /// Returns p=1 norm, the sum of absolute values, `0` if empty.
+    pub fn norm_p1(&self) -> FP
+    {
+        self.iter().fold(0., |sum, e| sum + e.abs())
+    }
+    /// Returns p=\\(\\infty\\) norm, the maximum absolute value, `0` if empty.
+    pub fn norm_inf(&self) -> FP
+    {
+        self.iter().fold(0., |m, e| m.max(e.abs()))
+    }
+    /// Returns operator norm induced by p=1 norm, the maximum absolute column sum, `0` if empty.
+    pub fn norm_op1(&self) -> FP
+    {
+        self.h_reduce_lines(false, |v| v.norm_p1()).norm_inf()
+    }
+    /// Returns operator norm induced by p=\\(\\infty\\) norm, the maximum absolute row sum, `0` if empty.
+    pub fn norm_op_inf(&self) -> FP
+    {
+        self.h_reduce_lines(true, |v| v.norm_p1()).norm_inf()
+    }
+    /// Returns nuclear norm, the sum of singular values, `0` if empty.
+    ///
+    /// It runs a full singular value decomposition by [`MatSVD`].
+    pub fn norm_nuclear(&self) -> FP
+    {
+        self.h_singular_values().sum()
+    }
+    /// Returns spectral norm, the largest singular value, `0` if empty.
+    ///
+    /// It runs a full singular value decomposition by [`MatSVD`].
+    /// See [`norm_spectral_est`](#method.norm_spectral_est) for a cheaper estimate.
+    pub fn norm_spectral(&self) -> FP
+    {
+        self.h_singular_values().norm_inf()
+    }
+    /// Returns an estimate of spectral norm by power iteration of \\(A^T A\\), `0` if empty.
+    ///
+    /// Iterations stop when the relative change of the estimate is within `tol`,
+    /// or after a fixed number of iterations, returning the last estimate.
+    /// The estimate never exceeds the spectral norm, and it is deterministic from a fixed starting vector.
+    pub fn norm_spectral_est(&self, tol: FP) -> FP
+    {
+        const MAX_ITER: usize = 1000;
+
+        let (l_nrows, l_ncols) = self.size();
+        if l_nrows * l_ncols == 0 {
+            return 0.;
+        }
+
+        let mut x = Mat::new_vec(l_ncols).set_rand(&mut Rng::default());
+        x /= x.norm_p2();
+        let mut sigma = 0.;
+
+        for _ in 0 .. MAX_ITER {
+            let y = self * &x;
+            let sigma_next = y.norm_p2();
+            let z = self.t() * y;
+            let z_norm = z.norm_p2();
+            if z_norm == 0. {
+                return sigma_next;
+            }
+            x = z / z_norm;
+
+            if (sigma_next - sigma).abs() <= tol * sigma_next {
+                return sigma_next;
+            }
+            sigma = sigma_next;
+        }
+
+        sigma
+    }
+    /// Returns condition number \\(\\sigma_{\\max} / \\sigma_{\\min}\\) for spectral norm, `0` if empty.
+    ///
+    /// It runs a full singular value decomposition by [`MatSVD`], and is infinite if \\(\\sigma_{\\min} = 0\\).
+    /// See [`cond_est_p1`](#method.cond_est_p1) for a cheaper estimate.
+    pub fn cond_p2(&self) -> FP
+    {
+        let s = self.h_singular_values();
+
+        match (s.max(), s.min()) {
+            (Some(s_max), Some(s_min)) => if s_min > 0. {s_max / s_min} else {FP::INFINITY},
+            _ => 0.
+        }
+    }
+    /// Returns an estimate of condition number \\(\\|A\\|_1 \\|A^{-1}\\|_1\\) of this square matrix for p=1 norm, `0` if empty.
+    ///
+    /// \\(\\|A^{-1}\\|_1\\) is estimated by the Hager/Higham method with a few solves of one [`MatLU`] factorization,
+    /// instead of the inverse itself.
+    /// The estimate never exceeds the condition number, and it is usually within a small factor of it.
+    /// It is infinite if [`MatLU`] finds this matrix singular.
+    pub fn cond_est_p1(&self) -> FP
+    {
+        const MAX_ITER: usize = 5;
+
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows == l_ncols, "cond_est_p1: size {}x{} is not square", l_nrows, l_ncols);
+        if l_nrows == 0 {
+            return 0.;
+        }
+        let n = l_nrows;
+
+        let lu = match MatLU::new(self) {
+            Ok(lu) => lu,
+            Err(_) => return FP::INFINITY
+        };
+
+        let mut x = Mat::new_vec(n).set_all(1. / n as FP);
+        let mut est = 0.;
+
+        for i in 0 .. MAX_ITER {
+            let y = lu.solve(&x);
+            let est_next = y.norm_p1();
+            if (i > 0) && (est_next <= est) {
+                break;
+            }
+            est = est_next;
+
+            let xi = y.map(|e| if e >= 0. {1.} else {-1.});
+            let z = lu.solve_t(&xi);
+            let j = z.abs_argmax().map_or(0, |(r, _)| r);
+            if (i > 0) && (z[(j, 0)].abs() <= z.prod(&x)) {
+                break;
+            }
+            x.assign_all(0.);
+            x[(j, 0)] = 1.;
+        }
+
+        // alternating vector guarding against the cases where the above underestimates
+        let x = Mat::new_vec(n).set_by(|r, _| {
+            let e = 1. + if n > 1 {r as FP / (n - 1) as FP} else {0.};
+            if r % 2 == 0 {e} else {-e}
+        });
+        let est_alt = 2. * lu.solve(&x).norm_p1() / (3 * n) as FP;
+
+        self.norm_op1() * est.max(est_alt)
+    }
+    /// Returns trace.
+    pub fn tr(&self) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        let mut sum = 0.;
+
+        for i in 0 .. l_nrows.min(l_ncols) {
+            sum += self[(i, i)];
+        }
+
+        sum
+    }
+    /// Returns inner product.
+    pub fn prod<V2: View>(&self, rhs: &MatGen<V2>) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = rhs.size();
+
+        assert_eq!(l_nrows, r_nrows);
+        assert_eq!(l_ncols, r_ncols);
+
+        let mut sum = 0.;
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                sum += self[(r, c)] * rhs[(r, c)];
+            }
+        }
+
+        sum
+    }
+    /// Returns inner product with compensated summation.
+    ///
+    /// Rounding errors of both products and sums are compensated,
+    /// so that the result is as accurate as if computed in twice the precision and then rounded.
+    pub fn prod_compensated<V2: View>(&self, rhs: &MatGen<V2>) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = rhs.size();
+
+        assert_eq!(l_nrows, r_nrows);
+        assert_eq!(l_ncols, r_ncols);
+
+        let mut acc = KahanSum::default();
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                acc.add_prod(self[(r, c)], rhs[(r, c)]);
+            }
+        }
+
+        acc.value()
+    }
+    /// Returns p=2 norm with compensated summation.
+    ///
+    /// Elements are scaled by their maximum absolute value, which also avoids overflow and underflow of squares.
+    pub fn norm_p2_accurate(&self) -> FP
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        let mut scale: FP = 0.;
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                scale = scale.max(self[(r, c)].abs());
+            }
+        }
+        if (scale == 0.) || !scale.is_finite() {
+            return scale;
+        }
+
+        let mut acc = KahanSum::default();
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                let v = self[(r, c)] / scale;
+                acc.add_prod(v, v);
+            }
+        }
+
+        FP::sqrt(acc.value()) * scale
+    }
+    // used by residual computations:
+    // p=2 norm, compensated with the compensated-sum feature
+    pub(crate) fn norm_p2_resid(&self) -> FP
+    {
+        if cfg!(feature = "compensated-sum") {
+            self.norm_p2_accurate()
+        }
+        else {
+            self.norm_p2()
+        }
+    }
+    /// Solves \\(LX = B\\) for each column of `b`, where \\(L\\) is the lower triangle of this square matrix.
+    ///
+    /// The diagonal is taken as ones if `unit`, otherwise a diagonal element
+    /// not larger in magnitude than \\(n \\epsilon\\) times the largest one is an error, which names its row.
+    /// Elements above the diagonal, and on it if `unit`, are not read.
+    pub fn solve_lower_triangular<V2: View>(&self, b: &MatGen<V2>, unit: bool) -> Result<Mat, SingularError>
+    {
+        self.h_solve_triangular("solve_lower_triangular", b, true, unit)
+    }
+    /// Solves \\(UX = B\\) for each column of `b`, where \\(U\\) is the upper triangle of this square matrix.
+    ///
+    /// See [`solve_lower_triangular`](#method.solve_lower_triangular) for `unit` and errors.
+    pub fn solve_upper_triangular<V2: View>(&self, b: &MatGen<V2>, unit: bool) -> Result<Mat, SingularError>
+    {
+        self.h_solve_triangular("solve_upper_triangular", b, false, unit)
+    }
+    /// Solves \\(AX = B\\) for each column of `b`, where \\(A\\) is this square matrix.
+    ///
+    /// It is by [`MatChol`] if \\(A\\) is symmetric within a tolerance of [`is_sym`](#method.is_sym)
+    /// and positive definite, otherwise by [`MatLU`] whose singularity is an error naming the pivot.
+    pub fn solve<V2: View>(&self, b: &MatGen<V2>) -> Result<Mat, SolveError>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = b.size();
+        if l_nrows != l_ncols {
+            return Err(SolveError::DimensionMismatch(format!("size {}x{} is not square", l_nrows, l_ncols)));
+        }
+        if l_nrows != r_nrows {
+            return Err(SolveError::DimensionMismatch(format!("size {}x{} does not match {}x{}", l_nrows, l_ncols, r_nrows, r_ncols)));
+        }
+
+        if self.is_sym(SOLVE_SYM_TOL) {
+            if let Ok(chol) = MatChol::new(self) {
+                return Ok(chol.solve(b));
+            }
+        }
+
+        let lu = MatLU::new(self).map_err(SolveError::Singular)?;
+        Ok(lu.solve(b))
+    }
+    /// Solves \\(A^T X = B\\) as [`solve`](#method.solve), through a transposed view.
+    pub fn solve_t<V2: View>(&self, b: &MatGen<V2>) -> Result<Mat, SolveError>
+    {
+        self.t().solve(b)
+    }
+    /// Returns the inverse of this square matrix by [`MatLU`], whose singularity is an error naming the pivot.
+    ///
+    /// Prefer [`solve`](#method.solve) unless the inverse itself is required.
+    pub fn inv(&self) -> Result<Mat, SingularError>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows == l_ncols, "inv: size {}x{} is not square", l_nrows, l_ncols);
+
+        MatLU::new(self).map(|lu| lu.inv())
+    }
+    /// Returns the Moore-Penrose pseudo-inverse \\(A^+\\) by [`MatSVD`].
+    ///
+    /// Singular values not above `tol` times the largest one are taken as zero,
+    /// where `tol` defaults to \\(\\max(m, n) \\epsilon\\) if `None`.
+    /// The result satisfies the Penrose conditions
+    /// \\(AA^+A = A\\), \\(A^+AA^+ = A^+\\), and symmetry of \\(AA^+\\) and \\(A^+A\\),
+    /// and it is the zero transpose for a zero matrix.
+    pub fn pinv(&self, tol: Option<FP>) -> Mat
+    {
+        let (l_nrows, l_ncols) = self.size();
+        if l_nrows * l_ncols == 0 {
+            return Mat::new(l_ncols, l_nrows);
+        }
+
+        let tol = tol.unwrap_or(l_nrows.max(l_ncols) as FP * FP_EPSILON);
+
+        let mut svd = MatSVD::new((l_nrows, l_ncols)).set_rank_revealing();
+        svd.decomp(&self.clone_sz());
+        svd.pinv(tol)
+    }
+    /// Solves the least-squares problem \\(\\min_X \\|AX - B\\|\\) for each column of `b` in one factorization.
+    ///
+    /// It is by [`MatQR`] if \\(A\\) has full column rank,
+    /// otherwise by [`MatSVD`] giving the minimum-norm solution.
+    /// Singular values, or diagonal entries of \\(R\\) for QR, not above `tol` times the largest one are negligible,
+    /// where `tol` defaults to \\(\\max(m, n) \\epsilon\\) if `None`.
+    pub fn lstsq<V2: View>(&self, b: &MatGen<V2>, tol: Option<FP>) -> LstsqResult
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = b.size();
+        assert!(l_nrows == r_nrows, "lstsq: size {}x{} does not match {}x{}", l_nrows, l_ncols, r_nrows, r_ncols);
+
+        let tol = tol.unwrap_or(l_nrows.max(l_ncols) as FP * FP_EPSILON);
+
+        let (x, rank) = if l_nrows * l_ncols == 0 {
+            (Mat::new(l_ncols, r_ncols), 0)
+        }
+        else {
+            let qr = if l_nrows >= l_ncols {Some(MatQR::new(self))} else {None};
+            let full = qr.as_ref().filter(|qr| qr.is_full_rank() && (qr.rank(tol) == l_ncols));
+
+            if let Some(qr) = full {
+                (qr.solve_ls(b), l_ncols)
+            }
+            else {
+                let mut svd = MatSVD::new((l_nrows, l_ncols)).set_rank_revealing();
+                svd.decomp(&self.clone_sz());
+                let s_max = svd.s().max().unwrap_or(0.);
+                let rank = svd.s().iter().filter(|&s| *s > tol * s_max).count();
+                (svd.pinv(tol) * b, rank)
+            }
+        };
+
+        let residual = (self * &x - b).col_norms_p2();
+
+        LstsqResult {x, residual, rank}
+    }
+    /// Returns the matrix exponential \\(e^A\\) of this square matrix.
+    ///
+    /// It is by scaling and squaring with a Padé approximant of degree 3, 5, 7, 9 or 13,
+    /// chosen by the operator norm induced by p=1 norm as in Higham (2005),
+    /// whose denominator is solved by [`MatLU`].
+    /// Work matrices are reused across squaring steps.
+    /// Every element is NaN if the matrix has NaN or infinity.
+    pub fn expm(&self) -> Mat
+    {
+        const PADE_3: [FP; 4] = [120., 60., 12., 1.];
+        const PADE_5: [FP; 6] = [30240., 15120., 3360., 420., 30., 1.];
+        const PADE_7: [FP; 8] = [17297280., 8648640., 1995840., 277200., 25200., 1512., 56., 1.];
+        const PADE_9: [FP; 10] = [17643225600., 8821612800., 2075673600., 302702400., 30270240., 2162160., 110880., 3960., 90., 1.];
+        const PADE_13: [FP; 14] = [64764752532480000., 32382376266240000., 7771770303897600., 1187353796428800., 129060195264000., 10559470521600., 670442572800., 33522128640., 1323241920., 40840800., 960960., 16380., 182., 1.];
+        const THETA: [FP; 4] = [1.495585217958292e-2, 2.53939833006323e-1, 9.504178996162932e-1, 2.097847961257068];
+        const THETA_13: FP = 5.371920351148152;
+
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows == l_ncols, "expm: size {}x{} is not square", l_nrows, l_ncols);
+        let n = l_nrows;
+
+        let norm = self.norm_op1();
+        if !norm.is_finite() {
+            return Mat::new(n, n).set_all(FP::NAN);
+        }
+
+        let eye = Mat::new(n, n).set_eye();
+
+        if let Some(i) = THETA.iter().position(|&theta| norm <= theta) {
+            let b: &[FP] = match i {
+                0 => &PADE_3,
+                1 => &PADE_5,
+                2 => &PADE_7,
+                _ => &PADE_9
+            };
+
+            // U = A sum b_{2k+1} A^{2k}, V = sum b_{2k} A^{2k}
+            let a2 = self * self;
+            let mut p = eye.clone();
+            let mut u = b[1] * &eye;
+            let mut v = b[0] * &eye;
+            for k in 1 .. b.len() / 2 {
+                p = &p * &a2;
+                u += b[2 * k + 1] * &p;
+                v += b[2 * k] * &p;
+            }
+            let u = self * u;
+
+            return h_pade_solve(&u, &v);
+        }
+
+        let scale = ((norm / THETA_13).log2().ceil() as i32).max(0);
+        let a = self * FP::powi(2., -scale);
+        let b = &PADE_13;
+
+        let a2 = &a * &a;
+        let a4 = &a2 * &a2;
+        let a6 = &a2 * &a4;
+
+        let u = &a * (&a6 * (b[13] * &a6 + b[11] * &a4 + b[9] * &a2)
+                      + b[7] * &a6 + b[5] * &a4 + b[3] * &a2 + b[1] * &eye);
+        let v = &a6 * (b[12] * &a6 + b[10] * &a4 + b[8] * &a2)
+                + b[6] * &a6 + b[4] * &a4 + b[2] * &a2 + b[0] * &eye;
+
+        let mut x = h_pade_solve(&u, &v);
+        let mut w = Mat::new(n, n);
+        for _ in 0 .. scale {
+            h_mul_into(&mut w, &x, &x);
+            std::mem::swap(&mut x, &mut w);
+        }
+
+        x
+    }
+    /// Returns eigenvalues of this symmetric matrix in descending order, as a column vector.
+    ///
+    /// It is a shortcut of [`MatEig::new_sym`], reading only the lower triangle.
+    pub fn eigvals_sym(&self) -> Result<Mat, NotConverged>
+    {
+        MatEig::new_sym(self).map(|eig| eig.eigvals().clone())
+    }
+    /// Returns the projection onto the positive semidefinite cone, the nearest positive semidefinite matrix in Frobenius norm.
+    ///
+    /// It runs [`MatEig::new_sym`] of the symmetric part of this matrix, which tolerates mild asymmetry,
+    /// and reconstructs with negative eigenvalues clipped to zero.
+    ///
+    /// Panics if not square or the decomposition does not converge, which is the case with NaN.
+    pub fn proj_psd(&self) -> Mat
+    {
+        self.proj_psd_eps(0.)
+    }
+    /// Returns the projection as [`proj_psd`](#method.proj_psd) with eigenvalues clipped to `eps` instead of zero,
+    /// which makes the result positive definite if `eps > 0`.
+    pub fn proj_psd_eps(&self, eps: FP) -> Mat
+    {
+        let eig = self.h_eig_sym("proj_psd").unwrap_or_else(|e| panic!("proj_psd: {}", e));
+
+        h_eig_map(&eig, |e| e.max(eps))
+    }
+    /// Returns the symmetric square root \\(S = S^T\\) of this positive semidefinite matrix, where \\(S^2 = A\\).
+    ///
+    /// It runs [`MatEig::new_sym`] of the symmetric part of this matrix, which tolerates mild asymmetry.
+    /// Negative eigenvalues within \\(16 n \\epsilon \\max_i |\\lambda_i|\\) are taken as zero,
+    /// otherwise returns `Err` with the smallest eigenvalue.
+    ///
+    /// Panics if not square.
+    pub fn sqrt_psd(&self) -> Result<Mat, NotPsd>
+    {
+        let eig = self.h_eig_sym("sqrt_psd").map_err(|_| NotPsd {value: FP::NAN})?;
+
+        let vals = eig.eigvals();
+        if let Some(min) = vals.min() {
+            let tol = vals.size().0 as FP * PSD_TOL * vals.norm_inf();
+            if min < -tol {
+                return Err(NotPsd {value: min});
+            }
+        }
+
+        Ok(h_eig_map(&eig, |e| e.max(0.).sqrt()))
+    }
+    /// Returns a factor \\(F\\) of this positive semidefinite matrix, where \\(FF^T = A\\).
+    ///
+    /// It is the lower triangular factor by [`MatChol`] if positive definite within a tolerance of [`is_sym`](#method.is_sym),
+    /// otherwise the symmetric root by [`sqrt_psd`](#method.sqrt_psd), which is costlier and also handles singular matrices.
+    pub fn chol_factor_or_sqrt(&self) -> Result<Mat, NotPsd>
+    {
+        if self.is_sym(SOLVE_SYM_TOL) {
+            if let Ok(chol) = MatChol::new(self) {
+                return Ok(chol.l().clone());
+            }
+        }
+
+        self.sqrt_psd()
+    }
+    // MatEig of the symmetric part, panicking if not square
+    fn h_eig_sym(&self, name: &str) -> Result<MatEig, NotConverged>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert!(l_nrows == l_ncols, "{}: size {}x{} is not square", name, l_nrows, l_ncols);
+
+        let mut sym = self.clone_sz();
+        sym.symmetrize();
+
+        MatEig::new_sym(&sym)
+    }
+    /// Finds the largest eigenvalue of this symmetric matrix and its eigenvector by power iteration.
+    ///
+    /// The matrix is shifted by its operator norm induced by p=\\(\\infty\\) norm to be positive semidefinite,
+    /// and the eigenvalue is estimated by the Rayleigh quotient.
+    /// Iterations stop when the residual is within `tol` times that norm,
+    /// otherwise returns `Err` after `max_iter` iterations.
+    /// The starting vector is `x0`, or a fixed pseudo-random one if `None` so that results are reproducible.
+    pub fn eig_max_sym(&self, tol: FP, max_iter: usize, x0: Option<&Mat>) -> Result<EigPair, NotConverged>
+    {
+        self.h_check_eig("eig_max_sym", x0);
+
+        let shift = self.norm_op_inf();
+        self.h_eig_iter(tol, max_iter, x0, |x| self * x + shift * x)
+    }
+    /// Finds the smallest eigenvalue of this symmetric matrix and its eigenvector by shifted inverse iteration.
+    ///
+    /// The shift is taken below the Gershgorin lower bound of the eigenvalues,
+    /// and the shifted matrix is factorized once by [`MatChol`], or by [`MatLU`] if it fails numerically.
+    /// The other parameters are the same as [`eig_max_sym`](#method.eig_max_sym).
+    pub fn eig_min_sym(&self, tol: FP, max_iter: usize, x0: Option<&Mat>) -> Result<EigPair, NotConverged>
+    {
+        let n = self.h_check_eig("eig_min_sym", x0);
+
+        let norm = self.norm_op_inf();
+        let lower = (0 .. n).map(|i| 2. * self[(i, i)] - self.row(i).norm_p1()).fold(FP::INFINITY, FP::min);
+        let shift = lower - (norm * 1e-3).max(FP_MINPOS);
+
+        let mut shifted = self.clone_sz();
+        shifted.add_diag(-shift);
+
+        match MatChol::new(&shifted) {
+            Ok(chol) => self.h_eig_iter(tol, max_iter, x0, |x| chol.solve(x)),
+            Err(_) => match MatLU::new(&shifted) {
+                Ok(lu) => self.h_eig_iter(tol, max_iter, x0, |x| lu.solve(x)),
+                Err(_) => Err(NotConverged {iterations: 0, residual: FP::NAN})
+            }
+        }
+    }
+    // dimension, panicking if not square, empty or x0 of a wrong size
+    fn h_check_eig(&self, name: &str, x0: Option<&Mat>) -> usize
+    {
+        let (l_nrows, l_ncols) = self.size();
+        assert!((l_nrows == l_ncols) && (l_nrows > 0), "{}: size {}x{} is not square or empty", name, l_nrows, l_ncols);
+        if let Some(x0) = x0 {
+            h_check_vec(name, x0.size(), l_nrows);
+        }
+
+        l_nrows
+    }
+    // iterates x by step until the Rayleigh quotient of x is an eigenvalue within tol
+    fn h_eig_iter<F>(&self, tol: FP, max_iter: usize, x0: Option<&Mat>, mut step: F) -> Result<EigPair, NotConverged>
+    where F: FnMut(&Mat) -> Mat
+    {
+        let n = self.size().0;
+        let scale = self.norm_op_inf();
+
+        let mut x = match x0 {
+            Some(x0) => x0.clone_sz(),
+            None => Mat::new_vec(n).set_rand(&mut Rng::default())
+        };
+        let x_norm = x.norm_p2();
+        if x_norm > 0. {
+            x /= x_norm;
+        }
+
+        let mut it = 0;
+        loop {
+            let ax = self * &x;
+            let value = x.prod(&ax);
+            let residual = (ax - value * &x).norm_p2();
+
+            if residual <= tol * scale {
+                return Ok(EigPair {value, vector: x, residual, iterations: it});
+            }
+            if residual.is_nan() || (it >= max_iter) {
+                return Err(NotConverged {iterations: it, residual});
+            }
+
+            let y = step(&x);
+            let y_norm = y.norm_p2();
+            if y_norm == 0. {
+                return Err(NotConverged {iterations: it, residual});
+            }
+            x = y / y_norm;
+            it += 1;
+        }
+    }
+    /// Returns an orthonormal basis of the null space \\(\\{x \\mid Ax = 0\\}\\) by [`MatSVD`], as columns of an \\(n \\times (n - r)\\) matrix.
+    ///
+    /// Singular values not above `tol` times the largest one are negligible,
+    /// where `tol` defaults to \\(\\max(m, n) \\epsilon\\) if `None`.
+    /// It has no columns if \\(A\\) has full column rank.
+    pub fn null_space(&self, tol: Option<FP>) -> Mat
+    {
+        let (l_nrows, l_ncols) = self.size();
+        if l_ncols == 0 {
+            return Mat::new(0, 0);
+        }
+        if l_nrows == 0 {
+            return Mat::new(l_ncols, l_ncols).set_eye();
+        }
+
+        let (s, v) = self.h_right_singular();
+        let thr = tol.unwrap_or(l_nrows.max(l_ncols) as FP * FP_EPSILON) * s.max().unwrap_or(0.);
+        let idx: Vec<usize> = (0 .. l_ncols).filter(|&i| s[(i, 0)] <= thr).collect();
+
+        v.select_cols(&idx)
+    }
+    /// Returns an orthonormal basis of the range \\(\\{Ax\\}\\) by [`MatSVD`], as columns of an \\(m \\times r\\) matrix.
+    ///
+    /// See [`null_space`](#method.null_space) for `tol`.
+    pub fn range_space(&self, tol: Option<FP>) -> Mat
+    {
+        let (l_nrows, l_ncols) = self.size();
+        if l_nrows * l_ncols == 0 {
+            return Mat::new(l_nrows, 0);
+        }
+
+        let (s, v) = self.t().h_right_singular();
+        let thr = tol.unwrap_or(l_nrows.max(l_ncols) as FP * FP_EPSILON) * s.max().unwrap_or(0.);
+        let idx: Vec<usize> = (0 .. l_nrows).filter(|&i| s[(i, 0)] > thr).collect();
+
+        v.select_cols(&idx)
+    }
+    // singular values and the complete right singular vectors, padding zero rows if wide
+    fn h_right_singular(&self) -> (Mat, Mat)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        let mut g = Mat::new(l_nrows.max(l_ncols), l_ncols);
+        g.rows_mut(0 .. l_nrows).assign(self);
+
+        let mut svd = MatSVD::new(g.size()).set_rank_revealing();
+        svd.decomp(&g);
+        (svd.s().clone(), svd.v().clone())
+    }
+    fn h_solve_triangular<V2: View>(&self, name: &str, b: &MatGen<V2>, lower: bool, unit: bool) -> Result<Mat, SingularError>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = b.size();
+        assert!(l_nrows == l_ncols, "{}: size {}x{} is not square", name, l_nrows, l_ncols);
+        assert!(l_nrows == r_nrows, "{}: size {}x{} does not match {}x{}", name, l_nrows, l_ncols, r_nrows, r_ncols);
+
+        let n = l_nrows;
+
+        if !unit {
+            let tol = n as FP * FP_EPSILON * self.diag().norm_inf();
+            if let Some(r) = (0 .. n).find(|&r| {let d = self[(r, r)].abs(); d.is_nan() || d <= tol}) {
+                return Err(SingularError {pivot: r, value: self[(r, r)].abs()});
+            }
+        }
+
+        let mut x = b.clone_sz();
+
+        // column-oriented, whose inner loops run down columns
+        for c in 0 .. r_ncols {
+            for k in 0 .. n {
+                let j = if lower {k} else {n - 1 - k};
+                if !unit {
+                    x[(j, c)] /= self[(j, j)];
+                }
+                let x_j = x[(j, c)];
+                if x_j != 0. {
+                    let rows = if lower {j + 1 .. n} else {0 .. j};
+                    for i in rows {
+                        x[(i, c)] -= self[(i, j)] * x_j;
+                    }
+                }
+            }
+        }
+
+        Ok(x)
+    }
+    //
+    /// Finds maximum value.
+    pub fn max(&self) -> Option<FP>
+    {
+        let mut iter = self.iter();
+        let first = *iter.next()?;
+
+        Some(iter.fold(first, |m, &e| if e > m {e} else {m}))
+    }
+    /// Finds minumum value.
+    pub fn min(&self) -> Option<FP>
+    {
+        let mut iter = self.iter();
+        let first = *iter.next()?;
+
+        Some(iter.fold(first, |m, &e| if e < m {e} else {m}))
+    }
+    /// Finds (row, column) of the maximum value, `None` if empty.
+    ///
+    /// NaN elements are skipped, so that it is `None` also if all elements are NaN.
+    /// Ties are broken by the first in column-major order.
+    pub fn argmax(&self) -> Option<(usize, usize)>
+    {
+        self.h_arg(|x, best| x > best)
+    }
+    /// Finds (row, column) of the minimum value, `None` if empty, in the same manner as [`argmax`](#method.argmax).
+    pub fn argmin(&self) -> Option<(usize, usize)>
+    {
+        self.h_arg(|x, best| x < best)
+    }
+    /// Finds (row, column) of the maximum absolute value, `None` if empty, in the same manner as [`argmax`](#method.argmax),
+    /// which is what pivoting looks for.
+    pub fn abs_argmax(&self) -> Option<(usize, usize)>
+    {
+        self.h_arg(|x, best| x.abs() > best.abs())
+    }
+    /// Finds the row of the maximum value of each column, in the same manner as [`argmax`](#method.argmax),
+    /// `None` for a column of no elements or all NaN.
+    pub fn col_argmax(&self) -> Vec<Option<usize>>
+    {
+        let (_, l_ncols) = self.size();
+
+        (0 .. l_ncols).map(|c| self.col(c).argmax().map(|(r, _)| r)).collect()
+    }
+    /// Finds the row of the minimum value of each column, in the same manner as [`col_argmax`](#method.col_argmax).
+    pub fn col_argmin(&self) -> Vec<Option<usize>>
+    {
+        let (_, l_ncols) = self.size();
+
+        (0 .. l_ncols).map(|c| self.col(c).argmin().map(|(r, _)| r)).collect()
+    }
+    /// Returns sum of elements, `0` if empty.
+    pub fn sum(&self) -> FP
+    {
+        self.iter().sum()
+    }
+    /// Returns mean of elements, `None` if empty.
+    pub fn mean(&self) -> Option<FP>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let len = l_nrows * l_ncols;
+
+        if len == 0 {
+            None
+        }
+        else {
+            Some(self.sum() / len as FP)
+        }
+    }
+    /// Returns population variance of elements, `None` if empty.
+    ///
+    /// Computed in two passes, summing squared deviations from the mean,
+    /// which does not lose precision as the naive mean of squares minus squared mean does.
+    pub fn variance(&self) -> Option<FP>
+    {
+        let mean = self.mean()?;
+        let len = self.size().0 * self.size().1;
+
+        Some(self.iter().fold(0., |sum, e| sum + (e - mean) * (e - mean)) / len as FP)
+    }
+    /// Returns population standard deviation of elements, `None` if empty.
+    pub fn std_dev(&self) -> Option<FP>
+    {
+        self.variance().map(FP::sqrt)
+    }
+    /// Returns sums of each column, as a column vector like the other axis reductions.
+    ///
+    /// Column reductions are column vectors of the number of columns, and row reductions are of the number of rows,
+    /// so that `a.t().col_sums()` equals `a.row_sums()`.
+    pub fn col_sums(&self) -> Mat
+    {
+        self.h_reduce_lines(false, |v| v.sum())
+    }
+    /// Returns sums of each row, as a column vector.
+    pub fn row_sums(&self) -> Mat
+    {
+        self.h_reduce_lines(true, |v| v.sum())
+    }
+    /// Returns means of each column, as a column vector, NaN if it has no elements.
+    pub fn col_means(&self) -> Mat
+    {
+        self.h_reduce_lines(false, |v| v.mean().unwrap_or(FP::NAN))
+    }
+    /// Returns means of each row, as a column vector, NaN if it has no elements.
+    pub fn row_means(&self) -> Mat
+    {
+        self.h_reduce_lines(true, |v| v.mean().unwrap_or(FP::NAN))
+    }
+    /// Returns maximum values of each column, as a column vector, NaN if it has no elements.
+    pub fn col_max(&self) -> Mat
+    {
+        self.h_reduce_lines(false, |v| v.max().unwrap_or(FP::NAN))
+    }
+    /// Returns maximum values of each row, as a column vector, NaN if it has no elements.
+    pub fn row_max(&self) -> Mat
+    {
+        self.h_reduce_lines(true, |v| v.max().unwrap_or(FP::NAN))
+    }
+    /// Returns minimum values of each column, as a column vector, NaN if it has no elements.
+    pub fn col_min(&self) -> Mat
+    {
+        self.h_reduce_lines(false, |v| v.min().unwrap_or(FP::NAN))
+    }
+    /// Returns minimum values of each row, as a column vector, NaN if it has no elements.
+    pub fn row_min(&self) -> Mat
+    {
+        self.h_reduce_lines(true, |v| v.min().unwrap_or(FP::NAN))
+    }
+    /// Returns p=2 norms of each column, as a column vector.
+    pub fn col_norms_p2(&self) -> Mat
+    {
+        self.h_reduce_lines(false, |v| v.norm_p2())
+    }
+    /// Returns p=2 norms of each row, as a column vector.
+    pub fn row_norms_p2(&self) -> Mat
+    {
+        self.h_reduce_lines(true, |v| v.norm_p2())
+    }
+    /// Counts elements satisfying `pred`.
+    pub fn count<F>(&self, mut pred: F) -> usize
+    where F: FnMut(FP) -> bool
+    {
+        self.iter().filter(|e| pred(**e)).count()
+    }
+    /// Checks if square and symmetric within `tol`, false if not square.
+    ///
+    /// Each pair is compared by \\(|a_{ij} - a_{ji}| \\le {\\rm tol} \\cdot \\max(1, |a_{ij}|, |a_{ji}|)\\),
+    /// which is absolute for small elements and relative for large ones.
+    pub fn is_sym(&self, tol: FP) -> bool
+    {
+        self.sym_violation().is_some_and(|(_, _, v)| v <= tol)
+    }
+    // used by solver modules:
+    // the worst pair (row, column) in the upper triangle and its asymmetry measured as is_sym,
+    // (0, 0, 0) if no pairs, or None if not square
+    pub(crate) fn sym_violation(&self) -> Option<(usize, usize, FP)>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        if l_nrows != l_ncols {
+            return None;
+        }
+
+        let mut worst: (usize, usize, FP) = (0, 0, 0.);
+        for c in 0 .. l_ncols {
+            for r in 0 .. c {
+                let (x, y) = (self[(r, c)], self[(c, r)]);
+                let v = (x - y).abs() / x.abs().max(y.abs()).max(1.);
+                if !worst.2.is_nan() && (v.is_nan() || (v > worst.2)) {
+                    worst = (r, c, v);
+                }
+            }
+        }
+        Some(worst)
+    }
+    /// Checks if any element is NaN.
+    pub fn has_nan(&self) -> bool
+    {
+        self.iter().any(|e| e.is_nan())
+    }
+    /// Checks if all elements are neither infinite nor NaN.
+    pub fn is_finite(&self) -> bool
+    {
+        self.iter().all(|e| e.is_finite())
+    }
+    /// Finds the first infinite or NaN element in column-major order, as (row, column, value).
+    pub fn find_non_finite(&self) -> Option<(usize, usize, FP)>
+    {
+        let (l_nrows, _) = self.size();
+
+        self.iter().position(|e| !e.is_finite()).map(|i| {
+            let (r, c) = (i % l_nrows, i / l_nrows);
+            (r, c, self[(r, c)])
+        })
+    }
+    //
+    /// Returns storage order of elements,
+    /// which is `Layout::RowMajor` for transposed views as well as row-major matrices.
+    pub fn layout(&self) -> Layout
+    {
+        if !self.transposed {
+            Layout::ColMajor
+        }
+        else {
+            Layout::RowMajor
+        }
+    }
+    /// Returns number of rows and columns.
+    pub fn size(&self) -> (usize, usize)
+    {
+        if !self.transposed {
+            (self.nrows, self.ncols)
+        }
+        else {
+            (self.ncols, self.nrows)
+        }
+    }
+    /// Returns a reference to the element at row `r` and column `c`,
+    /// or `None` if either is out of [`size`](#method.size).
+    pub fn get(&self, r: usize, c: usize) -> Option<&FP>
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        if (r < l_nrows) && (c < l_ncols) {
+            Some(&self[(r, c)])
+        }
+        else {
+            None
+        }
+    }
+    /// Returns a mutable reference to the element at row `r` and column `c`,
+    /// or `None` if either is out of [`size`](#method.size).
+    pub fn get_mut(&mut self, r: usize, c: usize) -> Option<&mut FP>
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        if (r < l_nrows) && (c < l_ncols) {
+            Some(&mut self[(r, c)])
+        }
+        else {
+            None
+        }
+    }
+    /// Returns a reference to the element at row `r` and column `c` without any bounds check.
+    ///
+    /// # Safety
+    ///
+    /// `r` and `c` must be within [`size`](#method.size).
+    pub unsafe fn get_unchecked(&self, r: usize, c: usize) -> &FP
+    {
+        let i = self.h_index((r, c));
+
+        self.view.get_ref().get_unchecked(i)
+    }
+    /// Returns a mutable reference to the element at row `r` and column `c` without any bounds check.
+    ///
+    /// # Safety
+    ///
+    /// `r` and `c` must be within [`size`](#method.size).
+    pub unsafe fn get_unchecked_mut(&mut self, r: usize, c: usize) -> &mut FP
+    {
+        let i = self.h_index((r, c));
+
+        self.view.get_mut().get_unchecked_mut(i)
+    }
+    /// Returns an iterator over elements in column-major order of the logical (row, column),
+    /// which is a plain slice iterator if the elements are contiguous and non-transposed.
+    pub fn iter(&self) -> impl Iterator<Item=&FP>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let contig = self.h_col_major();
+
+        let strided = if contig.is_none() {
+            Some((0 .. l_ncols).flat_map(move |c| (0 .. l_nrows).map(move |r| &self[(r, c)])))
+        }
+        else {
+            None
+        };
+
+        contig.into_iter().flatten().chain(strided.into_iter().flatten())
+    }
+    /// Returns a mutable iterator over elements in the same order as [`iter`](#method.iter).
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut FP>
+    {
+        let (l_nrows, l_ncols) = self.size();
+        let len = l_nrows * l_ncols;
+
+        if self.h_col_major().is_some() {
+            let offset = self.offset;
+            let contig = &mut self.view.get_mut()[offset .. offset + len];
+
+            return IterMut::Contig(contig.iter_mut());
+        }
+
+        let (col_step, row_step) = if !self.transposed {(self.stride, self.rstride)} else {(self.rstride, self.stride)};
+        let offset = self.offset as isize;
+        let view = self.view.get_mut();
+
+        IterMut::Strided(StridedIterMut {
+            len_view: view.len(),
+            ptr: view.as_mut_ptr(),
+            offset, col_step, row_step,
+            nrows: l_nrows,
+            pos: 0,
+            len,
+            _view: PhantomData
+        })
+    }
+}
+
+// mutable iterator of MatGen::iter_mut, a plain slice iterator if contiguous
+enum IterMut<'a>
+{
+    Contig(std::slice::IterMut<'a, FP>),
+    Strided(StridedIterMut<'a>)
+}
+
+impl<'a> Iterator for IterMut<'a>
+{
+    type Item = &'a mut FP;
+
+    fn next(&mut self) -> Option<&'a mut FP>
+    {
+        match self {
+            IterMut::Contig(it) => it.next(),
+            IterMut::Strided(it) => it.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        match self {
+            IterMut::Contig(it) => it.size_hint(),
+            IterMut::Strided(it) => it.size_hint()
+        }
+    }
+}
+
+// walks the logical (row, column) in column-major order by the signed strides, as h_index does
+struct StridedIterMut<'a>
+{
+    ptr: *mut FP,
+    len_view: usize,
+    offset: isize,
+    col_step: isize,
+    row_step: isize,
+    nrows: usize,
+    pos: usize,
+    len: usize,
+    _view: PhantomData<&'a mut FP>
+}
+
+impl<'a> Iterator for StridedIterMut<'a>
+{
+    type Item = &'a mut FP;
+
+    fn next(&mut self) -> Option<&'a mut FP>
+    {
+        if self.pos >= self.len {
+            return None;
+        }
+
+        let (r, c) = (self.pos % self.nrows, self.pos / self.nrows);
+        let i = (self.offset + self.col_step * c as isize + self.row_step * r as isize) as usize;
+        self.pos += 1;
+
+        assert!(i < self.len_view);
+        // SAFETY: i is within the view, and distinct (row, column) of a view never share an element,
+        // so that each element is lent at most once during 'a
+        unsafe {Some(&mut *self.ptr.add(i))}
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        let rest = self.len - self.pos;
+
+        (rest, Some(rest))
+    }
+}
+
+impl Mat
+{
+    /// Resizes in place, keeping the overlapping top-left block and filling new elements with zero.
+    ///
+    /// The buffer is re-strided in place,
+    /// or the elements are copied into a new buffer if `self` does not cover its whole buffer.
+    pub fn resize(&mut self, nrows: usize, ncols: usize)
+    {
+        let (l_nrows, l_ncols) = self.size();
+        if (l_nrows, l_ncols) == (nrows, ncols) {
+            return;
+        }
+
+        if (self.offset != 0) || (self.stride != self.nrows as isize) || (self.rstride != 1) || (self.view.len() != self.nrows * self.ncols) {
+            let mut mat = Mat::new(nrows, ncols);
+            let (r, c) = (l_nrows.min(nrows), l_ncols.min(ncols));
+            mat.slice_mut(0 .. r, 0 .. c).assign(&self.slice(0 .. r, 0 .. c));
+            *self = mat;
+            return;
+        }
+
+        // storage order of a transposed matrix is of its rows
+        let (new_r, new_c) = if !self.transposed {(nrows, ncols)} else {(ncols, nrows)};
+        let (old_r, old_c) = (self.nrows, self.ncols);
+        let min_c = old_c.min(new_c);
+        let v = &mut self.view;
+
+        if new_r <= old_r {
+            for c in 0 .. min_c {
+                for r in 0 .. new_r {
+                    v[c * new_r + r] = v[c * old_r + r];
+                }
+            }
+            v.truncate(min_c * new_r);
+            v.resize(new_r * new_c, 0.);
+        }
+        else {
+            v.truncate(min_c * old_r);
+            v.resize(new_r * new_c, 0.);
+            for c in (0 .. min_c).rev() {
+                for r in (0 .. old_r).rev() {
+                    v[c * new_r + r] = v[c * old_r + r];
+                }
+                for r in old_r .. new_r {
+                    v[c * new_r + r] = 0.;
+                }
+            }
+        }
+
+        self.nrows = new_r;
+        self.ncols = new_c;
+        self.stride = new_r as isize;
+    }
+}
+
+//
+
+impl<V: View> Index<(usize, usize)> for MatGen<V>
+{
+    type Output = FP;
+    fn index(&self, index: (usize, usize)) -> &FP
+    {
+        let i = self.h_index(index);
+
+        &self.view.get_ref()[i]
+    }
+}
+
+impl<V: View> IndexMut<(usize, usize)> for MatGen<V>
+{
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut FP
+    {
+        let i = self.h_index(index);
+
+        &mut self.view.get_mut()[i]
+    }
+}
+
+impl<V: View> Index<usize> for MatGen<V>
+{
+    type Output = FP;
+    /// Indexes a column or row vector along its long dimension.
+    ///
+    /// Panics if neither dimension is 1.
+    fn index(&self, i: usize) -> &FP
+    {
+        &self[self.h_vec_index(i)]
+    }
+}
+
+impl<V: View> IndexMut<usize> for MatGen<V>
+{
+    /// Indexes a column or row vector along its long dimension.
+    ///
+    /// Panics if neither dimension is 1.
+    fn index_mut(&mut self, i: usize) -> &mut FP
+    {
+        let index = self.h_vec_index(i);
+
+        &mut self[index]
+    }
+}
+
+//
+
+impl<V: View> fmt::LowerExp for MatGen<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        writeln!(f, "[")?;
+        for r in 0 .. l_nrows {
+            for c in 0 .. l_ncols {
+                write!(f, "  {:.precision$e},", self[(r, c)], precision = f.precision().unwrap_or(3))?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<V: View> fmt::Display for MatGen<V>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        writeln!(f, "{:.3e}", self)
+    }
+}
+
+//
+
+impl<V: View, V2: View> PartialEq<MatGen<V2>> for MatGen<V>
+{
+    fn eq(&self, other: &MatGen<V2>) -> bool
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        if (l_nrows, l_ncols) != other.size() {
+            return false;
+        }
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                if self[(r, c)] != other[(r, c)] {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+//
+
+impl Clone for Mat
+{
+    fn clone(&self) -> Mat
+    {
+        self.clone_sz()
+    }
+}
+
+//
+
+/// Error of fallible matrix operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatError
+{
+    /// Number of elements or its size in bytes overflows.
+    DimensionOverflow(String),
+    /// Number of given elements does not match dimensions.
+    LengthMismatch(String),
+    /// Index is out of bounds.
+    OutOfBounds(String),
+    /// Indices are not a permutation, having a duplicate.
+    NotPermutation(String),
+    /// Element is out of the domain of a function.
+    OutOfDomain(String)
+}
+
+impl fmt::Display for MatError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        match self {
+            MatError::DimensionOverflow(s) => write!(f, "dimension overflow: {}", s),
+            MatError::LengthMismatch(s) => write!(f, "length mismatch: {}", s),
+            MatError::OutOfBounds(s) => write!(f, "out of bounds: {}", s),
+            MatError::NotPermutation(s) => write!(f, "not a permutation: {}", s),
+            MatError::OutOfDomain(s) => write!(f, "out of domain: {}", s)
+        }
+    }
+}
+
+impl std::error::Error for MatError {}
+
+/// Error of [`MatGen::solve`](struct.MatGen.html#method.solve)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError
+{
+    /// Matrix is not square or does not match the right-hand side.
+    DimensionMismatch(String),
+    /// Matrix is singular or nearly singular.
+    Singular(SingularError)
+}
+
+impl fmt::Display for SolveError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        match self {
+            SolveError::DimensionMismatch(s) => write!(f, "dimension mismatch: {}", s),
+            SolveError::Singular(e) => e.fmt(f)
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Result of [`MatGen::lstsq`](struct.MatGen.html#method.lstsq)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LstsqResult
+{
+    /// Minimum-norm solution \\(X\\), whose columns correspond to those of the right-hand side.
+    pub x: Mat,
+    /// p=2 norms of the residual \\(AX - B\\) for each column, as a column vector.
+    pub residual: Mat,
+    /// Effective rank of \\(A\\).
+    pub rank: usize
+}
+
+// (V - U)^{-1} (V + U) of a Padé approximant, all NaN if singular
+fn h_pade_solve(u: &Mat, v: &Mat) -> Mat
+{
+    match MatLU::new(&(v - u)) {
+        Ok(lu) => lu.solve(&(v + u)),
+        Err(_) => Mat::new(u.size().0, u.size().1).set_all(FP::NAN)
+    }
+}
+
+// dst = lhs * rhs without allocation
+fn h_mul_into(dst: &mut Mat, lhs: &Mat, rhs: &Mat)
+{
+    let (l_nrows, l_ncols) = lhs.size();
+    let r_ncols = rhs.size().1;
+
+    for c in 0 .. r_ncols {
+        for r in 0 .. l_nrows {
+            let mut v: FP = 0.0;
+            for k in 0 .. l_ncols {
+                v += lhs[(r, k)] * rhs[(k, c)];
+            }
+            dst[(r, c)] = v;
+        }
+    }
+}
+
+// V f(Λ) V^T, exactly symmetric
+fn h_eig_map<F>(eig: &MatEig, f: F) -> Mat
+where F: Fn(FP) -> FP
+{
+    let vecs = eig.eigvecs();
+
+    let mut m = vecs * eig.eigvals().map(f).clone_diag() * vecs.t();
+    m.symmetrize();
+    m
+}
+
+// number of elements, whose size in bytes must be within isize::MAX as well for Vec
+fn h_len(nrows: usize, ncols: usize) -> Result<usize, MatError>
+{
+    nrows.checked_mul(ncols)
+         .filter(|len| len.checked_mul(std::mem::size_of::<FP>()).is_some_and(|sz| sz <= isize::MAX as usize))
+         .ok_or_else(|| MatError::DimensionOverflow(format!("{} x {}", nrows, ncols)))
+}
+
+// panics with both dimensions unless the sizes match
+fn h_check_size(name: &str, lhs: (usize, usize), rhs: (usize, usize))
+{
+    assert!(lhs == rhs, "{}: size {}x{} does not match {}x{}", name, lhs.0, lhs.1, rhs.0, rhs.1);
+}
+
+// panics unless the size is of a row or column vector of len
+fn h_check_vec(name: &str, size: (usize, usize), len: usize)
+{
+    assert!((size == (len, 1)) || (size == (1, len)), "{}: size {}x{} is not a vector of length {}", name, size.0, size.1, len);
+}
+
+// validates a permutation and applies it by swaps along its cycles
+fn h_permute<F>(perm: &[usize], len: usize, mut swap: F) -> Result<(), MatError>
+where F: FnMut(usize, usize)
+{
+    if perm.len() != len {
+        return Err(MatError::LengthMismatch(format!("permutation of {} for {}", perm.len(), len)));
+    }
+
+    // flags of seen indices, which mark visited ones of cycles afterwards
+    let mut seen = vec![false; len];
+    for &p in perm {
+        if p >= len {
+            return Err(MatError::OutOfBounds(format!("index {} for {}", p, len)));
+        }
+        if seen[p] {
+            return Err(MatError::NotPermutation(format!("index {} appears twice", p)));
+        }
+        seen[p] = true;
+    }
+
+    for i in 0 .. len {
+        if seen[i] {
+            let mut j = i;
+            seen[j] = false;
+            while perm[j] != i {
+                swap(j, perm[j]);
+                j = perm[j];
+                seen[j] = false;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn h_check_idx(name: &str, idx: &[usize], len: usize, lines: &str)
+{
+    if let Some(i) = idx.iter().find(|&&i| i >= len) {
+        panic!("{}: index {} out of range for {} {}", name, i, len, lines);
+    }
+}
+
+// collects exactly nrows * ncols elements, without running an excessive iterator to its end
+fn h_collect<T>(nrows: usize, ncols: usize, iter: T) -> Result<Vec<FP>, MatError>
+where T: IntoIterator<Item=FP>
+{
+    let len = h_len(nrows, ncols)?;
+    let vec: Vec<FP> = iter.into_iter().take(len + 1).collect();
+
+    if vec.len() < len {
+        Err(MatError::LengthMismatch(format!("{} elements are short of {} x {}", vec.len(), nrows, ncols)))
+    }
+    else if vec.len() > len {
+        Err(MatError::LengthMismatch(format!("elements are more than {} x {}", nrows, ncols)))
+    }
+    else {
+        Ok(vec)
+    }
+}
+
+impl FromIterator<FP> for Mat
+{
+    /// Makes a column vector.
+    fn from_iter<T>(iter: T) -> Mat
+    where T: IntoIterator<Item=FP>
+    {
+        let vec: Vec<FP> = iter.into_iter().collect();
+
+        Mat::from_vec(vec.len(), 1, vec)
+    }
+}
+
+impl Extend<FP> for Mat
+{
+    /// Appends elements to a column vector.
+    fn extend<T>(&mut self, iter: T)
+    where T: IntoIterator<Item=FP>
+    {
+        assert_eq!(self.size().1, 1);
+
+        let mut vec = std::mem::replace(self, Mat::new(0, 0)).into_vec();
+        vec.extend(iter);
+        *self = Mat::from_vec(vec.len(), 1, vec);
+    }
+}
+
+impl TryFrom<(usize, usize, Vec<FP>)> for Mat
+{
+    type Error = MatError;
+    /// Makes a matrix from `(nrows, ncols, vec)` of column-major elements, taking over the vector without copying.
+    fn try_from((nrows, ncols, vec): (usize, usize, Vec<FP>)) -> Result<Mat, MatError>
+    {
+        if h_len(nrows, ncols)? != vec.len() {
+            return Err(MatError::LengthMismatch(format!("length {} does not match {} x {}", vec.len(), nrows, ncols)));
+        }
+
+        Ok(MatGen {
+            nrows,
+            ncols,
+            offset: 0,
+            stride: nrows as isize,
+            rstride: 1,
+            transposed: false,
+            view: vec
+        })
+    }
+}
+
+impl TryFrom<Vec<Vec<FP>>> for Mat
+{
+    type Error = MatError;
+    /// Makes a matrix from rows, failing if the rows differ in length.
+    fn try_from(rows: Vec<Vec<FP>>) -> Result<Mat, MatError>
+    {
+        let ncols = rows.first().map_or(0, |r| r.len());
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != ncols {
+                return Err(MatError::LengthMismatch(format!("row {} has {} elements, expected {}", i, row.len(), ncols)));
+            }
+        }
+
+        Ok(Mat::new(rows.len(), ncols).set_by(|r, c| rows[r][c]))
+    }
+}
+
+impl<const M: usize, const N: usize> From<&[[FP; N]; M]> for Mat
+{
+    /// Makes a matrix from rows.
+    fn from(rows: &[[FP; N]; M]) -> Mat
+    {
+        Mat::new(M, N).set_by(|r, c| rows[r][c])
+    }
+}
+
+//
+
+/// Helper matrix accessor for operator overload
+pub trait MatAcc
+{
+    fn acc_size(&self) -> (usize, usize);
+    fn acc_get(&self, row: usize, col: usize) -> FP;
+}
+
+impl<V: View> MatAcc for MatGen<V>
+{
+    fn acc_size(&self) -> (usize, usize)
+    {
+        self.size()
+    }
+    //
+    fn acc_get(&self, row: usize, col: usize) -> FP
+    {
+        self[(row, col)]
+    }
+}
+
+impl<V: View> MatAcc for &MatGen<V>
+{
+    fn acc_size(&self) -> (usize, usize)
+    {
+        (*self).acc_size()
+    }
+    //
+    fn acc_get(&self, row: usize, col: usize) -> FP
+    {
+        (*self).acc_get(row, col)
+    }
+}
+
+/// Helper elementwise bound, either a scalar or a matrix, for [`MatGen::clamp`]
+pub trait MatBound
+{
+    /// `None` if applied to any size
+    fn bound_size(&self) -> Option<(usize, usize)>;
+    fn bound_get(&self, row: usize, col: usize) -> FP;
+}
+
+impl MatBound for FP
+{
+    fn bound_size(&self) -> Option<(usize, usize)>
+    {
+        None
+    }
+    //
+    fn bound_get(&self, _: usize, _: usize) -> FP
+    {
+        *self
+    }
+}
+
+impl<T: MatAcc> MatBound for T
+{
+    fn bound_size(&self) -> Option<(usize, usize)>
+    {
+        Some(self.acc_size())
+    }
+    //
+    fn bound_get(&self, row: usize, col: usize) -> FP
+    {
+        self.acc_get(row, col)
+    }
+}
+
+//
+impl<V: View> Neg for MatGen<V>
+{
+    type Output = Mat;
+
+    fn neg(self) -> Mat
+    {
+        let mut mat = self.h_own();
+        let (l_nrows, l_ncols) = mat.size();
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                mat[(r, c)] = -mat[(r, c)];
+            }
+        }
+
+        mat
+    }
+}
+
+impl<V: View> Neg for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn neg(self) -> Mat
+    {
+        self.clone_sz().neg()
+    }
+}
+
+//
+
+impl<V: View, T: MatAcc> AddAssign<T> for MatGen<V>
+{
+    fn add_assign(&mut self, rhs: T)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        assert_eq!((l_nrows, l_ncols), rhs.acc_size());
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] += rhs.acc_get(r, c);
+            }
+        }
+    }
+}
+
+impl<V: View> AddAssign<FP> for MatGen<V>
+{
+    fn add_assign(&mut self, rhs: FP)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] += rhs;
+            }
+        }
+    }
+}
+
+impl<V: View, T: MatAcc> Add<T> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn add(self, rhs: T) -> Mat
+    {
+        let mut mat = self.h_own();
+        mat.add_assign(rhs);
+        mat
+    }
+}
+
+impl<V: View, T: MatAcc> Add<T> for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn add(self, rhs: T) -> Mat
+    {
+        self.clone_sz().add(rhs)
+    }
+}
+
+impl<V: View> Add<FP> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn add(self, rhs: FP) -> Mat
+    {
+        let mut mat = self.h_own();
+        mat.add_assign(rhs);
+        mat
+    }
+}
+
+impl<V: View> Add<FP> for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn add(self, rhs: FP) -> Mat
+    {
+        self.clone_sz().add(rhs)
+    }
+}
+
+impl<V: View> Add<MatGen<V>> for FP
+{
+    type Output = Mat;
+
+    fn add(self, rhs: MatGen<V>) -> Mat
+    {
+        rhs.add(self)
+    }
+}
+
+impl<V: View> Add<&MatGen<V>> for FP
+{
+    type Output = Mat;
+
+    fn add(self, rhs: &MatGen<V>) -> Mat
+    {
+        rhs.add(self)
+    }
+}
+
+//
+
+impl<V: View, T: MatAcc> SubAssign<T> for MatGen<V>
+{
+    fn sub_assign(&mut self, rhs: T)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        assert_eq!((l_nrows, l_ncols), rhs.acc_size());
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] -= rhs.acc_get(r, c);
+            }
+        }
+    }
+}
+
+impl<V: View> SubAssign<FP> for MatGen<V>
+{
+    fn sub_assign(&mut self, rhs: FP)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] -= rhs;
+            }
+        }
+    }
+}
+
+impl<V: View, T: MatAcc> Sub<T> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn sub(self, rhs: T) -> Mat
+    {
+        let mut mat = self.h_own();
+        mat.sub_assign(rhs);
+        mat
+    }
+}
+
+impl<V: View, T: MatAcc> Sub<T> for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn sub(self, rhs: T) -> Mat
+    {
+        self.clone_sz().sub(rhs)
+    }
+}
+
+impl<V: View> Sub<FP> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn sub(self, rhs: FP) -> Mat
+    {
+        let mut mat = self.h_own();
+        mat.sub_assign(rhs);
+        mat
+    }
+}
+
+impl<V: View> Sub<FP> for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn sub(self, rhs: FP) -> Mat
+    {
+        self.clone_sz().sub(rhs)
+    }
+}
+
+impl<V: View> Sub<MatGen<V>> for FP
+{
+    type Output = Mat;
+
+    fn sub(self, rhs: MatGen<V>) -> Mat
+    {
+        rhs.neg().add(self)
+    }
+}
+
+impl<V: View> Sub<&MatGen<V>> for FP
+{
+    type Output = Mat;
+
+    fn sub(self, rhs: &MatGen<V>) -> Mat
+    {
+        rhs.neg().add(self)
+    }
+}
+
 //
 
-impl<V: View, V2: View> PartialEq<MatGen<V2>> for MatGen<V>
+impl<V: View> MulAssign<FP> for MatGen<V>
+{
+    fn mul_assign(&mut self, rhs: FP)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
+        for c in 0 .. l_ncols {
+            for r in 0 .. l_nrows {
+                self[(r, c)] *= rhs;
+            }
+        }
+    }
+}
+
+impl<V: View, T: MatAcc> Mul<T> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn mul(self, rhs: T) -> Mat
+    {
+        (&self).mul(rhs)
+    }
+}
+
+impl<V: View, T: MatAcc> Mul<T> for &MatGen<V>
 {
-    fn eq(&self, other: &MatGen<V2>) -> bool
+    type Output = Mat;
+
+    fn mul(self, rhs: T) -> Mat
     {
         let (l_nrows, l_ncols) = self.size();
+        let (r_nrows, r_ncols) = rhs.acc_size();
 
-        if (l_nrows, l_ncols) != other.size() {
-            return false;
+        assert_eq!(l_ncols, r_nrows);
+
+        let mut mat = Mat::new(l_nrows, r_ncols);
+
+        for c in 0 .. r_ncols {
+            for r in 0 .. l_nrows {
+                let mut v: FP = 0.0;
+                for k in 0 .. l_ncols {
+                    v += self[(r, k)] * rhs.acc_get(k, c);
+                }
+                mat[(r, c)] = v;
+            }
         }
 
+        mat
+    }
+}
+
+impl<V: View> Mul<FP> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn mul(self, rhs: FP) -> Mat
+    {
+        let mut mat = self.h_own();
+        mat.mul_assign(rhs);
+        mat
+    }
+}
+
+impl<V: View> Mul<FP> for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn mul(self, rhs: FP) -> Mat
+    {
+        self.clone_sz().mul(rhs)
+    }
+}
+
+impl<V: View> Mul<MatGen<V>> for FP
+{
+    type Output = Mat;
+
+    fn mul(self, rhs: MatGen<V>) -> Mat
+    {
+        rhs.mul(self)
+    }
+}
+
+impl<V: View> Mul<&MatGen<V>> for FP
+{
+    type Output = Mat;
+
+    fn mul(self, rhs: &MatGen<V>) -> Mat
+    {
+        rhs.mul(self)
+    }
+}
+
+//
+
+impl<V: View> DivAssign<FP> for MatGen<V>
+{
+    fn div_assign(&mut self, rhs: FP)
+    {
+        let (l_nrows, l_ncols) = self.size();
+
         for c in 0 .. l_ncols {
             for r in 0 .. l_nrows {
-                if self[(r, c)] != other[(r, c)] {
-                    return false;
-                }
+                self[(r, c)] /= rhs;
             }
         }
+    }
+}
 
-        true
+impl<V: View> Div<FP> for MatGen<V>
+{
+    type Output = Mat;
+
+    fn div(self, rhs: FP) -> Mat
+    {
+        let mut mat = self.h_own();
+        mat.div_assign(rhs);
+        mat
+    }
+}
+
+impl<V: View> Div<FP> for &MatGen<V>
+{
+    type Output = Mat;
+
+    fn div(self, rhs: FP) -> Mat
+    {
+        self.clone_sz().div(rhs)
+    }
+}
+
+//
+
+// Neumaier's variant of Kahan summation
+#[derive(Default)]
+pub(crate) struct KahanSum
+{
+    sum: FP,
+    c: FP
+}
+
+impl KahanSum
+{
+    pub(crate) fn add(&mut self, v: FP)
+    {
+        let t = self.sum + v;
+        if self.sum.abs() >= v.abs() {
+            self.c += (self.sum - t) + v;
+        }
+        else {
+            self.c += (v - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    // adds a product with its rounding error by fused multiply-add
+    pub(crate) fn add_prod(&mut self, a: FP, b: FP)
+    {
+        let p = a * b;
+        self.c += a.mul_add(b, -p);
+        self.add(p);
+    }
+
+    pub(crate) fn value(&self) -> FP
+    {
+        self.sum + self.c
+    }
+}
+
+//
+
+/// Makes a [`Mat`](mat/type.Mat.html) from a row-wise literal, whose rows are separated by `;`.
+///
+/// The size is determined by the literal,
+/// and rows of inconsistent lengths fail to compile.
+///
+/// ```
+/// use totsu::prelude::*;
+/// use totsu::mat;
+///
+/// let a = mat![1., 2., 3.;
+///              4., 5., 6.];
+/// assert_eq!(a.size(), (2, 3));
+/// assert_eq!(a[(1, 0)], 4.);
+/// ```
+///
+/// ```compile_fail
+/// use totsu::mat;
+///
+/// let a = mat![1., 2.;
+///              3.];
+/// ```
+#[macro_export]
+macro_rules! mat {
+    () => {
+        $crate::mat::Mat::new(0, 0)
+    };
+    ( $( $( $x:expr ),+ );+ $(;)? ) => {{
+        const LENS: &[usize] = &[ $( <[()]>::len(&[ $( $crate::__mat_unit!($x) ),+ ]) ),+ ];
+        const _: () = {
+            let mut i = 0;
+            while i < LENS.len() {
+                assert!(LENS[i] == LENS[0], "mat!: rows have inconsistent lengths");
+                i += 1;
+            }
+        };
+        $crate::mat::Mat::new(LENS.len(), LENS[0]).set_iter(&[ $( $( $x ),+ ),+ ])
+    }};
+}
+
+/// Makes a column vector [`Mat`](mat/type.Mat.html) from a literal.
+///
+/// ```
+/// use totsu::prelude::*;
+/// use totsu::matvec;
+///
+/// let v = matvec![1., 2., 3.];
+/// assert_eq!(v.size(), (3, 1));
+/// ```
+#[macro_export]
+macro_rules! matvec {
+    ( $( $x:expr ),* $(,)? ) => {{
+        let elems: &[$crate::mat::FP] = &[ $( $x ),* ];
+        $crate::mat::Mat::new_vec(elems.len()).set_iter(elems)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __mat_unit {
+    ( $x:expr ) => { () };
+}
+
+//
+
+/// Xorshift random number generator initializer
+pub const XOR64_INIT: u64 = 88172645463325252;
+
+/// Xorshift random number generator
+pub fn xor64(state: &mut u64) -> FP
+{
+    const MAX: FP = (1_u128 << 64) as FP;
+    *state = *state ^ (*state << 7);
+    *state = *state ^ (*state >> 9);
+
+    // [0.0, 1.0)
+    (*state as FP) / MAX
+}
+
+/// Pseudo-random number generator
+///
+/// It is the xorshift of [`xor64`](fn.xor64.html) with its state,
+/// and so deterministic for a seed.
+#[derive(Debug, Clone)]
+pub struct Rng
+{
+    state: u64,
+    spare: Option<FP>
+}
+
+impl Default for Rng
+{
+    fn default() -> Rng
+    {
+        Rng::new(XOR64_INIT)
+    }
+}
+
+impl Rng
+{
+    /// Makes a generator with a seed, where zero, a fixed point of xorshift, is replaced with `XOR64_INIT`.
+    pub fn new(seed: u64) -> Rng
+    {
+        Rng {
+            state: if seed == 0 {XOR64_INIT} else {seed},
+            spare: None
+        }
+    }
+    /// Returns a uniform random number in \\([0, 1)\\).
+    pub fn uniform(&mut self) -> FP
+    {
+        xor64(&mut self.state)
+    }
+    /// Returns a uniform random number in \\([lo, hi)\\).
+    pub fn uniform_range(&mut self, lo: FP, hi: FP) -> FP
+    {
+        lo + (hi - lo) * self.uniform()
+    }
+    /// Returns a standard normal random number by the Box-Muller transform.
+    pub fn normal(&mut self) -> FP
+    {
+        if let Some(z) = self.spare.take() {
+            return z;
+        }
+
+        // (0, 1] to avoid ln(0)
+        let u1 = 1. - self.uniform();
+        let u2 = self.uniform();
+        let r = (-2. * u1.ln()).sqrt();
+        let theta = 2. * std::f64::consts::PI as FP * u2;
+
+        self.spare = Some(r * theta.sin());
+        r * theta.cos()
+    }
+}
+
+//
+
+#[test]
+fn test_set()
+{
+    {
+        let a = Mat::new(3, 3).set_eye();
+        let b = Mat::new_like(&a).set_iter(&[
+            1., 0., 0.,
+            0., 1., 0.,
+            0., 0., 1.
+        ]);
+        assert_eq!(a, b);
+    }
+    {
+        let a = Mat::new(2, 4).set_by(|r, c| (r * 4 + c) as FP);
+        let b = Mat::new_like(&a).set_iter(&[
+            0., 1., 2., 3.,
+            4., 5., 6., 7.
+        ]);
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn test_misc()
+{
+    {
+        let a = Mat::new_vec(3);
+        let a = a.t();
+        let b = Mat::new(1, 3);
+        assert_eq!(a, b);
+    }
+    {
+        let a = Mat::new_vec(3).set_t();
+        let b = Mat::new(1, 3);
+        assert_eq!(a, b);
+    }
+    {
+        let mut r = XOR64_INIT;
+        let mut a = Mat::new(4, 4);
+        let b = Mat::new_like(&a).set_by(|_, _| xor64(&mut r));
+        a.assign(&b);
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn test_slice()
+{
+    {
+        let a = Mat::new(4, 4).set_eye();
+        let a = a.slice(1 ..= 2, 1 ..= 2);
+        let b = Mat::new(2, 2).set_eye();
+        assert_eq!(a, b);
+    }
+    {
+        let mut a = Mat::new(4, 4).set_eye();
+        let b = Mat::new(4, 4).set_iter(&[
+            1., 0., 0., 0.,
+            0., 2., 2., 0.,
+            0., 2., 2., 0.,
+            0., 0., 0., 1.
+        ]);
+        let mut a1 = a.slice_mut(1 ..= 2, 1 ..= 2);
+        let a2 = Mat::new(2, 2).set_all(2.);
+        a1.assign(&a2);
+        assert_eq!(a, b);
+    }
+    {
+        let mut a = Mat::new(4, 4).set_eye();
+        let b = Mat::new(4, 4).set_iter(&[
+            0., 0., 0., 0.,
+            0., 1., 0., 0.,
+            0., 0., 1., 0.,
+            1., 0., 0., 1.
+        ]);
+        let a1 = a.col(3).clone_sz();
+        a.col_mut(0).assign(&a1);
+        assert_eq!(a, b);
     }
 }
 
-//
-
-impl Clone for Mat
+#[test]
+fn test_stack()
 {
-    fn clone(&self) -> Mat
     {
-        self.clone_sz()
+        let a = Mat::new(2, 2).set_eye();
+        let b = Mat::new(3, 2).set_iter(&[
+            1., 2., 3.,
+            4., 5., 6.
+        ]);
+        let h = Mat::hstack(&[&a, &b.t(), &b.slice(0 ..= 1, 0 ..= 0)]);
+        let exp = Mat::new(2, 6).set_iter(&[
+            1., 0., 1., 3., 5., 1.,
+            0., 1., 2., 4., 6., 3.
+        ]);
+        assert_eq!(h, exp);
+
+        let v = Mat::vstack(&[&a, &b.rows(1 ..)]);
+        let exp = Mat::new(4, 2).set_iter(&[
+            1., 0.,
+            0., 1.,
+            3., 4.,
+            5., 6.
+        ]);
+        assert_eq!(v, exp);
+    }
+    {
+        // KKT-style layout
+        let p = Mat::new(2, 2).set_all(2.);
+        let a = Mat::new(1, 2).set_all(3.);
+        let z = Mat::new(1, 1);
+        let k = Mat::block(&[
+            &[&p, &a.t()],
+            &[&a, &z]
+        ]);
+        let exp = Mat::new(3, 3).set_iter(&[
+            2., 2., 3.,
+            2., 2., 3.,
+            3., 3., 0.
+        ]);
+        assert_eq!(k, exp);
+        assert_eq!(Mat::hstack(&[]).size(), (0, 0));
+    }
+    {
+        let a = Mat::new(2, 2);
+        let b = Mat::new(3, 2);
+        let rslt = std::panic::catch_unwind(|| Mat::hstack(&[&a, &a, &b]));
+        let msg = rslt.unwrap_err().downcast::<String>().unwrap();
+        assert!(msg.contains("block 2"));
+        let rslt = std::panic::catch_unwind(|| Mat::vstack(&[&a, &b.t()]));
+        let msg = rslt.unwrap_err().downcast::<String>().unwrap();
+        assert!(msg.contains("block 1"));
     }
 }
 
-//
+#[test]
+fn test_block_diag()
+{
+    {
+        let a = Mat::new(2, 2).set_all(1.);
+        let b = Mat::new(1, 2).set_all(2.);
+        let d = Mat::block_diag(&[&a, &b, &b.t()]);
+        let exp = Mat::new(5, 5).set_iter(&[
+            1., 1., 0., 0., 0.,
+            1., 1., 0., 0., 0.,
+            0., 0., 2., 2., 0.,
+            0., 0., 0., 0., 2.,
+            0., 0., 0., 0., 2.
+        ]);
+        assert_eq!(d, exp);
+    }
+    {
+        assert_eq!(Mat::block_diag(&[]).size(), (0, 0));
 
-/// Helper matrix accessor for operator overload
-pub trait MatAcc
+        let a = Mat::new(2, 3).set_by(|r, c| (r * 3 + c) as FP);
+        assert_eq!(Mat::block_diag(&[&a]), a);
+    }
+}
+
+#[test]
+fn test_from_rows()
 {
-    fn acc_size(&self) -> (usize, usize);
-    fn acc_get(&self, row: usize, col: usize) -> FP;
+    let exp = Mat::new(2, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.
+    ]);
+
+    let a = Mat::try_from(vec![vec![1., 2., 3.], vec![4., 5., 6.]]).unwrap();
+    assert_eq!(a, exp);
+    assert_eq!(a.layout(), Layout::ColMajor);
+
+    let b = Mat::from(&[[1., 2., 3.], [4., 5., 6.]]);
+    assert_eq!(b, exp);
+
+    assert_eq!(Mat::try_from(Vec::<Vec<FP>>::new()).unwrap().size(), (0, 0));
+    let e = Mat::try_from(vec![vec![1., 2.], vec![3., 4.], vec![5.]]).unwrap_err();
+    assert_eq!(e, MatError::LengthMismatch("row 2 has 1 elements, expected 2".into()));
 }
 
-impl<V: View> MatAcc for MatGen<V>
+#[test]
+fn test_rng()
 {
-    fn acc_size(&self) -> (usize, usize)
     {
-        self.size()
+        // same sequence as xor64
+        let mut r = XOR64_INIT;
+        let mut rng = Rng::default();
+        for _ in 0 .. 10 {
+            assert_eq!(rng.uniform(), xor64(&mut r));
+        }
+        let a = Mat::new(3, 3).set_rand(&mut Rng::new(1));
+        let b = Mat::new(3, 3).set_rand(&mut Rng::new(1));
+        assert_eq!(a, b);
+        assert!((a.min().unwrap() >= 0.) && (a.max().unwrap() < 1.));
+        assert!(Rng::new(0).uniform() > 0.);
     }
-    //
-    fn acc_get(&self, row: usize, col: usize) -> FP
     {
-        self[(row, col)]
+        let mut rng = Rng::default();
+        let n = 10000;
+        let u = Mat::new_vec(n).set_by(|_, _| rng.uniform_range(-2., 3.));
+        assert!((u.min().unwrap() >= -2.) && (u.max().unwrap() < 3.));
+
+        let z = Mat::new_vec(n).set_randn(&mut rng);
+        let mean = z.prod(&Mat::new_vec(n).set_all(1.)) / n as FP;
+        let var = z.norm_p2sq() / n as FP - mean * mean;
+        println!("mean = {}, var = {}", mean, var);
+        assert!(mean.abs() < 0.05);
+        assert!((var - 1.).abs() < 0.05);
     }
 }
 
-impl<V: View> MatAcc for &MatGen<V>
+#[test]
+fn test_spaces()
 {
-    fn acc_size(&self) -> (usize, usize)
     {
-        (*self).acc_size()
+        assert_eq!(Mat::linspace(0., 1., 5), Mat::new_vec(5).set_iter(&[0., 0.25, 0.5, 0.75, 1.]));
+        assert_eq!(Mat::linspace(2., -1., 4), Mat::new_vec(4).set_iter(&[2., 1., 0., -1.]));
+        let a = Mat::linspace(0.1, 0.7, 7);
+        assert_eq!(a[(6, 0)], 0.7);
+        assert_eq!(Mat::linspace(3., 5., 1), Mat::new_vec(1).set_all(3.));
+        assert_eq!(Mat::linspace(3., 5., 0).size(), (0, 1));
     }
-    //
-    fn acc_get(&self, row: usize, col: usize) -> FP
     {
-        (*self).acc_get(row, col)
+        assert_eq!(Mat::arange(0., 1., 0.25), Mat::new_vec(4).set_iter(&[0., 0.25, 0.5, 0.75]));
+        assert_eq!(Mat::arange(1., -1., -0.5), Mat::new_vec(4).set_iter(&[1., 0.5, 0., -0.5]));
+        // (0.3 - 0.0) / 0.1 is slightly above 3
+        let a = Mat::arange(0., 0.3, 0.1);
+        assert_eq!(a.size(), (3, 1));
+        assert!(a[(2, 0)] < 0.3);
+        assert_eq!(Mat::arange(1., 1., 0.1).size(), (0, 1));
+        assert_eq!(Mat::arange(1., 0., 0.1).size(), (0, 1));
+        assert!(std::panic::catch_unwind(|| Mat::arange(0., 1., 0.)).is_err());
     }
-}
-
-//
-impl<V: View> Neg for MatGen<V>
-{
-    type Output = Mat;
-
-    fn neg(self) -> Mat
     {
-        let mut mat = self.h_own();
-        let (l_nrows, l_ncols) = mat.size();
-
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                mat[(r, c)] = -mat[(r, c)];
-            }
-        }
-
-        mat
+        let g = Mat::geomspace(1., 1000., 4);
+        assert_eq!(g[(0, 0)], 1.);
+        assert_eq!(g[(3, 0)], 1000.);
+        assert!((g[(1, 0)] - 10.).abs() < 1e-12);
+        assert!((g[(2, 0)] - 100.).abs() < 1e-12);
+        let g = Mat::geomspace(-8., -2., 3);
+        assert_eq!(g[(0, 0)], -8.);
+        assert!((g[(1, 0)] + 4.).abs() < 1e-12);
+        assert_eq!(g[(2, 0)], -2.);
+        assert_eq!(Mat::geomspace(2., 3., 1), Mat::new_vec(1).set_all(2.));
+        assert_eq!(Mat::geomspace(2., 3., 0).size(), (0, 1));
+        assert!(std::panic::catch_unwind(|| Mat::geomspace(-1., 1., 3)).is_err());
     }
 }
 
-impl<V: View> Neg for &MatGen<V>
+#[test]
+fn test_from_vec()
 {
-    type Output = Mat;
+    {
+        let v = vec![1., 2., 3., 4., 5., 6.];
+        let ptr = v.as_ptr();
+        let a = Mat::from_vec(2, 3, v);
+        let exp = Mat::new(2, 3).set_iter(&[
+            1., 3., 5.,
+            2., 4., 6.
+        ]);
+        assert_eq!(a, exp);
 
-    fn neg(self) -> Mat
+        // round trip without copying
+        let v = a.into_vec();
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v, vec![1., 2., 3., 4., 5., 6.]);
+    }
     {
-        self.clone_sz().neg()
+        // transposed or sliced elements are copied in column-major order
+        let a = Mat::from_vec(2, 3, vec![1., 2., 3., 4., 5., 6.]);
+        assert_eq!(a.t().into_vec(), vec![1., 3., 5., 2., 4., 6.]);
+        assert_eq!(a.slice(.., 1 ..).into_vec(), vec![3., 4., 5., 6.]);
+        assert_eq!(a.set_t().into_vec(), vec![1., 3., 5., 2., 4., 6.]);
+    }
+    {
+        let rslt = std::panic::catch_unwind(|| Mat::from_vec(2, 3, vec![0.; 5]));
+        let msg = rslt.unwrap_err().downcast::<String>().unwrap();
+        assert!(msg.contains("length 5"));
     }
 }
 
-//
+#[test]
+fn test_macro()
+{
+    let a = mat![1., 2.;
+                 3., 4.;
+                 5., 6.];
+    let exp = Mat::new(3, 2).set_iter(&[
+        1., 2.,
+        3., 4.,
+        5., 6.
+    ]);
+    assert_eq!(a, exp);
+
+    let x = 2.;
+    let b = mat![x, -x, x * x];
+    assert_eq!(b, Mat::new(1, 3).set_iter(&[2., -2., 4.]));
+    assert_eq!(mat![].size(), (0, 0));
+
+    let v = matvec![1., 2., 3.,];
+    assert_eq!(v, Mat::new_vec(3).set_iter(&[1., 2., 3.]));
+    assert_eq!(matvec![].size(), (0, 1));
+}
 
-impl<V: View, T: MatAcc> AddAssign<T> for MatGen<V>
+#[test]
+fn test_diag()
 {
-    fn add_assign(&mut self, rhs: T)
     {
-        let (l_nrows, l_ncols) = self.size();
-
-        assert_eq!((l_nrows, l_ncols), rhs.acc_size());
+        let a = Mat::new_diag(&[1., 2., 3.]);
+        let exp = Mat::new_vec(3).set_iter(&[1., 2., 3.]).clone_diag();
+        assert_eq!(a, exp);
+
+        let w = [0.5, 4.];
+        let b = Mat::new_diag_iter(w.iter());
+        assert_eq!(b, Mat::new_diag(&w));
+        assert_eq!(Mat::new_diag(&[]).size(), (0, 0));
+    }
+    {
+        let mut a = Mat::new(3, 3).set_all(1.).set_diag(&[2., 3., 4.]);
+        let exp = Mat::new(3, 3).set_iter(&[
+            2., 1., 1.,
+            1., 3., 1.,
+            1., 1., 4.
+        ]);
+        assert_eq!(a, exp);
+
+        // rectangular and sliced
+        a.slice_mut(1 .., ..).assign_diag(&[0., 0.]);
+        let exp = Mat::new(3, 3).set_iter(&[
+            2., 1., 1.,
+            0., 3., 1.,
+            1., 0., 4.
+        ]);
+        assert_eq!(a, exp);
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                self[(r, c)] += rhs.acc_get(r, c);
-            }
-        }
+        let rslt = std::panic::catch_unwind(|| Mat::new(2, 3).set_diag(&[1., 2., 3.]));
+        let msg = rslt.unwrap_err().downcast::<String>().unwrap();
+        assert!(msg.contains("length 3"));
     }
 }
 
-impl<V: View> AddAssign<FP> for MatGen<V>
+#[test]
+fn test_reshape()
 {
-    fn add_assign(&mut self, rhs: FP)
     {
-        let (l_nrows, l_ncols) = self.size();
-
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                self[(r, c)] += rhs;
+        let v = Mat::new_vec(6).set_iter(&[1., 2., 3., 4., 5., 6.]);
+        let ptr = v.view.as_ptr();
+        let a = v.reshape(2, 3);
+        assert_eq!(a.view.as_ptr(), ptr);
+        assert_eq!(a, Mat::new(2, 3).set_iter(&[
+            1., 3., 5.,
+            2., 4., 6.
+        ]));
+
+        // transposed elements are copied in column-major order
+        let b = a.t().reshape(2, 3);
+        assert_eq!(b, Mat::new(2, 3).set_iter(&[
+            1., 5., 4.,
+            3., 2., 6.
+        ]));
+        assert!(std::panic::catch_unwind(|| Mat::new(2, 3).reshape(4, 2)).is_err());
+    }
+    {
+        let mut rng = Rng::default();
+        let sizes = [(3, 4), (5, 4), (2, 4), (3, 6), (3, 2), (5, 6), (2, 2), (5, 2), (2, 6), (0, 3), (3, 0)];
+        for &t in [false, true].iter() {
+            for &(nrows, ncols) in sizes.iter() {
+                let a = if !t {Mat::new(3, 4)} else {Mat::new_row_major(3, 4)}.set_rand(&mut rng);
+
+                let mut exp = Mat::new(nrows, ncols);
+                let (r, c) = (nrows.min(3), ncols.min(4));
+                exp.slice_mut(0 .. r, 0 .. c).assign(&a.slice(0 .. r, 0 .. c));
+
+                let mut b = a.clone();
+                b.resize(nrows, ncols);
+                assert_eq!(b, exp);
+                assert_eq!(b.layout(), a.layout());
+
+                // round trip keeps the top-left block
+                b.resize(3, 4);
+                let mut exp = Mat::new(3, 4);
+                exp.slice_mut(0 .. r, 0 .. c).assign(&a.slice(0 .. r, 0 .. c));
+                assert_eq!(b, exp);
             }
         }
     }
 }
 
-impl<V: View, T: MatAcc> Add<T> for MatGen<V>
+#[test]
+fn test_repeat()
 {
-    type Output = Mat;
-
-    fn add(self, rhs: T) -> Mat
-    {
-        let mut mat = self.h_own();
-        mat.add_assign(rhs);
-        mat
-    }
+    let a = Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        3., 4.
+    ]);
+    assert_eq!(a.repeat(2, 3), Mat::new(4, 6).set_iter(&[
+        1., 2., 1., 2., 1., 2.,
+        3., 4., 3., 4., 3., 4.,
+        1., 2., 1., 2., 1., 2.,
+        3., 4., 3., 4., 3., 4.
+    ]));
+    assert_eq!(a.t().repeat(1, 2), Mat::new(2, 4).set_iter(&[
+        1., 3., 1., 3.,
+        2., 4., 2., 4.
+    ]));
+    assert_eq!(a.col(1).repeat(1, 3), a.col(1).repeat_vec(3));
+    assert_eq!(a.row(0).repeat(2, 1), Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        1., 2.
+    ]));
+    assert_eq!(a.repeat(0, 2).size(), (0, 4));
+    assert_eq!(a.repeat(2, 0).size(), (4, 0));
+    assert_eq!(Mat::new(0, 2).repeat(3, 3).size(), (0, 6));
+    assert_eq!(a.col(0).repeat_vec(0).size(), (2, 0));
 }
 
-impl<V: View, T: MatAcc> Add<T> for &MatGen<V>
+#[test]
+fn test_kron()
 {
-    type Output = Mat;
+    let mut rng = Rng::default();
+    let a = Mat::new(2, 3).set_rand(&mut rng);
+
+    let k = Mat::new(3, 3).set_eye().kron(&a);
+    assert_eq!(k, Mat::block_diag(&[&a, &a, &a]));
+
+    let b = Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        3., 4.
+    ]);
+    let c = Mat::new(1, 2).set_iter(&[
+        0., 5.
+    ]);
+    assert_eq!(b.kron(&c), Mat::new(2, 4).set_iter(&[
+        0., 5., 0., 10.,
+        0., 15., 0., 20.
+    ]));
+    assert_eq!(b.t().kron(&c.t()), Mat::new(4, 2).set_iter(&[
+        0., 0.,
+        5., 15.,
+        0., 0.,
+        10., 20.
+    ]));
+
+    // mixed-product property
+    let d = Mat::new(3, 2).set_rand(&mut rng);
+    let e = Mat::new(2, 2).set_rand(&mut rng);
+    let lhs = a.kron(&b) * d.kron(&e);
+    let rhs = (&a * &d).kron(&(&b * &e));
+    assert!((lhs - rhs).norm_p2() < 1e-12);
+    assert_eq!(a.kron(&Mat::new(0, 2)).size(), (0, 6));
+}
 
-    fn add(self, rhs: T) -> Mat
+#[test]
+fn test_structured()
+{
     {
-        self.clone_sz().add(rhs)
+        let v = Mat::vander(&[1., -2., 0.5], 4, false);
+        assert_eq!(v, Mat::new(3, 4).set_iter(&[
+            1., 1., 1., 1.,
+            -8., 4., -2., 1.,
+            0.125, 0.25, 0.5, 1.
+        ]));
+        assert_eq!(Mat::vander(&[1., -2., 0.5], 4, true), Mat::new(3, 4).set_by(|r, c| v[(r, 3 - c)]));
+        assert_eq!(Mat::vander(&[], 3, true).size(), (0, 3));
+        assert_eq!(Mat::vander(&[2.], 0, true).size(), (1, 0));
+    }
+    {
+        let t = Mat::toeplitz(&[1., 2.], &[1., 3., 4., 5.]);
+        assert_eq!(t, Mat::new(2, 4).set_iter(&[
+            1., 3., 4., 5.,
+            2., 1., 3., 4.
+        ]));
+        // symmetric Toeplitz
+        let s = Mat::toeplitz(&[2., -1., 0.], &[2., -1., 0.]);
+        assert_eq!(s, s.t());
+        assert_eq!(Mat::toeplitz(&[], &[1.]).size(), (0, 1));
+        assert!(std::panic::catch_unwind(|| Mat::toeplitz(&[1., 2.], &[3., 4.])).is_err());
+    }
+    {
+        // circulant matrix times vector is circular convolution
+        let c = Mat::circulant(&[1., 2., 0., 0.]);
+        let x = Mat::new_vec(4).set_iter(&[1., 1., 0., 3.]);
+        assert_eq!(&c * &x, Mat::new_vec(4).set_iter(&[7., 3., 2., 3.]));
+        assert_eq!(Mat::circulant(&[]).size(), (0, 0));
     }
 }
 
-impl<V: View> Add<FP> for MatGen<V>
+#[test]
+fn test_collect()
 {
-    type Output = Mat;
+    {
+        let v: Mat = (0 .. 4).map(|i| i as FP * 0.5).collect();
+        assert_eq!(v, Mat::new_vec(4).set_iter(&[0., 0.5, 1., 1.5]));
 
-    fn add(self, rhs: FP) -> Mat
+        let mut v = v;
+        v.extend(vec![2., 2.5]);
+        assert_eq!(v, Mat::new_vec(6).set_iter(&[0., 0.5, 1., 1.5, 2., 2.5]));
+
+        let mut w = Mat::new(1, 3).set_iter(&[1., 2., 3.]).set_t();
+        w.extend(std::iter::once(4.));
+        assert_eq!(w, Mat::new_vec(4).set_iter(&[1., 2., 3., 4.]));
+
+        let e: Mat = std::iter::empty().collect();
+        assert_eq!(e.size(), (0, 1));
+    }
     {
-        let mut mat = self.h_own();
-        mat.add_assign(rhs);
-        mat
+        let exp = Mat::new(2, 3).set_iter(&[
+            1., 2., 3.,
+            4., 5., 6.
+        ]);
+        let a = Mat::from_row_major(2, 3, (1 ..= 6).map(|i| i as FP)).unwrap();
+        assert_eq!(a, exp);
+        let b = Mat::from_col_major(2, 3, vec![1., 4., 2., 5., 3., 6.]).unwrap();
+        assert_eq!(b, exp);
+
+        let e = Mat::from_row_major(2, 3, vec![1., 2.]).unwrap_err();
+        assert_eq!(e, MatError::LengthMismatch("2 elements are short of 2 x 3".into()));
+        assert!(Mat::from_col_major(2, 3, std::iter::repeat(1.)).is_err());
     }
 }
 
-impl<V: View> Add<FP> for &MatGen<V>
+#[test]
+fn test_try_new()
 {
-    type Output = Mat;
+    assert_eq!(Mat::try_new(2, 3).unwrap(), Mat::new(2, 3));
+    assert_eq!(Mat::try_new(0, usize::MAX).unwrap().size(), (0, usize::MAX));
+    assert!(matches!(Mat::try_new(usize::MAX, 2), Err(MatError::DimensionOverflow(_))));
+    assert!(matches!(Mat::try_new(usize::MAX / 8, 2), Err(MatError::DimensionOverflow(_))));
+    assert!(std::panic::catch_unwind(|| Mat::new(usize::MAX, 2)).is_err());
+
+    let a = Mat::try_from((2, 2, vec![1., 2., 3., 4.])).unwrap();
+    assert_eq!(a, Mat::from_vec(2, 2, vec![1., 2., 3., 4.]));
+    let e = Mat::try_from((2, 2, vec![1., 2., 3.])).unwrap_err();
+    assert_eq!(e.to_string(), "length mismatch: length 3 does not match 2 x 2");
+    assert!(matches!(Mat::try_from((usize::MAX, 2, vec![])), Err(MatError::DimensionOverflow(_))));
+    assert!(matches!(Mat::from_col_major(usize::MAX, 2, vec![]), Err(MatError::DimensionOverflow(_))));
+}
 
-    fn add(self, rhs: FP) -> Mat
-    {
-        self.clone_sz().add(rhs)
-    }
+#[test]
+fn test_index_vec()
+{
+    let mut v = Mat::new_vec(3).set_iter(&[1., 2., 3.]);
+    assert_eq!(v[2], 3.);
+    v[1] = 5.;
+    assert_eq!(v[(1, 0)], 5.);
+    assert_eq!(v.t()[1], 5.);
+
+    let mut a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
+    assert_eq!(a.row(2)[3], 23.);
+    assert_eq!(a.col(3)[2], 23.);
+    assert_eq!(a.t().row(3)[2], 23.);
+    a.row_mut(1)[2] = -1.;
+    a.t_mut().row_mut(0)[2] = -2.;
+    assert_eq!(a[(1, 2)], -1.);
+    assert_eq!(a[(2, 0)], -2.);
+
+    let mut one = Mat::new(1, 1);
+    one[0] = 7.;
+    assert_eq!(one[(0, 0)], 7.);
+
+    let e = std::panic::catch_unwind(|| a[0]).unwrap_err();
+    assert_eq!(e.downcast::<String>().unwrap().as_str(), "not a vector, size is 3x4");
+    let e = std::panic::catch_unwind(|| Mat::new_vec(0)[0]).unwrap_err();
+    assert_eq!(e.downcast::<String>().unwrap().as_str(), "index 0 out of range for vector of length 0");
+    // within the buffer but beyond the row
+    assert!(std::panic::catch_unwind(|| a.row(0)[4]).is_err());
 }
 
-impl<V: View> Add<MatGen<V>> for FP
+#[test]
+fn test_get()
 {
-    type Output = Mat;
+    let mut a = Mat::new(3, 3).set_by(|r, c| (r * 10 + c) as FP);
+
+    assert_eq!(a.get(2, 1), Some(&21.));
+    assert_eq!(a.get(3, 0), None);
+    assert_eq!(a.get(0, 3), None);
+    assert_eq!(a.t().get(1, 2), Some(&21.));
+
+    // flat index would be within the buffer, aliasing into the next column
+    let s = a.slice(0 .. 2, 0 .. 2);
+    assert_eq!(s.get(2, 0), None);
+    assert_eq!(s.t().get(0, 2), None);
+    assert_eq!(s.get(1, 1), Some(&11.));
+
+    *a.get_mut(1, 2).unwrap() = -1.;
+    assert_eq!(a[(1, 2)], -1.);
+    assert!(a.slice_mut(1 .., 1 ..).get_mut(2, 0).is_none());
+    assert!(Mat::new(0, 0).get(0, 0).is_none());
+
+    unsafe {
+        assert_eq!(*a.get_unchecked(2, 1), 21.);
+        *a.t_mut().get_unchecked_mut(0, 2) = -2.;
+    }
+    assert_eq!(a[(2, 0)], -2.);
+}
 
-    fn add(self, rhs: MatGen<V>) -> Mat
-    {
-        rhs.add(self)
+#[test]
+fn test_iter()
+{
+    let mut a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
+    let col_major = |m: &MatSlice| {
+        let (nr, nc) = m.size();
+        (0 .. nc).flat_map(|c| (0 .. nr).map(move |r| (r, c))).map(|i| m[i]).collect::<Vec<_>>()
+    };
+
+    for s in &[a.as_slice(), a.t(), a.slice(1 .., 1 .. 3), a.t().slice(1 .., 1 .. 3), a.row(1), a.col(2), a.slice(1 .. 1, ..)] {
+        assert_eq!(s.iter().copied().collect::<Vec<_>>(), col_major(s));
     }
+    assert_eq!(a.iter().filter(|e| **e >= 20.).count(), 4);
+
+    a.t_mut().slice_mut(1 .., 1 .. 3).iter_mut().for_each(|e| *e = -*e);
+    a.slice_mut(0 .. 2, 2 ..).iter_mut().for_each(|e| *e += 100.);
+    let mut n = 0.;
+    a.t_mut().iter_mut().for_each(|e| {*e += n; n += 1000.;});
+
+    assert_eq!(a, Mat::new(3, 4).set_iter(&[
+        0., 1001., 2102., 3103.,
+        4010., 4989., 6088., 7087.,
+        8020., 8979., 9978., 10977.
+    ]));
 }
 
-impl<V: View> Add<&MatGen<V>> for FP
+#[test]
+fn test_line_iter()
 {
-    type Output = Mat;
+    let mut a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
 
-    fn add(self, rhs: &MatGen<V>) -> Mat
-    {
-        rhs.add(self)
+    assert_eq!(a.row_iter().count(), 3);
+    for (r, row) in a.row_iter().enumerate() {
+        assert_eq!(row, a.row(r));
     }
-}
+    for (c, col) in a.t().col_iter().enumerate() {
+        assert_eq!(col, a.row(c).t());
+    }
+    assert_eq!(a.slice(1 .., 1 ..).col_iter().map(|c| c.iter().sum()).collect::<Vec<FP>>(), vec![32., 34., 36.]);
+
+    let b = a.clone();
+    let mut sub = a.cols_mut(1 ..);
+    let mut cols: Vec<_> = sub.col_iter_mut().collect();
+    cols[2].assign_all(-1.);
+    cols[0] *= 2.;
+    for (c, mut col) in a.col_iter_mut().enumerate() {
+        col[0] = c as FP * 100.;
+    }
+    assert_eq!(a, Mat::new(3, 4).set_iter(&[
+        0., 100., 200., 300.,
+        10., 22., 12., -1.,
+        20., 42., 22., -1.
+    ]));
+
+    a.assign(&b);
+    for (c, mut row) in a.t_mut().row_iter_mut().enumerate() {
+        assert_eq!(row.size(), (1, 3));
+        row[1] = -(c as FP);
+    }
+    assert_eq!(a, Mat::new(3, 4).set_by(|r, c| if r == 1 {-(c as FP)} else {b[(r, c)]}));
+    assert!(std::panic::catch_unwind(|| {a.clone().t_mut().col_iter_mut().count();}).is_err());
 
-//
+    // a column vector has rows of a single element
+    let mut v = Mat::new_vec(3);
+    for (r, mut row) in v.row_iter_mut().enumerate() {
+        row[0] = r as FP;
+    }
+    assert_eq!(v, Mat::new_vec(3).set_iter(&[0., 1., 2.]));
+    assert_eq!(Mat::new(2, 0).row_iter_mut().map(|r| r.size()).collect::<Vec<_>>(), vec![(1, 0), (1, 0)]);
+    assert_eq!(Mat::new(0, 2).col_iter_mut().count(), 2);
 
-impl<V: View, T: MatAcc> SubAssign<T> for MatGen<V>
+    let e = std::panic::catch_unwind(|| {Mat::new(2, 2).row_iter_mut().count();}).unwrap_err();
+    assert_eq!(e.downcast::<String>().unwrap().as_str(), "rows of column-major layout are interleaved in storage, use row_mut instead");
+}
+
+#[test]
+fn test_diag_view()
 {
-    fn sub_assign(&mut self, rhs: T)
-    {
-        let (l_nrows, l_ncols) = self.size();
+    let a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
+
+    assert_eq!(a.diag(), Mat::new_vec(3).set_iter(&[0., 11., 22.]));
+    assert_eq!(a.t().diag(), a.diag());
+    assert_eq!(a.diag_k(1), Mat::new_vec(3).set_iter(&[1., 12., 23.]));
+    assert_eq!(a.diag_k(2), Mat::new_vec(2).set_iter(&[2., 13.]));
+    assert_eq!(a.diag_k(-1), Mat::new_vec(2).set_iter(&[10., 21.]));
+    assert_eq!(a.t().diag_k(-1), a.diag_k(1));
+    assert_eq!(a.slice(1 .., 1 ..).diag(), Mat::new_vec(2).set_iter(&[11., 22.]));
+    assert_eq!(a.diag_k(4).size(), (0, 1));
+    assert_eq!(a.diag_k(-3).size(), (0, 1));
+    assert_eq!(Mat::new(0, 0).diag().size(), (0, 1));
+    assert_eq!(a.diag().clone_diag().diag(), a.diag());
+
+    let mut kkt = Mat::new(3, 3);
+    kkt.diag_mut().assign_all(1.);
+    let mut d = kkt.diag_mut();
+    d += 1e-3;
+    let mut d = kkt.diag_k_mut(-1);
+    d += 2.;
+    kkt.t_mut().diag_k_mut(-1)[1] = 3.;
+    assert_eq!(kkt, Mat::new(3, 3).set_iter(&[
+        1.001, 0., 0.,
+        2., 1.001, 3.,
+        0., 2., 1.001
+    ]));
+}
 
-        assert_eq!((l_nrows, l_ncols), rhs.acc_size());
+#[test]
+fn test_select()
+{
+    let a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
+
+    assert_eq!(a.select_rows(&[2, 0, 2]), Mat::new(3, 4).set_iter(&[
+        20., 21., 22., 23.,
+        0., 1., 2., 3.,
+        20., 21., 22., 23.
+    ]));
+    assert_eq!(a.select_cols(&[3, 1]), Mat::new(3, 2).set_iter(&[
+        3., 1.,
+        13., 11.,
+        23., 21.
+    ]));
+    assert_eq!(a.t().select_rows(&[3, 1]), a.select_cols(&[3, 1]).t());
+    assert_eq!(a.t().select_cols(&[1]), a.row(1).t());
+    assert_eq!(a.select_rows(&[]).size(), (0, 4));
+    assert_eq!(a.select_cols(&[]).size(), (3, 0));
+
+    let mut b = Mat::new(3, 4);
+    b.scatter_rows(&[2, 0], &a.select_rows(&[2, 0]));
+    assert_eq!(b.rows(1 ..= 1), Mat::new(1, 4));
+    assert_eq!(b.select_rows(&[0, 2]), a.select_rows(&[0, 2]));
+    b.t_mut().scatter_rows(&[1], &Mat::new(1, 3).set_all(-1.));
+    assert_eq!(b.col(1), Mat::new_vec(3).set_all(-1.));
+    b.scatter_rows(&[], &Mat::new(0, 4));
+
+    let e = std::panic::catch_unwind(|| a.select_rows(&[0, 3])).unwrap_err();
+    assert_eq!(e.downcast::<String>().unwrap().as_str(), "select_rows: index 3 out of range for 3 rows");
+    assert!(std::panic::catch_unwind(|| a.select_cols(&[4])).is_err());
+}
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                self[(r, c)] -= rhs.acc_get(r, c);
-            }
-        }
+#[test]
+fn test_permute()
+{
+    let a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
+
+    let mut b = a.clone();
+    b.swap_rows(0, 2);
+    b.swap_rows(1, 1);
+    assert_eq!(b, a.select_rows(&[2, 1, 0]));
+    b.slice_mut(.., 1 ..).swap_cols(0, 2);
+    assert_eq!(b, a.select_rows(&[2, 1, 0]).select_cols(&[0, 3, 2, 1]));
+    b.t_mut().swap_cols(0, 2);
+    assert_eq!(b, a.select_cols(&[0, 3, 2, 1]));
+
+    for perm in &[vec![0, 1, 2, 3], vec![1, 2, 3, 0], vec![3, 0, 2, 1], vec![2, 3, 0, 1]] {
+        let mut b = a.clone();
+        b.permute_cols(perm).unwrap();
+        assert_eq!(b, a.select_cols(perm));
+
+        let mut b = a.t().clone_sz();
+        b.permute_rows(perm).unwrap();
+        assert_eq!(b, a.t().select_rows(perm));
     }
+    let mut b = Mat::new(0, 2);
+    b.permute_rows(&[]).unwrap();
+
+    let mut b = a.clone();
+    assert!(matches!(b.permute_rows(&[0, 1]), Err(MatError::LengthMismatch(_))));
+    assert!(matches!(b.permute_rows(&[0, 1, 3]), Err(MatError::OutOfBounds(_))));
+    assert_eq!(b.permute_rows(&[0, 2, 0]), Err(MatError::NotPermutation("index 0 appears twice".into())));
+    assert_eq!(b, a);
 }
 
-impl<V: View> SubAssign<FP> for MatGen<V>
+#[test]
+fn test_split_mut()
 {
-    fn sub_assign(&mut self, rhs: FP)
-    {
-        let (l_nrows, l_ncols) = self.size();
+    let a = Mat::new(3, 4).set_by(|r, c| (r * 10 + c) as FP);
+
+    // trailing columns updated from a leading panel
+    let mut b = a.clone();
+    let (mut left, mut right) = b.split_at_col_mut(1);
+    assert_eq!((left.size(), right.size()), ((3, 1), (3, 3)));
+    for mut col in right.col_iter_mut() {
+        col -= &left;
+    }
+    left.assign_all(0.);
+    assert_eq!(b, Mat::new(3, 4).set_by(|r, c| if c == 0 {0.} else {a[(r, c)] - a[(r, 0)]}));
+
+    // on a slice and on a transposed parent
+    let mut b = a.clone();
+    let mut sub = b.slice_mut(1 .., 1 ..);
+    let (mut l, mut r) = sub.split_at_col_mut(2);
+    l.assign(&r.clone_sz().repeat(1, 2));
+    r[0] = -1.;
+    assert_eq!(b.slice(1 .., 1 ..), Mat::new(2, 3).set_iter(&[
+        13., 13., -1.,
+        23., 23., 23.
+    ]));
+    let mut b = a.clone();
+    let mut bt = b.t_mut();
+    let (mut top, mut bottom) = bt.split_at_row_mut(3);
+    assert_eq!((top.size(), bottom.size()), ((3, 3), (1, 3)));
+    top[(2, 1)] = -1.;
+    bottom.assign(&top.row(0));
+    assert_eq!(b.col(3), a.col(0));
+    assert_eq!(b[(1, 2)], -1.);
+
+    // vectors, and empty ends
+    let mut v = Mat::new_vec(4).set_iter(&[1., 2., 3., 4.]);
+    let (mut v0, v1) = v.split_at_row_mut(1);
+    v0 += &v1.rows(.. 1);
+    assert_eq!(v[0], 3.);
+    let mut w = v.t().clone_sz();
+    let (w0, w1) = w.split_at_col_mut(4);
+    assert_eq!((w0.size(), w1.size()), ((1, 4), (1, 0)));
+    let mut b = a.clone();
+    let (e, all) = b.split_at_col_mut(0);
+    assert_eq!((e.size(), all.size()), ((3, 0), (3, 4)));
+
+    let e = std::panic::catch_unwind(|| {a.clone().split_at_row_mut(1);}).unwrap_err();
+    assert_eq!(e.downcast::<String>().unwrap().as_str(), "rows of column-major layout are interleaved in storage, cannot be split");
+    assert!(std::panic::catch_unwind(|| {a.clone().split_at_col_mut(5);}).is_err());
+}
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                self[(r, c)] -= rhs;
-            }
-        }
+#[test]
+fn test_step()
+{
+    let a = Mat::new(5, 6).set_by(|r, c| (r * 10 + c) as FP);
+
+    let s = a.cols_step(1 .., 2);
+    assert_eq!(s.size(), (5, 3));
+    assert_eq!(s, a.select_cols(&[1, 3, 5]));
+    assert_eq!(a.rows_step(.., 2), a.select_rows(&[0, 2, 4]));
+    assert_eq!(a.rows_step(1 .. 4, 2), a.select_rows(&[1, 3]));
+    assert_eq!(a.rows_step(.., 7), a.rows(.. 1));
+    assert_eq!(a.rows_step(5 .., 2).size(), (0, 6));
+    assert_eq!(a.t().rows_step(1 .., 2), a.select_cols(&[1, 3, 5]).t());
+    assert_eq!(a.t().cols_step(.., 3), a.select_rows(&[0, 3]).t());
+
+    // composed
+    let even = a.rows_step(.., 2);
+    let s = even.cols_step(1 .., 2);
+    let b = a.select_rows(&[0, 2, 4]).select_cols(&[1, 3, 5]);
+    assert_eq!(s, b);
+    assert_eq!(s.t(), b.t());
+    assert_eq!(s.slice(1 .., 1 ..), b.slice(1 .., 1 ..));
+    assert_eq!(s.rows_step(.., 2), b.rows_step(.., 2));
+    assert_eq!(s.clone_sz(), b);
+    assert_eq!(s.clone_sz().into_vec(), b.clone().into_vec());
+    assert_eq!(s.diag(), b.diag());
+    assert_eq!(s.diag_k(-1), b.diag_k(-1));
+    assert_eq!(s.iter().copied().collect::<Vec<_>>(), b.iter().copied().collect::<Vec<_>>());
+    assert_eq!(s.t().iter().copied().collect::<Vec<_>>(), b.t().iter().copied().collect::<Vec<_>>());
+    assert_eq!(s.row_iter().collect::<Vec<_>>(), b.row_iter().collect::<Vec<_>>());
+    assert_eq!(&s * &b.t(), &b * &b.t());
+    assert_eq!(s.clone_sz() + &s, b.clone() * 2.);
+    assert_eq!(s.norm_p2sq(), b.norm_p2sq());
+    assert_eq!(s.col(2)[1], 25.);
+
+    // mutable
+    let mut c = a.clone();
+    c.rows_step_mut(1 .., 2).cols_step_mut(.., 3).assign_all(-1.);
+    assert_eq!(c, Mat::new(5, 6).set_by(|r, col| if r % 2 == 1 && col % 3 == 0 {-1.} else {a[(r, col)]}));
+    let mut c = a.clone();
+    let mut s = c.rows_step_mut(.., 2);
+    s.iter_mut().for_each(|e| *e = -*e);
+    for mut col in s.col_iter_mut() {
+        col[2] = 0.;
     }
+    let (mut l, r) = s.split_at_col_mut(3);
+    l.col_mut(0).assign(&r.col(0));
+    assert_eq!(c, Mat::new(5, 6).set_by(|r, col| {
+        if r == 4 {0.}
+        else if r % 2 == 0 {-a[(r, if col == 0 {3} else {col})]}
+        else {a[(r, col)]}
+    }));
+    let mut c = a.clone();
+    c.t_mut().rows_step_mut(.., 2).split_at_row_mut(1).1.assign_all(0.);
+    assert_eq!(c, Mat::new(5, 6).set_by(|r, col| if col >= 2 && col % 2 == 0 {0.} else {a[(r, col)]}));
+
+    assert!(std::panic::catch_unwind(|| {a.rows_step(.., 0);}).is_err());
 }
 
-impl<V: View, T: MatAcc> Sub<T> for MatGen<V>
+#[test]
+fn test_flip()
 {
-    type Output = Mat;
+    let a = Mat::new(4, 5).set_by(|r, c| (r * 10 + c) as FP);
+    let ud = Mat::new(4, 5).set_by(|r, c| ((3 - r) * 10 + c) as FP);
+    let lr = Mat::new(4, 5).set_by(|r, c| (r * 10 + 4 - c) as FP);
+
+    assert_eq!(a.flip_rows(), ud);
+    assert_eq!(a.flip_cols(), lr);
+    assert_eq!(a.flipud(), ud);
+    assert_eq!(a.fliplr(), lr);
+    assert_eq!(a.flipud().into_vec(), ud.clone().into_vec());
+    assert_eq!(a.flip_rows().flip_rows(), a);
+    assert_eq!(a.flip_rows().flip_cols(), a.flip_cols().flip_rows());
+
+    // composed with transpose, slicing and steps
+    assert_eq!(a.t().flip_rows(), lr.t());
+    assert_eq!(a.t().flip_cols(), ud.t());
+    assert_eq!(a.flip_rows().t(), ud.t());
+    assert_eq!(a.flip_rows().slice(1 .. 3, 2 ..), ud.slice(1 .. 3, 2 ..));
+    assert_eq!(a.slice(1 .., .. 3).flip_cols(), a.slice(1 .., .. 3).fliplr());
+    assert_eq!(a.slice(1 .., .. 3).flip_cols(), Mat::new(3, 3).set_by(|r, c| ((r + 1) * 10 + 2 - c) as FP));
+    assert_eq!(a.flip_rows().rows(4 ..).size(), (0, 5));
+    assert_eq!(a.flip_cols().cols_step(.., 2), a.select_cols(&[4, 2, 0]));
+    assert_eq!(a.rows_step(.., 2).flip_rows(), a.select_rows(&[2, 0]));
+    assert_eq!(a.flip_rows().diag(), ud.diag());
+    assert_eq!(a.flip_cols().diag_k(-1), lr.diag_k(-1));
+    assert_eq!(a.flip_rows().col(1)[3], 1.);
+    assert_eq!(a.flip_cols().row_iter().collect::<Vec<_>>(), lr.row_iter().collect::<Vec<_>>());
+    assert_eq!(a.flip_rows().iter().copied().collect::<Vec<_>>(), ud.iter().copied().collect::<Vec<_>>());
+    assert_eq!(a.t().flip_rows().iter().copied().collect::<Vec<_>>(), lr.t().iter().copied().collect::<Vec<_>>());
+    assert_eq!(&a.flip_rows() * &a.t(), &ud * &a.t());
+    assert_eq!(a.flip_cols().norm_p2sq(), a.norm_p2sq());
+
+    // mutable
+    let mut c = a.clone();
+    let mut f = c.flip_rows_mut();
+    f.iter_mut().enumerate().for_each(|(i, e)| *e = i as FP);
+    assert_eq!(c, Mat::new(4, 5).set_by(|r, col| (col * 4 + 3 - r) as FP));
+
+    // both flipped, transposed and sliced, in the order of iter
+    let mut c = a.clone();
+    let exp: Vec<FP> = c.flip_rows().flip_cols().t().slice(1 .., .. 3).iter().copied().collect();
+    let mut f = c.flip_rows_mut();
+    let mut f = f.flip_cols_mut();
+    let mut f = f.t_mut();
+    let mut f = f.slice_mut(1 .., .. 3);
+    let it = f.iter_mut();
+    assert_eq!(it.size_hint(), (12, Some(12)));
+    assert_eq!(it.map(|e| *e).collect::<Vec<_>>(), exp);
+
+    let mut c = a.clone();
+    c.flip_cols_mut().col_mut(0).assign_all(-1.);
+    assert_eq!(c, Mat::new(4, 5).set_by(|r, col| if col == 4 {-1.} else {a[(r, col)]}));
+
+    let mut c = a.clone();
+    for (i, mut col) in c.flip_cols_mut().col_iter_mut().enumerate() {
+        col.flip_rows_mut()[0] = i as FP;
+    }
+    assert_eq!(c, Mat::new(4, 5).set_by(|r, col| if r == 3 {(4 - col) as FP} else {a[(r, col)]}));
 
-    fn sub(self, rhs: T) -> Mat
-    {
-        let mut mat = self.h_own();
-        mat.sub_assign(rhs);
-        mat
+    let mut c = a.clone();
+    for (i, mut row) in c.t_mut().flip_rows_mut().row_iter_mut().enumerate() {
+        row.assign_all(i as FP);
     }
+    assert_eq!(c, Mat::new(4, 5).set_by(|_, col| (4 - col) as FP));
+
+    let mut c = a.clone();
+    let mut f = c.flip_cols_mut();
+    let (mut l, r) = f.split_at_col_mut(2);
+    l.assign(&r.cols(.. 2));
+    assert_eq!(l.size(), (4, 2));
+    assert_eq!(c, Mat::new(4, 5).set_by(|r, col| (r * 10 + if col >= 3 {col - 2} else {col}) as FP));
+
+    let mut c = a.clone();
+    let mut t = c.t_mut();
+    let mut f = t.flip_rows_mut();
+    let (u, mut d) = f.split_at_row_mut(4);
+    assert_eq!(u, lr.t().rows(.. 4));
+    d.assign_all(0.);
+    assert_eq!(c, Mat::new(4, 5).set_by(|r, col| if col == 0 {0.} else {a[(r, col)]}));
+
+    let mut v = Mat::new(6, 1).set_by(|r, _| r as FP);
+    let mut f = v.flip_rows_mut();
+    let (u, d) = f.split_at_row_mut(2);
+    assert_eq!(u, Mat::new(2, 1).set_iter(&[5., 4.]));
+    assert_eq!(d, Mat::new(4, 1).set_iter(&[3., 2., 1., 0.]));
+    let mut t = v.t_mut();
+    let mut f = t.flip_cols_mut();
+    let (l, r) = f.split_at_col_mut(6);
+    assert_eq!((l.size(), r.size()), ((1, 6), (1, 0)));
 }
 
-impl<V: View, T: MatAcc> Sub<T> for &MatGen<V>
+#[test]
+fn test_map()
 {
-    type Output = Mat;
+    let a = Mat::new(3, 4).set_by(|r, c| r as FP - c as FP);
+    let relu = Mat::new(3, 4).set_by(|r, c| (r as FP - c as FP).max(0.));
+
+    assert_eq!(a.map(|x| x.max(0.)), relu);
+    assert_eq!(a.t().map(|x| x.max(0.)), relu.t());
+    assert_eq!(a.cols_step(.., 2).map(|x| -x), -a.select_cols(&[0, 2]));
+    assert_eq!(a.map_indexed(|r, c, x| x + c as FP - r as FP), Mat::new(3, 4));
+    assert_eq!(a.t().map_indexed(|r, _, x| if r == 0 {x} else {0.}), Mat::new(4, 3).set_by(|r, c| if r == 0 {c as FP} else {0.}));
+
+    let mut b = a.clone();
+    b.map_inplace(|x| x.max(0.));
+    assert_eq!(b, relu);
+
+    let mut b = a.clone();
+    b.t_mut().map_inplace(|x| x.max(0.));
+    assert_eq!(b, relu);
+
+    let mut b = a.clone();
+    b.slice_mut(1 .., 1 .. 3).map_inplace(|x| x * 10.);
+    assert_eq!(b, Mat::new(3, 4).set_by(|r, c| a[(r, c)] * if r >= 1 && (1 .. 3).contains(&c) {10.} else {1.}));
+
+    let mut n = 0;
+    b.flip_rows_mut().map_inplace(|_| {n += 1; n as FP});
+    assert_eq!(n, 12);
+    assert_eq!(b[(2, 0)], 1.);
+}
 
-    fn sub(self, rhs: T) -> Mat
-    {
-        self.clone_sz().sub(rhs)
-    }
+#[test]
+fn test_zip_map()
+{
+    let a = Mat::new(3, 2).set_by(|r, c| (r + c) as FP);
+    let b = Mat::new(2, 3).set_by(|r, c| (r * c) as FP);
+
+    assert_eq!(a.zip_map(&a, |x, y| x - y), Mat::new(3, 2));
+    assert_eq!(a.zip_map(b.t(), |x, y| x * 10. + y), Mat::new(3, 2).set_by(|r, c| ((r + c) * 10 + r * c) as FP));
+    assert_eq!(a.t().zip_map(b.clone(), FP::max), Mat::new(2, 3).set_by(|r, c| (r + c).max(r * c) as FP));
+    assert_eq!(a.rows(1 ..).zip_map(b.t().rows(.. 2), |x, y| (x - y).abs() / (1. + y.abs())),
+               Mat::new(2, 2).set_iter(&[1., 2., 2. / 1., 2. / 2.]));
+
+    let mut c = a.clone();
+    c.zip_map_inplace(b.t(), FP::min);
+    assert_eq!(c, Mat::new(3, 2).set_by(|r, c| (r + c).min(r * c) as FP));
+
+    let mut c = a.clone();
+    c.col_mut(1).zip_map_inplace(a.col(0), |x, y| x * y);
+    assert_eq!(c.col(1), Mat::new_vec(3).set_iter(&[0., 2., 6.]));
+
+    assert!(std::panic::catch_unwind(|| {a.zip_map(&b, FP::max);}).is_err());
 }
 
-impl<V: View> Sub<FP> for MatGen<V>
+#[test]
+fn test_elem_mul_div()
 {
-    type Output = Mat;
+    let s = Mat::new_vec(4).set_iter(&[1., 2., 3., 4.]);
+    let z = Mat::new_vec(4).set_iter(&[4., 0., -1., 0.5]);
+
+    assert_eq!(s.elem_mul(&z), Mat::new_vec(4).set_iter(&[4., 0., -3., 2.]));
+    assert_eq!(s.elem_div(&s), Mat::new_vec(4).set_all(1.));
+    assert_eq!(s.t().elem_mul(&z.t()), s.elem_mul(&z).t());
+
+    let q = s.elem_div(&z);
+    assert_eq!(q[1], FP::INFINITY);
+    assert_eq!(q[3], 8.);
+    assert!(Mat::new_vec(1).elem_div(&Mat::new_vec(1))[0].is_nan());
+
+    let a = Mat::new(3, 3).set_by(|r, c| (r * 3 + c + 1) as FP);
+    let mut b = a.clone();
+    b.slice_mut(.. 2, 1 ..).elem_mul_assign(&a.slice(1 .., .. 2).t());
+    assert_eq!(b, Mat::new(3, 3).set_by(|r, c| if r < 2 && c >= 1 {a[(r, c)] * a[(c, r)]} else {a[(r, c)]}));
+    b.slice_mut(.. 2, 1 ..).elem_div_assign(&a.slice(1 .., .. 2).t());
+    assert_eq!(b, a);
+
+    assert!(std::panic::catch_unwind(|| {s.elem_mul(&s.t());}).is_err());
+}
 
-    fn sub(self, rhs: FP) -> Mat
-    {
-        let mut mat = self.h_own();
-        mat.sub_assign(rhs);
-        mat
-    }
+#[test]
+fn test_abs_clamp()
+{
+    let a = Mat::new(2, 3).set_iter(&[
+        -2., 0.5, 3.,
+        0., -0.5, -4.
+    ]);
+
+    assert_eq!(a.abs(), Mat::new(2, 3).set_iter(&[2., 0.5, 3., 0., 0.5, 4.]));
+    assert_eq!(a.t().signum(), Mat::new(2, 3).set_iter(&[-1., 1., 1., 1., -1., -1.]).t());
+    assert_eq!(a.clamp(-1., 1.), Mat::new(2, 3).set_iter(&[-1., 0.5, 1., 0., -0.5, -1.]));
+
+    let lo = Mat::new(2, 3).set_by(|r, _| -(r as FP));
+    let hi = Mat::new(3, 2).set_all(2.);
+    assert_eq!(a.clamp(&lo, hi.t()), Mat::new(2, 3).set_iter(&[0., 0.5, 2., 0., -0.5, -1.]));
+    assert_eq!(a.col(2).clamp(lo.col(0), 10.), Mat::new_vec(2).set_iter(&[3., -1.]));
+
+    let mut b = a.clone();
+    b.row_mut(1).abs_inplace();
+    b.row_mut(0).signum_inplace();
+    assert_eq!(b, Mat::new(2, 3).set_iter(&[-1., 1., 1., 0., 0.5, 4.]));
+    b.t_mut().clamp_inplace(0., Mat::new(3, 2).set_all(0.75));
+    assert_eq!(b, Mat::new(2, 3).set_iter(&[0., 0.75, 0.75, 0., 0.5, 0.75]));
+
+    assert!(std::panic::catch_unwind(|| {a.clamp(1., -1.);}).is_err());
+    assert!(std::panic::catch_unwind(|| {a.clamp(lo.t(), 1.);}).is_err());
 }
 
-impl<V: View> Sub<FP> for &MatGen<V>
+#[test]
+fn test_elem_func()
 {
-    type Output = Mat;
+    let a = Mat::new(2, 3).set_iter(&[
+        1., 4., 9.,
+        0.25, 0., 16.
+    ]);
+
+    assert_eq!(a.sqrt(), Mat::new(2, 3).set_iter(&[1., 2., 3., 0.5, 0., 4.]));
+    assert_eq!(a.t().powf(0.5), a.sqrt().t());
+    assert_eq!(a.slice(.., 1 ..).t().powf(2.), a.slice(.., 1 ..).elem_mul(&a.slice(.., 1 ..)).t());
+    assert!((a.exp().ln() - &a).norm_p2() < 1e-12);
+    assert_eq!(a.ln()[(1, 1)], FP::NEG_INFINITY);
+    assert!((-&a).sqrt()[(0, 0)].is_nan());
+    assert!((-&a).ln()[(0, 0)].is_nan());
+
+    assert_eq!(a.cols(2 ..).try_ln().unwrap(), a.cols(2 ..).ln());
+    assert_eq!(a.t().try_ln(), Err(MatError::OutOfDomain("ln of 0 at (1, 1)".to_string())));
+    assert_eq!(Mat::new_vec(1).set_all(FP::NAN).try_ln().unwrap_err().to_string(), "out of domain: ln of NaN at (0, 0)");
+
+    let e = Mat::new(0, 3);
+    assert_eq!(e.exp().size(), (0, 3));
+    assert_eq!(e.t().try_ln().unwrap().size(), (3, 0));
+
+    let mut b = a.clone();
+    b.t_mut().rows_mut(1 ..).sqrt_inplace();
+    assert_eq!(b, Mat::new(2, 3).set_iter(&[1., 2., 3., 0.25, 0., 4.]));
+    b.col_mut(0).ln_inplace();
+    b.col_mut(0).exp_inplace();
+    b.powf_inplace(2.);
+    assert!((b - Mat::new(2, 3).set_iter(&[1., 4., 9., 0.0625, 0., 16.])).norm_p2() < 1e-12);
+}
 
-    fn sub(self, rhs: FP) -> Mat
-    {
-        self.clone_sz().sub(rhs)
-    }
+#[test]
+fn test_non_finite()
+{
+    let mut a = Mat::new(3, 4).set_by(|r, c| (r + c) as FP);
+    assert!(a.is_finite() && !a.has_nan());
+    assert_eq!(a.find_non_finite(), None);
+    assert_eq!(Mat::new(0, 2).find_non_finite(), None);
+
+    a[(2, 1)] = FP::INFINITY;
+    a[(1, 3)] = FP::NAN;
+    assert!(!a.is_finite() && a.has_nan());
+    assert_eq!(a.find_non_finite(), Some((2, 1, FP::INFINITY)));
+    assert_eq!(a.t().find_non_finite().map(|(r, c, _)| (r, c)), Some((3, 1)));
+    assert!(!a.cols(1 .. 2).has_nan());
+    assert_eq!(a.cols(2 ..).find_non_finite().map(|(r, c, _)| (r, c)), Some((1, 1)));
+    assert_eq!(a.flip_rows().rows(.. 2).find_non_finite().map(|(r, c, _)| (r, c)), Some((0, 1)));
+    assert!(a.slice(.. 2, .. 3).is_finite());
 }
 
-impl<V: View> Sub<MatGen<V>> for FP
+#[test]
+fn test_mask()
 {
-    type Output = Mat;
-
-    fn sub(self, rhs: MatGen<V>) -> Mat
-    {
-        rhs.neg().add(self)
+    let a = Mat::new(2, 3).set_iter(&[
+        -1., 0., 2.,
+        3., 0., -4.
+    ]);
+    let b = Mat::new(2, 3).set_all(0.);
+
+    assert_eq!(a.lt(&b), Mat::new(2, 3).set_iter(&[1., 0., 0., 0., 0., 1.]));
+    assert_eq!(a.le(&b), Mat::new(2, 3).set_iter(&[1., 1., 0., 0., 1., 1.]));
+    assert_eq!(a.gt(&b), Mat::new(2, 3).set_iter(&[0., 0., 1., 1., 0., 0.]));
+    assert_eq!(a.ge(&b), Mat::new(2, 3).set_iter(&[0., 1., 1., 1., 1., 0.]));
+    assert_eq!(a.eq_elem(&b), Mat::new(2, 3).set_iter(&[0., 1., 0., 0., 1., 0.]));
+    assert_eq!(a.ne_elem(&b), Mat::new(2, 3).set_iter(&[1., 0., 1., 1., 0., 1.]));
+    for (m, s) in [(a.lt(&b), a.lt_scalar(0.)), (a.le(&b), a.le_scalar(0.)), (a.gt(&b), a.gt_scalar(0.)),
+                   (a.ge(&b), a.ge_scalar(0.)), (a.eq_elem(&b), a.eq_scalar(0.)), (a.ne_elem(&b), a.ne_scalar(0.))] {
+        assert_eq!(m, s);
     }
+    assert_eq!(a.t().gt(b.t()), a.gt(&b).t());
+    assert_eq!(a.gt(a.col(0).repeat_vec(3)), Mat::new(2, 3).set_iter(&[0., 1., 1., 0., 0., 0.]));
+    assert_eq!(a.gt_scalar(0.).iter().sum::<FP>(), 2.);
+
+    let clipped = Mat::select(a.ge_scalar(0.), &a, &b);
+    assert_eq!(clipped, Mat::new(2, 3).set_iter(&[0., 0., 2., 3., 0., 0.]));
+    assert_eq!(Mat::select(a.t().lt_scalar(0.), a.t(), -&a.t()), a.abs().t() * -1.);
+
+    let r = std::panic::catch_unwind(|| {a.lt(a.t());});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "lt: size 2x3 does not match 3x2");
+    let r = std::panic::catch_unwind(|| {Mat::select(&a, &b, a.t());});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "select: size 2x3 does not match 3x2");
 }
 
-impl<V: View> Sub<&MatGen<V>> for FP
+#[test]
+fn test_reduce()
 {
-    type Output = Mat;
+    let a = Mat::new(2, 4).set_iter(&[
+        2., 4., 4., 4.,
+        5., 5., 7., 9.
+    ]);
+
+    assert_eq!(a.sum(), 40.);
+    assert_eq!(a.mean(), Some(5.));
+    assert_eq!(a.variance(), Some(4.));
+    assert_eq!(a.std_dev(), Some(2.));
+    assert_eq!(a.count(|x| x > 4.), 4);
+    assert_eq!(a.t().sum(), 40.);
+    assert_eq!(a.t().variance(), Some(4.));
+    assert_eq!(a.cols_step(.., 2).sum(), 18.);
+    assert_eq!(a.flip_rows().rows(1 ..).mean(), Some(3.5));
+
+    let e = Mat::new(0, 3);
+    assert_eq!(e.sum(), 0.);
+    assert_eq!((e.mean(), e.variance(), e.std_dev()), (None, None, None));
+    assert_eq!(e.count(|_| true), 0);
+
+    let one = Mat::new_vec(1).set_all(-3.);
+    assert_eq!((one.sum(), one.mean(), one.variance()), (-3., Some(-3.), Some(0.)));
+
+    // stable with a large offset
+    let b = Mat::new_vec(3).set_iter(&[1e9 + 4., 1e9 + 7., 1e9 + 13.]);
+    assert_eq!(b.variance(), Some(14.));
+}
 
-    fn sub(self, rhs: &MatGen<V>) -> Mat
-    {
-        rhs.neg().add(self)
-    }
+#[test]
+fn test_reduce_axis()
+{
+    let a = Mat::new(2, 3).set_iter(&[
+        1., -2., 3.,
+        4., 0., -6.
+    ]);
+
+    assert_eq!(a.col_sums(), Mat::new_vec(3).set_iter(&[5., -2., -3.]));
+    assert_eq!(a.row_sums(), Mat::new_vec(2).set_iter(&[2., -2.]));
+    assert_eq!(a.col_means(), Mat::new_vec(3).set_iter(&[2.5, -1., -1.5]));
+    assert_eq!(a.row_means(), Mat::new_vec(2).set_iter(&[2. / 3., -2. / 3.]));
+    assert_eq!(a.col_max(), Mat::new_vec(3).set_iter(&[4., 0., 3.]));
+    assert_eq!(a.col_min(), Mat::new_vec(3).set_iter(&[1., -2., -6.]));
+    assert_eq!(a.row_max(), Mat::new_vec(2).set_iter(&[3., 4.]));
+    assert_eq!(a.row_min(), Mat::new_vec(2).set_iter(&[-2., -6.]));
+    assert!((a.col_norms_p2() - Mat::new_vec(3).set_iter(&[FP::sqrt(17.), 2., FP::sqrt(45.)])).norm_inf() < 1e-14);
+    assert!((a.row_norms_p2() - Mat::new_vec(2).set_iter(&[FP::sqrt(14.), FP::sqrt(52.)])).norm_inf() < 1e-14);
+
+    assert_eq!(a.t().col_sums(), a.row_sums());
+    assert_eq!(a.t().row_max(), a.col_max());
+    assert_eq!(a.t().col_norms_p2(), a.row_norms_p2());
+    assert_eq!(a.cols(1 ..).col_min(), a.col_min().rows(1 ..));
+    assert_eq!(a.flip_cols().row_sums(), a.row_sums());
+
+    let e = Mat::new(0, 2);
+    assert_eq!(e.col_sums(), Mat::new_vec(2));
+    assert_eq!(e.row_sums().size(), (0, 1));
+    assert!(e.col_means()[0].is_nan() && e.col_max()[1].is_nan());
 }
 
-//
+#[test]
+fn test_scale()
+{
+    let a = Mat::new(2, 3).set_iter(&[
+        3., 0., 1.,
+        4., 0., -1.
+    ]);
+
+    let mut b = a.clone();
+    b.scale_rows(&Mat::new_vec(2).set_iter(&[2., -1.]));
+    assert_eq!(b, &Mat::new_diag(&[2., -1.]) * &a);
+    let mut b = a.clone();
+    b.scale_cols(&Mat::new_vec(3).set_iter(&[1., 2., 3.]).t());
+    assert_eq!(b, &a * &Mat::new_diag(&[1., 2., 3.]));
+
+    let mut b = a.clone();
+    b.t_mut().scale_rows(&Mat::new_vec(3).set_iter(&[1., 2., 3.]));
+    assert_eq!(b, &a * &Mat::new_diag(&[1., 2., 3.]));
+    let mut b = a.clone();
+    b.cols_mut(1 ..).scale_cols(&Mat::new_vec(2).set_all(10.));
+    assert_eq!(b.col(2), a.col(2) * 10.);
+
+    let mut b = a.clone();
+    let norms = b.normalize_cols();
+    assert_eq!(norms, Mat::new_vec(3).set_iter(&[5., 0., FP::sqrt(2.)]));
+    assert_eq!(b.col(0), Mat::new_vec(2).set_iter(&[0.6, 0.8]));
+    assert_eq!(b.col(1), a.col(1));
+    assert!((b.col(2).norm_p2() - 1.).abs() < 1e-15);
+    b.scale_cols(&norms);
+    assert!((b - &a).norm_p2() < 1e-15);
+
+    let r = std::panic::catch_unwind(|| {a.clone().scale_rows(&Mat::new_vec(3));});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "scale_rows: size 3x1 is not a vector of length 2");
+}
 
-impl<V: View> MulAssign<FP> for MatGen<V>
+#[test]
+fn test_norms()
 {
-    fn mul_assign(&mut self, rhs: FP)
-    {
-        let (l_nrows, l_ncols) = self.size();
+    let a = Mat::new(2, 3).set_iter(&[
+        1., -2., 3.,
+        -4., 0., 6.
+    ]);
+
+    assert_eq!(a.norm_p1(), 16.);
+    assert_eq!(a.norm_inf(), 6.);
+    assert_eq!(a.norm_op1(), 9.);
+    assert_eq!(a.norm_op_inf(), 10.);
+    assert_eq!(a.t().norm_op1(), 10.);
+    assert_eq!(a.cols(.. 2).norm_op_inf(), 4.);
+    assert_eq!(a.row(0).norm_inf(), 3.);
+
+    for e in [Mat::new(0, 3), Mat::new(3, 0)] {
+        assert_eq!((e.norm_p1(), e.norm_inf(), e.norm_op1(), e.norm_op_inf()), (0., 0., 0., 0.));
+    }
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                self[(r, c)] *= rhs;
-            }
-        }
+    let mut rng = Rng::new(0);
+    for (nrows, ncols) in [(1, 1), (3, 5), (6, 2), (4, 4)] {
+        let a = Mat::new(nrows, ncols).set_rand(&mut rng) - 0.5;
+        assert_eq!(a.t().norm_op1(), a.norm_op_inf());
+        assert_eq!(a.t().norm_op_inf(), a.norm_op1());
+        assert!((a.t().norm_p1() - a.norm_p1()).abs() < 1e-12);
+        assert!(a.norm_inf() <= a.norm_op1() && a.norm_op1() <= a.norm_p1());
+        // operator norms bound the ratio of vector norms
+        let x = Mat::new_vec(ncols).set_rand(&mut rng) - 0.5;
+        assert!((&a * &x).norm_p1() <= a.norm_op1() * x.norm_p1() * (1. + 1e-12));
+        assert!((&a * &x).norm_inf() <= a.norm_op_inf() * x.norm_inf() * (1. + 1e-12));
     }
 }
 
-impl<V: View, T: MatAcc> Mul<T> for MatGen<V>
+#[test]
+fn test_norm_sv()
 {
-    type Output = Mat;
-
-    fn mul(self, rhs: T) -> Mat
-    {
-        (&self).mul(rhs)
+    let d = Mat::new_diag(&[3., -4., 1.]);
+    assert!((d.norm_nuclear() - 8.).abs() < 1e-12);
+    assert!((d.norm_spectral() - 4.).abs() < 1e-12);
+    assert!((d.norm_spectral_est(1e-12) - 4.).abs() < 1e-6);
+
+    // rank-1 u v^T has the only singular value |u| |v|
+    let u = Mat::new_vec(3).set_iter(&[1., 2., 2.]);
+    let v = Mat::new_vec(4).set_iter(&[1., 1., 1., 1.]);
+    let a = &u * v.t();
+    assert!((a.norm_nuclear() - 6.).abs() < 1e-12);
+    assert!((a.norm_spectral() - 6.).abs() < 1e-12);
+    assert!((a.t().norm_spectral() - 6.).abs() < 1e-12);
+    assert!((a.norm_spectral_est(1e-12) - 6.).abs() < 1e-12);
+    assert!((a.rows(1 ..).norm_nuclear() - FP::sqrt(32.)).abs() < 1e-12);
+
+    let b = Mat::new(5, 3).set_rand(&mut Rng::new(1));
+    let est = b.norm_spectral_est(1e-10);
+    assert!(est <= b.norm_spectral() * (1. + 1e-12));
+    assert!((est - b.norm_spectral()).abs() < 1e-6);
+    assert!(b.norm_spectral() <= b.norm_p2() && b.norm_p2() <= b.norm_nuclear());
+
+    for e in [Mat::new(0, 3), Mat::new(2, 0)] {
+        assert_eq!((e.norm_nuclear(), e.norm_spectral(), e.norm_spectral_est(1e-6)), (0., 0., 0.));
     }
+    assert_eq!(Mat::new(2, 2).norm_spectral_est(1e-6), 0.);
 }
 
-impl<V: View, T: MatAcc> Mul<T> for &MatGen<V>
+#[test]
+fn test_norm_p2_scaled()
 {
-    type Output = Mat;
-
-    fn mul(self, rhs: T) -> Mat
-    {
-        let (l_nrows, l_ncols) = self.size();
-        let (r_nrows, r_ncols) = rhs.acc_size();
+    let big = Mat::new_vec(2).set_iter(&[3e200, 4e200]);
+    assert!(FP::sqrt(big.norm_p2sq()).is_infinite());
+    assert!((big.norm_p2() / 5e200 - 1.).abs() < 1e-15);
+
+    let small = Mat::new_vec(2).set_iter(&[3e-200, -4e-200]);
+    assert_eq!(FP::sqrt(small.norm_p2sq()), 0.);
+    assert!((small.norm_p2() / 5e-200 - 1.).abs() < 1e-15);
+
+    let mixed = Mat::new(2, 2).set_iter(&[1e-300, 1e300, 0., -1e300]);
+    assert!((mixed.t().norm_p2() / (FP::sqrt(2.) * 1e300) - 1.).abs() < 1e-15);
+
+    assert_eq!(Mat::new(0, 2).norm_p2(), 0.);
+    assert_eq!(Mat::new(2, 2).norm_p2(), 0.);
+    assert_eq!(Mat::new_vec(3).set_iter(&[1., 2., 2.]).norm_p2(), 3.);
+    assert_eq!(Mat::new_vec(2).set_iter(&[1., FP::INFINITY]).norm_p2(), FP::INFINITY);
+    assert!(Mat::new_vec(2).set_iter(&[FP::NAN, 1.]).norm_p2().is_nan());
+}
 
-        assert_eq!(l_ncols, r_nrows);
+#[test]
+fn test_add_diag()
+{
+    let a = Mat::new(3, 3).set_by(|r, c| (r * 3 + c) as FP);
+
+    let mut b = a.clone();
+    b.add_diag(1.5);
+    assert_eq!(b, &a + Mat::new(3, 3).set_eye() * 1.5);
+    b.add_diag_vec(&Mat::new_vec(3).set_iter(&[1., 2., 3.]).t());
+    assert_eq!(b, &a + Mat::new_diag(&[2.5, 3.5, 4.5]));
+
+    // a block of a larger matrix
+    let mut kkt = Mat::new(5, 5);
+    kkt.slice_mut(3 .., 3 ..).add_diag(-1e-3);
+    assert_eq!(kkt, Mat::new_diag(&[0., 0., 0., -1e-3, -1e-3]));
+    kkt.t_mut().slice_mut(1 .., ..).add_diag_vec(&Mat::new_vec(4).set_all(1.));
+    assert_eq!(kkt.diag_k(1), Mat::new_vec(4).set_all(1.));
+
+    // non-square on min(nrows, ncols)
+    let mut c = Mat::new(2, 4);
+    c.add_diag(1.);
+    assert_eq!(c, Mat::new(2, 4).set_eye());
+    let mut c = Mat::new(4, 2);
+    c.t_mut().add_diag_vec(&Mat::new_vec(2).set_iter(&[1., 2.]));
+    assert_eq!(c, Mat::new(4, 2).set_by(|r, col| if r == col {(r + 1) as FP} else {0.}));
+
+    let r = std::panic::catch_unwind(|| {Mat::new(2, 4).add_diag_vec(&Mat::new_vec(4));});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "add_diag_vec: size 4x1 is not a vector of length 2");
+}
 
-        let mut mat = Mat::new(l_nrows, r_ncols);
+#[test]
+fn test_triangle()
+{
+    let a = Mat::new(3, 4).set_by(|r, c| (r * 4 + c + 1) as FP);
+
+    assert_eq!(a.triu(0), Mat::new(3, 4).set_iter(&[
+        1., 2., 3., 4.,
+        0., 6., 7., 8.,
+        0., 0., 11., 12.
+    ]));
+    assert_eq!(a.triu(2), Mat::new(3, 4).set_by(|r, c| if c >= r + 2 {a[(r, c)]} else {0.}));
+    assert_eq!(a.triu(-1), Mat::new(3, 4).set_by(|r, c| if r <= c + 1 {a[(r, c)]} else {0.}));
+    assert_eq!(a.tril(0), Mat::new(3, 4).set_iter(&[
+        1., 0., 0., 0.,
+        5., 6., 0., 0.,
+        9., 10., 11., 0.
+    ]));
+    assert_eq!(a.tril(1), Mat::new(3, 4).set_by(|r, c| if c <= r + 1 {a[(r, c)]} else {0.}));
+    assert_eq!(a.tril(-3), Mat::new(3, 4));
+    assert_eq!(a.triu(-3), a);
+    assert_eq!(a.triu(1) + a.tril(0), a);
+
+    // transposed views
+    assert_eq!(a.t().triu(0), a.tril(0).t());
+    assert_eq!(a.t().tril(-1), a.triu(1).t());
+    assert_eq!(a.slice(1 .., 1 ..).triu(0), a.triu(0).slice(1 .., 1 ..));
+
+    let mut b = a.clone();
+    b.zero_tril(1);
+    assert_eq!(b, a.triu(1));
+    let mut b = a.clone();
+    b.t_mut().zero_triu(-1);
+    assert_eq!(b, a.triu(1));
+    let mut b = a.clone();
+    b.cols_mut(1 ..).zero_triu(0);
+    assert_eq!(b.cols(1 ..), a.cols(1 ..).tril(0));
+
+    let mut s = Mat::new(3, 3).set_by(|r, c| if r <= c {(r * 3 + c) as FP} else {-1.});
+    s.copy_triu_to_tril();
+    assert_eq!(s, s.t());
+    assert_eq!(s.triu(0), Mat::new(3, 3).set_by(|r, c| if r <= c {(r * 3 + c) as FP} else {0.}));
+    let mut s = Mat::new(3, 3).set_by(|r, c| if r >= c {(r * 3 + c) as FP} else {-1.});
+    s.t_mut().copy_triu_to_tril();
+    assert_eq!(s, s.t());
+
+    let r = std::panic::catch_unwind(|| {a.clone().copy_triu_to_tril();});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "copy_triu_to_tril: size 3x4 is not square");
+}
 
-        for c in 0 .. r_ncols {
-            for r in 0 .. l_nrows {
-                let mut v: FP = 0.0;
-                for k in 0 .. l_ncols {
-                    v += self[(r, k)] * rhs.acc_get(k, c);
-                }
-                mat[(r, c)] = v;
-            }
-        }
+#[test]
+fn test_sym()
+{
+    let a = Mat::new(3, 3).set_by(|r, c| (r * 3 + c) as FP);
+    assert!(!a.is_sym(1e-9));
+    assert!(!a.rows(.. 2).is_sym(1e9));
+    assert!(Mat::new(0, 0).is_sym(0.) && Mat::new(1, 1).is_sym(0.));
+
+    let mut s = a.clone();
+    s.symmetrize();
+    assert_eq!(s, (&a + a.t()) * 0.5);
+    assert!(s.is_sym(0.) && s.t().is_sym(0.));
+
+    let mut s = a.clone();
+    s.t_mut().symmetrize();
+    assert_eq!(s, (&a + a.t()) * 0.5);
+    let mut s = a.clone();
+    s.slice_mut(1 .., 1 ..).symmetrize();
+    assert!(s.slice(1 .., 1 ..).is_sym(0.) && !s.is_sym(0.));
+
+    // absolute for small, relative for large
+    let mut b = Mat::new(2, 2).set_iter(&[0., 1e-7, 0., 0.]);
+    assert!(b.is_sym(1e-6) && !b.is_sym(1e-8));
+    b.assign_iter(&[0., 1e6 + 0.1, 1e6, 0.]);
+    assert!(b.is_sym(1e-6) && !b.is_sym(1e-8));
+    b[(0, 1)] = FP::NAN;
+    assert!(!b.is_sym(1e9));
+    assert_eq!(b.sym_violation().map(|(r, c, _)| (r, c)), Some((0, 1)));
+
+    let r = std::panic::catch_unwind(|| {Mat::new(2, 3).symmetrize();});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "symmetrize: size 2x3 is not square");
+}
 
-        mat
+#[test]
+fn test_svec()
+{
+    let a = Mat::new(3, 3).set_iter(&[
+        1., 2., 4.,
+        2., 3., 5.,
+        4., 5., 6.
+    ]);
+    let r2 = FP::sqrt(2.);
+
+    let v = a.svec();
+    assert_eq!(v, Mat::new_vec(6).set_iter(&[1., 2. * r2, 4. * r2, 3., 5. * r2, 6.]));
+    assert!((Mat::smat(&v, 3) - &a).norm_inf() < 1e-15);
+    assert!((Mat::smat(&v.t(), 3) - &a).norm_inf() < 1e-15);
+    assert_eq!(a.t().svec(), v);
+    assert_eq!(a.slice(1 .., 1 ..).svec(), Mat::new_vec(3).set_iter(&[3., 5. * r2, 6.]));
+
+    let mut rng = Rng::new(1);
+    for n in [1, 2, 5] {
+        let mut b = Mat::new(n, n).set_rand(&mut rng);
+        let mut c = Mat::new(n, n).set_rand(&mut rng);
+        b.symmetrize();
+        c.symmetrize();
+        assert!((b.svec().prod(&c.svec()) - b.prod(&c)).abs() < 1e-12);
+        assert!((b.svec().norm_p2() - b.norm_p2()).abs() < 1e-12);
+        assert!((Mat::smat(&b.svec(), n) - &b).norm_inf() < 1e-15);
     }
+    assert_eq!(Mat::new(0, 0).svec().size(), (0, 1));
+    assert_eq!(Mat::smat(&Mat::new_vec(0), 0).size(), (0, 0));
+
+    let r = std::panic::catch_unwind(|| {Mat::smat(&Mat::new_vec(5), 3);});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "smat: size 5x1 is not a vector of length 6");
 }
 
-impl<V: View> Mul<FP> for MatGen<V>
+#[test]
+fn test_vec()
 {
-    type Output = Mat;
-
-    fn mul(self, rhs: FP) -> Mat
-    {
-        let mut mat = self.h_own();
-        mat.mul_assign(rhs);
-        mat
-    }
+    let x = Mat::new(2, 3).set_by(|r, c| (r * 3 + c) as FP);
+
+    // no copy for a plain owned matrix
+    let v = x.vec().unwrap();
+    assert_eq!(v.size(), (6, 1));
+    assert_eq!(v, Mat::new_vec(6).set_iter(&[0., 3., 1., 4., 2., 5.]));
+    assert!(std::ptr::eq(&v[3], &x[(1, 1)]));
+    assert_eq!(v.unvec(2, 3).unwrap(), x);
+    assert_eq!(x.unvec(3, 2).unwrap(), x.clone().reshape(3, 2));
+    assert_eq!(x.cols(1 ..).vec().unwrap(), x.vec_copy().rows(2 ..));
+
+    // copied otherwise
+    assert!(x.t().vec().is_none() && x.rows(1 ..).vec().is_none());
+    assert_eq!(x.t().vec_copy(), Mat::new_vec(6).set_iter(&[0., 1., 2., 3., 4., 5.]));
+    assert_eq!(x.rows(1 ..).vec_copy(), x.row(1).t());
+    assert_eq!(x.t().unvec_copy(2, 3), Mat::new(2, 3).set_iter(&[0., 2., 4., 1., 3., 5.]));
+    assert_eq!(x.vec_copy().unvec_copy(2, 3), x);
+
+    // vec(A X B) = (B^T kron A) vec(X)
+    let mut rng = Rng::new(1);
+    let a = Mat::new(4, 2).set_rand(&mut rng);
+    let b = Mat::new(3, 5).set_rand(&mut rng);
+    let axb = &a * &x * &b;
+    let kron = b.t().kron(&a) * x.vec().unwrap();
+    assert!((axb.vec().unwrap() - kron).norm_inf() < 1e-12);
+
+    let r = std::panic::catch_unwind(|| {x.unvec(4, 2);});
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "unvec: 2 x 3 into 4 x 2");
 }
 
-impl<V: View> Mul<FP> for &MatGen<V>
+#[test]
+fn test_argmax()
 {
-    type Output = Mat;
+    let a = Mat::new(3, 3).set_iter(&[
+        1., 7., -9.,
+        7., FP::NAN, 0.,
+        -9., 2., 3.
+    ]);
+
+    // first in column-major order on ties
+    assert_eq!(a.argmax(), Some((1, 0)));
+    assert_eq!(a.argmin(), Some((2, 0)));
+    assert_eq!(a.abs_argmax(), Some((2, 0)));
+    assert_eq!(a.t().argmax(), Some((1, 0)));
+    assert_eq!(a.t().abs_argmax(), Some((2, 0)));
+    assert_eq!(a.cols(1 ..).argmin(), Some((0, 1)));
+    assert_eq!(a.col_argmax(), vec![Some(1), Some(0), Some(2)]);
+    assert_eq!(a.col_argmin(), vec![Some(2), Some(2), Some(0)]);
+
+    let nan = Mat::new(2, 2).set_all(FP::NAN);
+    assert_eq!((nan.argmax(), nan.argmin(), nan.abs_argmax()), (None, None, None));
+    assert_eq!(Mat::new(0, 2).argmax(), None);
+    assert_eq!(Mat::new(0, 2).col_argmax(), vec![None, None]);
+    let mut b = nan.clone();
+    b[(1, 1)] = -1.;
+    assert_eq!((b.argmax(), b.col_argmin()), (Some((1, 1)), vec![None, Some(1)]));
+}
 
-    fn mul(self, rhs: FP) -> Mat
-    {
-        self.clone_sz().mul(rhs)
-    }
+#[test]
+fn test_cumsum()
+{
+    let a = Mat::new(3, 2).set_iter(&[
+        1., 2.,
+        3., 4.,
+        5., 6.
+    ]);
+
+    assert_eq!(a.cumsum_cols(), Mat::new(3, 2).set_iter(&[1., 2., 4., 6., 9., 12.]));
+    assert_eq!(a.cumsum_rows(), Mat::new(3, 2).set_iter(&[1., 3., 3., 7., 5., 11.]));
+    assert_eq!(a.t().cumsum_cols(), a.cumsum_rows().t());
+    assert_eq!(a.t().cumsum_rows(), a.cumsum_cols().t());
+    assert_eq!(a.flip_rows().cumsum_cols().flip_rows().row(0), a.col_sums().t());
+    assert_eq!(a.cumsum_cols().row(2), a.col_sums().t());
+
+    // single row or column
+    assert_eq!(a.row(1).cumsum_cols(), a.row(1));
+    assert_eq!(a.row(1).cumsum_rows(), Mat::new(1, 2).set_iter(&[3., 7.]));
+    assert_eq!(a.col(0).cumsum_rows(), a.col(0));
+    assert_eq!(Mat::new(0, 3).cumsum_cols().size(), (0, 3));
+
+    let mut b = a.clone();
+    b.cumsum_cols_inplace();
+    assert_eq!(b, a.cumsum_cols());
+    let mut b = a.clone();
+    b.t_mut().cumsum_cols_inplace();
+    assert_eq!(b, a.cumsum_rows());
+    let mut b = a.clone();
+    b.rows_mut(1 ..).cumsum_cols_inplace();
+    assert_eq!(b, Mat::new(3, 2).set_iter(&[1., 2., 3., 4., 8., 10.]));
 }
 
-impl<V: View> Mul<MatGen<V>> for FP
+#[test]
+fn test_solve_triangular()
 {
-    type Output = Mat;
+    use crate::matlu::MatLU;
+    use crate::matchol::MatChol;
+
+    const TOL: FP = 1e-12;
+
+    let l = Mat::new(3, 3).set_iter(&[
+        2., 0., 0.,
+        1., 4., 0.,
+        -1., 3., 5.
+    ]);
+    let l_inv = Mat::new(3, 3).set_iter(&[
+        0.5, 0., 0.,
+        -0.125, 0.25, 0.,
+        0.175, -0.15, 0.2
+    ]);
+    let b = Mat::new(3, 2).set_iter(&[
+        1., 0.,
+        2., 1.,
+        3., -1.
+    ]);
+
+    let x = l.solve_lower_triangular(&b, false).unwrap();
+    assert!((&x - &l_inv * &b).norm_inf() < TOL);
+    let x = l.t().solve_upper_triangular(&b, false).unwrap();
+    assert!((&x - l_inv.t() * &b).norm_inf() < TOL);
+
+    // the other triangle is not read
+    let g = l.clone().set_by(|r, c| if c > r {FP::NAN} else {l[(r, c)]});
+    assert_eq!(g.solve_lower_triangular(&b, false).unwrap(), l.solve_lower_triangular(&b, false).unwrap());
+
+    // unit diagonal
+    let u = Mat::new(2, 2).set_iter(&[
+        FP::NAN, 2.,
+        0., 0.
+    ]);
+    let x = u.solve_upper_triangular(&Mat::new_vec(2).set_iter(&[5., 2.]), true).unwrap();
+    assert_eq!(x, Mat::new_vec(2).set_iter(&[1., 2.]));
+
+    // zero and tiny diagonal
+    let mut s = l.clone();
+    s[(1, 1)] = 0.;
+    assert_eq!(s.solve_lower_triangular(&b, false).unwrap_err(), SingularError {pivot: 1, value: 0.});
+    s[(1, 1)] = 1e-20;
+    assert_eq!(s.solve_lower_triangular(&b, false).unwrap_err().pivot, 1);
+    assert!(s.solve_lower_triangular(&b, true).is_ok());
+
+    // agree with factorizations
+    let a = Mat::new(3, 3).set_iter(&[
+        4., 12., -16.,
+        12., 37., -43.,
+        -16., -43., 98.
+    ]);
+    let chol = MatChol::new(&a).unwrap();
+    let y = chol.l().solve_lower_triangular(&b, false).unwrap();
+    let x = chol.l().t().solve_upper_triangular(&y, false).unwrap();
+    assert!((&x - chol.solve(&b)).norm_inf() < TOL * 1e3);
+    assert!((&x - MatLU::new(&a).unwrap().solve(&b)).norm_inf() < TOL * 1e3);
+
+    let r = std::panic::catch_unwind(|| Mat::new(2, 3).solve_lower_triangular(&Mat::new_vec(2), false));
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "solve_lower_triangular: size 2x3 is not square");
+}
 
-    fn mul(self, rhs: MatGen<V>) -> Mat
-    {
-        rhs.mul(self)
+#[test]
+fn test_solve()
+{
+    const TOL: FP = 1e-12;
+
+    let b = Mat::new(3, 2).set_iter(&[
+        1., 0.,
+        2., 1.,
+        3., -1.
+    ]);
+
+    // symmetric positive definite, indefinite and non-symmetric
+    let spd = Mat::new(3, 3).set_iter(&[
+        4., 12., -16.,
+        12., 37., -43.,
+        -16., -43., 98.
+    ]);
+    let sym = Mat::new(3, 3).set_iter(&[
+        1., 2., 0.,
+        2., 1., 0.,
+        0., 0., 1.
+    ]);
+    let gen = Mat::new(3, 3).set_iter(&[
+        2., 1., 1.,
+        4., -6., 0.,
+        -2., 7., 2.
+    ]);
+    for a in &[spd, sym, gen] {
+        let x = a.solve(&b).unwrap();
+        assert!((a * &x - &b).norm_inf() < TOL * 1e3);
+        let x = a.solve_t(&b).unwrap();
+        assert!((a.t() * &x - &b).norm_inf() < TOL * 1e3);
     }
+
+    let singular = Mat::new(3, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.,
+        7., 8., 9.
+    ]);
+    let e = singular.solve(&b).unwrap_err();
+    println!("{}", e);
+    assert!(matches!(e, SolveError::Singular(SingularError {pivot: 2, ..})));
+
+    assert!(matches!(Mat::new(2, 3).solve(&b), Err(SolveError::DimensionMismatch(_))));
+    assert!(matches!(Mat::new(2, 2).set_eye().solve(&b), Err(SolveError::DimensionMismatch(_))));
 }
 
-impl<V: View> Mul<&MatGen<V>> for FP
+#[test]
+fn test_inv_pinv()
 {
-    type Output = Mat;
-
-    fn mul(self, rhs: &MatGen<V>) -> Mat
-    {
-        rhs.mul(self)
+    const TOL: FP = 1e-10;
+
+    let a = Mat::new(3, 3).set_iter(&[
+        2., 1., 1.,
+        4., -6., 0.,
+        -2., 7., 2.
+    ]);
+    let mut eye = Mat::new(3, 3);
+    eye.assign_eye();
+    let inv = a.inv().unwrap();
+    assert!((&a * &inv - &eye).norm_inf() < TOL);
+    assert!((a.pinv(None) - &inv).norm_inf() < TOL);
+
+    let singular = Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        2., 4.
+    ]);
+    assert_eq!(singular.inv().unwrap_err().pivot, 1);
+
+    // rank-deficient rectangular, both tall and wide
+    let mut rng = Rng::default();
+    let u = Mat::new(5, 2).set_rand(&mut rng);
+    let v = Mat::new(2, 4).set_rand(&mut rng);
+    let tall = &u * &v;
+    for a in &[tall.clone(), tall.t().clone_sz()] {
+        let p = a.pinv(None);
+        assert_eq!(p.size(), (a.size().1, a.size().0));
+
+        // Penrose conditions
+        let ap = a * &p;
+        let pa = &p * a;
+        assert!((&ap * a - a).norm_inf() < TOL);
+        assert!((&pa * &p - &p).norm_inf() < TOL);
+        assert!((ap.t() - &ap).norm_inf() < TOL);
+        assert!((pa.t() - &pa).norm_inf() < TOL);
     }
-}
 
-//
+    // a tiny singular value is cut by tol
+    let d = Mat::new_vec(2).set_iter(&[1., 1e-12]).clone_diag();
+    assert_eq!(d.pinv(None)[(1, 1)], 1e12);
+    assert_eq!(d.pinv(Some(1e-9))[(1, 1)], 0.);
 
-impl<V: View> DivAssign<FP> for MatGen<V>
-{
-    fn div_assign(&mut self, rhs: FP)
-    {
-        let (l_nrows, l_ncols) = self.size();
+    let z = Mat::new(2, 3).pinv(None);
+    assert_eq!(z, Mat::new(3, 2));
+    assert_eq!(Mat::new(0, 3).pinv(None).size(), (3, 0));
+}
 
-        for c in 0 .. l_ncols {
-            for r in 0 .. l_nrows {
-                self[(r, c)] /= rhs;
-            }
-        }
-    }
+#[test]
+fn test_eigvals_sym()
+{
+    let a = Mat::new(2, 2).set_iter(&[
+        2., 1.,
+        1., 2.
+    ]);
+    let vals = a.eigvals_sym().unwrap();
+    assert!((vals - Mat::new_vec(2).set_iter(&[3., 1.])).norm_inf() < 1e-14);
+
+    let d = Mat::new_vec(3).set_iter(&[-1., 3., 0.]).clone_diag();
+    assert_eq!(d.t().eigvals_sym().unwrap(), Mat::new_vec(3).set_iter(&[3., 0., -1.]));
 }
 
-impl<V: View> Div<FP> for MatGen<V>
+#[test]
+fn test_eig_max_min_sym()
 {
-    type Output = Mat;
+    const TOL: FP = 1e-12;
 
-    fn div(self, rhs: FP) -> Mat
-    {
-        let mut mat = self.h_own();
-        mat.div_assign(rhs);
-        mat
+    let mut rng = Rng::default();
+    let n = 10;
+
+    for _ in 0 .. 3 {
+        let b = Mat::new(n, n).set_rand(&mut rng) - 0.5;
+        let a = &b + b.t();
+
+        let eig = MatEig::new_sym(&a).unwrap();
+        let vals = eig.eigvals();
+
+        let max = a.eig_max_sym(TOL, 100_000, None).unwrap();
+        let min = a.eig_min_sym(TOL, 100_000, None).unwrap();
+        println!("max = {:e} ({} iters), min = {:e} ({} iters)", max.value, max.iterations, min.value, min.iterations);
+
+        assert!((max.value - vals[(0, 0)]).abs() < 1e-9);
+        assert!((min.value - vals[(n - 1, 0)]).abs() < 1e-9);
+        assert!(max.residual <= TOL * a.norm_op_inf());
+        assert!((max.vector.prod(&eig.eigvecs().col(0)).abs() - 1.).abs() < 1e-9);
+        assert!((min.vector.prod(&eig.eigvecs().col(n - 1)).abs() - 1.).abs() < 1e-9);
+
+        // reproducible
+        assert_eq!(a.eig_max_sym(TOL, 100_000, None).unwrap(), max);
     }
+
+    let d = Mat::new_vec(3).set_iter(&[1., -2., 3.]).clone_diag();
+    let x0 = Mat::new_vec(3).set_all(1.);
+    assert!((d.eig_max_sym(TOL, 1000, Some(&x0)).unwrap().value - 3.).abs() < 1e-12);
+    assert!((d.eig_min_sym(TOL, 1000, Some(&x0)).unwrap().value + 2.).abs() < 1e-12);
+
+    // an eigenvector as the start
+    let e1 = Mat::new_vec(3).set_iter(&[0., 0., 1.]);
+    assert_eq!(d.eig_max_sym(TOL, 0, Some(&e1)).unwrap().iterations, 0);
+
+    let e = d.eig_max_sym(TOL, 1, Some(&x0)).unwrap_err();
+    assert_eq!(e.iterations, 1);
+
+    assert_eq!(Mat::new(2, 2).eig_min_sym(TOL, 10, None).unwrap().value, 0.);
 }
 
-impl<V: View> Div<FP> for &MatGen<V>
+#[test]
+fn test_null_range()
 {
-    type Output = Mat;
+    const TOL: FP = 1e-12;
 
-    fn div(self, rhs: FP) -> Mat
-    {
-        self.clone_sz().div(rhs)
-    }
-}
+    let mut rng = Rng::default();
 
-//
+    // rank 2 of both tall and wide
+    let u = Mat::new(5, 2).set_rand(&mut rng);
+    let v = Mat::new(2, 4).set_rand(&mut rng);
+    let tall = &u * &v;
+    let wide = tall.t().clone_sz();
 
-/// Xorshift random number generator initializer
-pub const XOR64_INIT: u64 = 88172645463325252;
+    for (a, null, range) in &[(&tall, 2, 2), (&wide, 3, 2)] {
+        let (m, n) = a.size();
 
-/// Xorshift random number generator
-pub fn xor64(state: &mut u64) -> FP
-{
-    const MAX: FP = (1_u128 << 64) as FP;
-    *state = *state ^ (*state << 7);
-    *state = *state ^ (*state >> 9);
+        let z = a.null_space(None);
+        assert_eq!(z.size(), (n, *null));
+        assert!((*a * &z).norm_inf() < TOL * a.norm_spectral() * (m * n) as FP);
+        assert!((z.t() * &z - Mat::new(*null, *null).set_eye()).norm_inf() < TOL);
 
-    // [0.0, 1.0)
-    (*state as FP) / MAX
+        let y = a.range_space(None);
+        assert_eq!(y.size(), (m, *range));
+        assert!((y.t() * &y - Mat::new(*range, *range).set_eye()).norm_inf() < TOL);
+        // the columns of a are in the range
+        assert!((&y * (y.t() * *a) - *a).norm_inf() < TOL * 10.);
+    }
+
+    // full column rank
+    let a = Mat::new(3, 2).set_iter(&[
+        1., 0.,
+        0., 1.,
+        1., 1.
+    ]);
+    assert_eq!(a.null_space(None).size(), (2, 0));
+    assert_eq!(a.range_space(None).size(), (3, 2));
+
+    assert_eq!(Mat::new(2, 3).null_space(None), Mat::new(3, 3).set_eye());
+    assert_eq!(Mat::new(2, 3).range_space(None).size(), (2, 0));
+    assert_eq!(Mat::new(0, 2).null_space(None), Mat::new(2, 2).set_eye());
+    assert_eq!(Mat::new(2, 0).null_space(None).size(), (0, 0));
 }
 
-//
+#[test]
+fn test_lstsq()
+{
+    const TOL: FP = 1e-10;
+
+    let mut rng = Rng::default();
+
+    // well-conditioned against the normal equations
+    let a = Mat::new(8, 3).set_randn(&mut rng);
+    let b = Mat::new(8, 2).set_randn(&mut rng);
+
+    let res = a.lstsq(&b, None);
+    assert_eq!(res.rank, 3);
+    let x_ne = (a.t() * &a).solve(&(a.t() * &b)).unwrap();
+    assert!((&res.x - &x_ne).norm_inf() < TOL);
+    assert!((&res.residual - (&a * &x_ne - &b).col_norms_p2()).norm_inf() < TOL);
+
+    // rank-deficient with known minimum-norm answers
+    let a = Mat::new(3, 2).set_iter(&[
+        1., 1.,
+        1., 1.,
+        0., 0.
+    ]);
+    let b = Mat::new(3, 2).set_iter(&[
+        2., 1.,
+        2., 3.,
+        0., 1.
+    ]);
+    let res = a.lstsq(&b, None);
+    println!("x = {}", res.x);
+    assert_eq!(res.rank, 1);
+    let x = Mat::new(2, 2).set_iter(&[
+        1., 1.,
+        1., 1.
+    ]);
+    assert!((&res.x - x).norm_inf() < TOL);
+    let residual = Mat::new_vec(2).set_iter(&[0., FP::sqrt(3.)]);
+    assert!((&res.residual - residual).norm_inf() < TOL);
+
+    // underdetermined
+    let a = Mat::new(1, 2).set_iter(&[1., 1.]);
+    let res = a.lstsq(&Mat::new(1, 1).set_iter(&[2.]), None);
+    assert_eq!(res.rank, 1);
+    assert!((res.x - Mat::new_vec(2).set_iter(&[1., 1.])).norm_inf() < TOL);
+    assert!(res.residual[(0, 0)] < TOL);
+
+    let res = Mat::new(2, 2).lstsq(&Mat::new_vec(2).set_all(1.), None);
+    assert_eq!(res.rank, 0);
+    assert_eq!(res.x, Mat::new_vec(2));
+    let res = Mat::new(2, 0).lstsq(&Mat::new_vec(2).set_all(1.), None);
+    assert_eq!((res.x.size(), res.rank), ((0, 1), 0));
+}
 
 #[test]
-fn test_set()
+fn test_cond()
 {
-    {
-        let a = Mat::new(3, 3).set_eye();
-        let b = Mat::new_like(&a).set_iter(&[
-            1., 0., 0.,
-            0., 1., 0.,
-            0., 0., 1.
-        ]);
-        assert_eq!(a, b);
+    let hilbert = |n: usize| Mat::new(n, n).set_by(|r, c| 1. / (r + c + 1) as FP);
+
+    // known values of spectral condition numbers
+    for &(n, cond) in &[(4, 1.5513738e4), (5, 4.7660725e5), (6, 1.4951058e7)] {
+        let h = hilbert(n);
+        let cond_p2 = h.cond_p2();
+        println!("{}: cond_p2 = {:e}", n, cond_p2);
+        assert!((cond_p2 - cond).abs() < 1e-6 * cond);
     }
-    {
-        let a = Mat::new(2, 4).set_by(|r, c| (r * 4 + c) as FP);
-        let b = Mat::new_like(&a).set_iter(&[
-            0., 1., 2., 3.,
-            4., 5., 6., 7.
-        ]);
-        assert_eq!(a, b);
+    assert!((hilbert(4).inv().unwrap().norm_op1() * hilbert(4).norm_op1() - 28375.).abs() < 1e-6);
+
+    // the estimate explodes as well
+    let mut prev = 1.;
+    for n in 2 ..= 8 {
+        let h = hilbert(n);
+        let cond = h.norm_op1() * h.inv().unwrap().norm_op1();
+        let est = h.cond_est_p1();
+        println!("{}: cond_est_p1 = {:e} of {:e}", n, est, cond);
+        assert!(est <= cond * (1. + 1e-6));
+        assert!(est >= cond / 3.);
+        assert!(est > prev * 10.);
+        prev = est;
     }
+
+    assert_eq!(Mat::new(2, 2).cond_p2(), FP::INFINITY);
+    assert_eq!(Mat::new(2, 2).cond_est_p1(), FP::INFINITY);
+    assert_eq!(Mat::new(3, 3).set_eye().cond_est_p1(), 1.);
+    assert_eq!(Mat::new(0, 0).cond_p2(), 0.);
+    assert_eq!(Mat::new(0, 0).cond_est_p1(), 0.);
 }
 
 #[test]
-fn test_misc()
+fn test_proj_psd()
 {
-    {
-        let a = Mat::new_vec(3);
-        let a = a.t();
-        let b = Mat::new(1, 3);
-        assert_eq!(a, b);
-    }
-    {
-        let a = Mat::new_vec(3).set_t();
-        let b = Mat::new(1, 3);
-        assert_eq!(a, b);
+    const TOL: FP = 1e-12;
+
+    // eigenvalues 3 and -1
+    let a = Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        2., 1.
+    ]);
+    let p = a.proj_psd();
+    println!("p = {}", p);
+    let p0 = Mat::new(2, 2).set_all(1.5);
+    assert!((&p - p0).norm_inf() < TOL);
+
+    // closer than other positive semidefinite candidates
+    let dist = (&a - &p).norm_p2();
+    assert!((dist - 1.).abs() < TOL);
+    for cand in &[&a + Mat::new(2, 2).set_eye(), Mat::new(2, 2), Mat::new(2, 2).set_eye() * 3.] {
+        assert!(cand.eigvals_sym().unwrap().min().unwrap() >= -TOL);
+        assert!((&a - cand).norm_p2() > dist);
     }
-    {
-        let mut r = XOR64_INIT;
-        let mut a = Mat::new(4, 4);
-        let b = Mat::new_like(&a).set_by(|_, _| xor64(&mut r));
-        a.assign(&b);
-        assert_eq!(a, b);
+
+    let mut rng = Rng::default();
+    let n = 6;
+    let b = Mat::new(n, n).set_randn(&mut rng);
+    let a = &b + b.t();
+    let p = a.proj_psd();
+    assert!(p.eigvals_sym().unwrap().min().unwrap() >= -TOL);
+    assert!(p.is_sym(0.));
+    let dist = (&a - &p).norm_p2();
+    for _ in 0 .. 10 {
+        let c = Mat::new(n, n).set_randn(&mut rng);
+        let cand = (&c * c.t() + &p) * 0.5;
+        assert!((&a - cand).norm_p2() >= dist);
     }
+
+    // mild asymmetry is symmetrized
+    let mut skew = Mat::new(n, n).set_randn(&mut rng) * 1e-9;
+    skew -= skew.t().clone_sz();
+    assert!(((&a + skew).proj_psd() - &p).norm_inf() < 1e-6);
+
+    // positive semidefinite unchanged
+    let q = p.proj_psd();
+    assert!((&q - &p).norm_inf() < 1e-9);
+
+    let p = a.proj_psd_eps(1e-3);
+    assert!(p.eigvals_sym().unwrap().min().unwrap() >= 1e-3 - TOL);
+    assert!(MatChol::new(&p).is_ok());
+
+    assert_eq!(Mat::new(0, 0).proj_psd(), Mat::new(0, 0));
+    let r = std::panic::catch_unwind(|| Mat::new(2, 3).proj_psd());
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "proj_psd: size 2x3 is not square");
 }
 
 #[test]
-fn test_slice()
+fn test_sqrt_psd()
 {
-    {
-        let a = Mat::new(4, 4).set_eye();
-        let a = a.slice(1 ..= 2, 1 ..= 2);
-        let b = Mat::new(2, 2).set_eye();
-        assert_eq!(a, b);
-    }
-    {
-        let mut a = Mat::new(4, 4).set_eye();
-        let b = Mat::new(4, 4).set_iter(&[
-            1., 0., 0., 0.,
-            0., 2., 2., 0.,
-            0., 2., 2., 0.,
-            0., 0., 0., 1.
+    const TOL: FP = 1e-12;
+
+    let p = Mat::new(2, 2).set_iter(&[
+        5., 4.,
+        4., 5.
+    ]);
+    let s = p.sqrt_psd().unwrap();
+    println!("s = {}", s);
+    let s0 = Mat::new(2, 2).set_iter(&[
+        2., 1.,
+        1., 2.
+    ]);
+    assert!((&s - s0).norm_inf() < TOL);
+
+    // singular
+    let mut rng = Rng::default();
+    let n = 6;
+    let b = Mat::new(n, 3).set_randn(&mut rng);
+    let p = &b * b.t();
+    let s = p.sqrt_psd().unwrap();
+    assert!(s.is_sym(0.));
+    assert!((&s * &s - &p).norm_inf() < 1e-9);
+    assert!(s.eigvals_sym().unwrap().min().unwrap() >= -1e-9);
+
+    let f = p.chol_factor_or_sqrt().unwrap();
+    assert_eq!(f, s);
+
+    // positive definite
+    let p = &p + Mat::new(n, n).set_eye();
+    let f = p.chol_factor_or_sqrt().unwrap();
+    assert_eq!(f.triu(1), Mat::new(n, n));
+    assert!((&f * f.t() - &p).norm_inf() < 1e-9);
+
+    let a = Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        2., 1.
+    ]);
+    let e = a.sqrt_psd().unwrap_err();
+    println!("{}", e);
+    assert!((e.value - (-1.)).abs() < TOL);
+    assert_eq!(a.chol_factor_or_sqrt().unwrap_err(), e);
+
+    let nan = Mat::new(2, 2).set_all(FP::NAN);
+    assert!(nan.sqrt_psd().unwrap_err().value.is_nan());
+    assert_eq!(Mat::new(0, 0).sqrt_psd().unwrap(), Mat::new(0, 0));
+}
+
+#[test]
+fn test_expm()
+{
+    const TOL: FP = 1e-12;
+
+    let d = Mat::new(3, 3).set_iter(&[
+        1., 0., 0.,
+        0., -2., 0.,
+        0., 0., 0.5
+    ]);
+    let e = d.expm();
+    println!("e = {}", e);
+    let e0 = Mat::new_vec(3).set_iter(&[FP::exp(1.), FP::exp(-2.), FP::exp(0.5)]).clone_diag();
+    assert!((e - e0).norm_inf() < TOL * 10.);
+
+    // rotation generators of small and large angles, the latter with scaling
+    for &theta in &[0.01, 0.3, 1., 10.] {
+        let g = Mat::new(2, 2).set_iter(&[
+            0., -theta,
+            theta, 0.
         ]);
-        let mut a1 = a.slice_mut(1 ..= 2, 1 ..= 2);
-        let a2 = Mat::new(2, 2).set_all(2.);
-        a1.assign(&a2);
-        assert_eq!(a, b);
-    }
-    {
-        let mut a = Mat::new(4, 4).set_eye();
-        let b = Mat::new(4, 4).set_iter(&[
-            0., 0., 0., 0.,
-            0., 1., 0., 0.,
-            0., 0., 1., 0.,
-            1., 0., 0., 1.
+        let rot = Mat::new(2, 2).set_iter(&[
+            theta.cos(), -theta.sin(),
+            theta.sin(), theta.cos()
         ]);
-        let a1 = a.col(3).clone_sz();
-        a.col_mut(0).assign(&a1);
-        assert_eq!(a, b);
+        let err = (g.expm() - rot).norm_inf();
+        println!("{}: err = {:e}", theta, err);
+        assert!(err < TOL * theta.max(1.) * 10.);
+    }
+
+    // series expansion for small norms
+    let mut rng = Rng::default();
+    for &scale in &[1e-3, 1e-1, 1.] {
+        let a = Mat::new(5, 5).set_randn(&mut rng) * scale;
+        let mut series = Mat::new(5, 5).set_eye();
+        let mut term = series.clone();
+        for k in 1 ..= 30 {
+            term = &term * &a / k as FP;
+            series += &term;
+        }
+        let err = (a.expm() - &series).norm_inf() / series.norm_inf();
+        println!("{}: err = {:e}", scale, err);
+        assert!(err < TOL);
+    }
+
+    // between the last Padé threshold and THETA_13, no scaling
+    for &x in &[2.1, 2.2, 2.5, 2.7] {
+        let e = Mat::new(1, 1).set_all(x).expm();
+        println!("{}: e = {}", x, e[(0, 0)]);
+        assert!((e[(0, 0)] / FP::exp(x) - 1.).abs() < TOL);
     }
+    let theta: FP = 2.4;
+    let g = Mat::new(2, 2).set_iter(&[
+        0., -theta,
+        theta, 0.
+    ]);
+    let rot = Mat::new(2, 2).set_iter(&[
+        theta.cos(), -theta.sin(),
+        theta.sin(), theta.cos()
+    ]);
+    assert!((g.expm() - rot).norm_inf() < TOL * 10.);
+
+    // exp(A) exp(-A) = I with scaling
+    let a = Mat::new(4, 4).set_randn(&mut rng) * 3.;
+    let eye = Mat::new(4, 4).set_eye();
+    assert!((a.expm() * (-&a).expm() - &eye).norm_inf() < 1e-8);
+
+    // zero and nilpotent
+    assert_eq!(Mat::new(3, 3).expm(), Mat::new(3, 3).set_eye());
+    let nil = Mat::new(3, 3).set_iter(&[
+        0., 1., 2.,
+        0., 0., 3.,
+        0., 0., 0.
+    ]);
+    let e0 = Mat::new(3, 3).set_iter(&[
+        1., 1., 3.5,
+        0., 1., 3.,
+        0., 0., 1.
+    ]);
+    assert!((nil.expm() - &e0).norm_inf() < TOL);
+    let e0 = Mat::new(3, 3).set_eye() + &nil * 10. + &nil * &nil * 50.;
+    assert!(((&nil * 10.).expm() - e0).norm_inf() < TOL * 100.);
+
+    assert_eq!(Mat::new(0, 0).expm(), Mat::new(0, 0));
+    assert!(Mat::new(2, 2).set_all(FP::NAN).expm().iter().all(|x| x.is_nan()));
+    let r = std::panic::catch_unwind(|| Mat::new(2, 3).expm());
+    assert_eq!(*r.unwrap_err().downcast::<String>().unwrap(), "expm: size 2x3 is not square");
 }
 
 #[test]
@@ -1173,3 +6266,93 @@ fn test_ops()
         println!("{}", a1);
     }
 }
+
+#[test]
+fn test_row_major()
+{
+    let mut r = XOR64_INIT;
+    let a_col = Mat::new(3, 4).set_by(|_, _| xor64(&mut r));
+    let b_col = Mat::new(4, 2).set_by(|_, _| xor64(&mut r));
+
+    let mut a_row = Mat::new_row_major(3, 4);
+    a_row.assign(&a_col);
+    assert_eq!(a_row.size(), (3, 4));
+    assert_eq!(a_row.layout(), Layout::RowMajor);
+    assert_eq!(a_col.layout(), Layout::ColMajor);
+    assert_eq!(a_row, a_col);
+
+    let v: Vec<FP> = (0 .. 8).map(|i| b_col[(i / 2, i % 2)]).collect();
+    let b_row = Mat::from_row_major_vec_noswap(4, 2, v);
+    assert_eq!(b_row.size(), (4, 2));
+    assert_eq!(b_row, b_col);
+
+    // operators across mixed layouts
+    let exp = &a_col * &b_col;
+    assert_eq!(&a_row * &b_col, exp);
+    assert_eq!(&a_col * &b_row, exp);
+    assert_eq!(&a_row * &b_row, exp);
+    assert_eq!(b_row.t() * a_row.t(), exp.t());
+    assert_eq!(&a_row + &a_col, 2. * &a_col);
+    assert_eq!(a_row.clone().layout(), Layout::RowMajor);
+
+    // slices and set methods
+    assert_eq!(a_row.slice(1 ..= 2, 1 .. 4), a_col.slice(1 ..= 2, 1 .. 4));
+    assert_eq!(a_row.col(3), a_col.col(3));
+    let c = Mat::new_row_major(2, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.
+    ]);
+    assert_eq!(c, Mat::from_row_major_vec_noswap(2, 3, vec![1., 2., 3., 4., 5., 6.]));
+    assert_eq!(c, Mat::new(2, 3).set_by(|r, c| (r * 3 + c + 1) as FP));
+    let mut d = Mat::new_row_major(4, 4).set_eye();
+    d.slice_mut(1 ..= 2, 0 ..= 1).assign(&c.cols(1 ..= 2));
+    assert_eq!(d[(1, 0)], 2.);
+    assert_eq!(d[(2, 1)], 6.);
+    assert_eq!(d[(3, 3)], 1.);
+}
+
+#[test]
+fn test_compensated()
+{
+    // reference in double-double arithmetic by error-free transformations
+    fn dd_sum(v: &[FP]) -> FP
+    {
+        let (mut hi, mut lo): (FP, FP) = (0., 0.);
+        for &x in v {
+            let s = hi + x;
+            let bb = s - hi;
+            let e = (hi - (s - bb)) + (x - bb);
+            hi = s;
+            lo += e;
+        }
+        hi + lo
+    }
+
+    let mut v = vec![1e8];
+    v.extend(std::iter::repeat_n(1e-8, 10000));
+    v.push(-1e8);
+    let n = v.len();
+    let a = Mat::new_vec(n).set_iter(&v);
+    let ones = Mat::new_vec(n).set_all(1.);
+
+    let exact = dd_sum(&v);
+    let naive = a.prod(&ones);
+    let comp = a.prod_compensated(&ones);
+    println!("exact = {:e}, naive = {:e}, compensated = {:e}", exact, naive, comp);
+    assert!((naive - exact).abs() > 1e-2 * exact);
+    assert!((comp - exact).abs() <= 1e-12 * exact);
+
+    // products are compensated as well
+    let x = Mat::new_vec(3).set_iter(&[1. + FP_EPSILON, 1., 1.]);
+    let y = Mat::new_vec(3).set_iter(&[1. - FP_EPSILON, -1., 0.]);
+    assert_eq!(x.prod(&y), 0.);
+    assert_eq!(x.prod_compensated(&y), -FP_EPSILON * FP_EPSILON);
+
+    // squares of 1e8 and many 1e-8
+    let sq: Vec<FP> = v.iter().map(|x| x * x).collect();
+    let exact = FP::sqrt(dd_sum(&sq));
+    assert!((a.norm_p2_accurate() - exact).abs() <= 1e-15 * exact);
+    assert!((Mat::new_vec(2).set_iter(&[3e200, 4e200]).norm_p2_accurate() / 5e200 - 1.).abs() < 4. * FP_EPSILON);
+    assert!(FP::sqrt(Mat::new_vec(2).set_iter(&[3e200, 4e200]).norm_p2sq()).is_infinite());
+    assert_eq!(Mat::new_vec(3).norm_p2_accurate(), 0.);
+}
@@ -0,0 +1,94 @@
+//! Conversions between [`Mat`] and `nalgebra` dynamically sized matrices and vectors.
+//!
+//! Both are column-major, so the elements are copied as a single slice
+//! unless the source is a transposed or strided view.
+
+use super::{Mat, MatGen, View, FP};
+use nalgebra::{DMatrix, DVector};
+
+impl From<&DMatrix<FP>> for Mat
+{
+    fn from(m: &DMatrix<FP>) -> Mat
+    {
+        let (nrows, ncols) = m.shape();
+
+        Mat::from_vec(nrows, ncols, m.as_slice().to_vec())
+    }
+}
+
+impl<V: View> From<&MatGen<V>> for DMatrix<FP>
+{
+    fn from(m: &MatGen<V>) -> DMatrix<FP>
+    {
+        let (nrows, ncols) = m.size();
+
+        match m.h_col_major() {
+            Some(s) => DMatrix::from_column_slice(nrows, ncols, s),
+            None => DMatrix::from_fn(nrows, ncols, |r, c| m[(r, c)])
+        }
+    }
+}
+
+impl From<&DVector<FP>> for Mat
+{
+    /// Makes a column vector.
+    fn from(v: &DVector<FP>) -> Mat
+    {
+        Mat::from_vec(v.len(), 1, v.as_slice().to_vec())
+    }
+}
+
+impl<V: View> From<&MatGen<V>> for DVector<FP>
+{
+    /// Panics if `m` is not a column vector.
+    fn from(m: &MatGen<V>) -> DVector<FP>
+    {
+        let (nrows, ncols) = m.size();
+        assert_eq!(ncols, 1);
+
+        match m.h_col_major() {
+            Some(s) => DVector::from_column_slice(s),
+            None => DVector::from_fn(nrows, |r, _| m[(r, 0)])
+        }
+    }
+}
+
+#[test]
+fn test_nalgebra()
+{
+    let a = Mat::new(2, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.
+    ]);
+
+    let na = DMatrix::from(&a);
+    assert_eq!(na, DMatrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.]));
+    assert_eq!(Mat::from(&na), a);
+
+    let na_t = DMatrix::from(&a.t());
+    assert_eq!(na_t, na.transpose());
+    assert_eq!(Mat::from(&na_t), a.t());
+
+    let na_c = DMatrix::from(&a.cols(1 ..));
+    assert_eq!(Mat::from(&na_c), a.cols(1 ..));
+
+    let na_r = DMatrix::from(&a.rows(1 ..));
+    assert_eq!(Mat::from(&na_r), a.rows(1 ..));
+
+    let na_s = DMatrix::from(&a.cols_step(.., 2));
+    assert_eq!(Mat::from(&na_s), a.cols_step(.., 2));
+
+    let e = DMatrix::<FP>::zeros(0, 3);
+    assert_eq!(Mat::from(&e).size(), (0, 3));
+    assert_eq!(DMatrix::from(&Mat::new(0, 3)), e);
+
+    let v = Mat::new_vec(3).set_iter(&[1., 2., 3.]);
+    let nv = DVector::from(&v);
+    assert_eq!(nv, DVector::from_column_slice(&[1., 2., 3.]));
+    assert_eq!(Mat::from(&nv), v);
+
+    let nv_r = DVector::from(&a.row(1).t());
+    assert_eq!(Mat::from(&nv_r), a.row(1).t());
+
+    assert!(std::panic::catch_unwind(|| DVector::from(&a)).is_err());
+}
@@ -0,0 +1,230 @@
+//! Conversions between [`Mat`] and `ndarray` two- and one-dimensional arrays.
+//!
+//! A contiguous array view, either column-major or row-major, is borrowed as a [`MatSlice`]
+//! through [`MatCow`], since a row-major layout is just a transposed column-major one.
+//! Conversely a matrix is borrowed as an `ArrayView2` with its stride as it is, inverted axes if flipped.
+
+use super::{Mat, MatGen, MatSlice, View, FP};
+use ndarray::{Array1, Array2, ArrayBase, ArrayView2, Axis, Data, Ix1, Ix2, ShapeBuilder};
+
+/// Matrix borrowed from an array view if possible, otherwise copied
+#[derive(Debug)]
+pub enum MatCow<'a>
+{
+    /// Borrowing a contiguous array view.
+    Borrowed(MatSlice<'a>),
+    /// Copied from a non-contiguous array view.
+    Owned(Mat)
+}
+
+impl<'a> MatCow<'a>
+{
+    /// *slice* - Reference whichever borrowed or copied.
+    pub fn as_slice(&self) -> MatSlice<'_>
+    {
+        match self {
+            MatCow::Borrowed(m) => m.as_slice(),
+            MatCow::Owned(m) => m.as_slice()
+        }
+    }
+    /// Converts into an owned matrix, copying if borrowed.
+    pub fn into_owned(self) -> Mat
+    {
+        match self {
+            MatCow::Borrowed(m) => m.clone_sz(),
+            MatCow::Owned(m) => m
+        }
+    }
+    /// Returns `true` if borrowing without copy.
+    pub fn is_borrowed(&self) -> bool
+    {
+        matches!(self, MatCow::Borrowed(_))
+    }
+}
+
+impl<'a> From<ArrayView2<'a, FP>> for MatCow<'a>
+{
+    fn from(a: ArrayView2<'a, FP>) -> MatCow<'a>
+    {
+        let (nrows, ncols) = a.dim();
+
+        if let Some(s) = a.reversed_axes().to_slice() {
+            // column-major
+            MatCow::Borrowed(MatGen {
+                nrows,
+                ncols,
+                offset: 0,
+                stride: nrows as isize,
+                rstride: 1,
+                transposed: false,
+                view: s
+            })
+        }
+        else if let Some(s) = a.to_slice() {
+            // row-major
+            MatCow::Borrowed(MatGen {
+                nrows: ncols,
+                ncols: nrows,
+                offset: 0,
+                stride: ncols as isize,
+                rstride: 1,
+                transposed: true,
+                view: s
+            })
+        }
+        else {
+            MatCow::Owned(Mat::new(nrows, ncols).set_by(|r, c| a[(r, c)]))
+        }
+    }
+}
+
+impl<S: Data<Elem=FP>> From<&ArrayBase<S, Ix2>> for Mat
+{
+    fn from(a: &ArrayBase<S, Ix2>) -> Mat
+    {
+        MatCow::from(a.view()).into_owned()
+    }
+}
+
+impl From<Array2<FP>> for Mat
+{
+    /// Takes over the buffer without copying if `a` is column-major and contiguous.
+    fn from(a: Array2<FP>) -> Mat
+    {
+        let (nrows, ncols) = a.dim();
+
+        if a.t().is_standard_layout() {
+            let (vec, offset) = a.into_raw_vec_and_offset();
+            let offset = offset.unwrap_or(0);
+
+            if (offset == 0) && (vec.len() == nrows * ncols) {
+                Mat::from_vec(nrows, ncols, vec)
+            }
+            else {
+                Mat::from_vec(nrows, ncols, vec[offset .. offset + nrows * ncols].to_vec())
+            }
+        }
+        else {
+            Mat::from(&a)
+        }
+    }
+}
+
+impl<'a, V: View> From<&'a MatGen<V>> for ArrayView2<'a, FP>
+{
+    fn from(m: &'a MatGen<V>) -> ArrayView2<'a, FP>
+    {
+        let (nrows, ncols) = m.size();
+        let (rs, cs) = if !m.transposed {(m.rstride, m.stride)} else {(m.stride, m.rstride)};
+
+        // borrowed from the lowest element with absolute strides, then flipped axes are inverted
+        let base = if nrows * ncols == 0 {
+            m.offset
+        }
+        else {
+            (m.offset as isize + rs.min(0) * (nrows - 1) as isize + cs.min(0) * (ncols - 1) as isize) as usize
+        };
+        let strides = (rs.unsigned_abs(), cs.unsigned_abs());
+
+        let mut a = ArrayView2::from_shape((nrows, ncols).strides(strides), &m.view.get_ref()[base ..])
+            .expect("matrix elements must be within the buffer");
+        if rs < 0 {
+            a.invert_axis(Axis(0));
+        }
+        if cs < 0 {
+            a.invert_axis(Axis(1));
+        }
+        a
+    }
+}
+
+impl<V: View> From<&MatGen<V>> for Array2<FP>
+{
+    fn from(m: &MatGen<V>) -> Array2<FP>
+    {
+        ArrayView2::from(m).to_owned()
+    }
+}
+
+impl<S: Data<Elem=FP>> From<&ArrayBase<S, Ix1>> for Mat
+{
+    /// Makes a column vector.
+    fn from(a: &ArrayBase<S, Ix1>) -> Mat
+    {
+        Mat::from_vec(a.len(), 1, a.to_vec())
+    }
+}
+
+impl<V: View> From<&MatGen<V>> for Array1<FP>
+{
+    /// Panics if `m` is not a column vector.
+    fn from(m: &MatGen<V>) -> Array1<FP>
+    {
+        let (nrows, ncols) = m.size();
+        assert_eq!(ncols, 1);
+
+        match m.h_col_major() {
+            Some(s) => Array1::from(s.to_vec()),
+            None => Array1::from_shape_fn(nrows, |r| m[(r, 0)])
+        }
+    }
+}
+
+#[test]
+fn test_ndarray()
+{
+    use ndarray::{array, s};
+
+    let a = Mat::new(2, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.
+    ]);
+    let nd = array![[1., 2., 3.], [4., 5., 6.]];
+
+    assert_eq!(Array2::from(&a), nd);
+    assert_eq!(Array2::from(&a.t()), nd.t());
+    assert_eq!(Array2::from(&a.rows(1 ..)), nd.slice(s![1 .., ..]));
+    assert_eq!(ArrayView2::from(&a.cols(1 ..)), nd.slice(s![.., 1 ..]));
+    assert_eq!(ArrayView2::from(&a.cols_step(.., 2)), nd.slice(s![.., ..;2]));
+    assert_eq!(ArrayView2::from(&a.t().rows_step(.., 2)), nd.slice(s![.., ..;2]).t());
+    assert_eq!(ArrayView2::from(&a.flip_rows()), nd.slice(s![..;-1, ..]));
+    assert_eq!(ArrayView2::from(&a.t().flip_rows().cols(1 ..)), nd.slice(s![1.., ..;-1]).t());
+    assert_eq!(Mat::from(&nd), a);
+    assert_eq!(Mat::from(&nd.t()), a.t());
+
+    // row-major and column-major views are borrowed
+    let c = MatCow::from(nd.view());
+    assert!(c.is_borrowed());
+    assert_eq!(c.as_slice(), a);
+    let nd_f = Array2::from(&a.t()).reversed_axes();
+    assert!(!nd_f.is_standard_layout());
+    let c = MatCow::from(nd_f.view());
+    assert!(c.is_borrowed());
+    assert_eq!(c.as_slice(), a);
+    assert_eq!(c.into_owned(), a);
+
+    // strided views are copied
+    let c = MatCow::from(nd.slice(s![.., ..;2]));
+    assert!(!c.is_borrowed());
+    assert_eq!(c.as_slice(), Mat::new(2, 2).set_iter(&[1., 3., 4., 6.]));
+
+    // column-major owned arrays are taken over
+    let ptr = nd_f.as_ptr();
+    let m = Mat::from(nd_f);
+    assert_eq!(m, a);
+    let vec = m.into_vec();
+    assert_eq!(vec.as_ptr(), ptr);
+
+    let e = Array2::<FP>::zeros((0, 3));
+    assert_eq!(Mat::from(e).size(), (0, 3));
+    assert_eq!(Array2::from(&Mat::new(0, 3)).dim(), (0, 3));
+
+    let v = Mat::new_vec(3).set_iter(&[1., 2., 3.]);
+    let nv = array![1., 2., 3.];
+    assert_eq!(Array1::from(&v), nv);
+    assert_eq!(Array1::from(&a.row(1).t()), array![4., 5., 6.]);
+    assert_eq!(Mat::from(&nv), v);
+    assert_eq!(Mat::from(&nd.column(2)), a.col(2));
+
+    assert!(std::panic::catch_unwind(|| Array1::from(&a)).is_err());
+}
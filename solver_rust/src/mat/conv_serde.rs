@@ -0,0 +1,84 @@
+//! `serde` support for [`Mat`].
+//!
+//! A matrix is serialized as a struct of `nrows`, `ncols` and `data`,
+//! where `data` has the `nrows * ncols` elements in column-major order.
+//! Offset, stride and transposition are normalized away, so the same values serialize identically.
+
+use super::{Mat, FP};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::SerializeStruct;
+use serde::de::Error;
+use std::convert::TryFrom;
+
+impl Serialize for Mat
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let (nrows, ncols) = self.size();
+
+        let mut st = serializer.serialize_struct("Mat", 3)?;
+        st.serialize_field("nrows", &nrows)?;
+        st.serialize_field("ncols", &ncols)?;
+        match self.h_col_major() {
+            Some(s) => st.serialize_field("data", s)?,
+            None => st.serialize_field("data", &self.clone_sz().into_vec())?
+        }
+        st.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Mat")]
+struct MatData
+{
+    nrows: usize,
+    ncols: usize,
+    data: Vec<FP>
+}
+
+impl<'de> Deserialize<'de> for Mat
+{
+    /// Fails if the length of `data` does not match `nrows * ncols`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Mat, D::Error>
+    {
+        let d = MatData::deserialize(deserializer)?;
+
+        Mat::try_from((d.nrows, d.ncols, d.data)).map_err(D::Error::custom)
+    }
+}
+
+#[test]
+fn test_serde()
+{
+    let sq = Mat::new(2, 2).set_iter(&[
+        1., 2.,
+        3., 4.
+    ]);
+    let rect = Mat::new(2, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.
+    ]);
+
+    let json = serde_json::to_string(&sq).unwrap();
+    assert_eq!(json, r#"{"nrows":2,"ncols":2,"data":[1.0,3.0,2.0,4.0]}"#);
+
+    for a in &[sq.clone(), rect.clone(), rect.clone().set_t(), rect.rows(1 ..).clone_sz(), Mat::new(0, 3)] {
+        let json = serde_json::to_string(a).unwrap();
+        let b: Mat = serde_json::from_str(&json).unwrap();
+        assert_eq!(&b, a);
+        assert_eq!(serde_json::to_string(&b).unwrap(), json);
+
+        let bin = bincode::serialize(a).unwrap();
+        let b: Mat = bincode::deserialize(&bin).unwrap();
+        assert_eq!(&b, a);
+    }
+
+    // transposed one is canonicalized
+    let rect_t = rect.clone().set_t();
+    let rect_t_own = Mat::new(3, 2).set_by(|r, c| rect[(c, r)]);
+    assert_eq!(serde_json::to_string(&rect_t).unwrap(), serde_json::to_string(&rect_t_own).unwrap());
+
+    let e = serde_json::from_str::<Mat>(r#"{"nrows":2,"ncols":2,"data":[1.0,3.0,2.0]}"#).unwrap_err();
+    assert!(e.to_string().contains("length 3 does not match 2 x 2"));
+    assert!(serde_json::from_str::<Mat>(r#"{"nrows":2,"ncols":2}"#).is_err());
+}
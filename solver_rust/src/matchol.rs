@@ -0,0 +1,340 @@
+//! Matrix Cholesky decomposition
+
+use super::mat::{Mat, MatGen, View, FP};
+use std::fmt;
+
+/// Error of a matrix which is not positive definite
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotPositiveDefinite
+{
+    /// Row at which the pivot is found non-positive.
+    pub row: usize,
+    /// Value of the pivot.
+    pub value: FP
+}
+
+impl fmt::Display for NotPositiveDefinite
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        write!(f, "not positive definite: pivot {:e} at row {}", self.value, self.row)
+    }
+}
+
+impl std::error::Error for NotPositiveDefinite {}
+
+/// Matrix Cholesky decomposition
+///
+/// \\(A = LL^T\\) of a symmetric positive definite matrix,
+/// where \\(L\\) is lower triangular with a positive diagonal.
+#[derive(Debug)]
+pub struct MatChol
+{
+    l: Mat
+}
+
+impl MatChol
+{
+    /// Factorizes a symmetric positive definite matrix `a`.
+    ///
+    /// Only the lower triangle of `a` is read, so the upper one may be anything.
+    /// Returns `Err` if a pivot is non-positive, which tells that `a` is not positive definite.
+    pub fn new<V: View>(a: &MatGen<V>) -> Result<MatChol, NotPositiveDefinite>
+    {
+        let (n, ncols) = a.size();
+        assert_eq!(n, ncols, "MatChol: matrix must be square");
+
+        let mut l = Mat::new(n, n);
+
+        for j in 0 .. n {
+            let mut d = a[(j, j)];
+            for k in 0 .. j {
+                d -= l[(j, k)] * l[(j, k)];
+            }
+            if d.is_nan() || d <= 0. {
+                return Err(NotPositiveDefinite {row: j, value: d});
+            }
+            let l_jj = d.sqrt();
+            l[(j, j)] = l_jj;
+
+            for i in j + 1 .. n {
+                let mut s = a[(i, j)];
+                for k in 0 .. j {
+                    s -= l[(i, k)] * l[(j, k)];
+                }
+                l[(i, j)] = s / l_jj;
+            }
+        }
+
+        Ok(MatChol {l})
+    }
+
+    /// Lower triangular factor \\(L\\).
+    pub fn l(&self) -> &Mat
+    {
+        &self.l
+    }
+
+    /// Solves \\(AX = B\\) for each column of `b`.
+    pub fn solve<V: View>(&self, b: &MatGen<V>) -> Mat
+    {
+        let n = self.l.size().0;
+        let (b_nrows, b_ncols) = b.size();
+        assert_eq!(b_nrows, n, "MatChol: size {}x{} does not match {} rows", b_nrows, b_ncols, n);
+
+        let mut x = b.clone_sz();
+
+        for c in 0 .. b_ncols {
+            // forward substitution with L
+            for i in 0 .. n {
+                let mut s = x[(i, c)];
+                for j in 0 .. i {
+                    s -= self.l[(i, j)] * x[(j, c)];
+                }
+                x[(i, c)] = s / self.l[(i, i)];
+            }
+            // backward substitution with L^T
+            for i in (0 .. n).rev() {
+                let mut s = x[(i, c)];
+                for j in i + 1 .. n {
+                    s -= self.l[(j, i)] * x[(j, c)];
+                }
+                x[(i, c)] = s / self.l[(i, i)];
+            }
+        }
+
+        x
+    }
+
+    /// Determinant of \\(A\\).
+    pub fn det(&self) -> FP
+    {
+        let d = self.l.diag().iter().product::<FP>();
+
+        d * d
+    }
+
+    /// Updates the factorization into that of \\(A + vv^T\\) in \\(O(n^2)\\) by rotations.
+    pub fn update<V: View>(&mut self, v: &MatGen<V>)
+    {
+        let n = self.l.size().0;
+        assert_eq!(v.size(), (n, 1), "MatChol: update by a non-vector or a wrong length");
+
+        let mut x = v.clone_sz();
+
+        for k in 0 .. n {
+            let l_kk = self.l[(k, k)];
+            let r = l_kk.hypot(x[(k, 0)]);
+            let (c, s) = (r / l_kk, x[(k, 0)] / l_kk);
+            self.l[(k, k)] = r;
+
+            for i in k + 1 .. n {
+                let l_ik = (self.l[(i, k)] + s * x[(i, 0)]) / c;
+                self.l[(i, k)] = l_ik;
+                x[(i, 0)] = c * x[(i, 0)] - s * l_ik;
+            }
+        }
+    }
+
+    /// Downdates the factorization into that of \\(A - vv^T\\) in \\(O(n^2)\\) by hyperbolic rotations.
+    ///
+    /// Returns `Err` if \\(A - vv^T\\) is not positive definite, leaving the factorization unchanged.
+    pub fn downdate<V: View>(&mut self, v: &MatGen<V>) -> Result<(), NotPositiveDefinite>
+    {
+        let n = self.l.size().0;
+        assert_eq!(v.size(), (n, 1), "MatChol: downdate by a non-vector or a wrong length");
+
+        let mut l = self.l.clone_sz();
+        let mut x = v.clone_sz();
+
+        for k in 0 .. n {
+            let l_kk = l[(k, k)];
+            let d = (l_kk - x[(k, 0)]) * (l_kk + x[(k, 0)]);
+            if d.is_nan() || d <= 0. {
+                return Err(NotPositiveDefinite {row: k, value: d});
+            }
+            let r = d.sqrt();
+            let (c, s) = (r / l_kk, x[(k, 0)] / l_kk);
+            l[(k, k)] = r;
+
+            for i in k + 1 .. n {
+                let l_ik = (l[(i, k)] - s * x[(i, 0)]) / c;
+                l[(i, k)] = l_ik;
+                x[(i, 0)] = c * x[(i, 0)] - s * l_ik;
+            }
+        }
+
+        self.l = l;
+        Ok(())
+    }
+
+    /// Inverse of the lower triangular factor, \\(L^{-1}\\), which is lower triangular as well.
+    pub fn inv_lower(&self) -> Mat
+    {
+        let n = self.l.size().0;
+        let mut inv = Mat::new(n, n);
+
+        for c in 0 .. n {
+            inv[(c, c)] = 1. / self.l[(c, c)];
+            for i in c + 1 .. n {
+                let mut s = 0.;
+                for j in c .. i {
+                    s -= self.l[(i, j)] * inv[(j, c)];
+                }
+                inv[(i, c)] = s / self.l[(i, i)];
+            }
+        }
+
+        inv
+    }
+}
+
+//
+
+#[cfg(test)]
+use super::mat::{xor64, XOR64_INIT};
+
+#[test]
+fn test_decomp()
+{
+    const TOL: FP = 1e-12;
+
+    let a = Mat::new(3, 3).set_iter(&[
+        4., 12., -16.,
+        12., 37., -43.,
+        -16., -43., 98.
+    ]);
+
+    let chol = MatChol::new(&a).unwrap();
+    println!("l = {}", chol.l());
+
+    let l = Mat::new(3, 3).set_iter(&[
+        2., 0., 0.,
+        6., 1., 0.,
+        -8., 5., 3.
+    ]);
+    assert!((chol.l() - &l).norm_inf() < TOL);
+    assert!((chol.det() - 36.).abs() < TOL * 36.);
+
+    let b = Mat::new(3, 2).set_iter(&[
+        1., 0.,
+        2., 1.,
+        3., 0.
+    ]);
+    let x = chol.solve(&b);
+    assert!((&a * &x - &b).norm_inf() < TOL * 1e3);
+
+    let mut eye = Mat::new(3, 3);
+    eye.assign_eye();
+    assert!((chol.inv_lower() * &l - &eye).norm_inf() < TOL);
+    assert_eq!(chol.inv_lower().triu(1), Mat::new(3, 3));
+
+    // the upper triangle is not read
+    let g = a.clone().set_by(|r, c| if c > r {FP::NAN} else {a[(r, c)]});
+    let chol_g = MatChol::new(&g).unwrap();
+    assert_eq!(chol_g.l(), chol.l());
+}
+
+#[test]
+fn test_random()
+{
+    const TOL: FP = 1e-9;
+
+    let mut r = XOR64_INIT;
+    let n = 20;
+
+    let b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let a = b.t() * &b + Mat::new(n, n).set_eye();
+    let y = Mat::new(n, 3).set_by(|_, _| xor64(&mut r) - 0.5);
+
+    let chol = MatChol::new(&a).unwrap();
+    let x = chol.solve(&y);
+
+    let err = (&a * &x - &y).norm_inf();
+    println!("err = {:e}", err);
+    assert!(err < TOL);
+
+    let l = chol.l();
+    assert!((l * l.t() - &a).norm_inf() < TOL);
+}
+
+#[test]
+fn test_not_pd()
+{
+    let a = Mat::new(3, 3).set_iter(&[
+        1., 2., 0.,
+        2., 1., 0.,
+        0., 0., 1.
+    ]);
+    let e = MatChol::new(&a).unwrap_err();
+    println!("{}", e);
+    assert_eq!(e, NotPositiveDefinite {row: 1, value: -3.});
+
+    let a = Mat::new(2, 2).set_iter(&[
+        1., 0.,
+        0., 0.
+    ]);
+    assert_eq!(MatChol::new(&a).unwrap_err().row, 1);
+
+    let chol = MatChol::new(&Mat::new(0, 0)).unwrap();
+    assert_eq!(chol.det(), 1.);
+}
+
+#[test]
+fn test_update()
+{
+    const TOL: FP = 1e-9;
+
+    let mut r = XOR64_INIT;
+    let n = 12;
+
+    for _ in 0 .. 5 {
+        let b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let a = b.t() * &b + Mat::new(n, n).set_eye();
+        let v = Mat::new_vec(n).set_by(|_, _| xor64(&mut r) - 0.5);
+
+        let mut chol = MatChol::new(&a).unwrap();
+
+        let a_up = &a + &v * v.t();
+        chol.update(&v);
+        let err = (chol.l() - MatChol::new(&a_up).unwrap().l()).norm_inf();
+        println!("update err = {:e}", err);
+        assert!(err < TOL);
+
+        chol.downdate(&v).unwrap();
+        let err = (chol.l() - MatChol::new(&a).unwrap().l()).norm_inf();
+        println!("downdate err = {:e}", err);
+        assert!(err < TOL);
+    }
+}
+
+#[test]
+fn test_downdate_not_pd()
+{
+    let a = Mat::new(2, 2).set_iter(&[
+        4., 2.,
+        2., 2.
+    ]);
+    let mut chol = MatChol::new(&a).unwrap();
+    let l = chol.l().clone();
+
+    // a - v v^T is singular
+    let v = Mat::new_vec(2).set_iter(&[2., 1.]);
+    let e = chol.downdate(&v).unwrap_err();
+    println!("{}", e);
+    assert_eq!(e.row, 0);
+    assert_eq!(chol.l(), &l);
+
+    // a - v v^T has a negative eigenvalue
+    let v = Mat::new_vec(2).set_iter(&[1., 2.]);
+    let e = chol.downdate(&v).unwrap_err();
+    assert_eq!(e.row, 1);
+    assert!(e.value < 0.);
+    assert_eq!(chol.l(), &l);
+    assert!(MatChol::new(&(&a - &v * v.t())).is_err());
+
+    let v = Mat::new_vec(2).set_iter(&[1., 0.]);
+    chol.downdate(&v).unwrap();
+    assert!(chol.l().iter().all(|x| x.is_finite()));
+    assert!((chol.det() - 2.).abs() < 1e-12);
+}
@@ -0,0 +1,250 @@
+//! Matrix eigenvalue decomposition
+
+use super::mat::{Mat, MatGen, View, FP, FP_EPSILON};
+use std::fmt;
+
+const DEFAULT_TOL: FP = FP_EPSILON;
+const DEFAULT_MAX_SWEEPS: usize = 50;
+
+/// Error of an iterative eigensolver which does not converge
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotConverged
+{
+    /// Number of iterations done.
+    pub iterations: usize,
+    /// Residual achieved at the last iteration.
+    pub residual: FP
+}
+
+impl fmt::Display for NotConverged
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        write!(f, "not converged: residual {:e} after {} iterations", self.residual, self.iterations)
+    }
+}
+
+impl std::error::Error for NotConverged {}
+
+/// Error of a symmetric matrix which is not positive semidefinite
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotPsd
+{
+    /// Smallest eigenvalue, negative beyond a tolerance, or NaN if the decomposition does not converge.
+    pub value: FP
+}
+
+impl fmt::Display for NotPsd
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        write!(f, "not positive semidefinite: eigenvalue {:e}", self.value)
+    }
+}
+
+impl std::error::Error for NotPsd {}
+
+/// Eigenpair found by an iterative eigensolver
+#[derive(Debug, Clone, PartialEq)]
+pub struct EigPair
+{
+    /// Eigenvalue \\(\lambda\\), the Rayleigh quotient of `vector`.
+    pub value: FP,
+    /// Eigenvector \\(v\\) of unit norm.
+    pub vector: Mat,
+    /// Residual \\(\|Av - \lambda v\|_2\\).
+    pub residual: FP,
+    /// Number of iterations done.
+    pub iterations: usize
+}
+
+/// Matrix eigenvalue decomposition
+///
+/// \\(A = V {\rm diag}(\lambda) V^T\\) of a symmetric matrix by cyclic Jacobi rotations,
+/// where \\(V\\) is orthogonal and \\(\lambda\\) is sorted in descending order.
+#[derive(Debug)]
+pub struct MatEig
+{
+    vals: Mat,
+    vecs: Mat
+}
+
+impl MatEig
+{
+    /// Decomposes a symmetric matrix `a` with the default tolerance \\(\epsilon\\) and at most 50 sweeps.
+    ///
+    /// See [`new_sym_with`](#method.new_sym_with).
+    pub fn new_sym<V: View>(a: &MatGen<V>) -> Result<MatEig, NotConverged>
+    {
+        MatEig::new_sym_with(a, DEFAULT_TOL, DEFAULT_MAX_SWEEPS)
+    }
+
+    /// Decomposes a symmetric matrix `a`.
+    ///
+    /// Only the lower triangle of `a` is read, so the upper one may be anything.
+    /// Sweeps of rotations over all off-diagonal pairs are repeated until the Frobenius norm of
+    /// the off-diagonal part is within `tol` times that of `a`.
+    /// Returns `Err` with the relative off-diagonal norm as the residual if not within `max_sweeps`.
+    pub fn new_sym_with<V: View>(a: &MatGen<V>, tol: FP, max_sweeps: usize) -> Result<MatEig, NotConverged>
+    {
+        let (n, ncols) = a.size();
+        assert_eq!(n, ncols, "MatEig: matrix must be square");
+
+        let mut w = a.clone_sz();
+        for c in 0 .. n {
+            for r in 0 .. c {
+                w[(r, c)] = w[(c, r)];
+            }
+        }
+        let mut v = Mat::new(n, n).set_eye();
+
+        let norm = w.norm_p2();
+        let off_rel = |w: &Mat| {
+            let mut off = w.clone_sz();
+            off.diag_mut().assign_all(0.);
+            if norm == 0. {0.} else {off.norm_p2() / norm}
+        };
+
+        let mut sweeps = 0;
+        loop {
+            let off = off_rel(&w);
+            if off <= tol {
+                break;
+            }
+            if off.is_nan() || (sweeps >= max_sweeps) {
+                return Err(NotConverged {iterations: sweeps, residual: off});
+            }
+
+            for p in 0 .. n {
+                for q in p + 1 .. n {
+                    h_rotate(&mut w, &mut v, p, q);
+                }
+            }
+            sweeps += 1;
+        }
+
+        // descending order
+        let mut idx: Vec<usize> = (0 .. n).collect();
+        idx.sort_by(|&i, &j| w[(j, j)].total_cmp(&w[(i, i)]));
+
+        let vals = Mat::new_vec(n).set_by(|r, _| w[(idx[r], idx[r])]);
+        let vecs = v.select_cols(&idx);
+
+        Ok(MatEig {vals, vecs})
+    }
+
+    /// Eigenvalues in descending order, as a column vector.
+    pub fn eigvals(&self) -> &Mat
+    {
+        &self.vals
+    }
+
+    /// Orthogonal matrix of eigenvectors, whose columns correspond to [`eigvals`](#method.eigvals).
+    pub fn eigvecs(&self) -> &Mat
+    {
+        &self.vecs
+    }
+}
+
+// Jacobi rotation which zeroes w[(p, q)] and w[(q, p)], accumulated into v
+fn h_rotate(w: &mut Mat, v: &mut Mat, p: usize, q: usize)
+{
+    let a_pq = w[(p, q)];
+    if a_pq == 0. {
+        return;
+    }
+
+    let n = w.size().0;
+
+    let theta = (w[(q, q)] - w[(p, p)]) / (2. * a_pq);
+    let t = theta.signum() / (theta.abs() + theta.hypot(1.));
+    let c = 1. / t.hypot(1.);
+    let s = t * c;
+    let tau = s / (1. + c);
+
+    w[(p, p)] -= t * a_pq;
+    w[(q, q)] += t * a_pq;
+    w[(p, q)] = 0.;
+    w[(q, p)] = 0.;
+
+    for r in 0 .. n {
+        if (r != p) && (r != q) {
+            let (g, h) = (w[(r, p)], w[(r, q)]);
+            let (g, h) = (g - s * (h + g * tau), h + s * (g - h * tau));
+            w[(r, p)] = g;
+            w[(p, r)] = g;
+            w[(r, q)] = h;
+            w[(q, r)] = h;
+        }
+
+        let (g, h) = (v[(r, p)], v[(r, q)]);
+        v[(r, p)] = g - s * (h + g * tau);
+        v[(r, q)] = h + s * (g - h * tau);
+    }
+}
+
+//
+
+#[cfg(test)]
+use super::mat::{xor64, XOR64_INIT};
+
+#[test]
+fn test_decomp()
+{
+    const TOL: FP = 1e-12;
+
+    let a = Mat::new(3, 3).set_iter(&[
+        2., -1., 0.,
+        -1., 2., -1.,
+        0., -1., 2.
+    ]);
+
+    let eig = MatEig::new_sym(&a).unwrap();
+    println!("vals = {}", eig.eigvals());
+
+    let sq2 = FP::sqrt(2.);
+    let vals = Mat::new_vec(3).set_iter(&[2. + sq2, 2., 2. - sq2]);
+    assert!((eig.eigvals() - vals).norm_inf() < TOL);
+
+    // the upper triangle is not read
+    let g = a.clone().set_by(|r, c| if c > r {FP::NAN} else {a[(r, c)]});
+    assert_eq!(MatEig::new_sym(&g).unwrap().eigvals(), eig.eigvals());
+
+    assert_eq!(MatEig::new_sym(&Mat::new(0, 0)).unwrap().eigvals().size(), (0, 1));
+    let z = MatEig::new_sym(&Mat::new(2, 2)).unwrap();
+    assert_eq!(z.eigvals(), &Mat::new_vec(2));
+
+    assert!(MatEig::new_sym(&Mat::new(2, 2).set_all(FP::NAN)).unwrap_err().residual.is_nan());
+
+    let e = MatEig::new_sym_with(&a, 0., 1).unwrap_err();
+    println!("{}", e);
+    assert_eq!(e.iterations, 1);
+    assert!(e.residual > 0.);
+}
+
+#[test]
+fn test_random()
+{
+    const TOL: FP = 1e-12;
+
+    let mut r = XOR64_INIT;
+
+    for &n in &[1, 2, 10, 50] {
+        let b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let a = &b + b.t();
+
+        let eig = MatEig::new_sym(&a).unwrap();
+        let (vals, vecs) = (eig.eigvals(), eig.eigvecs());
+
+        let scale = a.norm_inf().max(1.);
+        let err = (&a * vecs - vecs * vals.clone_diag()).norm_inf() / scale;
+        println!("{}: err = {:e}", n, err);
+        assert!(err < TOL);
+
+        let mut eye = Mat::new(n, n);
+        eye.assign_eye();
+        assert!((vecs.t() * vecs - eye).norm_inf() < TOL);
+
+        assert!((1 .. n).all(|i| vals[(i - 1, 0)] >= vals[(i, 0)]));
+    }
+}
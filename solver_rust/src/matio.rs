@@ -0,0 +1,497 @@
+/*!
+Matrix input and output
+
+[`MatGen::to_bytes`](../mat/struct.MatGen.html#method.to_bytes) and
+[`Mat::from_bytes`](../mat/struct.MatGen.html#method.from_bytes) provide a compact binary codec:
+
+| bytes | content |
+|-------|---------|
+| 8 | magic `TOTSUMAT` |
+| 4 | version, little-endian `u32` |
+| 8 | number of rows, little-endian `u64` |
+| 8 | number of columns, little-endian `u64` |
+| 8 × rows × columns | elements in column-major order, little-endian `f64` |
+
+[`MatGen::write_csv`](../mat/struct.MatGen.html#method.write_csv) and
+[`Mat::read_csv`](../mat/struct.MatGen.html#method.read_csv) exchange a text of comma-separated values,
+a row of the matrix in each line.
+
+[`MatGen::write_npy`](../mat/struct.MatGen.html#method.write_npy) and
+[`Mat::read_npy`](../mat/struct.MatGen.html#method.read_npy) exchange a file of NumPy `.npy` format version 1.0
+with a little-endian `float64` array, which is one-dimensional for a column vector or two-dimensional.
+*/
+
+use super::mat::{Mat, MatGen, View, FP};
+
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::io::{self, Read, Write, BufRead, BufReader};
+use std::fs::File;
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"TOTSUMAT";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 8 + 4 + 8 + 8;
+
+const NPY_MAGIC: &[u8; 6] = b"\x93NUMPY";
+const NPY_PREAMBLE_LEN: usize = 6 + 2 + 2;
+const NPY_ALIGN: usize = 64;
+
+/// Error of matrix input and output
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatIOError
+{
+    /// Magic bytes do not match.
+    BadMagic,
+    /// Version is not supported.
+    BadVersion(u32),
+    /// Length of input does not match its header.
+    BadLength {
+        /// Expected number of bytes.
+        expected: usize,
+        /// Actual number of bytes.
+        actual: usize
+    },
+    /// Reading failed, with a message of the underlying I/O error.
+    Io(String),
+    /// A field cannot be parsed as a number.
+    Parse {
+        /// Line number, starting from 1.
+        line: usize,
+        /// Field which failed to parse.
+        field: String
+    },
+    /// A row has a different number of fields from the first row.
+    BadWidth {
+        /// Line number, starting from 1.
+        line: usize,
+        /// Number of fields of the first row.
+        expected: usize,
+        /// Number of fields of the row.
+        actual: usize
+    },
+    /// Header of `.npy` is malformed or not supported.
+    BadHeader(String),
+    /// Element type of `.npy` is other than little-endian `float64`.
+    BadDtype(String)
+}
+
+impl fmt::Display for MatIOError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        match self {
+            MatIOError::BadMagic => write!(f, "bad magic"),
+            MatIOError::BadVersion(v) => write!(f, "unsupported version {}", v),
+            MatIOError::BadLength {expected, actual} => write!(f, "length {} does not match expected {}", actual, expected),
+            MatIOError::Io(e) => write!(f, "I/O error: {}", e),
+            MatIOError::Parse {line, field} => write!(f, "line {}: cannot parse {:?}", line, field),
+            MatIOError::BadWidth {line, expected, actual} => write!(f, "line {}: {} fields, expected {}", line, actual, expected),
+            MatIOError::BadHeader(h) => write!(f, "bad npy header: {}", h),
+            MatIOError::BadDtype(d) => write!(f, "unsupported dtype {}, expected '<f8'", d)
+        }
+    }
+}
+
+impl std::error::Error for MatIOError {}
+
+impl<V: View> MatGen<V>
+{
+    /// Encodes into the binary format.
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let (nrows, ncols) = self.size();
+        let mut bytes = Vec::with_capacity(HEADER_LEN + nrows * ncols * 8);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(nrows as u64).to_le_bytes());
+        bytes.extend_from_slice(&(ncols as u64).to_le_bytes());
+
+        for c in 0 .. ncols {
+            for r in 0 .. nrows {
+                bytes.extend_from_slice(&self[(r, c)].to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+    /// Writes in CSV, a row in each line regardless of transposition.
+    ///
+    /// Elements are written in exponential form of the shortest digits that read back exactly.
+    pub fn write_csv<W: Write>(&self, w: W) -> io::Result<()>
+    {
+        self.h_write_csv(w, None)
+    }
+    /// Writes in CSV like [`write_csv`](#method.write_csv),
+    /// with elements in exponential form of `precision` digits after the decimal point.
+    pub fn write_csv_prec<W: Write>(&self, w: W, precision: usize) -> io::Result<()>
+    {
+        self.h_write_csv(w, Some(precision))
+    }
+    //
+    fn h_write_csv<W: Write>(&self, mut w: W, precision: Option<usize>) -> io::Result<()>
+    {
+        let (nrows, ncols) = self.size();
+
+        for r in 0 .. nrows {
+            for c in 0 .. ncols {
+                if c > 0 {
+                    write!(w, ",")?;
+                }
+                match precision {
+                    Some(p) => write!(w, "{:.*e}", p, self[(r, c)])?,
+                    None => write!(w, "{:e}", self[(r, c)])?
+                }
+            }
+            writeln!(w)?;
+        }
+
+        w.flush()
+    }
+    /// Writes a `.npy` file of Fortran order, a one-dimensional array if `self` is a column vector.
+    pub fn write_npy<P: AsRef<Path>>(&self, path: P) -> io::Result<()>
+    {
+        File::create(path)?.write_all(&npy_encode(self))
+    }
+}
+
+impl Mat
+{
+    /// *new* - Decodes from the binary format.
+    ///
+    /// Returns `Err` with [`MatIOError`](../matio/enum.MatIOError.html) for a malformed or truncated input.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Mat, MatIOError>
+    {
+        if bytes.len() < HEADER_LEN {
+            return Err(MatIOError::BadLength {expected: HEADER_LEN, actual: bytes.len()});
+        }
+        if &bytes[0 .. 8] != MAGIC {
+            return Err(MatIOError::BadMagic);
+        }
+
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i .. i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i .. i + 8].try_into().unwrap());
+
+        let version = u32_at(8);
+        if version != VERSION {
+            return Err(MatIOError::BadVersion(version));
+        }
+
+        let nrows = u64_at(12);
+        let ncols = u64_at(20);
+
+        let payload = nrows.checked_mul(ncols)
+                           .and_then(|len| len.checked_mul(8))
+                           .and_then(|len| len.checked_add(HEADER_LEN as u64))
+                           .and_then(|len| usize::try_from(len).ok());
+        match payload {
+            Some(expected) if expected == bytes.len() => {},
+            Some(expected) => return Err(MatIOError::BadLength {expected, actual: bytes.len()}),
+            None => return Err(MatIOError::BadLength {expected: usize::MAX, actual: bytes.len()})
+        }
+
+        let (nrows, ncols) = (nrows as usize, ncols as usize);
+        let mut chunks = bytes[HEADER_LEN ..].chunks_exact(8);
+
+        let mut mat = Mat::new(nrows, ncols);
+        for c in 0 .. ncols {
+            for r in 0 .. nrows {
+                mat[(r, c)] = FP::from_le_bytes(chunks.next().unwrap().try_into().unwrap());
+            }
+        }
+
+        Ok(mat)
+    }
+    /// *new* - Reads CSV, a row in each line.
+    ///
+    /// The number of columns is taken from the first row, and blank lines are skipped.
+    /// Fields may be surrounded by whitespace and written in either `1.5` or `1.5e-3` form.
+    /// Returns `Err` with [`MatIOError`](../matio/enum.MatIOError.html) for an unparsable field or a row of different width,
+    /// telling its line number.
+    pub fn read_csv<R: Read>(r: R) -> Result<Mat, MatIOError>
+    {
+        let mut elems = Vec::new();
+        let mut nrows = 0;
+        let mut ncols = 0;
+
+        for (i, line) in BufReader::new(r).lines().enumerate() {
+            let line = line.map_err(|e| MatIOError::Io(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let len = elems.len();
+            for field in line.split(',') {
+                let field = field.trim();
+                let e = field.parse::<FP>()
+                             .map_err(|_| MatIOError::Parse {line: i + 1, field: field.to_string()})?;
+                elems.push(e);
+            }
+
+            let width = elems.len() - len;
+            if nrows == 0 {
+                ncols = width;
+            }
+            else if width != ncols {
+                return Err(MatIOError::BadWidth {line: i + 1, expected: ncols, actual: width});
+            }
+            nrows += 1;
+        }
+
+        Ok(Mat::from_row_major(nrows, ncols, elems).unwrap())
+    }
+    /// *new* - Reads a `.npy` file.
+    ///
+    /// Either C or Fortran order is accepted, and a one-dimensional array becomes a column vector.
+    /// Returns `Err` with [`MatIOError`](../matio/enum.MatIOError.html) for an element type other than little-endian `float64`,
+    /// a malformed file, or a failure of reading it.
+    pub fn read_npy<P: AsRef<Path>>(path: P) -> Result<Mat, MatIOError>
+    {
+        let mut bytes = Vec::new();
+        File::open(path).and_then(|mut f| f.read_to_end(&mut bytes))
+                        .map_err(|e| MatIOError::Io(e.to_string()))?;
+
+        npy_decode(&bytes)
+    }
+}
+
+fn npy_encode<V: View>(mat: &MatGen<V>) -> Vec<u8>
+{
+    let (nrows, ncols) = mat.size();
+    let shape = if ncols == 1 {format!("({},)", nrows)} else {format!("({}, {})", nrows, ncols)};
+
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': True, 'shape': {}, }}", shape);
+    // padded with spaces and terminated by a newline, so that the data is aligned
+    let pad = NPY_ALIGN - (NPY_PREAMBLE_LEN + header.len() + 1) % NPY_ALIGN;
+    header.extend(std::iter::repeat_n(' ', pad % NPY_ALIGN));
+    header.push('\n');
+
+    let mut bytes = Vec::with_capacity(NPY_PREAMBLE_LEN + header.len() + nrows * ncols * 8);
+    bytes.extend_from_slice(NPY_MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+
+    match mat.h_col_major() {
+        Some(s) => bytes.extend(s.iter().flat_map(|e| e.to_le_bytes())),
+        None => {
+            for c in 0 .. ncols {
+                for r in 0 .. nrows {
+                    bytes.extend_from_slice(&mat[(r, c)].to_le_bytes());
+                }
+            }
+        }
+    }
+
+    bytes
+}
+
+// value of `key` in a header dictionary, a quoted string, a tuple or a bare word
+fn npy_value<'a>(header: &'a str, key: &str) -> Option<&'a str>
+{
+    let rest = &header[header.find(&format!("'{}'", key))? + key.len() + 2 ..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+
+    let end = match rest.chars().next()? {
+        '(' => rest.find(')')? + 1,
+        '\'' => rest[1 ..].find('\'')? + 2,
+        _ => rest.find([',', '}'])?
+    };
+
+    Some(rest[.. end].trim())
+}
+
+fn npy_decode(bytes: &[u8]) -> Result<Mat, MatIOError>
+{
+    if bytes.len() < NPY_PREAMBLE_LEN {
+        return Err(MatIOError::BadLength {expected: NPY_PREAMBLE_LEN, actual: bytes.len()});
+    }
+    if &bytes[0 .. 6] != NPY_MAGIC {
+        return Err(MatIOError::BadMagic);
+    }
+    if bytes[6] != 1 {
+        return Err(MatIOError::BadVersion(bytes[6] as u32));
+    }
+
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let data_pos = NPY_PREAMBLE_LEN + header_len;
+    let header = bytes.get(NPY_PREAMBLE_LEN .. data_pos)
+                      .ok_or(MatIOError::BadLength {expected: data_pos, actual: bytes.len()})?;
+    let header = std::str::from_utf8(header).map_err(|_| MatIOError::BadHeader("not ASCII".to_string()))?;
+    let bad = |what: &str| MatIOError::BadHeader(format!("{} in {}", what, header.trim_end()));
+
+    let descr = npy_value(header, "descr").ok_or_else(|| bad("no descr"))?;
+    if descr != "'<f8'" {
+        return Err(MatIOError::BadDtype(descr.to_string()));
+    }
+
+    let fortran_order = match npy_value(header, "fortran_order") {
+        Some("True") => true,
+        Some("False") => false,
+        _ => return Err(bad("no fortran_order"))
+    };
+
+    let shape = npy_value(header, "shape").ok_or_else(|| bad("no shape"))?;
+    let dims = shape.trim_start_matches('(').trim_end_matches(')')
+                    .split(',').map(|d| d.trim()).filter(|d| !d.is_empty())
+                    .map(|d| d.parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>().map_err(|_| bad("bad shape"))?;
+    let (nrows, ncols) = match dims[..] {
+        [n] => (n, 1),
+        [r, c] => (r, c),
+        _ => return Err(bad("shape of neither 1 nor 2 dimensions"))
+    };
+
+    let expected = nrows.checked_mul(ncols)
+                        .and_then(|len| len.checked_mul(8))
+                        .and_then(|len| len.checked_add(data_pos));
+    match expected {
+        Some(expected) if expected == bytes.len() => {},
+        Some(expected) => return Err(MatIOError::BadLength {expected, actual: bytes.len()}),
+        None => return Err(MatIOError::BadLength {expected: usize::MAX, actual: bytes.len()})
+    }
+
+    let elems = bytes[data_pos ..].chunks_exact(8).map(|e| FP::from_le_bytes(e.try_into().unwrap()));
+    let mat = if fortran_order || (ncols == 1) {
+        Mat::from_col_major(nrows, ncols, elems)
+    }
+    else {
+        Mat::from_row_major(nrows, ncols, elems)
+    };
+
+    Ok(mat.unwrap())
+}
+
+#[test]
+fn test_bytes_roundtrip()
+{
+    let mat = Mat::new(3, 2).set_iter(&[
+        1., 2.,
+        -3., 4.5,
+        FP::INFINITY, 1e-300
+    ]);
+
+    let bytes = mat.to_bytes();
+    assert_eq!(bytes.len(), HEADER_LEN + 6 * 8);
+    assert_eq!(Mat::from_bytes(&bytes).unwrap(), mat);
+
+    // views are encoded as they appear
+    let sub = mat.slice(1 .., ..);
+    let sub = sub.t();
+    let dec = Mat::from_bytes(&sub.to_bytes()).unwrap();
+    assert_eq!(dec.size(), (2, 2));
+    assert_eq!(dec, sub);
+
+    let empty = Mat::new(0, 5);
+    assert_eq!(Mat::from_bytes(&empty.to_bytes()).unwrap().size(), (0, 5));
+}
+
+#[test]
+fn test_csv()
+{
+    let mat = Mat::new(2, 3).set_iter(&[
+        1.5, -2., 1.5e-3,
+        0.1, 1e300, -0.
+    ]);
+
+    let mut csv = Vec::new();
+    mat.write_csv(&mut csv).unwrap();
+    assert_eq!(String::from_utf8(csv.clone()).unwrap(), "1.5e0,-2e0,1.5e-3\n1e-1,1e300,-0e0\n");
+    assert_eq!(Mat::read_csv(&csv[..]).unwrap(), mat);
+
+    // logical order of a transposed view
+    let mut csv = Vec::new();
+    mat.t().write_csv_prec(&mut csv, 2).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "1.50e0,1.00e-1\n-2.00e0,1.00e300\n1.50e-3,-0.00e0\n");
+
+    let csv = " 1.5 , 2\n\n3,4e-1\n";
+    assert_eq!(Mat::read_csv(csv.as_bytes()).unwrap(), Mat::new(2, 2).set_iter(&[1.5, 2., 3., 0.4]));
+    assert_eq!(Mat::read_csv(&b""[..]).unwrap().size(), (0, 0));
+
+    assert_eq!(Mat::read_csv(&b"1,2\n3,x\n"[..]),
+               Err(MatIOError::Parse {line: 2, field: "x".to_string()}));
+    assert_eq!(Mat::read_csv(&b"1,2\n\n3\n"[..]),
+               Err(MatIOError::BadWidth {line: 3, expected: 2, actual: 1}));
+    assert_eq!(Mat::read_csv(&b"1,2,\n"[..]).unwrap_err().to_string(), "line 1: cannot parse \"\"");
+    assert!(matches!(Mat::read_csv(&[0xff, b'\n'][..]), Err(MatIOError::Io(_))));
+}
+
+#[test]
+fn test_npy()
+{
+    let mat = Mat::new(2, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.
+    ]);
+
+    let bytes = npy_encode(&mat);
+    let head = b"\x93NUMPY\x01\x00\x76\x00{'descr': '<f8', 'fortran_order': True, 'shape': (2, 3), }";
+    assert_eq!(&bytes[.. head.len()], &head[..]);
+    assert_eq!((bytes.len() - 6 * 8) % NPY_ALIGN, 0);
+    assert_eq!(npy_decode(&bytes).unwrap(), mat);
+    assert_eq!(npy_decode(&npy_encode(&mat.t())).unwrap(), mat.t());
+
+    let vec = Mat::new_vec(3).set_iter(&[1., 2., 3.]);
+    let bytes = npy_encode(&vec);
+    assert!(std::str::from_utf8(&bytes[NPY_PREAMBLE_LEN .. NPY_ALIGN]).unwrap().contains("'shape': (3,)"));
+    assert_eq!(npy_decode(&bytes).unwrap(), vec);
+
+    // as numpy.save writes a C-order array
+    let npy = |header: &str, data: &[FP]| {
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend(data.iter().flat_map(|e| e.to_le_bytes()));
+        bytes
+    };
+    let data = [1., 2., 3., 4., 5., 6.];
+    let c_order = npy("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }\n", &data);
+    assert_eq!(npy_decode(&c_order).unwrap(), mat);
+    let c_order = npy("{'descr': '<f8', 'fortran_order': False, 'shape': (6,), }\n", &data);
+    assert_eq!(npy_decode(&c_order).unwrap(), Mat::new_vec(6).set_iter(&data));
+
+    assert_eq!(npy_decode(&npy("{'descr': '<f4', 'fortran_order': False, 'shape': (3,), }\n", &data[.. 3])),
+               Err(MatIOError::BadDtype("'<f4'".to_string())));
+    assert_eq!(npy_decode(&npy("{'descr': '>f8', 'fortran_order': False, 'shape': (3,), }\n", &data[.. 3])),
+               Err(MatIOError::BadDtype("'>f8'".to_string())));
+    assert!(matches!(npy_decode(&npy("{'descr': '<f8', 'fortran_order': False, 'shape': (1, 2, 3), }\n", &data)),
+                     Err(MatIOError::BadHeader(_))));
+    assert!(matches!(npy_decode(&npy("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 2), }\n", &data)),
+                     Err(MatIOError::BadLength {..})));
+
+    let path = std::env::temp_dir().join(format!("totsu_test_npy_{}.npy", std::process::id()));
+    mat.write_npy(&path).unwrap();
+    let rd = Mat::read_npy(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(rd.unwrap(), mat);
+    assert!(matches!(Mat::read_npy(&path), Err(MatIOError::Io(_))));
+}
+
+#[test]
+fn test_bytes_corrupt()
+{
+    let bytes = Mat::new(2, 2).set_eye().to_bytes();
+
+    for len in 0 .. bytes.len() {
+        assert!(matches!(Mat::from_bytes(&bytes[.. len]), Err(MatIOError::BadLength {..})));
+    }
+
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(matches!(Mat::from_bytes(&longer), Err(MatIOError::BadLength {..})));
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(Mat::from_bytes(&magic), Err(MatIOError::BadMagic));
+
+    let mut version = bytes.clone();
+    version[8] = 9;
+    assert_eq!(Mat::from_bytes(&version), Err(MatIOError::BadVersion(9)));
+
+    // huge dims must not overflow nor allocate
+    let mut huge = bytes;
+    huge[12 .. 20].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(matches!(Mat::from_bytes(&huge), Err(MatIOError::BadLength {..})));
+}
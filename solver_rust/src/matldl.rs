@@ -0,0 +1,376 @@
+//! Matrix LDLT decomposition
+
+use super::mat::{Mat, MatGen, View, FP};
+use super::matlu::SingularError;
+
+/// Matrix LDLT decomposition
+///
+/// \\(PAP^T = LDL^T\\) of a symmetric, possibly indefinite matrix by Bunch-Kaufman pivoting,
+/// where \\(L\\) is unit lower triangular, \\(D\\) is block diagonal of \\(1 \times 1\\) and \\(2 \times 2\\) blocks
+/// and \\(P\\) is a symmetric permutation.
+/// By Sylvester's law of inertia, \\(D\\) has as many positive, negative and zero eigenvalues as \\(A\\).
+#[derive(Debug)]
+pub struct MatLDL
+{
+    l: Mat,
+    d: Mat,
+    // sizes of the blocks of d in order
+    blocks: Vec<usize>,
+    // row i of PAP^T is row perm[i] of A
+    perm: Vec<usize>,
+    // magnitude not above which a pivot or an eigenvalue of a block is zero
+    zero: FP
+}
+
+impl MatLDL
+{
+    /// Factorizes a symmetric matrix `a`.
+    ///
+    /// Only the lower triangle of `a` is read, so the upper one may be anything.
+    /// Pivots and eigenvalues of \\(D\\) not larger in magnitude than `tol` times \\(\max_{ij} |a_{ij}|\\)
+    /// are taken as zero, which the factorization skips instead of failing.
+    pub fn new<V: View>(a: &MatGen<V>, tol: FP) -> MatLDL
+    {
+        let (n, ncols) = a.size();
+        assert_eq!(n, ncols, "MatLDL: matrix must be square");
+
+        // Bunch-Kaufman threshold, which bounds the growth of elements
+        let alpha = (1. + FP::sqrt(17.)) / 8.;
+
+        let mut w = a.clone_sz();
+        for c in 0 .. n {
+            for r in 0 .. c {
+                w[(r, c)] = w[(c, r)];
+            }
+        }
+        let zero = tol * w.norm_inf();
+
+        let mut l = Mat::new(n, n);
+        let mut d = Mat::new(n, n);
+        let mut blocks = Vec::new();
+        let mut perm: Vec<usize> = (0 .. n).collect();
+
+        let mut k = 0;
+        while k < n {
+            let absakk = w[(k, k)].abs();
+            let (r, colmax) = (k + 1 .. n).map(|i| (i, w[(i, k)].abs()))
+                                          .fold((k, 0.), |m, e| if e.1 > m.1 {e} else {m});
+
+            // pivot size, and the row to be interchanged with the last row of the pivot
+            let (kstep, p) = if (absakk.max(colmax) <= zero) || (absakk >= alpha * colmax) {
+                (1, k)
+            }
+            else {
+                let rowmax = (k .. n).filter(|&j| j != r).map(|j| w[(r, j)].abs()).fold(0., FP::max);
+
+                if absakk * rowmax >= alpha * colmax * colmax {
+                    (1, k)
+                }
+                else if w[(r, r)].abs() >= alpha * rowmax {
+                    (1, r)
+                }
+                else {
+                    (2, r)
+                }
+            };
+
+            let kk = k + kstep - 1;
+            if p != kk {
+                w.swap_rows(kk, p);
+                w.swap_cols(kk, p);
+                l.swap_rows(kk, p);
+                perm.swap(kk, p);
+            }
+
+            if kstep == 1 {
+                let d_kk = w[(k, k)];
+                d[(k, k)] = d_kk;
+
+                // columns of a zero pivot are negligible as well
+                if d_kk.abs() > zero {
+                    for i in k + 1 .. n {
+                        l[(i, k)] = w[(i, k)] / d_kk;
+                    }
+                    for j in k + 1 .. n {
+                        let w_jk = w[(j, k)];
+                        for i in k + 1 .. n {
+                            w[(i, j)] -= l[(i, k)] * w_jk;
+                        }
+                    }
+                }
+            }
+            else {
+                let (e00, e10, e11) = (w[(k, k)], w[(k + 1, k)], w[(k + 1, k + 1)]);
+                let det = e00 * e11 - e10 * e10;
+                d[(k, k)] = e00;
+                d[(k + 1, k)] = e10;
+                d[(k, k + 1)] = e10;
+                d[(k + 1, k + 1)] = e11;
+
+                for i in k + 2 .. n {
+                    let (w0, w1) = (w[(i, k)], w[(i, k + 1)]);
+                    l[(i, k)] = (w0 * e11 - w1 * e10) / det;
+                    l[(i, k + 1)] = (w1 * e00 - w0 * e10) / det;
+                }
+                for j in k + 2 .. n {
+                    let (w0, w1) = (w[(j, k)], w[(j, k + 1)]);
+                    for i in k + 2 .. n {
+                        w[(i, j)] -= l[(i, k)] * w0 + l[(i, k + 1)] * w1;
+                    }
+                }
+            }
+
+            blocks.push(kstep);
+            k += kstep;
+        }
+
+        l.diag_mut().assign_all(1.);
+
+        MatLDL {l, d, blocks, perm, zero}
+    }
+
+    /// Unit lower triangular factor \\(L\\).
+    pub fn l(&self) -> &Mat
+    {
+        &self.l
+    }
+
+    /// Block diagonal factor \\(D\\).
+    pub fn d(&self) -> &Mat
+    {
+        &self.d
+    }
+
+    /// Symmetric permutation \\(P\\): row `i` of \\(PAP^T\\) is row `perm()[i]` of \\(A\\).
+    pub fn perm(&self) -> &[usize]
+    {
+        &self.perm
+    }
+
+    /// Inertia of \\(A\\), the numbers of its positive, negative and zero eigenvalues.
+    pub fn inertia(&self) -> (usize, usize, usize)
+    {
+        let mut inertia = (0, 0, 0);
+
+        for (_, ev) in self.h_block_eigs() {
+            for e in ev.iter().flatten() {
+                if e.abs() <= self.zero {
+                    inertia.2 += 1;
+                }
+                else if *e > 0. {
+                    inertia.0 += 1;
+                }
+                else {
+                    inertia.1 += 1;
+                }
+            }
+        }
+
+        inertia
+    }
+
+    /// Solves \\(AX = B\\) for each column of `b`.
+    ///
+    /// Returns `Err` naming the first row of a block of \\(D\\) which has a zero eigenvalue.
+    pub fn solve<V: View>(&self, b: &MatGen<V>) -> Result<Mat, SingularError>
+    {
+        let n = self.l.size().0;
+        let (b_nrows, b_ncols) = b.size();
+        assert_eq!(b_nrows, n, "MatLDL: size {}x{} does not match {} rows", b_nrows, b_ncols, n);
+
+        for (k, ev) in self.h_block_eigs() {
+            if let Some(e) = ev.iter().flatten().find(|e| e.abs() <= self.zero) {
+                return Err(SingularError {pivot: k, value: e.abs()});
+            }
+        }
+
+        let y = b.select_rows(&self.perm);
+        let mut z = self.l.solve_lower_triangular(&y, true)?;
+
+        let mut k = 0;
+        for &kstep in &self.blocks {
+            for c in 0 .. b_ncols {
+                if kstep == 1 {
+                    z[(k, c)] /= self.d[(k, k)];
+                }
+                else {
+                    let (e00, e10, e11) = (self.d[(k, k)], self.d[(k + 1, k)], self.d[(k + 1, k + 1)]);
+                    let det = e00 * e11 - e10 * e10;
+                    let (z0, z1) = (z[(k, c)], z[(k + 1, c)]);
+                    z[(k, c)] = (z0 * e11 - z1 * e10) / det;
+                    z[(k + 1, c)] = (z1 * e00 - z0 * e10) / det;
+                }
+            }
+            k += kstep;
+        }
+
+        let u = self.l.t().solve_upper_triangular(&z, true)?;
+
+        // x = P^T u
+        let mut inv_perm = vec![0; n];
+        for (i, &p) in self.perm.iter().enumerate() {
+            inv_perm[p] = i;
+        }
+        Ok(u.select_rows(&inv_perm))
+    }
+
+    // first rows of the blocks of d and their eigenvalues
+    fn h_block_eigs(&self) -> Vec<(usize, [Option<FP>; 2])>
+    {
+        let mut eigs = Vec::new();
+
+        let mut k = 0;
+        for &kstep in &self.blocks {
+            if kstep == 1 {
+                eigs.push((k, [Some(self.d[(k, k)]), None]));
+            }
+            else {
+                let (e00, e10, e11) = (self.d[(k, k)], self.d[(k + 1, k)], self.d[(k + 1, k + 1)]);
+                let mean = (e00 + e11) / 2.;
+                let rad = ((e00 - e11) / 2.).hypot(e10);
+                eigs.push((k, [Some(mean + rad), Some(mean - rad)]));
+            }
+            k += kstep;
+        }
+
+        eigs
+    }
+}
+
+//
+
+#[cfg(test)]
+use super::mat::{xor64, XOR64_INIT};
+
+#[cfg(test)]
+const TOL_ZERO: FP = 1e-12;
+
+// Q diag(eigs) Q^T of a random orthogonal Q
+#[cfg(test)]
+fn h_sym_of_eigs(eigs: &[FP], r: &mut u64) -> Mat
+{
+    use super::matqr::MatQR;
+
+    let n = eigs.len();
+    let q = MatQR::new(&Mat::new(n, n).set_by(|_, _| xor64(r) - 0.5)).q();
+    let d = Mat::new_vec(n).set_iter(eigs).clone_diag();
+
+    let a = &q * d * q.t();
+    // exactly symmetric
+    (&a + a.t()) / 2.
+}
+
+#[test]
+fn test_two_by_two()
+{
+    let a = Mat::new(2, 2).set_iter(&[
+        0., 1.,
+        1., 0.
+    ]);
+    let ldl = MatLDL::new(&a, TOL_ZERO);
+    println!("d = {}", ldl.d());
+    assert_eq!(ldl.inertia(), (1, 1, 0));
+
+    let b = Mat::new_vec(2).set_iter(&[2., 3.]);
+    assert_eq!(ldl.solve(&b).unwrap(), Mat::new_vec(2).set_iter(&[3., 2.]));
+
+    // eigenvalues 0 and -sqrt(2), sqrt(2)
+    let a = Mat::new(3, 3).set_iter(&[
+        0., 1., 0.,
+        1., 0., 1.,
+        0., 1., 0.
+    ]);
+    let ldl = MatLDL::new(&a, TOL_ZERO);
+    assert_eq!(ldl.inertia(), (1, 1, 1));
+    assert!(ldl.solve(&Mat::new_vec(3)).is_err());
+
+    // the upper triangle is not read
+    let g = a.clone().set_by(|r, c| if c > r {FP::NAN} else {a[(r, c)]});
+    assert_eq!(MatLDL::new(&g, TOL_ZERO).d(), ldl.d());
+}
+
+#[test]
+fn test_random()
+{
+    const TOL: FP = 1e-9;
+
+    let mut r = XOR64_INIT;
+    let n = 10;
+
+    for &(pos, neg) in &[(10, 0), (0, 10), (4, 6), (3, 4)] {
+        let eigs: Vec<FP> = (0 .. n).map(|i| {
+            let e = 0.5 + xor64(&mut r);
+            if i < pos {e} else if i < pos + neg {-e} else {0.}
+        }).collect();
+        let a = h_sym_of_eigs(&eigs, &mut r);
+
+        let ldl = MatLDL::new(&a, TOL_ZERO);
+        println!("blocks = {:?}", ldl.blocks);
+        assert_eq!(ldl.inertia(), (pos, neg, n - pos - neg));
+
+        // P A P^T = L D L^T
+        let pap = a.select_rows(ldl.perm()).select_cols(ldl.perm());
+        assert!((ldl.l() * ldl.d() * ldl.l().t() - pap).norm_inf() < TOL);
+
+        let b = Mat::new(n, 2).set_by(|_, _| xor64(&mut r) - 0.5);
+        if pos + neg == n {
+            let x = ldl.solve(&b).unwrap();
+            assert!((&a * x - &b).norm_inf() < TOL);
+        }
+        else {
+            assert!(ldl.solve(&b).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_kkt()
+{
+    const TOL: FP = 1e-9;
+
+    let mut r = XOR64_INIT;
+    let (n, p) = (6, 3);
+
+    // [H A^T; A 0] of positive definite H and full row rank A has inertia (n, p, 0)
+    let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mat_a = Mat::new(p, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mut kkt = Mat::new(n + p, n + p);
+    kkt.slice_mut(0 .. n, 0 .. n).assign(&(mat_b.t() * &mat_b + Mat::new(n, n).set_eye()));
+    kkt.slice_mut(n .. n + p, 0 .. n).assign(&mat_a);
+    kkt.slice_mut(0 .. n, n .. n + p).assign(&mat_a.t());
+
+    let ldl = MatLDL::new(&kkt, TOL_ZERO);
+    assert_eq!(ldl.inertia(), (n, p, 0));
+
+    let b = Mat::new_vec(n + p).set_by(|_, _| xor64(&mut r) - 0.5);
+    let x = ldl.solve(&b).unwrap();
+    assert!((&kkt * x - &b).norm_inf() < TOL);
+
+    // zero diagonal requires 2x2 pivots
+    let mut anti = Mat::new(2 * p, 2 * p);
+    anti.slice_mut(p .., 0 .. p).assign(&mat_a.cols(0 .. p));
+    anti.slice_mut(0 .. p, p ..).assign(&mat_a.cols(0 .. p).t());
+    let ldl = MatLDL::new(&anti, TOL_ZERO);
+    assert!(ldl.blocks.contains(&2));
+    assert_eq!(ldl.inertia(), (p, p, 0));
+    let b = Mat::new_vec(2 * p).set_by(|_, _| xor64(&mut r) - 0.5);
+    let x = ldl.solve(&b).unwrap();
+    assert!((&anti * x - &b).norm_inf() < TOL);
+}
+
+#[test]
+fn test_inertia_eig()
+{
+    let mut r = XOR64_INIT;
+    let n = 12;
+
+    for _ in 0 .. 5 {
+        let b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let a = &b + b.t();
+
+        let vals = a.eigvals_sym().unwrap();
+        let pos = vals.count(|e| e > 0.);
+
+        assert_eq!(MatLDL::new(&a, TOL_ZERO).inertia(), (pos, n - pos, 0));
+    }
+}
@@ -0,0 +1,266 @@
+//! Matrix LU decomposition
+
+use super::mat::{Mat, MatGen, View, FP, FP_EPSILON};
+use std::fmt;
+
+/// Error of a singular or nearly singular matrix
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingularError
+{
+    /// Index of the pivot found too small.
+    pub pivot: usize,
+    /// Magnitude of the pivot.
+    pub value: FP
+}
+
+impl fmt::Display for SingularError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        write!(f, "singular matrix: pivot {} of magnitude {:e}", self.pivot, self.value)
+    }
+}
+
+impl std::error::Error for SingularError {}
+
+/// Matrix LU decomposition
+///
+/// \\(PA = LU\\) with partial pivoting,
+/// where \\(L\\) is unit lower triangular, \\(U\\) is upper triangular and \\(P\\) is a row permutation.
+#[derive(Debug)]
+pub struct MatLU
+{
+    // L below the diagonal and U on and above it
+    lu: Mat,
+    // row i of PA is row perm[i] of A
+    perm: Vec<usize>,
+    sign: FP
+}
+
+impl MatLU
+{
+    /// Factorizes a square matrix `a`.
+    ///
+    /// Returns `Err` if a pivot magnitude is not above \\(n \epsilon \max_{ij} |a_{ij}|\\),
+    /// reporting its index.
+    pub fn new<V: View>(a: &MatGen<V>) -> Result<MatLU, SingularError>
+    {
+        let (n, ncols) = a.size();
+        assert_eq!(n, ncols, "MatLU: matrix must be square");
+
+        let mut lu = a.clone_sz();
+        let mut perm: Vec<usize> = (0 .. n).collect();
+        let mut sign = 1.;
+
+        let tol = n as FP * FP_EPSILON * a.norm_inf();
+
+        for k in 0 .. n {
+            let p = lu.col(k).rows(k ..).abs_argmax().map_or(k, |(r, _)| k + r);
+            let pivot = lu[(p, k)];
+            if pivot.is_nan() || pivot.abs() <= tol {
+                return Err(SingularError {pivot: k, value: pivot.abs()});
+            }
+
+            if p != k {
+                lu.swap_rows(k, p);
+                perm.swap(k, p);
+                sign = -sign;
+            }
+
+            for r in k + 1 .. n {
+                lu[(r, k)] /= pivot;
+            }
+            for c in k + 1 .. n {
+                let u_kc = lu[(k, c)];
+                if u_kc != 0. {
+                    for r in k + 1 .. n {
+                        lu[(r, c)] -= lu[(r, k)] * u_kc;
+                    }
+                }
+            }
+        }
+
+        Ok(MatLU {lu, perm, sign})
+    }
+
+    /// Solves \\(AX = B\\) for each column of `b`.
+    pub fn solve<V: View>(&self, b: &MatGen<V>) -> Mat
+    {
+        let n = self.lu.size().0;
+        let (b_nrows, b_ncols) = b.size();
+        assert_eq!(b_nrows, n, "MatLU: size {}x{} does not match {} rows", b_nrows, b_ncols, n);
+
+        let mut x = b.select_rows(&self.perm);
+
+        for c in 0 .. b_ncols {
+            // forward substitution with unit L
+            for i in 0 .. n {
+                let mut s = x[(i, c)];
+                for j in 0 .. i {
+                    s -= self.lu[(i, j)] * x[(j, c)];
+                }
+                x[(i, c)] = s;
+            }
+            // backward substitution with U
+            for i in (0 .. n).rev() {
+                let mut s = x[(i, c)];
+                for j in i + 1 .. n {
+                    s -= self.lu[(i, j)] * x[(j, c)];
+                }
+                x[(i, c)] = s / self.lu[(i, i)];
+            }
+        }
+
+        x
+    }
+
+    /// Solves \\(A^T X = B\\) for each column of `b`, reusing the factorization.
+    pub fn solve_t<V: View>(&self, b: &MatGen<V>) -> Mat
+    {
+        let n = self.lu.size().0;
+        let (b_nrows, b_ncols) = b.size();
+        assert_eq!(b_nrows, n, "MatLU: size {}x{} does not match {} rows", b_nrows, b_ncols, n);
+
+        // A^T = U^T L^T P
+        let mut y = b.clone_sz();
+
+        for c in 0 .. b_ncols {
+            // forward substitution with U^T
+            for i in 0 .. n {
+                let mut s = y[(i, c)];
+                for j in 0 .. i {
+                    s -= self.lu[(j, i)] * y[(j, c)];
+                }
+                y[(i, c)] = s / self.lu[(i, i)];
+            }
+            // backward substitution with unit L^T
+            for i in (0 .. n).rev() {
+                let mut s = y[(i, c)];
+                for j in i + 1 .. n {
+                    s -= self.lu[(j, i)] * y[(j, c)];
+                }
+                y[(i, c)] = s;
+            }
+        }
+
+        let mut x = Mat::new(n, b_ncols);
+        for (i, &p) in self.perm.iter().enumerate() {
+            x.row_mut(p).assign(&y.row(i));
+        }
+
+        x
+    }
+
+    /// Determinant of \\(A\\).
+    pub fn det(&self) -> FP
+    {
+        self.lu.diag().iter().fold(self.sign, |d, u| d * u)
+    }
+
+    /// Inverse of \\(A\\).
+    pub fn inv(&self) -> Mat
+    {
+        let n = self.lu.size().0;
+
+        self.solve(&Mat::new(n, n).set_eye())
+    }
+}
+
+//
+
+#[cfg(test)]
+use super::mat::{xor64, XOR64_INIT};
+
+#[test]
+fn test_solve()
+{
+    const TOL: FP = 1e-12;
+
+    let a = Mat::new(3, 3).set_iter(&[
+        2., 1., 1.,
+        4., -6., 0.,
+        -2., 7., 2.
+    ]);
+    let b = Mat::new(3, 2).set_iter(&[
+        5., 1.,
+        -2., 0.,
+        9., 0.
+    ]);
+
+    let lu = MatLU::new(&a).unwrap();
+    let x = lu.solve(&b);
+    println!("x = {}", x);
+
+    let x0 = Mat::new_vec(3).set_iter(&[1., 1., 2.]);
+    assert!((x.col(0) - x0).norm_inf() < TOL);
+    assert!((&a * &x - &b).norm_inf() < TOL);
+    assert!((lu.det() - (-16.)).abs() < TOL);
+
+    let a = Mat::new(4, 4).set_iter(&[
+        0., 2., 0., 1.,
+        2., 2., 3., 2.,
+        4., -3., 0., 1.,
+        6., 1., -6., -5.
+    ]);
+    let b = Mat::new_vec(4).set_iter(&[0., -2., -7., 6.]);
+
+    // the first pivot is zero without pivoting
+    let lu = MatLU::new(&a).unwrap();
+    let x = lu.solve(&b);
+    println!("x = {}", x);
+
+    let x0 = Mat::new_vec(4).set_iter(&[-0.5, 1., 1. / 3., -2.]);
+    assert!((x - x0).norm_inf() < TOL);
+
+    let x = lu.solve_t(&b);
+    assert!((a.t() * x - &b).norm_inf() < TOL);
+    assert!((lu.det() - (-234.)).abs() < TOL * 234.);
+
+    let inv = lu.inv();
+    let mut eye = Mat::new(4, 4);
+    eye.assign_eye();
+    assert!((&a * inv - eye).norm_inf() < TOL);
+
+    // views are factorized as well
+    let lu_t = MatLU::new(&a.t()).unwrap();
+    assert!((lu_t.det() - lu.det()).abs() < TOL * 234.);
+}
+
+#[test]
+fn test_random()
+{
+    const TOL: FP = 1e-9;
+
+    let mut r = XOR64_INIT;
+    let n = 20;
+
+    let a = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let b = Mat::new(n, 3).set_by(|_, _| xor64(&mut r) - 0.5);
+
+    let lu = MatLU::new(&a).unwrap();
+    let x = lu.solve(&b);
+
+    let err = (&a * &x - &b).norm_inf();
+    println!("err = {:e}", err);
+    assert!(err < TOL);
+}
+
+#[test]
+fn test_singular()
+{
+    let a = Mat::new(3, 3).set_iter(&[
+        1., 2., 3.,
+        4., 5., 6.,
+        7., 8., 9.
+    ]);
+    let e = MatLU::new(&a).unwrap_err();
+    println!("{}", e);
+    assert_eq!(e.pivot, 2);
+
+    let e = MatLU::new(&Mat::new(2, 2)).unwrap_err();
+    assert_eq!(e, SingularError {pivot: 0, value: 0.});
+
+    let lu = MatLU::new(&Mat::new(0, 0)).unwrap();
+    assert_eq!(lu.det(), 1.);
+    assert_eq!(lu.solve(&Mat::new(0, 2)).size(), (0, 2));
+}
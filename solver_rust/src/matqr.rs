@@ -0,0 +1,346 @@
+//! Matrix QR decomposition
+
+use super::mat::{Mat, MatGen, View, FP, FP_EPSILON};
+
+// relative tolerance of the diagonal of R per row, to tell the rank deficiency
+const TOL_RANK: FP = FP_EPSILON * 16.;
+
+/// Matrix QR decomposition
+///
+/// \\(AP = QR\\) of an \\(m \times n\\) matrix with \\(m \ge n\\) by Householder reflections,
+/// where \\(Q\\) has orthonormal columns, \\(R\\) is \\(n \times n\\) upper triangular
+/// and \\(P\\) is a column permutation, which is the identity unless [`new_pivoted`](#method.new_pivoted).
+#[derive(Debug)]
+pub struct MatQR
+{
+    // R on and above the diagonal and the reflectors below it, whose leading 1 is implicit
+    qr: Mat,
+    // coefficients of the reflectors I - tau v v^T
+    tau: Vec<FP>,
+    // column j of AP is column perm[j] of A
+    perm: Vec<usize>
+}
+
+impl MatQR
+{
+    /// Factorizes `a` of \\(m \ge n\\).
+    pub fn new<V: View>(a: &MatGen<V>) -> MatQR
+    {
+        MatQR::h_new(a, false)
+    }
+
+    /// Factorizes `a` of \\(m \ge n\\) with Businger-Golub column pivoting,
+    /// which takes the column of the largest remaining norm at each step.
+    ///
+    /// The diagonal of \\(R\\) is then non-increasing in magnitude and reveals the rank of `a`.
+    pub fn new_pivoted<V: View>(a: &MatGen<V>) -> MatQR
+    {
+        MatQR::h_new(a, true)
+    }
+
+    fn h_new<V: View>(a: &MatGen<V>, pivoting: bool) -> MatQR
+    {
+        let (m, n) = a.size();
+        assert!(m >= n, "MatQR: size {}x{} has fewer rows than columns", m, n);
+
+        let mut qr = a.clone_sz();
+        let mut tau = vec![0.; n];
+        let mut perm: Vec<usize> = (0 .. n).collect();
+
+        for k in 0 .. n {
+            if pivoting {
+                // remaining norms are computed afresh, free from cancellation of downdating
+                let norms = qr.slice(k .., k ..).col_norms_p2();
+                if let Some((j, _)) = norms.argmax() {
+                    if j > 0 {
+                        qr.swap_cols(k, k + j);
+                        perm.swap(k, k + j);
+                    }
+                }
+            }
+
+            let norm = qr.col(k).rows(k ..).norm_p2();
+            if norm == 0. {
+                continue;
+            }
+
+            let x0 = qr[(k, k)];
+            let beta = if x0 < 0. {norm} else {-norm};
+            tau[k] = (beta - x0) / beta;
+            qr.col_mut(k).rows_mut(k + 1 ..).map_inplace(|x| x / (x0 - beta));
+            qr[(k, k)] = beta;
+
+            let v = qr.col(k).rows(k + 1 ..).clone_sz();
+            for c in k + 1 .. n {
+                h_reflect(&v, k, tau[k], &mut qr, c);
+            }
+        }
+
+        MatQR {qr, tau, perm}
+    }
+
+    /// Column permutation \\(P\\): column `j` of \\(AP\\) is column `perm()[j]` of \\(A\\).
+    pub fn perm(&self) -> &[usize]
+    {
+        &self.perm
+    }
+
+    /// Diagonal of \\(R\\) as a column vector.
+    pub fn r_diag(&self) -> Mat
+    {
+        self.qr.diag().clone_sz()
+    }
+
+    /// Numerical rank, the number of diagonal entries of \\(R\\) larger in magnitude than
+    /// `tol` times the largest one.
+    ///
+    /// It is reliable only if [`new_pivoted`](#method.new_pivoted).
+    pub fn rank(&self, tol: FP) -> usize
+    {
+        let r_diag = self.qr.diag();
+        let thr = tol * r_diag.norm_inf();
+
+        let rank = r_diag.iter().filter(|r| r.abs() > thr).count();
+        rank
+    }
+
+    /// Indices of columns of \\(A\\) in ascending order, as many as [`rank`](#method.rank),
+    /// which are a well-conditioned basis of the range of \\(A\\) if [`new_pivoted`](#method.new_pivoted).
+    pub fn basis_cols(&self, tol: FP) -> Vec<usize>
+    {
+        let mut cols = self.perm[.. self.rank(tol)].to_vec();
+        cols.sort_unstable();
+        cols
+    }
+
+    /// Explicit \\(m \times n\\) thin \\(Q\\).
+    pub fn q(&self) -> Mat
+    {
+        let (m, n) = self.qr.size();
+        let mut eye = Mat::new(m, n);
+        eye.assign_eye();
+
+        self.q_mul(&eye)
+    }
+
+    /// \\(n \times n\\) upper triangular \\(R\\) of \\(AP\\).
+    pub fn r(&self) -> Mat
+    {
+        let n = self.qr.size().1;
+
+        self.qr.rows(0 .. n).triu(0)
+    }
+
+    /// Applies the full \\(m \times m\\) \\(Q\\) to `x` of \\(m\\) rows, without forming it.
+    pub fn q_mul<V: View>(&self, x: &MatGen<V>) -> Mat
+    {
+        let (m, n) = self.qr.size();
+        assert_eq!(x.size().0, m, "MatQR: size {}x{} does not match {} rows", x.size().0, x.size().1, m);
+
+        let mut y = x.clone_sz();
+        for k in (0 .. n).rev() {
+            for c in 0 .. y.size().1 {
+                h_reflect(&self.qr.col(k).rows(k + 1 ..), k, self.tau[k], &mut y, c);
+            }
+        }
+
+        y
+    }
+
+    /// Applies \\(Q^T\\) to `x` of \\(m\\) rows, without forming \\(Q\\).
+    pub fn qt_mul<V: View>(&self, x: &MatGen<V>) -> Mat
+    {
+        let (m, n) = self.qr.size();
+        assert_eq!(x.size().0, m, "MatQR: size {}x{} does not match {} rows", x.size().0, x.size().1, m);
+
+        let mut y = x.clone_sz();
+        for k in 0 .. n {
+            for c in 0 .. y.size().1 {
+                h_reflect(&self.qr.col(k).rows(k + 1 ..), k, self.tau[k], &mut y, c);
+            }
+        }
+
+        y
+    }
+
+    /// Returns `true` if every diagonal entry of \\(R\\) is larger in magnitude than
+    /// \\(16 m \epsilon \max_k |r_{kk}|\\), otherwise \\(A\\) is numerically rank deficient.
+    pub fn is_full_rank(&self) -> bool
+    {
+        let m = self.qr.size().0;
+        let r_diag = self.qr.diag();
+        let tol = m as FP * TOL_RANK * r_diag.norm_inf();
+
+        let full = r_diag.iter().all(|r| r.abs() > tol);
+        full
+    }
+
+    /// Solves the least-squares problem \\(\min_X \\|AX - B\\|\\) for each column of `b`.
+    ///
+    /// The result is meaningful only if [`is_full_rank`](#method.is_full_rank).
+    pub fn solve_ls<V: View>(&self, b: &MatGen<V>) -> Mat
+    {
+        let n = self.qr.size().1;
+        let y = self.qt_mul(b);
+        let mut z = y.rows(0 .. n).clone_sz();
+
+        for c in 0 .. z.size().1 {
+            for i in (0 .. n).rev() {
+                let mut s = z[(i, c)];
+                for j in i + 1 .. n {
+                    s -= self.qr[(i, j)] * z[(j, c)];
+                }
+                z[(i, c)] = s / self.qr[(i, i)];
+            }
+        }
+
+        // x = P z
+        let mut inv_perm = vec![0; n];
+        for (j, &p) in self.perm.iter().enumerate() {
+            inv_perm[p] = j;
+        }
+        z.select_rows(&inv_perm)
+    }
+}
+
+// applies the k-th reflector, whose elements following the leading 1 are v, to the column c of y
+fn h_reflect<V: View>(v: &MatGen<V>, k: usize, tau: FP, y: &mut Mat, c: usize)
+{
+    if tau == 0. {
+        return;
+    }
+
+    let len = v.size().0;
+
+    let mut w = y[(k, c)];
+    for i in 0 .. len {
+        w += v[(i, 0)] * y[(k + 1 + i, c)];
+    }
+    w *= tau;
+
+    y[(k, c)] -= w;
+    for i in 0 .. len {
+        y[(k + 1 + i, c)] -= w * v[(i, 0)];
+    }
+}
+
+//
+
+#[cfg(test)]
+use super::mat::{xor64, XOR64_INIT};
+
+#[test]
+fn test_decomp()
+{
+    const TOL: FP = 1e-12;
+
+    let a = Mat::new(4, 3).set_iter(&[
+        12., -51., 4.,
+        6., 167., -68.,
+        -4., 24., -41.,
+        0., 0., 2.
+    ]);
+
+    let qr = MatQR::new(&a);
+    let (q, r) = (qr.q(), qr.r());
+    println!("q = {}", q);
+    println!("r = {}", r);
+
+    assert_eq!(r.size(), (3, 3));
+    assert_eq!(r.tril(-1), Mat::new(3, 3));
+    assert!((r[(0, 0)].abs() - 14.).abs() < TOL * 14.);
+    assert!((&q * &r - &a).norm_inf() < TOL * 1e3);
+
+    let mut eye = Mat::new(3, 3);
+    eye.assign_eye();
+    assert!((q.t() * &q - &eye).norm_inf() < TOL);
+    assert!(qr.is_full_rank());
+
+    let x = Mat::new(4, 2).set_by(|r, c| (r + 2 * c) as FP);
+    assert!((qr.qt_mul(&qr.q_mul(&x)) - &x).norm_inf() < TOL * 10.);
+    assert!((qr.qt_mul(&a).rows(0 .. 3) - &r).norm_inf() < TOL * 1e3);
+
+    // the second column is twice the first
+    let a = Mat::new(3, 2).set_iter(&[
+        1., 2.,
+        2., 4.,
+        3., 6.
+    ]);
+    assert!(!MatQR::new(&a).is_full_rank());
+    assert!(!MatQR::new(&Mat::new(3, 2)).is_full_rank());
+}
+
+#[test]
+fn test_solve_ls()
+{
+    use super::matsvd::MatSVD;
+
+    const TOL: FP = 1e-9;
+
+    let mut r = XOR64_INIT;
+
+    for &(m, n) in &[(10, 4), (20, 20), (30, 7)] {
+        let a = Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let b = Mat::new_vec(m).set_by(|_, _| xor64(&mut r) - 0.5);
+
+        let qr = MatQR::new(&a);
+        let x = qr.solve_ls(&b);
+
+        let mut svd = MatSVD::new(a.size());
+        svd.decomp(&a);
+        let x_svd = svd.solve(&b);
+
+        let err = (&x - &x_svd).norm_inf();
+        println!("{}x{}: err = {:e}", m, n, err);
+        assert!(err < TOL);
+
+        // normal equation
+        assert!((a.t() * (&a * &x - &b)).norm_inf() < TOL);
+    }
+}
+
+#[test]
+fn test_pivoted()
+{
+    const TOL: FP = 1e-10;
+
+    let mut r = XOR64_INIT;
+    let (m, n, rank) = (10, 6, 3);
+
+    // product of thin random factors, exactly of the rank
+    let u = Mat::new(m, rank).set_by(|_, _| xor64(&mut r) - 0.5);
+    let v = Mat::new(rank, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let a = &u * &v;
+
+    let qr = MatQR::new_pivoted(&a);
+    println!("perm = {:?}", qr.perm());
+    println!("r_diag = {:e}", qr.r_diag().t());
+
+    let r_diag = qr.r_diag();
+    assert!((0 .. n - 1).all(|k| r_diag[(k, 0)].abs() >= r_diag[(k + 1, 0)].abs()));
+    assert_eq!(qr.rank(TOL), rank);
+    assert!(!qr.is_full_rank());
+
+    // AP = QR
+    let ap = a.select_cols(qr.perm());
+    assert!((qr.q() * qr.r() - ap).norm_inf() < TOL);
+
+    // the selected columns span the range of a
+    let cols = qr.basis_cols(TOL);
+    println!("cols = {:?}", cols);
+    assert_eq!(cols.len(), rank);
+    let basis = a.select_cols(&cols);
+    let qr_b = MatQR::new(&basis);
+    assert!(qr_b.is_full_rank());
+    let x = qr_b.solve_ls(&a);
+    assert!((&basis * x - &a).norm_inf() < TOL);
+
+    // full rank gives the same least squares as without pivoting
+    let a = Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let b = Mat::new_vec(m).set_by(|_, _| xor64(&mut r) - 0.5);
+    let qr_p = MatQR::new_pivoted(&a);
+    assert_eq!(qr_p.rank(TOL), n);
+    assert!((qr_p.solve_ls(&b) - MatQR::new(&a).solve_ls(&b)).norm_inf() < TOL);
+
+    assert_eq!(MatQR::new_pivoted(&Mat::new(3, 2)).rank(TOL), 0);
+}
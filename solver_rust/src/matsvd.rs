@@ -11,6 +11,7 @@ const TOL_SINV: FP = FP_EPSILON;
 pub struct MatSVD
 {
     transposed: bool,
+    tol_zero2: FP,
     //
     u: Mat,
     s: Mat,
@@ -31,23 +32,31 @@ impl MatSVD
             (ncols, nrows)
         };
 
-        let svd = MatSVD {
+        MatSVD {
             transposed,
+            tol_zero2: 0.,
             u: Mat::new(u_nrows, u_ncols),
             s: Mat::new_vec(u_ncols),
             v: Mat::new(u_ncols, u_ncols).set_eye()
-        };
-
-        svd
+        }
+    }
+    //
+    // used by mat:
+    // columns of rounding noise in a rank-deficient matrix never get orthogonal,
+    // so those negligible in the Frobenius norm are left as converged
+    pub(crate) fn set_rank_revealing(mut self) -> MatSVD
+    {
+        self.tol_zero2 = TOL_CNV2;
+        self
     }
     //
-    fn apply_jacobi_rot(&mut self, c1: usize, c2: usize) -> bool
+    fn apply_jacobi_rot(&mut self, c1: usize, c2: usize, tol_zero: FP) -> bool
     {
         let a = self.u.col(c1).norm_p2sq();
         let b = self.u.col(c2).norm_p2sq();
         let d = self.u.col(c1).prod(&self.u.col(c2));
 
-        if d * d <= TOL_CNV2 * a * b {
+        if (d * d <= TOL_CNV2 * a * b) || (a.min(b) <= tol_zero) {
             true
         }
         else {
@@ -96,13 +105,16 @@ impl MatSVD
     {
         let (_, n) = self.u.size();
 
+        // rotations keep the Frobenius norm
+        let tol_zero = self.tol_zero2 * self.u.norm_p2sq();
+
         let mut converged_all = false;
         while !converged_all {
             converged_all = true;
 
             for i in 0 .. n - 1 {
                 for j in i + 1 .. n {
-                    if !self.apply_jacobi_rot(i, j) {converged_all = false;}
+                    if !self.apply_jacobi_rot(i, j, tol_zero) {converged_all = false;}
                 }
             }
         }
@@ -110,6 +122,12 @@ impl MatSVD
         self.norm_singular();
     }
     //
+    /// Resets the state for warm-start, as made by `new`.
+    pub fn reset_warm(&mut self)
+    {
+        self.v.assign_eye();
+    }
+    //
     /// Runs SVD of a specified matrix.
     pub fn decomp(&mut self, g: &Mat)
     {
@@ -163,6 +181,29 @@ impl MatSVD
         }
     }
     //
+    /// Returns the Moore-Penrose pseudo-inverse using the last SVD result,
+    /// where singular values not above `tol` times the largest one are taken as zero.
+    pub fn pinv(&self, tol: FP) -> Mat
+    {
+        let s_max = self.s.max().unwrap_or(0.);
+        let sinv = self.s.map(|s| if s <= tol * s_max {0.} else {1. / s});
+
+        if !self.transposed {
+            &self.v * (sinv.clone_diag() * self.u.t())
+        }
+        else {
+            &self.u * (sinv.clone_diag() * self.v.t())
+        }
+    }
+    //
+    // used by mat:
+    // right singular vectors, complete if not transposed
+    pub(crate) fn v(&self) -> &Mat
+    {
+        assert!(!self.transposed);
+        &self.v
+    }
+    //
     /// Returns singular values.
     pub fn s(&self) -> &Mat
     {
@@ -252,3 +293,22 @@ fn test_solve()
     let h_err = (h - vec).norm_p2sq() / ((h_size.0 * h_size.1) as FP);
     assert!(h_err < TOL_RMSE);
 }
+
+#[test]
+fn test_rank_deficient()
+{
+    // the third column goes to rounding noise by rotations
+    let mat = Mat::new(3, 3).set_iter(&[
+        1., 0., 1.,
+        0., 1., 1.,
+        0., 0., 0.
+    ]);
+
+    let mut svd = MatSVD::new(mat.size()).set_rank_revealing();
+    svd.decomp(&mat);
+    println!("s = {}", svd.s());
+
+    let g = &svd.u * svd.s.clone_diag() * svd.v.t();
+    assert!((g - &mat).norm_inf() < 1e-12);
+    assert_eq!(svd.s().iter().filter(|&s| *s < 1e-12).count(), 1);
+}
@@ -0,0 +1,1086 @@
+/*!
+Modeling layer
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+This module lets you write a problem in terms of [`Variable`](struct.Variable.html) handles
+and affine [`Expr`](struct.Expr.html) expressions instead of assembling \\(P\\), \\(G\\), \\(h\\) and so on by hand.
+[`Problem`](struct.Problem.html) lowers the expressions to stacked matrices,
+detects the problem class ([`ProblemClass`](enum.ProblemClass.html)) and dispatches to
+[`LP`](../lp/trait.LP.html), [`QP`](../qp/trait.QP.html) or [`SOCP`](../socp/trait.SOCP.html).
+
+Supported forms are
+* objective: affine scalar expression plus [`quad_form`](fn.quad_form.html) / [`sum_squares`](fn.sum_squares.html) terms,
+* constraints: affine `expr.le(rhs)`, `expr.ge(rhs)`, `expr.eq(rhs)` and
+  second-order cone `norm2(expr).le(t)`.
+
+Quadratic objective terms must be convex to minimize and concave to maximize.
+With second-order cone constraints, they are lifted into a rotated cone by an epigraph variable.
+
+[`Problem`](struct.Problem.html) of this module is not the [`Problem`](../problem/trait.Problem.html) trait of the prelude,
+so import it explicitly when both glob imports are used.
+
+# Example
+
+```
+use totsu::prelude::*;
+use totsu::modeling::*;
+use totsu::modeling::Problem;
+
+let x = Variable::new(2);
+
+// minimize (x0 - 1)^2 + (x1 - 2)^2 subject to x0 + x1 <= 1
+let a = Mat::new_vec(2).set_iter(&[1., 2.]);
+let ones = Mat::new(1, 2).set_all(1.);
+
+let rslt = Problem::minimize(sum_squares(x - &a))
+                   .subject_to(vec![(&ones * x).le(1.)])
+                   .solve().unwrap();
+
+let exp = Mat::new_vec(2).set_iter(&[0., 1.]);
+assert_eq!(rslt.class(), ProblemClass::QP);
+assert!((rslt.value(&x).unwrap() - exp).norm_p2() < 1e-6);
+```
+*/
+
+use super::mat::{MatGen, View};
+use super::prelude::*;
+use super::problem::SolverError;
+
+use std::io::Write;
+use std::ops::{Add, Sub, Mul, Neg, Range, RangeBounds, Bound};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static VARIABLE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Variable handle
+///
+/// A column vector of optimization variables.
+/// Each handle created by [`new`](#method.new) is distinct from every other one.
+#[derive(Debug, Clone, Copy)]
+pub struct Variable
+{
+    id: usize,
+    size: usize
+}
+
+impl Variable
+{
+    /// Makes a column vector variable.
+    pub fn new(size: usize) -> Variable
+    {
+        Variable {
+            id: VARIABLE_ID.fetch_add(1, Ordering::Relaxed),
+            size
+        }
+    }
+    /// Returns number of entries.
+    pub fn size(&self) -> usize
+    {
+        self.size
+    }
+    /// Affine expression of row entries.
+    pub fn rows<RR>(&self, rows: RR) -> Expr
+    where RR: RangeBounds<usize>
+    {
+        Expr::from(*self).rows(rows)
+    }
+    /// Affine expression of a row entry.
+    pub fn row(&self, r: usize) -> Expr
+    {
+        Expr::from(*self).row(r)
+    }
+    /// Constraint `self <= rhs`.
+    pub fn le<T: Into<Expr>>(&self, rhs: T) -> Constraint
+    {
+        Expr::from(*self).le(rhs)
+    }
+    /// Constraint `self >= rhs`.
+    pub fn ge<T: Into<Expr>>(&self, rhs: T) -> Constraint
+    {
+        Expr::from(*self).ge(rhs)
+    }
+    /// Constraint `self == rhs`.
+    pub fn eq<T: Into<Expr>>(&self, rhs: T) -> Constraint
+    {
+        Expr::from(*self).eq(rhs)
+    }
+}
+
+//
+
+/// Affine expression
+///
+/// A column vector \\( \\sum_j C_j x_j + d \\) where \\(x_j\\) are variables.
+#[derive(Debug, Clone)]
+pub struct Expr
+{
+    terms: Vec<(Variable, Mat)>,
+    constant: Mat
+}
+
+impl Expr
+{
+    // private helper methods
+    fn h_range<RR>(&self, rows: RR) -> Range<usize>
+    where RR: RangeBounds<usize>
+    {
+        let start = match rows.start_bound() {
+            Bound::Unbounded => 0,
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i + 1
+        };
+        let end = match rows.end_bound() {
+            Bound::Unbounded => self.nrows(),
+            Bound::Included(&i) => i + 1,
+            Bound::Excluded(&i) => i
+        };
+        assert!(start <= end && end <= self.nrows(), "rows {}..{} out of {} rows", start, end, self.nrows());
+
+        start .. end
+    }
+    //
+    fn h_broadcast(self, nrows: usize) -> Expr
+    {
+        if self.nrows() == 1 && nrows != 1 && self.terms.is_empty() {
+            Expr::constant(&Mat::new_vec(nrows).set_all(self.constant[(0, 0)]))
+        }
+        else {
+            self
+        }
+    }
+    //
+    fn h_add(mut self, rhs: Expr, sign: FP) -> Expr
+    {
+        assert_eq!(self.nrows(), rhs.nrows(), "expression rows mismatch");
+
+        for (var, coef) in rhs.terms {
+            if let Some(term) = self.terms.iter_mut().find(|(v, _)| v.id == var.id) {
+                term.1 += sign * coef;
+            }
+            else {
+                self.terms.push((var, sign * coef));
+            }
+        }
+        self.constant += sign * rhs.constant;
+
+        self
+    }
+    //
+    fn h_lower(&self, vars: &[(Variable, usize)], n: usize) -> (Mat, Mat)
+    {
+        let mut coef = Mat::new(self.nrows(), n);
+
+        for (var, c) in &self.terms {
+            let offset = h_offset(vars, var);
+            let mut coef_var = coef.cols_mut(offset .. offset + var.size);
+            coef_var += c;
+        }
+
+        (coef, self.constant.clone())
+    }
+    //
+    fn h_eval(&self, vars: &[(Variable, usize)], x: &Mat) -> Mat
+    {
+        let mut val = self.constant.clone();
+
+        for (var, c) in &self.terms {
+            let offset = h_offset(vars, var);
+            val += c * x.rows(offset .. offset + var.size);
+        }
+
+        val
+    }
+    //
+    /// Makes a constant expression from a column vector.
+    pub fn constant<V: View>(vec: &MatGen<V>) -> Expr
+    {
+        let (_, ncols) = vec.size();
+        assert_eq!(ncols, 1, "constant expression must be a column vector");
+
+        Expr {
+            terms: Vec::new(),
+            constant: vec.clone_sz()
+        }
+    }
+    /// Returns number of rows.
+    pub fn nrows(&self) -> usize
+    {
+        self.constant.size().0
+    }
+    /// Affine expression of row entries.
+    pub fn rows<RR>(&self, rows: RR) -> Expr
+    where RR: RangeBounds<usize>
+    {
+        let range = self.h_range(rows);
+
+        Expr {
+            terms: self.terms.iter().map(|(v, c)| (*v, c.rows(range.clone()).clone_sz())).collect(),
+            constant: self.constant.rows(range).clone_sz()
+        }
+    }
+    /// Affine expression of a row entry.
+    pub fn row(&self, r: usize) -> Expr
+    {
+        self.rows(r ..= r)
+    }
+    /// Sum of all rows.
+    pub fn sum(&self) -> Expr
+    {
+        Mat::new(1, self.nrows()).set_all(1.) * self
+    }
+    /// Constraint `self <= rhs`.
+    ///
+    /// A scalar constant `rhs` is broadcast to all rows.
+    pub fn le<T: Into<Expr>>(self, rhs: T) -> Constraint
+    {
+        let nrows = self.nrows();
+        Constraint::Le(self - rhs.into().h_broadcast(nrows))
+    }
+    /// Constraint `self >= rhs`.
+    ///
+    /// A scalar constant `rhs` is broadcast to all rows.
+    pub fn ge<T: Into<Expr>>(self, rhs: T) -> Constraint
+    {
+        let nrows = self.nrows();
+        Constraint::Le(rhs.into().h_broadcast(nrows) - self)
+    }
+    /// Constraint `self == rhs`.
+    ///
+    /// A scalar constant `rhs` is broadcast to all rows.
+    pub fn eq<T: Into<Expr>>(self, rhs: T) -> Constraint
+    {
+        let nrows = self.nrows();
+        Constraint::Eq(self - rhs.into().h_broadcast(nrows))
+    }
+}
+
+fn h_offset(vars: &[(Variable, usize)], var: &Variable) -> usize
+{
+    vars.iter().find(|(v, _)| v.id == var.id).map(|(_, o)| *o).unwrap()
+}
+
+impl From<Variable> for Expr
+{
+    fn from(var: Variable) -> Expr
+    {
+        Expr {
+            terms: vec![(var, Mat::new(var.size, var.size).set_eye())],
+            constant: Mat::new_vec(var.size)
+        }
+    }
+}
+
+impl From<&Variable> for Expr
+{
+    fn from(var: &Variable) -> Expr
+    {
+        Expr::from(*var)
+    }
+}
+
+impl From<&Expr> for Expr
+{
+    fn from(expr: &Expr) -> Expr
+    {
+        expr.clone()
+    }
+}
+
+impl From<Mat> for Expr
+{
+    fn from(vec: Mat) -> Expr
+    {
+        Expr::constant(&vec)
+    }
+}
+
+impl From<&Mat> for Expr
+{
+    fn from(vec: &Mat) -> Expr
+    {
+        Expr::constant(vec)
+    }
+}
+
+impl From<FP> for Expr
+{
+    fn from(value: FP) -> Expr
+    {
+        Expr::constant(&Mat::new_vec(1).set_all(value))
+    }
+}
+
+impl Neg for Expr
+{
+    type Output = Expr;
+
+    fn neg(self) -> Expr
+    {
+        -1. * self
+    }
+}
+
+impl<T: Into<Expr>> Add<T> for Expr
+{
+    type Output = Expr;
+
+    fn add(self, rhs: T) -> Expr
+    {
+        self.h_add(rhs.into(), 1.)
+    }
+}
+
+impl<T: Into<Expr>> Sub<T> for Expr
+{
+    type Output = Expr;
+
+    fn sub(self, rhs: T) -> Expr
+    {
+        self.h_add(rhs.into(), -1.)
+    }
+}
+
+impl<T: Into<Expr>> Add<T> for Variable
+{
+    type Output = Expr;
+
+    fn add(self, rhs: T) -> Expr
+    {
+        Expr::from(self) + rhs
+    }
+}
+
+impl<T: Into<Expr>> Sub<T> for Variable
+{
+    type Output = Expr;
+
+    fn sub(self, rhs: T) -> Expr
+    {
+        Expr::from(self) - rhs
+    }
+}
+
+impl Mul<Expr> for FP
+{
+    type Output = Expr;
+
+    fn mul(self, rhs: Expr) -> Expr
+    {
+        Expr {
+            terms: rhs.terms.into_iter().map(|(v, c)| (v, c * self)).collect(),
+            constant: rhs.constant * self
+        }
+    }
+}
+
+impl Mul<Variable> for FP
+{
+    type Output = Expr;
+
+    fn mul(self, rhs: Variable) -> Expr
+    {
+        self * Expr::from(rhs)
+    }
+}
+
+fn h_mat_mul_expr<V: View>(lhs: &MatGen<V>, rhs: &Expr) -> Expr
+{
+    let (_, l_ncols) = lhs.size();
+    assert_eq!(l_ncols, rhs.nrows(), "matrix columns and expression rows mismatch");
+
+    Expr {
+        terms: rhs.terms.iter().map(|(v, c)| (*v, lhs * c)).collect(),
+        constant: lhs * &rhs.constant
+    }
+}
+
+macro_rules! impl_mat_mul_expr {
+    ( $rhs: ty, $conv: expr ) => {
+        impl<V: View> Mul<$rhs> for MatGen<V>
+        {
+            type Output = Expr;
+
+            fn mul(self, rhs: $rhs) -> Expr
+            {
+                h_mat_mul_expr(&self, &$conv(rhs))
+            }
+        }
+
+        impl<V: View> Mul<$rhs> for &MatGen<V>
+        {
+            type Output = Expr;
+
+            fn mul(self, rhs: $rhs) -> Expr
+            {
+                h_mat_mul_expr(self, &$conv(rhs))
+            }
+        }
+    };
+}
+
+impl_mat_mul_expr!(Expr, |e| e);
+impl_mat_mul_expr!(&Expr, |e: &Expr| e.clone());
+impl_mat_mul_expr!(Variable, Expr::from);
+impl_mat_mul_expr!(&Variable, Expr::from);
+
+//
+
+/// Euclidean norm of an affine expression, only to make a second-order cone constraint
+#[derive(Debug, Clone)]
+pub struct Norm2(Expr);
+
+/// Euclidean norm of an affine expression.
+pub fn norm2<T: Into<Expr>>(arg: T) -> Norm2
+{
+    Norm2(arg.into())
+}
+
+impl Norm2
+{
+    /// Second-order cone constraint `norm2(arg) <= bound`.
+    pub fn le<T: Into<Expr>>(self, bound: T) -> Constraint
+    {
+        let bound = bound.into();
+        assert_eq!(bound.nrows(), 1, "bound of norm2 must be a scalar");
+
+        Constraint::Soc(self.0, bound)
+    }
+}
+
+/// Constraint
+#[derive(Debug, Clone)]
+pub enum Constraint
+{
+    /// Affine inequality `expr <= 0`.
+    Le(Expr),
+    /// Affine equality `expr == 0`.
+    Eq(Expr),
+    /// Second-order cone `norm2(arg) <= bound`.
+    Soc(Expr, Expr)
+}
+
+//
+
+/// Quadratic expression
+///
+/// A scalar \\( \\sum_k e_k^T W_k e_k + a \\) where \\(e_k\\) and \\(a\\) are affine expressions.
+#[derive(Debug, Clone)]
+pub struct QuadExpr
+{
+    forms: Vec<(Expr, Mat)>,
+    affine: Expr
+}
+
+/// Quadratic form \\( e^T W e \\).
+pub fn quad_form<T: Into<Expr>, V: View>(arg: T, mat_w: &MatGen<V>) -> QuadExpr
+{
+    let arg = arg.into();
+    assert_eq!(mat_w.size(), (arg.nrows(), arg.nrows()), "quad_form matrix size mismatch");
+
+    QuadExpr {
+        forms: vec![(arg, mat_w.clone_sz())],
+        affine: Expr::from(0.)
+    }
+}
+
+/// Sum of squares \\( e^T e \\).
+pub fn sum_squares<T: Into<Expr>>(arg: T) -> QuadExpr
+{
+    let arg = arg.into();
+    let eye = Mat::new(arg.nrows(), arg.nrows()).set_eye();
+
+    quad_form(arg, &eye)
+}
+
+impl QuadExpr
+{
+    // private helper methods
+    fn h_lower(&self, vars: &[(Variable, usize)], n: usize) -> (Mat, Mat, FP)
+    {
+        let (coef, cnst) = self.affine.h_lower(vars, n);
+        let mut mat_p = Mat::new(n, n);
+        let mut vec_q = coef.t().clone_sz();
+        let mut scl_r = cnst[(0, 0)];
+
+        for (e, w) in &self.forms {
+            let (coef, cnst) = e.h_lower(vars, n);
+            let w_sym = (w + w.t()) * 0.5;
+            let wc = &w_sym * &coef;
+            mat_p += 2. * coef.t() * &wc;
+            vec_q += 2. * wc.t() * &cnst;
+            scl_r += cnst.t().prod(&(&w_sym * &cnst).t());
+        }
+
+        (mat_p, vec_q, scl_r)
+    }
+    //
+    fn h_eval(&self, vars: &[(Variable, usize)], x: &Mat) -> FP
+    {
+        let mut val = self.affine.h_eval(vars, x)[(0, 0)];
+
+        for (e, w) in &self.forms {
+            let v = e.h_eval(vars, x);
+            val += v.prod(&(w * &v));
+        }
+
+        val
+    }
+}
+
+impl From<Expr> for QuadExpr
+{
+    fn from(affine: Expr) -> QuadExpr
+    {
+        assert_eq!(affine.nrows(), 1, "objective must be a scalar");
+
+        QuadExpr {
+            forms: Vec::new(),
+            affine
+        }
+    }
+}
+
+impl From<Variable> for QuadExpr
+{
+    fn from(var: Variable) -> QuadExpr
+    {
+        QuadExpr::from(Expr::from(var))
+    }
+}
+
+impl From<FP> for QuadExpr
+{
+    fn from(value: FP) -> QuadExpr
+    {
+        QuadExpr::from(Expr::from(value))
+    }
+}
+
+impl<T: Into<QuadExpr>> Add<T> for QuadExpr
+{
+    type Output = QuadExpr;
+
+    fn add(mut self, rhs: T) -> QuadExpr
+    {
+        let rhs = rhs.into();
+        self.forms.extend(rhs.forms);
+        self.affine = self.affine + rhs.affine;
+        self
+    }
+}
+
+impl<T: Into<QuadExpr>> Sub<T> for QuadExpr
+{
+    type Output = QuadExpr;
+
+    fn sub(self, rhs: T) -> QuadExpr
+    {
+        self + (-1.) * rhs.into()
+    }
+}
+
+impl Add<QuadExpr> for Expr
+{
+    type Output = QuadExpr;
+
+    fn add(self, rhs: QuadExpr) -> QuadExpr
+    {
+        rhs + self
+    }
+}
+
+impl Mul<QuadExpr> for FP
+{
+    type Output = QuadExpr;
+
+    fn mul(self, rhs: QuadExpr) -> QuadExpr
+    {
+        QuadExpr {
+            forms: rhs.forms.into_iter().map(|(e, w)| (e, w * self)).collect(),
+            affine: self * rhs.affine
+        }
+    }
+}
+
+//
+
+/// Problem class detected by [`Problem`](struct.Problem.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemClass
+{
+    /// Linear program
+    LP,
+    /// Quadratic program
+    QP,
+    /// Second-order cone program
+    SOCP
+}
+
+/// Problem built from expressions
+#[derive(Debug, Clone)]
+pub struct Problem
+{
+    objective: QuadExpr,
+    sense: Sense,
+    constraints: Vec<Constraint>
+}
+
+/// Solution of [`Problem`](struct.Problem.html)
+#[derive(Debug, Clone)]
+pub struct Solution
+{
+    class: ProblemClass,
+    vars: Vec<(Variable, usize)>,
+    x: Mat,
+    objective: FP
+}
+
+impl Problem
+{
+    // private helper methods
+    fn h_vars(&self) -> (Vec<(Variable, usize)>, usize)
+    {
+        let mut vars: Vec<(Variable, usize)> = Vec::new();
+        let mut n = 0;
+
+        let mut exprs: Vec<&Expr> = self.objective.forms.iter().map(|(e, _)| e).collect();
+        exprs.push(&self.objective.affine);
+        for c in &self.constraints {
+            match c {
+                Constraint::Le(e) | Constraint::Eq(e) => exprs.push(e),
+                Constraint::Soc(e, t) => {exprs.push(e); exprs.push(t);}
+            }
+        }
+
+        for e in exprs {
+            for (var, _) in &e.terms {
+                if !vars.iter().any(|(v, _)| v.id == var.id) {
+                    vars.push((*var, n));
+                    n += var.size;
+                }
+            }
+        }
+
+        (vars, n)
+    }
+    //
+    fn h_stack<'a, I>(exprs: I, vars: &[(Variable, usize)], n: usize) -> (Mat, Mat)
+    where I: Iterator<Item=&'a Expr> + Clone
+    {
+        let m = exprs.clone().map(|e| e.nrows()).sum();
+        let mut mat = Mat::new(m, n);
+        let mut vec = Mat::new_vec(m);
+
+        let mut r = 0;
+        for e in exprs {
+            let (coef, cnst) = e.h_lower(vars, n);
+            mat.rows_mut(r .. r + e.nrows()).assign(&coef);
+            vec.rows_mut(r .. r + e.nrows()).assign(&(-cnst));
+            r += e.nrows();
+        }
+
+        (mat, vec)
+    }
+    //
+    /// Makes a problem minimizing `objective`.
+    pub fn minimize<T: Into<QuadExpr>>(objective: T) -> Problem
+    {
+        Problem {
+            objective: objective.into(),
+            sense: Sense::Minimize,
+            constraints: Vec::new()
+        }
+    }
+    /// Makes a problem maximizing `objective`.
+    pub fn maximize<T: Into<QuadExpr>>(objective: T) -> Problem
+    {
+        Problem {
+            objective: objective.into(),
+            sense: Sense::Maximize,
+            constraints: Vec::new()
+        }
+    }
+    /// Adds constraints.
+    pub fn subject_to<I>(mut self, constraints: I) -> Problem
+    where I: IntoIterator<Item=Constraint>
+    {
+        self.constraints.extend(constraints);
+        self
+    }
+    /// Detects the problem class.
+    pub fn class(&self) -> ProblemClass
+    {
+        if self.constraints.iter().any(|c| matches!(c, Constraint::Soc(..))) {
+            ProblemClass::SOCP
+        }
+        else if !self.objective.forms.is_empty() {
+            ProblemClass::QP
+        }
+        else {
+            ProblemClass::LP
+        }
+    }
+    /// Solves with default parameters.
+    pub fn solve(&self) -> Result<Solution, SolverError>
+    {
+        self.solve_with(&PDIPMParam::default(), &mut std::io::sink())
+    }
+    /// Solves with given parameters.
+    ///
+    /// Returns `Ok` with [`Solution`](struct.Solution.html) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    pub fn solve_with<L>(&self, param: &PDIPMParam, log: &mut L) -> Result<Solution, SolverError>
+    where L: Write
+    {
+        let (vars, n) = self.h_vars();
+        if n == 0 {return Err(SolverError::InvalidProblem("problem: no variables".into()));}
+
+        let class = self.class();
+        let (mat_p, vec_q, _) = self.objective.h_lower(&vars, n);
+        let (mat_p, vec_q) = (self.sense.sign() * mat_p, self.sense.sign() * vec_q);
+
+        // the quadratic terms to be minimized must be convex, P = F F^T
+        let fac_p = if self.objective.forms.is_empty() {
+            None
+        }
+        else {
+            let curv = match self.sense {
+                Sense::Minimize => "convex",
+                Sense::Maximize => "concave"
+            };
+            let fac_p = mat_p.chol_factor_or_sqrt()
+                             .map_err(|e| SolverError::InvalidProblem(format!("objective: quadratic terms not {}, {}", curv, e)))?;
+            Some(fac_p)
+        };
+
+        let les = self.constraints.iter().filter_map(|c| if let Constraint::Le(e) = c {Some(e)} else {None});
+        let eqs = self.constraints.iter().filter_map(|c| if let Constraint::Eq(e) = c {Some(e)} else {None});
+
+        let (mat_g, vec_h) = Problem::h_stack(les, &vars, n);
+        let (mat_a, vec_b) = Problem::h_stack(eqs, &vars, n);
+
+        let mut solver = PDIPM::new();
+
+        let x = match class {
+            ProblemClass::LP => {
+                solver.solve_lp(param, log,
+                                &vec_q,
+                                &mat_g, &vec_h,
+                                &mat_a, &vec_b)?
+            },
+            ProblemClass::QP => {
+                solver.solve_qp(param, log,
+                                &mat_p, &vec_q,
+                                &mat_g, &vec_h,
+                                &mat_a, &vec_b)?
+            },
+            ProblemClass::SOCP => {
+                // an epigraph variable s of the quadratic terms follows x if any
+                let n_s = if fac_p.is_some() {n + 1} else {n};
+                let widen = |mat: &Mat| {
+                    let mut wide = Mat::new(mat.size().0, n_s);
+                    wide.cols_mut(0 .. n).assign(mat);
+                    wide
+                };
+
+                let (m, _) = mat_g.size();
+                let mut socp_g = Vec::new();
+                let mut socp_h = Vec::new();
+                let mut socp_c = Vec::new();
+                let mut socp_d = Vec::new();
+
+                // affine inequalities as cones of zero dimension
+                for r in 0 .. m {
+                    socp_g.push(Mat::new(0, n_s));
+                    socp_h.push(Mat::new_vec(0));
+                    socp_c.push(-widen(&mat_g.row(r).clone_sz()).t());
+                    socp_d.push(vec_h[(r, 0)]);
+                }
+
+                for c in &self.constraints {
+                    if let Constraint::Soc(e, t) = c {
+                        let (coef, cnst) = e.h_lower(&vars, n);
+                        let (coef_t, cnst_t) = t.h_lower(&vars, n);
+                        socp_g.push(widen(&coef));
+                        socp_h.push(cnst);
+                        socp_c.push(widen(&coef_t).t().clone_sz());
+                        socp_d.push(cnst_t[(0, 0)]);
+                    }
+                }
+
+                let mut vec_f = widen(&vec_q.t().clone_sz()).t().clone_sz();
+
+                // (1/2) x^T P x <= s as the rotated cone ||F^T x||^2 <= 2 s,
+                // that is ||(F^T x, s - 1/2)|| <= s + 1/2
+                if let Some(fac_p) = &fac_p {
+                    let mut g = Mat::new(n + 1, n_s);
+                    g.slice_mut(0 .. n, 0 .. n).assign(&fac_p.t());
+                    g[(n, n)] = 1.;
+                    let mut h = Mat::new_vec(n + 1);
+                    h[(n, 0)] = -0.5;
+                    let mut c = Mat::new_vec(n_s);
+                    c[(n, 0)] = 1.;
+
+                    socp_g.push(g);
+                    socp_h.push(h);
+                    socp_c.push(c);
+                    socp_d.push(0.5);
+                    vec_f[(n, 0)] = 1.;
+                }
+
+                solver.solve_socp(param, log,
+                                  &vec_f,
+                                  &socp_g, &socp_h, &socp_c, &socp_d,
+                                  &widen(&mat_a), &vec_b)?
+            }
+        };
+
+        let objective = self.objective.h_eval(&vars, &x);
+
+        Ok(Solution {
+            class,
+            vars,
+            x,
+            objective
+        })
+    }
+}
+
+impl Solution
+{
+    /// Returns the detected problem class.
+    pub fn class(&self) -> ProblemClass
+    {
+        self.class
+    }
+    /// Returns the optimal objective value.
+    pub fn objective(&self) -> FP
+    {
+        self.objective
+    }
+    /// Returns the optimal value of a variable,
+    /// or `None` if the variable does not appear in the problem.
+    pub fn value(&self, var: &Variable) -> Option<Mat>
+    {
+        self.vars.iter().find(|(v, _)| v.id == var.id)
+                 .map(|(_, o)| self.x.rows(*o .. *o + var.size).clone_sz())
+    }
+    /// Evaluates an affine expression at the solution.
+    pub fn eval(&self, expr: &Expr) -> Option<Mat>
+    {
+        if expr.terms.iter().all(|(var, _)| self.vars.iter().any(|(v, _)| v.id == var.id)) {
+            Some(expr.h_eval(&self.vars, &self.x))
+        }
+        else {
+            None
+        }
+    }
+}
+
+//
+
+#[test]
+fn test_portfolio()
+{
+    let n: usize = 3;
+    let gamma = 0.5;
+
+    let mu = Mat::new_vec(n).set_iter(&[
+        0.10, 0.07, 0.03
+    ]);
+    let sigma = Mat::new(n, n).set_iter(&[
+        0.20, 0.02, 0.00,
+        0.02, 0.10, 0.01,
+        0.00, 0.01, 0.05
+    ]);
+
+    // modeling
+    let x = Variable::new(n);
+    let ones = Mat::new(1, n).set_all(1.);
+
+    let rslt = Problem::minimize(gamma * quad_form(x, &sigma) - mu.t() * x)
+                       .subject_to(vec![
+                           (&ones * x).eq(1.),
+                           x.ge(0.)
+                       ])
+                       .solve().unwrap();
+    assert_eq!(rslt.class(), ProblemClass::QP);
+    let x_dsl = rslt.value(&x).unwrap();
+
+    // hand-assembled
+    let mat_p = 2. * gamma * &sigma;
+    let vec_q = -&mu;
+    let mat_g = -Mat::new(n, n).set_eye();
+    let vec_h = Mat::new_vec(n);
+    let mat_a = ones.clone();
+    let vec_b = Mat::new_vec(1).set_all(1.);
+
+    let param = PDIPMParam::default();
+    let x_hand = PDIPM::new().solve_qp(&param, &mut std::io::sink(),
+                                       &mat_p, &vec_q,
+                                       &mat_g, &vec_h,
+                                       &mat_a, &vec_b).unwrap();
+
+    println!("x_dsl = {}", x_dsl);
+    println!("x_hand = {}", x_hand);
+    assert!((&x_dsl - &x_hand).norm_p2() < param.eps.sqrt());
+
+    let obj_hand = 0.5 * x_hand.prod(&(&mat_p * &x_hand)) + vec_q.prod(&x_hand);
+    assert!((rslt.objective() - obj_hand).abs() < param.eps.sqrt());
+}
+
+#[test]
+fn test_robust_ls()
+{
+    let n: usize = 2;
+    let k: usize = 3;
+    let rho = 0.5;
+
+    let mat_a = Mat::new(k, n).set_iter(&[
+        1., 0.,
+        1., 1.,
+        0., 2.
+    ]);
+    let vec_b = Mat::new_vec(k).set_iter(&[
+        1., 2., 2.
+    ]);
+
+    // the SOCP solver needs several hundred iterations for this instance
+    let param = PDIPMParam {
+        n_loop: 1024,
+        .. Default::default()
+    };
+
+    // modeling: minimize ||A x - b|| + rho ||x||
+    let x = Variable::new(n);
+    let t = Variable::new(2);
+
+    let rslt = Problem::minimize(t.row(0) + rho * t.row(1))
+                       .subject_to(vec![
+                           norm2(&mat_a * x - &vec_b).le(t.row(0)),
+                           norm2(x).le(t.row(1))
+                       ])
+                       .solve_with(&param, &mut std::io::sink()).unwrap();
+    assert_eq!(rslt.class(), ProblemClass::SOCP);
+    let x_dsl = rslt.value(&x).unwrap();
+
+    // hand-assembled: variables (x, t0, t1)
+    let vec_f = Mat::new_vec(n + 2).set_iter(&[
+        0., 0., 1., rho
+    ]);
+    let mut mat_g = vec![Mat::new(k, n + 2), Mat::new(n, n + 2)];
+    mat_g[0].cols_mut(0 .. n).assign(&mat_a);
+    mat_g[1].cols_mut(0 .. n).assign_eye();
+    let vec_h = vec![-&vec_b, Mat::new_vec(n)];
+    let mut vec_c = vec![Mat::new_vec(n + 2); 2];
+    vec_c[0][(n, 0)] = 1.;
+    vec_c[1][(n + 1, 0)] = 1.;
+    let scl_d = vec![0.; 2];
+
+    let y_hand = PDIPM::new().solve_socp(&param, &mut std::io::sink(),
+                                         &vec_f,
+                                         &mat_g, &vec_h, &vec_c, &scl_d,
+                                         &Mat::new(0, n + 2), &Mat::new_vec(0)).unwrap();
+    let x_hand = y_hand.rows(0 .. n);
+
+    println!("x_dsl = {}", x_dsl);
+    println!("x_hand = {}", x_hand);
+    assert!((&x_dsl - x_hand).norm_p2() < param.eps.sqrt());
+}
+
+#[test]
+fn test_quad_socp()
+{
+    let a = Mat::new_vec(2).set_iter(&[
+        2., 1.
+    ]);
+
+    // minimize ||x - a||^2 subject to ||x|| <= 1, the projection onto the unit ball
+    let x = Variable::new(2);
+    let rslt = Problem::minimize(sum_squares(x - &a))
+                       .subject_to(vec![
+                           norm2(x).le(1.)
+                       ])
+                       .solve().unwrap();
+    assert_eq!(rslt.class(), ProblemClass::SOCP);
+
+    let exp = &a / a.norm_p2();
+    println!("x = {}", rslt.value(&x).unwrap());
+    assert!((rslt.value(&x).unwrap() - &exp).norm_p2() < 1e-6);
+    assert!((rslt.objective() - (a.norm_p2() - 1.).powi(2)).abs() < 1e-6);
+
+    // the same by maximizing the negated objective
+    let rslt = Problem::maximize(-1. * sum_squares(x - &a))
+                       .subject_to(vec![
+                           norm2(x).le(1.)
+                       ])
+                       .solve().unwrap();
+    assert!((rslt.value(&x).unwrap() - &exp).norm_p2() < 1e-6);
+}
+
+#[test]
+fn test_curvature()
+{
+    let x = Variable::new(2);
+
+    for prob in [Problem::maximize(sum_squares(x)).subject_to(vec![x.le(1.)]),
+                 Problem::minimize(-1. * sum_squares(x)).subject_to(vec![x.le(1.)]),
+                 Problem::maximize(sum_squares(x)).subject_to(vec![norm2(x).le(1.)])] {
+        match prob.solve() {
+            Err(SolverError::InvalidProblem(msg)) => {
+                println!("{}", msg);
+                assert!(msg.starts_with("objective: quadratic terms not"));
+            },
+            rslt => panic!("{:?}", rslt)
+        }
+    }
+
+    let w = Mat::new(2, 2).set_iter(&[
+        1., 0.,
+        0., -1.
+    ]);
+    // indefinite
+    match Problem::minimize(quad_form(x, &w)).subject_to(vec![x.le(1.)]).solve() {
+        Err(SolverError::InvalidProblem(msg)) => assert!(msg.starts_with("objective: quadratic terms not convex")),
+        rslt => panic!("{:?}", rslt)
+    }
+}
+
+#[test]
+fn test_lp_class()
+{
+    let x = Variable::new(2);
+    let c = Mat::new_vec(2).set_iter(&[
+        1., 1.
+    ]);
+
+    // minimize x0 + x1 subject to x0 >= 1, x1 >= 2, x0 <= 5
+    let rslt = Problem::minimize(c.t() * x)
+                       .subject_to(vec![
+                           x.row(0).ge(1.),
+                           x.row(1).ge(2.),
+                           x.row(0).le(5.)
+                       ])
+                       .solve().unwrap();
+    assert_eq!(rslt.class(), ProblemClass::LP);
+
+    let exp = Mat::new_vec(2).set_iter(&[
+        1., 2.
+    ]);
+    println!("x = {}", rslt.value(&x).unwrap());
+    assert!((rslt.value(&x).unwrap() - exp).norm_p2() < 1e-6);
+    assert!((rslt.objective() - 3.).abs() < 1e-6);
+    assert!(rslt.value(&Variable::new(1)).is_none());
+
+    // maximize -x0 - x1 under the same constraints
+    let rslt = Problem::maximize(-(c.t() * x))
+                       .subject_to(vec![
+                           x.row(0).ge(1.),
+                           x.row(1).ge(2.),
+                           x.row(0).le(5.)
+                       ])
+                       .solve().unwrap();
+    assert!((rslt.objective() - (-3.)).abs() < 1e-6);
+}
@@ -0,0 +1,626 @@
+/*!
+Linear model predictive control
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+[`MPCProblem`](struct.MPCProblem.html) is a finite-horizon optimal control problem
+\\[
+\\begin{array}{ll}
+{\\rm minimize} & \\sum_{k=0}^{N-1} {1 \\over 2} u_k^T R_k u_k + \\sum_{k=1}^{N} {1 \\over 2} x_k^T Q_k x_k \\\\
+{\\rm subject \\ to} & x_{k+1} = A_k x_k + B_k u_k \\quad (k = 0, \\ldots, N - 1) \\\\
+& u_{\\rm min} \\preceq u_k \\preceq u_{\\rm max} \\\\
+& x_{\\rm min} \\preceq x_k \\preceq x_{\\rm max},
+\\end{array}
+\\]
+where
+- variables \\( u_k \\in {\\bf R}^m \\) and \\( x_k \\in {\\bf R}^n \\) with the given initial state \\( x_0 \\)
+- \\( Q_k \\in {\\bf S}_+^n \\), \\( R_k \\in {\\bf S}_{++}^m \\)
+- infinite elements of the bounds are absent.
+
+It is solved in either [`MPCMode`](enum.MPCMode.html):
+- `Condensed` eliminates the states into a dense [`QPProblem`](../qp/struct.QPProblem.html) of the inputs,
+  which costs \\( O(N^3 m^3) \\) per iteration.
+- `Structured` keeps the states and the dynamics, and solves the block-banded KKT system of each interior-point iteration
+  by a Riccati recursion, which costs \\( O(N (n^3 + m^3)) \\) per iteration.
+
+```
+use totsu::prelude::*;
+use totsu::mpc::*;
+
+// double integrator from (1, 0) with |u| <= 1
+let mat_a = Mat::new(2, 2).set_iter(&[
+    1., 0.1,
+    0., 1.
+]);
+let mat_b = Mat::new(2, 1).set_iter(&[
+    0.005,
+    0.1
+]);
+let prob = MPCProblem::new_lti(Mat::new_vec(2).set_iter(&[1., 0.]),
+                               &mat_a, &mat_b, &Mat::new(2, 2).set_eye(), &Mat::new(1, 1).set_all(0.1), 20)
+                      .with_input_bounds(Mat::new_vec(1).set_all(-1.), Mat::new_vec(1).set_all(1.));
+
+let param = PDIPMParam::default();
+let sol_s = prob.solve(&param, MPCMode::Structured).unwrap();
+let sol_c = prob.solve(&param, MPCMode::Condensed).unwrap();
+assert!((&sol_s.u[0] - &sol_c.u[0]).norm_p2() < 1e-6);
+```
+*/
+
+use super::prelude::*;
+use super::matsvd::MatSVD;
+use super::problem::{check_dim, check_col_vec};
+
+/// Mode to solve [`MPCProblem`](struct.MPCProblem.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MPCMode
+{
+    /// Dense QP of the inputs by [`PDIPM`](../pdipm/struct.PDIPM.html).
+    Condensed,
+    /// Interior-point iterations with a Riccati recursion over the stages.
+    Structured
+}
+
+/// Linear MPC problem
+#[derive(Debug, Clone)]
+pub struct MPCProblem
+{
+    /// Initial state \\(x_0\\).
+    pub x0: Mat,
+    /// \\(A_k\\) for each stage.
+    pub mat_a: Vec<Mat>,
+    /// \\(B_k\\) for each stage.
+    pub mat_b: Vec<Mat>,
+    /// \\(Q_k\\) of \\(x_{k+1}\\) for each stage.
+    pub mat_q: Vec<Mat>,
+    /// \\(R_k\\) for each stage.
+    pub mat_r: Vec<Mat>,
+    /// \\(u_{\\rm min}\\), \\(u_{\\rm max}\\).
+    pub input_bounds: (Mat, Mat),
+    /// \\(x_{\\rm min}\\), \\(x_{\\rm max}\\).
+    pub state_bounds: (Mat, Mat)
+}
+
+/// Solution of [`MPCProblem`](struct.MPCProblem.html)
+#[derive(Debug, Clone)]
+pub struct MPCSolution
+{
+    /// Optimal inputs \\(u_0, \\ldots, u_{N-1}\\).
+    pub u: Vec<Mat>,
+    /// Optimal states \\(x_0, \\ldots, x_N\\).
+    pub x: Vec<Mat>,
+    /// Optimal objective value.
+    pub objective: FP,
+    /// Number of iterations.
+    pub iterations: usize,
+    /// Status of termination.
+    pub status: SolverStatus
+}
+
+fn h_unbounded(n: usize) -> (Mat, Mat)
+{
+    (Mat::new_vec(n).set_all(FP::NEG_INFINITY), Mat::new_vec(n).set_all(FP::INFINITY))
+}
+
+impl MPCProblem
+{
+    /// Creates a problem without bounds.
+    /// * `x0` is \\(x_0\\).
+    /// * `mat_a`, `mat_b`, `mat_q`, `mat_r` are \\(A_k\\), \\(B_k\\), \\(Q_k\\), \\(R_k\\) of \\(k = 0, \\ldots, N - 1\\),
+    ///   where \\(Q_k\\) weights \\(x_{k+1}\\).
+    pub fn new(x0: Mat, mat_a: Vec<Mat>, mat_b: Vec<Mat>, mat_q: Vec<Mat>, mat_r: Vec<Mat>) -> MPCProblem
+    {
+        let (n, _) = x0.size();
+        let m = mat_b.first().map_or(0, |b| b.size().1);
+
+        MPCProblem {
+            x0, mat_a, mat_b, mat_q, mat_r,
+            input_bounds: h_unbounded(m),
+            state_bounds: h_unbounded(n)
+        }
+    }
+
+    /// Creates a time-invariant problem without bounds.
+    /// * `x0` is \\(x_0\\).
+    /// * `mat_a`, `mat_b`, `mat_q`, `mat_r` are \\(A_k\\), \\(B_k\\), \\(Q_k\\), \\(R_k\\) common to all stages.
+    /// * `horizon` is \\(N\\).
+    pub fn new_lti(x0: Mat, mat_a: &Mat, mat_b: &Mat, mat_q: &Mat, mat_r: &Mat, horizon: usize) -> MPCProblem
+    {
+        MPCProblem::new(x0,
+                        vec![mat_a.clone(); horizon], vec![mat_b.clone(); horizon],
+                        vec![mat_q.clone(); horizon], vec![mat_r.clone(); horizon])
+    }
+
+    /// Sets bounds of inputs, whose elements may be infinite.
+    pub fn with_input_bounds(mut self, u_min: Mat, u_max: Mat) -> MPCProblem
+    {
+        self.input_bounds = (u_min, u_max);
+        self
+    }
+
+    /// Sets bounds of states \\(x_1, \\ldots, x_N\\), whose elements may be infinite.
+    pub fn with_state_bounds(mut self, x_min: Mat, x_max: Mat) -> MPCProblem
+    {
+        self.state_bounds = (x_min, x_max);
+        self
+    }
+
+    /// Returns the horizon \\(N\\).
+    pub fn horizon(&self) -> usize
+    {
+        self.mat_a.len()
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let (n, _) = self.x0.size();
+        let nh = self.horizon();
+        let m = self.input_bounds.0.size().0;
+
+        if nh == 0 {return Err(SolverError::InvalidProblem("mat_a: horizon 0".into()));}
+        check_col_vec("x0", &self.x0)?;
+        check_dim("mat_b stages", self.mat_b.len(), "mat_a stages", nh)?;
+        check_dim("mat_q stages", self.mat_q.len(), "mat_a stages", nh)?;
+        check_dim("mat_r stages", self.mat_r.len(), "mat_a stages", nh)?;
+
+        for k in 0 .. nh {
+            check_dim(&format!("mat_a[{}] rows", k), self.mat_a[k].size().0, "x0 rows", n)?;
+            check_dim(&format!("mat_a[{}] columns", k), self.mat_a[k].size().1, "x0 rows", n)?;
+            check_dim(&format!("mat_b[{}] rows", k), self.mat_b[k].size().0, "x0 rows", n)?;
+            check_dim(&format!("mat_b[{}] columns", k), self.mat_b[k].size().1, "input_bounds rows", m)?;
+            check_dim(&format!("mat_q[{}] rows", k), self.mat_q[k].size().0, "x0 rows", n)?;
+            check_dim(&format!("mat_q[{}] columns", k), self.mat_q[k].size().1, "x0 rows", n)?;
+            check_dim(&format!("mat_r[{}] rows", k), self.mat_r[k].size().0, "input_bounds rows", m)?;
+            check_dim(&format!("mat_r[{}] columns", k), self.mat_r[k].size().1, "input_bounds rows", m)?;
+        }
+
+        for (name, (lo, hi), len) in [("input_bounds", &self.input_bounds, m), ("state_bounds", &self.state_bounds, n)].iter() {
+            check_col_vec(name, lo)?;
+            check_col_vec(name, hi)?;
+            check_dim(&format!("{} upper rows", name), hi.size().0, &format!("{} lower rows", name), *len)?;
+            for i in 0 .. *len {
+                if lo[(i, 0)].is_nan() || hi[(i, 0)].is_nan() || (lo[(i, 0)] >= hi[(i, 0)]) {
+                    return Err(SolverError::InvalidProblem(format!("{}: lower >= upper at {}", name, i)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn h_objective(&self, u: &[Mat], x: &[Mat]) -> FP
+    {
+        (0 .. self.horizon()).map(|k| {
+            u[k].prod(&(&self.mat_r[k] * &u[k])) / 2. + x[k + 1].prod(&(&self.mat_q[k] * &x[k + 1])) / 2.
+        }).sum()
+    }
+
+    /// Solves the problem.
+    ///
+    /// Returns `Ok` with [`MPCSolution`](struct.MPCSolution.html) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters, of which `Structured` uses `eps`, `mu`, `s_coef`, `margin`, `n_loop` and `max_iter`.
+    /// * `mode` is how to solve.
+    pub fn solve(&self, param: &PDIPMParam, mode: MPCMode) -> Result<MPCSolution, SolverError>
+    {
+        self.validate()?;
+
+        match mode {
+            MPCMode::Condensed => self.h_solve_condensed(param),
+            MPCMode::Structured => self.h_solve_structured(param)
+        }
+    }
+
+    fn h_solve_condensed(&self, param: &PDIPMParam) -> Result<MPCSolution, SolverError>
+    {
+        let (n, _) = self.x0.size();
+        let nh = self.horizon();
+        let m = self.input_bounds.0.size().0;
+
+        // stacked states X = S U + T x0
+        let mut mat_s = Mat::new(nh * n, nh * m);
+        let mut mat_t = Mat::new(nh * n, n);
+        let mut phi = Mat::new(n, n).set_eye();
+        for k in 0 .. nh {
+            phi = &self.mat_a[k] * &phi;
+            mat_t.rows_mut(k * n .. (k + 1) * n).assign(&phi);
+
+            mat_s.slice_mut(k * n .. (k + 1) * n, k * m .. (k + 1) * m).assign(&self.mat_b[k]);
+            for j in 0 .. k {
+                let prev = mat_s.slice(k * n - n .. k * n, j * m .. (j + 1) * m).clone_sz();
+                mat_s.slice_mut(k * n .. (k + 1) * n, j * m .. (j + 1) * m).assign(&(&self.mat_a[k] * prev));
+            }
+        }
+        let x_free = &mat_t * &self.x0;
+
+        let mut mat_qb = Mat::new(nh * n, nh * n);
+        let mut mat_rb = Mat::new(nh * m, nh * m);
+        for k in 0 .. nh {
+            mat_qb.slice_mut(k * n .. (k + 1) * n, k * n .. (k + 1) * n).assign(&self.mat_q[k]);
+            mat_rb.slice_mut(k * m .. (k + 1) * m, k * m .. (k + 1) * m).assign(&self.mat_r[k]);
+        }
+        let mat_p = mat_s.t() * &mat_qb * &mat_s + mat_rb;
+        let vec_q = mat_s.t() * (&mat_qb * &x_free);
+
+        // finite bounds as G U <= h
+        let mut rows: Vec<(Mat, FP)> = Vec::new();
+        for k in 0 .. nh {
+            for i in 0 .. m {
+                let e = Mat::new(1, nh * m).set_by(|_, c| if c == k * m + i {1.} else {0.});
+                let (lo, hi) = (self.input_bounds.0[(i, 0)], self.input_bounds.1[(i, 0)]);
+                if hi.is_finite() {
+                    rows.push((e.clone(), hi));
+                }
+                if lo.is_finite() {
+                    rows.push((-e, -lo));
+                }
+            }
+            for i in 0 .. n {
+                let s = mat_s.row(k * n + i).clone_sz();
+                let xf = x_free[(k * n + i, 0)];
+                let (lo, hi) = (self.state_bounds.0[(i, 0)], self.state_bounds.1[(i, 0)]);
+                if hi.is_finite() {
+                    rows.push((s.clone(), hi - xf));
+                }
+                if lo.is_finite() {
+                    rows.push((-s, xf - lo));
+                }
+            }
+        }
+        let mut mat_g = Mat::new(rows.len(), nh * m);
+        let mut vec_h = Mat::new_vec(rows.len());
+        for (i, (g, h)) in rows.iter().enumerate() {
+            mat_g.row_mut(i).assign(g);
+            vec_h[(i, 0)] = *h;
+        }
+
+        let qp = QPProblem::new(mat_p, vec_q, mat_g, vec_h, Mat::new(0, nh * m), Mat::new_vec(0));
+        let rslt = qp.solve(param)?;
+
+        let vec_x = &mat_s * &rslt.x + x_free;
+        let u: Vec<Mat> = (0 .. nh).map(|k| rslt.x.rows(k * m .. (k + 1) * m).clone_sz()).collect();
+        let mut x = vec![self.x0.clone()];
+        x.extend((0 .. nh).map(|k| vec_x.rows(k * n .. (k + 1) * n).clone_sz()));
+
+        Ok(MPCSolution {
+            objective: self.h_objective(&u, &x),
+            u, x,
+            iterations: rslt.iterations,
+            status: rslt.status
+        })
+    }
+
+    fn h_solve_structured(&self, param: &PDIPMParam) -> Result<MPCSolution, SolverError>
+    {
+        let (n, _) = self.x0.size();
+        let nh = self.horizon();
+        let m = self.input_bounds.0.size().0;
+        let (a, b, q, r) = (&self.mat_a, &self.mat_b, &self.mat_q, &self.mat_r);
+
+        let mut u: Vec<Mat> = vec![Stage::start(&self.input_bounds); nh];
+        let mut x: Vec<Mat> = vec![Stage::start(&self.state_bounds); nh + 1];
+        x[0] = self.x0.clone();
+        let mut nu = vec![Mat::new_vec(n); nh];
+        let mut su: Vec<Stage> = (0 .. nh).map(|_| Stage::new(&self.input_bounds, param.margin)).collect();
+        // sx[k] for x[k + 1]
+        let mut sx: Vec<Stage> = (0 .. nh).map(|_| Stage::new(&self.state_bounds, param.margin)).collect();
+        let m_ineq: usize = su.iter().chain(sx.iter()).map(|s| s.count()).sum();
+
+        // Riccati recursion workspace
+        let mut mat_p = vec![Mat::new(n, n); nh + 1];
+        let mut vec_p = vec![Mat::new_vec(n); nh + 1];
+        let mut mat_k = vec![Mat::new(m, n); nh];
+        let mut vec_k = vec![Mat::new_vec(m); nh];
+        let mut svd = MatSVD::new((m, m));
+
+        let mut status = SolverStatus::Converged;
+        let mut iterations = 0;
+        loop {
+            // ----- residuals
+
+            for k in 0 .. nh {
+                su[k].update(&self.input_bounds, &u[k]);
+                sx[k].update(&self.state_bounds, &x[k + 1]);
+            }
+            let eta: FP = su.iter().chain(sx.iter()).map(|s| s.gap()).sum();
+            let inv_t = if m_ineq > 0 {eta / (param.mu * m_ineq as FP)} else {0.};
+
+            // dynamics x[k + 1] - A x[k] - B u[k]
+            let r_pri: Vec<Mat> = (0 .. nh).map(|k| &x[k + 1] - &a[k] * &x[k] - &b[k] * &u[k]).collect();
+            let r_dual_u: Vec<Mat> = (0 .. nh).map(|k| &r[k] * &u[k] - b[k].t() * &nu[k] + su[k].d_lmd()).collect();
+            let r_dual_x: Vec<Mat> = (0 .. nh).map(|k| {
+                let mut rd = &q[k] * &x[k + 1] + &nu[k] + sx[k].d_lmd();
+                if k + 1 < nh {
+                    rd -= a[k + 1].t() * &nu[k + 1];
+                }
+                rd
+            }).collect();
+
+            let r_pri_norm = FP::sqrt(r_pri.iter().map(|v| v.norm_p2sq()).sum());
+            let r_dual_norm = FP::sqrt(r_dual_u.iter().chain(r_dual_x.iter()).map(|v| v.norm_p2sq()).sum());
+            if (r_dual_norm <= param.eps) && (r_pri_norm <= param.eps) && (eta <= param.eps) {
+                break;
+            }
+            if iterations >= param.max_iter.unwrap_or(param.n_loop) {
+                status = SolverStatus::MaxIterReached;
+                break;
+            }
+
+            // ----- Riccati recursion of the Newton step, with barrier Hessians and right-hand sides
+
+            mat_p[nh] = &q[nh - 1] + sx[nh - 1].hessian();
+            vec_p[nh] = -&r_dual_x[nh - 1] + sx[nh - 1].rhs(inv_t);
+            for k in (0 .. nh).rev() {
+                let pb = &mat_p[k + 1] * &b[k];
+                let mat_m = &r[k] + su[k].hessian() + b[k].t() * &pb;
+                svd.decomp(&mat_m);
+
+                let rhs_u = -&r_dual_u[k] + su[k].rhs(inv_t);
+                mat_k[k] = -svd.solve(&(pb.t() * &a[k]));
+                vec_k[k] = svd.solve(&(pb.t() * &r_pri[k] + b[k].t() * &vec_p[k + 1] + rhs_u));
+
+                if k > 0 {
+                    let a_cl = &a[k] + &b[k] * &mat_k[k];
+                    let c_cl = &b[k] * &vec_k[k] - &r_pri[k];
+                    let p = &q[k - 1] + sx[k - 1].hessian() + a[k].t() * &mat_p[k + 1] * a_cl;
+                    mat_p[k] = (&p + p.t()) / 2.;
+                    vec_p[k] = -&r_dual_x[k - 1] + sx[k - 1].rhs(inv_t)
+                               - a[k].t() * (&mat_p[k + 1] * c_cl - &vec_p[k + 1]);
+                }
+            }
+
+            let mut du = Vec::with_capacity(nh);
+            let mut dx = vec![Mat::new_vec(n)];
+            let mut dnu = Vec::with_capacity(nh);
+            for k in 0 .. nh {
+                du.push(&mat_k[k] * &dx[k] + &vec_k[k]);
+                dx.push(&a[k] * &dx[k] + &b[k] * &du[k] - &r_pri[k]);
+                dnu.push(&vec_p[k + 1] - &mat_p[k + 1] * &dx[k + 1]);
+            }
+
+            // ----- step
+
+            let mut s_max: FP = 1.;
+            for k in 0 .. nh {
+                su[k].newton(&du[k], inv_t);
+                sx[k].newton(&dx[k + 1], inv_t);
+                s_max = s_max.min(su[k].step_max(&du[k])).min(sx[k].step_max(&dx[k + 1]));
+            }
+            let s = if s_max < 1. {param.s_coef * s_max} else {1.};
+
+            for k in 0 .. nh {
+                u[k] += s * &du[k];
+                x[k + 1] += s * &dx[k + 1];
+                nu[k] += s * &dnu[k];
+                su[k].step(s);
+                sx[k].step(s);
+            }
+
+            iterations += 1;
+        }
+
+        Ok(MPCSolution {
+            objective: self.h_objective(&u, &x),
+            u, x,
+            iterations,
+            status
+        })
+    }
+}
+
+// slacks and dual variables of the finite bounds of a stage variable v:
+// f = lo - v <= 0 and f = v - hi <= 0
+struct Stage
+{
+    finite: Vec<(bool, bool)>,
+    s_lo: Mat,
+    s_hi: Mat,
+    lmd_lo: Mat,
+    lmd_hi: Mat,
+    dlmd_lo: Mat,
+    dlmd_hi: Mat
+}
+
+impl Stage
+{
+    fn new((lo, hi): &(Mat, Mat), margin: FP) -> Stage
+    {
+        let (len, _) = lo.size();
+        let finite: Vec<(bool, bool)> = (0 .. len).map(|i| (lo[(i, 0)].is_finite(), hi[(i, 0)].is_finite())).collect();
+
+        Stage {
+            lmd_lo: Mat::new_vec(len).set_by(|i, _| if finite[i].0 {margin} else {0.}),
+            lmd_hi: Mat::new_vec(len).set_by(|i, _| if finite[i].1 {margin} else {0.}),
+            s_lo: Mat::new_vec(len),
+            s_hi: Mat::new_vec(len),
+            dlmd_lo: Mat::new_vec(len),
+            dlmd_hi: Mat::new_vec(len),
+            finite
+        }
+    }
+
+    // strictly interior start point
+    fn start((lo, hi): &(Mat, Mat)) -> Mat
+    {
+        lo.clone_sz().set_by(|i, _| {
+            match (lo[(i, 0)].is_finite(), hi[(i, 0)].is_finite()) {
+                (true, true) => (lo[(i, 0)] + hi[(i, 0)]) / 2.,
+                (true, false) => lo[(i, 0)] + 1.,
+                (false, true) => hi[(i, 0)] - 1.,
+                (false, false) => 0.
+            }
+        })
+    }
+
+    fn count(&self) -> usize
+    {
+        self.finite.iter().map(|&(l, h)| l as usize + h as usize).sum()
+    }
+
+    fn update(&mut self, (lo, hi): &(Mat, Mat), v: &Mat)
+    {
+        for (i, &(fl, fh)) in self.finite.iter().enumerate() {
+            self.s_lo[(i, 0)] = if fl {v[(i, 0)] - lo[(i, 0)]} else {1.};
+            self.s_hi[(i, 0)] = if fh {hi[(i, 0)] - v[(i, 0)]} else {1.};
+        }
+    }
+
+    fn gap(&self) -> FP
+    {
+        self.lmd_lo.prod(&self.s_lo) + self.lmd_hi.prod(&self.s_hi)
+    }
+
+    // gradient of the bound terms of the Lagrangian
+    fn d_lmd(&self) -> Mat
+    {
+        &self.lmd_hi - &self.lmd_lo
+    }
+
+    fn hessian(&self) -> Mat
+    {
+        Mat::new_vec(self.finite.len()).set_by(|i, _| {
+            self.lmd_lo[(i, 0)] / self.s_lo[(i, 0)] + self.lmd_hi[(i, 0)] / self.s_hi[(i, 0)]
+        }).clone_diag()
+    }
+
+    // right-hand side of central residuals lmd s - inv_t
+    fn rhs(&self, inv_t: FP) -> Mat
+    {
+        Mat::new_vec(self.finite.len()).set_by(|i, _| {
+            let (fl, fh) = self.finite[i];
+            let lo = if fl {(self.lmd_lo[(i, 0)] * self.s_lo[(i, 0)] - inv_t) / self.s_lo[(i, 0)]} else {0.};
+            let hi = if fh {(self.lmd_hi[(i, 0)] * self.s_hi[(i, 0)] - inv_t) / self.s_hi[(i, 0)]} else {0.};
+            hi - lo
+        })
+    }
+
+    fn newton(&mut self, dv: &Mat, inv_t: FP)
+    {
+        for (i, &(fl, fh)) in self.finite.iter().enumerate() {
+            let (l, s) = (self.lmd_lo[(i, 0)], self.s_lo[(i, 0)]);
+            self.dlmd_lo[(i, 0)] = if fl {(inv_t - l * s - l * dv[(i, 0)]) / s} else {0.};
+            let (l, s) = (self.lmd_hi[(i, 0)], self.s_hi[(i, 0)]);
+            self.dlmd_hi[(i, 0)] = if fh {(inv_t - l * s + l * dv[(i, 0)]) / s} else {0.};
+        }
+    }
+
+    // max step size which keeps slacks and dual variables positive
+    fn step_max(&self, dv: &Mat) -> FP
+    {
+        let mut s_max: FP = FP::INFINITY;
+        for (i, &(fl, fh)) in self.finite.iter().enumerate() {
+            let cands = [
+                (fl, self.s_lo[(i, 0)], dv[(i, 0)]),
+                (fh, self.s_hi[(i, 0)], -dv[(i, 0)]),
+                (fl, self.lmd_lo[(i, 0)], self.dlmd_lo[(i, 0)]),
+                (fh, self.lmd_hi[(i, 0)], self.dlmd_hi[(i, 0)])
+            ];
+            for &(f, v, dv) in cands.iter() {
+                if f && (dv < 0.) {
+                    s_max = s_max.min(-v / dv);
+                }
+            }
+        }
+        s_max
+    }
+
+    fn step(&mut self, s: FP)
+    {
+        self.lmd_lo += s * &self.dlmd_lo;
+        self.lmd_hi += s * &self.dlmd_hi;
+    }
+}
+
+#[cfg(test)]
+fn gen_mpc(n: usize, m: usize, horizon: usize, seed: u64) -> MPCProblem
+{
+    use super::mat::xor64;
+
+    let mut r = seed;
+    // stable dynamics: spectral radius of the random part is about 0.06
+    let scale = 0.2 / (n as FP).sqrt();
+    let mat_a = Mat::new(n, n).set_by(|i, j| if i == j {0.9} else {0.}) + Mat::new(n, n).set_by(|_, _| (xor64(&mut r) - 0.5) * scale);
+    let mat_b = Mat::new(n, m).set_by(|_, _| xor64(&mut r) - 0.5);
+    let x0 = Mat::new_vec(n).set_by(|_, _| (xor64(&mut r) - 0.5) * 4.);
+
+    MPCProblem::new_lti(x0, &mat_a, &mat_b, &Mat::new(n, n).set_eye(), &(Mat::new(m, m).set_eye() * 0.1), horizon)
+        .with_input_bounds(Mat::new_vec(m).set_all(-0.5), Mat::new_vec(m).set_all(0.5))
+}
+
+#[test]
+fn test_mpc_modes()
+{
+    use super::mat::XOR64_INIT;
+
+    let param = PDIPMParam::default();
+
+    for (n, m, horizon) in [(2, 1, 10), (3, 2, 8)].iter() {
+        let prob = gen_mpc(*n, *m, *horizon, XOR64_INIT + *n as u64);
+        let x_max = Mat::new_vec(*n).set_all(2.5);
+        for prob in [prob.clone(), prob.with_state_bounds(-&x_max, x_max.clone())].iter() {
+            let sol_s = prob.solve(&param, MPCMode::Structured).unwrap();
+            let sol_c = prob.solve(&param, MPCMode::Condensed).unwrap();
+            println!("u0 = {} / {}, iterations = {} / {}", sol_s.u[0], sol_c.u[0], sol_s.iterations, sol_c.iterations);
+            assert_eq!(sol_s.status, SolverStatus::Converged);
+            assert!((&sol_s.u[0] - &sol_c.u[0]).norm_p2() < 1e-6);
+            assert!((sol_s.objective - sol_c.objective).abs() < 1e-6);
+
+            for k in 0 .. *horizon {
+                assert!((&sol_s.x[k + 1] - &prob.mat_a[k] * &sol_s.x[k] - &prob.mat_b[k] * &sol_s.u[k]).norm_p2() < 1e-6);
+                assert!((&sol_s.u[k] - &sol_c.u[k]).norm_p2() < 1e-5);
+                assert!(sol_s.u[k].max().unwrap() <= 0.5);
+            }
+        }
+    }
+
+    // time-varying stages without bounds are solved by a single Newton step
+    let prob = MPCProblem::new(Mat::new_vec(1).set_all(1.),
+                               vec![Mat::new(1, 1).set_all(1.), Mat::new(1, 1).set_all(2.)],
+                               vec![Mat::new(1, 1).set_all(1.), Mat::new(1, 1).set_all(0.5)],
+                               vec![Mat::new(1, 1).set_all(1.), Mat::new(1, 1).set_all(3.)],
+                               vec![Mat::new(1, 1).set_all(1.), Mat::new(1, 1).set_all(1.)]);
+    let sol_s = prob.solve(&param, MPCMode::Structured).unwrap();
+    let sol_c = prob.solve(&param, MPCMode::Condensed).unwrap();
+    assert_eq!(sol_s.iterations, 1);
+    assert!((sol_s.objective - sol_c.objective).abs() < 1e-9);
+
+    assert!(prob.clone().with_input_bounds(Mat::new_vec(1).set_all(1.), Mat::new_vec(1).set_all(-1.))
+                .solve(&param, MPCMode::Structured).is_err());
+}
+
+#[test]
+fn test_mpc_long_horizon()
+{
+    use super::mat::XOR64_INIT;
+
+    // horizon 100 with 20 states, which the condensed mode takes too long for a unit test
+    let prob = gen_mpc(20, 2, 100, XOR64_INIT);
+    let sol = prob.solve(&PDIPMParam::default(), MPCMode::Structured).unwrap();
+    println!("iterations = {}, objective = {:.6e}", sol.iterations, sol.objective);
+    assert_eq!(sol.status, SolverStatus::Converged);
+    for k in 0 .. 100 {
+        assert!((&sol.x[k + 1] - &prob.mat_a[k] * &sol.x[k] - &prob.mat_b[k] * &sol.u[k]).norm_p2() < 1e-6);
+        assert!(sol.u[k].norm_p2() <= FP::sqrt(2.) * 0.5 + 1e-9);
+    }
+}
+
+#[test]
+#[ignore]
+fn bench_mpc_modes()
+{
+    use super::mat::XOR64_INIT;
+    use std::time::Instant;
+
+    let prob = gen_mpc(20, 2, 100, XOR64_INIT);
+    let param = PDIPMParam::default();
+
+    let start = Instant::now();
+    let sol_s = prob.solve(&param, MPCMode::Structured).unwrap();
+    let elapsed_s = start.elapsed();
+
+    let start = Instant::now();
+    let sol_c = prob.solve(&param, MPCMode::Condensed).unwrap();
+    let elapsed_c = start.elapsed();
+
+    println!("structured: {:?}, condensed: {:?}", elapsed_s, elapsed_c);
+    assert!((&sol_s.u[0] - &sol_c.u[0]).norm_p2() < 1e-5);
+}
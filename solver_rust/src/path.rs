@@ -0,0 +1,145 @@
+/*!
+Parametric solution path
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+[`solve_qp_path`](fn.solve_qp_path.html) solves a family of QPs whose objective depends on a scalar \\(\\lambda\\),
+such as a regularization path, warm-starting each solve from the previous solution along the path.
+*/
+
+use super::prelude::*;
+
+/// QP of a parameter \\(\\lambda\\)
+///
+/// The problem is
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize} & {1 \\over 2} x^T (P + \\lambda P_\\lambda) x + (q + \\lambda q_\\lambda)^T x \\\\
+/// {\\rm subject \\ to} & G x \\preceq h \\\\
+/// & A x = b,
+/// \\end{array}
+/// \\]
+/// where \\( P \\), \\( q \\), \\( G \\), \\( h \\), \\( A \\) and \\( b \\) are of `base`.
+#[derive(Debug, Clone)]
+pub struct QPPathProblem
+{
+    /// The problem at \\( \\lambda = 0 \\).
+    pub base: QPProblem,
+    /// \\( P_\\lambda \\)
+    pub mat_p_lambda: Mat,
+    /// \\( q_\\lambda \\)
+    pub vec_q_lambda: Mat
+}
+
+impl QPPathProblem
+{
+    /// Makes a problem.
+    pub fn new(base: QPProblem, mat_p_lambda: Mat, vec_q_lambda: Mat) -> QPPathProblem
+    {
+        QPPathProblem {
+            base,
+            mat_p_lambda,
+            vec_q_lambda
+        }
+    }
+
+    /// Returns the problem at `lambda`.
+    pub fn at(&self, lambda: FP) -> QPProblem
+    {
+        QPProblem {
+            mat_p: &self.base.mat_p + lambda * &self.mat_p_lambda,
+            vec_q: &self.base.vec_q + lambda * &self.vec_q_lambda,
+            .. self.base.clone()
+        }
+    }
+}
+
+/// Solves a QP along a path of \\(\\lambda\\).
+///
+/// Returns results in the same order as `lambdas`.
+/// The problems are solved in the descending order of \\(\\lambda\\), reusing a solver workspace,
+/// and each solve starts from the result of the previous \\(\\lambda\\).
+/// A failure of one \\(\\lambda\\) is recorded and the next one starts cold, without aborting the path.
+/// * `problem` is the problem.
+/// * `lambdas` are values of \\(\\lambda\\).
+/// * `param` is solver parameters.
+pub fn solve_qp_path(problem: &QPPathProblem, lambdas: &[FP], param: &PDIPMParam) -> Vec<Result<SolverResult, SolverError>>
+{
+    let mut order: Vec<usize> = (0 .. lambdas.len()).collect();
+    order.sort_by(|&i, &j| lambdas[j].partial_cmp(&lambdas[i]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut solver = PDIPM::new();
+    let mut rslts: Vec<Option<Result<SolverResult, SolverError>>> = lambdas.iter().map(|_| None).collect();
+    let mut last: Option<usize> = None;
+
+    for i in order {
+        let prob = problem.at(lambdas[i]);
+        let warm = last.and_then(|l| rslts[l].as_ref()).and_then(|r| r.as_ref().ok());
+
+        let rslt = prob.solve_warm(&mut solver, param, &mut std::io::sink(), warm);
+
+        last = if rslt.is_ok() {Some(i)} else {None};
+        rslts[i] = Some(rslt);
+    }
+
+    rslts.into_iter().map(|r| r.unwrap()).collect()
+}
+
+#[test]
+fn test_solve_qp_path()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    // LASSO: minimize 1/2 |D x - e|^2 + lambda |x|_1 with x = u - v, u >= 0, v >= 0
+    let k: usize = 8;
+    let n: usize = 5;
+
+    let mut r = XOR64_INIT;
+    let mat_d = Mat::new(k, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let vec_e = Mat::new_vec(k).set_by(|_, _| xor64(&mut r) - 0.5);
+
+    let mut mat_du = Mat::new(k, 2 * n);
+    mat_du.cols_mut(0 .. n).assign(&mat_d);
+    mat_du.cols_mut(n .. 2 * n).assign(&-&mat_d);
+
+    let base = QPProblem::new(
+        mat_du.t() * &mat_du, -(mat_du.t() * &vec_e),
+        -Mat::new(2 * n, 2 * n).set_eye(), Mat::new_vec(2 * n),
+        Mat::new(0, 2 * n), Mat::new_vec(0)
+    );
+    let path = QPPathProblem::new(base, Mat::new(2 * n, 2 * n), Mat::new_vec(2 * n).set_all(1.));
+
+    let lambdas: Vec<FP> = (0 .. 10).map(|i| 0.02 * (i as FP)).collect();
+    let param = PDIPMParam::default();
+
+    let rslts = solve_qp_path(&path, &lambdas, &param);
+
+    let mut nnz_last = n + 1;
+    let mut iter_path = 0;
+    let mut iter_cold = 0;
+    for (lambda, rslt) in lambdas.iter().zip(&rslts) {
+        let rslt = rslt.as_ref().unwrap();
+        let cold = path.at(*lambda).solve(&param).unwrap();
+        iter_path += rslt.iterations;
+        iter_cold += cold.iterations;
+
+        let x = rslt.x.rows(0 .. n) - rslt.x.rows(n .. 2 * n);
+        let x_cold = cold.x.rows(0 .. n) - cold.x.rows(n .. 2 * n);
+        assert!((&x - x_cold).norm_p2() < 1e-5);
+
+        // the number of nonzeros is non-increasing in lambda
+        let nnz = (0 .. n).filter(|&j| x[(j, 0)].abs() > 1e-6).count();
+        println!("lambda {:.2}: nnz {} iterations {} (cold {})", lambda, nnz, rslt.iterations, cold.iterations);
+        assert!(nnz <= nnz_last);
+        nnz_last = nnz;
+    }
+    println!("iterations: path {} cold {}", iter_path, iter_cold);
+    // warm-start saves at least a third of iterations
+    assert!(iter_path * 3 < iter_cold * 2);
+
+    // an unbounded point of a negative lambda does not abort the path
+    let rslts = solve_qp_path(&path, &[0.1, -0.1, 0.05], &param);
+    assert!(rslts[0].is_ok());
+    assert!(rslts[1].is_err());
+    assert!(rslts[2].is_ok());
+}
@@ -4,14 +4,365 @@ Primal-dual interior point method
 
 use super::mat::{Mat, MatSlice, MatSliMu, FP, FP_MINPOS, FP_EPSILON};
 use super::matsvd::MatSVD;
+use super::matchol::{MatChol, NotPositiveDefinite};
+use super::matlu::MatLU;
+use super::matqr::MatQR;
+use super::problem::{SolverError, SolverStatus, Residuals, ConvergenceFailure, check_dim};
 
 use std::io::Write;
+use std::time::{Duration, Instant};
 macro_rules! writeln_or {
     ( $( $arg: expr ),* ) => {
-        writeln!( $( $arg ),* ).or(Err("log: I/O Error"))
+        writeln!( $( $arg ),* ).or(Err(SolverError::LogFailure))
     };
 }
 
+// Gondzio correctors: the step is enlarged by DELTA for prediction and must improve by GAMMA * DELTA,
+// and complementarity products are kept in [BETA_MIN, BETA_MAX] times the target
+const GONDZIO_DELTA: FP = 0.1;
+const GONDZIO_GAMMA: FP = 0.1;
+const GONDZIO_BETA_MIN: FP = 0.1;
+const GONDZIO_BETA_MAX: FP = 10.;
+
+// diagonal of the equality block of the KKT matrix for DependentRows::Regularize
+const DEPENDENT_REG: FP = 1e-8;
+
+// rows of a which are linearly dependent on the others, by rank-revealing QR of a^T,
+// or Err with the rows of a combination whose b conflicts
+fn dependent_rows(a: &Mat, b: &Mat) -> Result<Vec<usize>, Vec<usize>>
+{
+    let (p, n) = a.size();
+
+    // zero rows pad a^T to be tall, which keeps its rank
+    let mut a_t = Mat::new(n.max(p), p);
+    a_t.rows_mut(0 .. n).assign(&a.t());
+
+    // relative to the largest row norm, as MatGen::pinv is to the largest singular value
+    let tol = n.max(p) as FP * FP_EPSILON;
+    let basis = MatQR::new_pivoted(&a_t).basis_cols(tol);
+    let dependent: Vec<usize> = (0 .. p).filter(|r| !basis.contains(r)).collect();
+    if dependent.is_empty() {
+        return Ok(dependent);
+    }
+
+    // combinations of the basis rows making the dependent rows, which must make their b as well;
+    // the combinations carry rounding errors, so that b is checked looser than the rank
+    let coef = MatQR::new(&a.select_rows(&basis).t()).solve_ls(&a.select_rows(&dependent).t());
+    let tol_b = tol.sqrt();
+
+    for (k, &i) in dependent.iter().enumerate() {
+        let mut beta = b[(i, 0)];
+        let mut scale = beta.abs();
+        for (j, &r) in basis.iter().enumerate() {
+            let t = coef[(j, k)] * b[(r, 0)];
+            beta -= t;
+            scale += t.abs();
+        }
+
+        if beta.abs() > tol_b * scale {
+            let c_max = coef.col(k).norm_inf();
+            let mut rows: Vec<usize> = basis.iter().enumerate()
+                                            .filter(|&(j, _)| coef[(j, k)].abs() > tol * c_max)
+                                            .map(|(_, &r)| r)
+                                            .collect();
+            rows.push(i);
+            rows.sort_unstable();
+            return Err(rows);
+        }
+    }
+
+    Ok(dependent)
+}
+
+// direct factorization of the KKT matrix at an iteration, instead of SVD
+enum DirectKKT
+{
+    Reduced(ReducedKKT),
+    LU(MatLU)
+}
+
+// KKT matrix reduced by eliminating lmd and then x, factorized by Cholesky for Factorization::Cholesky:
+// the Hessian part H + Df^T diag(lmd / -f) Df + C^T diag(sigma) C of ranges and its Schur complement A H^-1 A^T for nu
+struct ReducedKKT
+{
+    chol_h: MatChol,
+    chol_s: Option<MatChol>
+}
+
+impl ReducedKKT
+{
+    // Err if either of them is not positive definite
+    fn new(sv: &PDIPM, lmd: &MatSlice, reg: FP) -> Result<ReducedKKT, NotPositiveDefinite>
+    {
+        let (n, m, p) = sv.n_m_p;
+
+        let mut mat_h = sv.kkt.slice(0 .. n, 0 .. n).clone_sz();
+        if m > 0 {
+            let mut w_df = sv.df_i.clone_sz();
+            w_df.scale_rows(&lmd.zip_map(&sv.f_i, |l, f| -l / f));
+            mat_h += sv.df_i.t() * w_df;
+        }
+        if let Some(ranges) = &sv.ranges {
+            let mut w_c = ranges.mat_c.clone_sz();
+            w_c.scale_rows(&ranges.sigmas());
+            mat_h += ranges.mat_c.t() * w_c;
+        }
+        let chol_h = MatChol::new(&mat_h)?;
+
+        let chol_s = if p > 0 {
+            let mut mat_s = &sv.a * chol_h.solve(&sv.a.t());
+            // dropped rows are zero, whose nu are kept as they are
+            for &r in &sv.dropped_eqs {
+                mat_s[(r, r)] = 1.;
+            }
+            mat_s.add_diag(reg);
+            Some(MatChol::new(&mat_s)?)
+        }
+        else {
+            None
+        };
+
+        Ok(ReducedKKT {chol_h, chol_s})
+    }
+
+    // solves the KKT system with the right-hand side rhs
+    fn solve(&self, sv: &PDIPM, lmd: &MatSlice, rhs: &Mat) -> Mat
+    {
+        let (n, m, p) = sv.n_m_p;
+        let k = sv.k;
+        let (r_x, r_lmd, r_nu) = (rhs.rows(0 .. n), rhs.rows(n .. n + m), rhs.rows(n + m .. n + m + p));
+        let r_z = rhs.rows(n + m + p .. n + m + p + k);
+
+        // -lmd Df dx - f dlmd = r_lmd
+        let mut g = r_x.clone_sz();
+        if m > 0 {
+            g += sv.df_i.t() * r_lmd.zip_map(&sv.f_i, |r, f| r / f);
+        }
+        // sigma C dx - dz = r_z
+        if let Some(ranges) = &sv.ranges {
+            g += ranges.mat_c.t() * &r_z;
+        }
+        let u = self.chol_h.solve(&g);
+
+        let mut dy = Mat::new_vec(n + m + p + k);
+        match &self.chol_s {
+            Some(chol_s) => {
+                let dnu = chol_s.solve(&(&sv.a * &u - r_nu));
+                dy.rows_mut(0 .. n).assign(&(u - self.chol_h.solve(&(sv.a.t() * &dnu))));
+                dy.rows_mut(n + m .. n + m + p).assign(&dnu);
+            },
+            None => {
+                dy.rows_mut(0 .. n).assign(&u);
+            }
+        }
+        if m > 0 {
+            let df = &sv.df_i * dy.rows(0 .. n);
+            let mut dlmd = dy.rows_mut(n .. n + m);
+            for i in 0 .. m {
+                dlmd[(i, 0)] = -(r_lmd[(i, 0)] + lmd[(i, 0)] * df[(i, 0)]) / sv.f_i[(i, 0)];
+            }
+        }
+        if let Some(ranges) = &sv.ranges {
+            let mut dc = &ranges.mat_c * dy.rows(0 .. n);
+            dc.scale_rows(&ranges.sigmas());
+            dy.rows_mut(n + m + p .. n + m + p + k).assign(&(dc - r_z));
+        }
+
+        dy
+    }
+}
+
+// two-sided rows l <= C x <= u of a solve, set by PDIPM::set_ranges:
+// each row has its value w kept strictly within the finite bounds and the dual variables of the bounds,
+// and the signed dual variable z of C x = w is appended to y, so that the KKT matrix has one row per range.
+// the dual variables of infinite bounds stay zero.
+struct RangeRows
+{
+    mat_c: Mat,
+    vec_l: Mat,
+    vec_u: Mat,
+    // columns of w, lmd_l and lmd_u
+    v: Mat,
+    best_v: Mat,
+    // columns of the residuals -z - lmd_l + lmd_u, lmd_l (w - l) - 1/t and lmd_u (u - w) - 1/t
+    r: Mat
+}
+
+impl RangeRows
+{
+    fn new(mat_c: Mat, vec_l: Mat, vec_u: Mat) -> RangeRows
+    {
+        let k = mat_c.size().0;
+
+        RangeRows {
+            mat_c, vec_l, vec_u,
+            v: Mat::new(k, 3),
+            best_v: Mat::new(k, 3),
+            r: Mat::new(k, 3)
+        }
+    }
+
+    fn rows(&self) -> usize
+    {
+        self.mat_c.size().0
+    }
+
+    // number of finite bounds
+    fn sides(&self) -> usize
+    {
+        (0 .. self.rows()).map(|r| {
+            (self.vec_l[(r, 0)] > FP::NEG_INFINITY) as usize + (self.vec_u[(r, 0)] < FP::INFINITY) as usize
+        }).sum()
+    }
+
+    // w - l and u - w of v, which are infinite for infinite bounds
+    fn slacks(&self, v: &Mat, r: usize) -> (FP, FP)
+    {
+        (v[(r, 0)] - self.vec_l[(r, 0)], self.vec_u[(r, 0)] - v[(r, 0)])
+    }
+
+    // w at C x moved into the bounds by margin, and the dual variables of margin;
+    // returns z which makes the residual of w zero
+    fn start(&mut self, x: &MatSlice, margin: FP) -> Mat
+    {
+        let cx = &self.mat_c * x;
+        let mut z = Mat::new_vec(self.rows());
+
+        for r in 0 .. self.rows() {
+            let (l, u) = (self.vec_l[(r, 0)], self.vec_u[(r, 0)]);
+            let (has_l, has_u) = (l > FP::NEG_INFINITY, u < FP::INFINITY);
+            let w = match (has_l, has_u) {
+                (true, true) => (l + u) / 2.,
+                (true, false) => cx[(r, 0)].max(l + margin),
+                (false, true) => cx[(r, 0)].min(u - margin),
+                (false, false) => cx[(r, 0)]
+            };
+            let lmd_l = if has_l {margin} else {0.};
+            let lmd_u = if has_u {margin} else {0.};
+
+            self.v[(r, 0)] = w;
+            self.v[(r, 1)] = lmd_l;
+            self.v[(r, 2)] = lmd_u;
+            z[(r, 0)] = lmd_u - lmd_l;
+        }
+
+        z
+    }
+
+    // sum of the complementarity products
+    fn gap(&self) -> FP
+    {
+        (0 .. self.rows()).map(|r| {
+            let (s_l, s_u) = self.slacks(&self.v, r);
+            let mut g = 0.;
+            if s_l < FP::INFINITY {
+                g += self.v[(r, 1)] * s_l;
+            }
+            if s_u < FP::INFINITY {
+                g += self.v[(r, 2)] * s_u;
+            }
+            g
+        }).sum()
+    }
+
+    // residuals at v, x and z, with C x - w into r_z
+    fn residual(&self, v: &Mat, x: &MatSlice, z: &MatSlice, inv_t: FP, r_z: &mut MatSliMu) -> Mat
+    {
+        r_z.assign(&(&self.mat_c * x - v.col(0)));
+
+        Mat::new(self.rows(), 3).set_by(|r, c| {
+            let (s_l, s_u) = self.slacks(v, r);
+            match c {
+                0 => -z[(r, 0)] - v[(r, 1)] + v[(r, 2)],
+                1 => if s_l < FP::INFINITY {v[(r, 1)] * s_l - inv_t} else {0.},
+                _ => if s_u < FP::INFINITY {v[(r, 2)] * s_u - inv_t} else {0.}
+            }
+        })
+    }
+
+    // lmd_l / (w - l) + lmd_u / (u - w), zero for two infinite bounds
+    fn sigma(&self, r: usize) -> FP
+    {
+        let (s_l, s_u) = self.slacks(&self.v, r);
+
+        self.v[(r, 1)] / s_l + self.v[(r, 2)] / s_u
+    }
+
+    fn sigmas(&self) -> Mat
+    {
+        Mat::new_vec(self.rows()).set_by(|r, _| self.sigma(r))
+    }
+
+    // right-hand side of the rows of z, which eliminate w and the dual variables of the bounds:
+    // sigma C dx - dz = -sigma r_z - r_w - r_l / (w - l) + r_u / (u - w)
+    fn rhs(&self, r_z: &MatSlice) -> Mat
+    {
+        Mat::new_vec(self.rows()).set_by(|r, _| {
+            let (s_l, s_u) = self.slacks(&self.v, r);
+
+            -self.sigma(r) * r_z[(r, 0)] - self.r[(r, 0)] - self.r[(r, 1)] / s_l + self.r[(r, 2)] / s_u
+        })
+    }
+
+    // direction of v from dx and r_z
+    fn direction(&self, dx: &MatSlice, r_z: &MatSlice) -> Mat
+    {
+        let dw = &self.mat_c * dx + r_z;
+
+        Mat::new(self.rows(), 3).set_by(|r, c| {
+            let (s_l, s_u) = self.slacks(&self.v, r);
+            match c {
+                0 => dw[(r, 0)],
+                1 => if s_l < FP::INFINITY {-(self.r[(r, 1)] + self.v[(r, 1)] * dw[(r, 0)]) / s_l} else {0.},
+                _ => if s_u < FP::INFINITY {-(self.r[(r, 2)] - self.v[(r, 2)] * dw[(r, 0)]) / s_u} else {0.}
+            }
+        })
+    }
+
+    // max step size in [0, 1] along dv which keeps the slacks and the dual variables of finite bounds positive
+    fn step_max(&self, dv: &Mat) -> FP
+    {
+        let mut s_max: FP = 1.;
+        for r in 0 .. self.rows() {
+            let (s_l, s_u) = self.slacks(&self.v, r);
+            let dw = dv[(r, 0)];
+            if (s_l < FP::INFINITY) && (dw < -FP_MINPOS) {
+                s_max = s_max.min(-s_l / dw);
+            }
+            if (s_u < FP::INFINITY) && (dw > FP_MINPOS) {
+                s_max = s_max.min(s_u / dw);
+            }
+            for c in 1 ..= 2 {
+                if dv[(r, c)] < -FP_MINPOS {
+                    s_max = s_max.min(-self.v[(r, c)] / dv[(r, c)]);
+                }
+            }
+        }
+
+        s_max
+    }
+}
+
+// max step size in [0, 1] along dy which keeps lmd positive and the linearized f_i negative
+fn step_max(lmd: &MatSlice, f_i: &Mat, df_i: &Mat, dy: &Mat) -> FP
+{
+    let (m, n) = df_i.size();
+    let df = df_i * dy.rows(0 .. n);
+
+    let mut s_max: FP = 1.;
+    for i in 0 .. m {
+        let dlmd = dy[(n + i, 0)];
+        if dlmd < -FP_MINPOS {
+            s_max = s_max.min(-lmd[(i, 0)] / dlmd);
+        }
+        if df[(i, 0)] > FP_MINPOS {
+            s_max = s_max.min(-f_i[(i, 0)] / df[(i, 0)]);
+        }
+    }
+
+    s_max
+}
+
 /**
 A basic Primal-Dual Interior-Point Method solver struct.
 
@@ -37,6 +388,22 @@ as well as dual variables \\(\\lambda \\in {\\bf R}^m\\) and \\(\\nu \\in {\\bf
 pub struct PDIPM
 {
     n_m_p: (usize, usize, usize),
+    // number of range rows
+    k: usize,
+    iterations: usize,
+    timings: Option<Timings>,
+    warm_dual: Option<(Mat, Mat)>,
+    next_ranges: Option<RangeRows>,
+    ranges: Option<RangeRows>,
+    status: SolverStatus,
+    residuals: Residuals,
+    factorizations: usize,
+    corrections: usize,
+    // the last factorization is not of SVD but direct, without singular values
+    direct: bool,
+    dropped_eqs: Vec<usize>,
+    step_size: FP,
+    step_max: FP,
 
     /***** matrix *****/
     // constant across loop
@@ -44,6 +411,7 @@ pub struct PDIPM
     b: Mat,
     // loop variable
     y: Mat,
+    best_y: Mat,
     kkt: Mat,
     // temporal in loop
     df_o: Mat,
@@ -76,10 +444,132 @@ pub struct PDIPMParam
     /// Max iteration number of outer-loop for the Newton step.
     /// Max iteration number of inner-loop for the backtracking line search.
     pub n_loop: usize,
+    /// Max iteration number of outer-loop overriding `n_loop`, which then limits only inner-loop.
+    pub max_iter: Option<usize>,
+    /// Max number of Gondzio centrality correctors per iteration, each of which reuses the KKT matrix factorization.
+    pub max_correctors: usize,
     /// Enables to warm-start svd.
     pub svd_warm: bool,
     /// Enables to log kkt matrix.
-    pub log_kkt: bool
+    pub log_kkt: bool,
+    /// Enables to log a summary of `QPProblem::analyze` and `LPProblem::analyze` at the start of a solve.
+    pub log_stats: bool,
+    /// Enables to record durations of solver phases.
+    pub timing: bool,
+    /// Formulation of `LPProblem` and `SOCPProblem`, which the other problems ignore.
+    pub formulation: Formulation,
+    /// Handling of linearly dependent rows of the equality constraints.
+    pub dependent_rows: DependentRows,
+    /// Tolerance of asymmetry of `QPProblem::mat_p` and `SDPProblem::mat_f`, checked before iterations if `Some`.
+    ///
+    /// See [`MatGen::is_sym`](../mat/struct.MatGen.html#method.is_sym) for the measure.
+    pub sym_tol: Option<FP>,
+    /// Factorization of the KKT matrix.
+    pub factorization: Factorization
+}
+
+/// Factorization which the KKT matrix is solved by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Factorization
+{
+    /// SVD of the whole KKT matrix, which tolerates its singularity.
+    #[default]
+    SVD,
+    /// Cholesky of the KKT matrix reduced to \\(x\\) and then to \\(\\nu\\),
+    /// falling back to SVD at iterations where either is not positive definite.
+    /// It is much faster when the Hessian of the objective is positive definite, e.g. QP with \\(P \\succ 0\\).
+    /// The condition number of the KKT matrix is not available at such iterations.
+    Cholesky,
+    /// LU with partial pivoting of the whole KKT matrix, as [`MatGen::solve`](../mat/struct.MatGen.html#method.solve)
+    /// does for a non-symmetric matrix, falling back to SVD at iterations where it is singular.
+    /// It is faster when the KKT matrix is nonsingular.
+    /// The condition number of the KKT matrix is not available at such iterations.
+    LU
+}
+
+/// Handling of linearly dependent rows of the equality constraints \\(A x = b\\)
+///
+/// Such rows, e.g. duplicated ones, make the KKT matrix singular.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DependentRows
+{
+    /// Keeps all rows as they are without detection, so that dependent rows leave the KKT matrix singular.
+    Keep,
+    /// Detects dependent rows before iterations by QR of \\(A^T\\) with column pivoting,
+    /// and drops them with a warning in the log
+    /// if \\(b\\) is consistent, otherwise fails with `SolverError::Infeasible` naming the conflicting rows.
+    /// Rows of larger norms are kept in preference, and the dual variables of the dropped rows are zero.
+    /// The rank is taken relative to \\(\\max(p, n) \\epsilon\\) times the largest row norm of \\(A\\).
+    #[default]
+    Drop,
+    /// Keeps all rows and regularizes the KKT matrix, which tolerates inconsistent \\(b\\) in the least-squares sense.
+    Regularize
+}
+
+/// Formulation which a problem is solved through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Formulation
+{
+    /// The problem itself.
+    #[default]
+    Standard,
+    /// The [homogeneous self-dual embedding](../selfdual/index.html),
+    /// which detects infeasibility and unboundedness with certificates.
+    SelfDual
+}
+
+/// Cumulative durations of solver phases
+///
+/// Recorded only when `PDIPMParam::timing` is enabled.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timings
+{
+    /// Problem validation and initialization.
+    pub setup: Duration,
+    /// KKT matrix assembly.
+    pub kkt_assembly: Duration,
+    /// KKT matrix factorization.
+    pub factorization: Duration,
+    /// Search direction solves.
+    pub solve: Duration,
+    /// Backtracking line search.
+    pub line_search: Duration,
+    /// Whole solving.
+    pub total: Duration
+}
+
+impl Timings
+{
+    /// Returns the sum of all phases except `total`.
+    pub fn sum_phases(&self) -> Duration
+    {
+        self.setup + self.kkt_assembly + self.factorization + self.solve + self.line_search
+    }
+
+    // used by solver modules: starts to measure a phase only if timing is enabled
+    pub(crate) fn start(param: &PDIPMParam) -> Option<Instant>
+    {
+        if param.timing {
+            Some(Instant::now())
+        }
+        else {
+            None
+        }
+    }
+
+    // used by solver modules: duration from start, zero if not started
+    pub(crate) fn elapsed(start: Option<Instant>) -> Duration
+    {
+        start.map_or(Duration::default(), |start| start.elapsed())
+    }
+
+    // used by solver modules: accumulates a phase duration from start
+    pub(crate) fn lap(start: Option<Instant>, phase: &mut Duration)
+    {
+        if let Some(start) = start {
+            *phase += start.elapsed();
+        }
+    }
 }
 
 impl Default for PDIPMParam
@@ -94,12 +584,28 @@ impl Default for PDIPMParam
             s_coef: 0.99,
             margin: 1.,
             n_loop: 256,
+            max_iter: None,
+            max_correctors: 0,
             svd_warm: true,
-            log_kkt: false
+            log_kkt: false,
+            log_stats: false,
+            timing: false,
+            formulation: Formulation::Standard,
+            dependent_rows: DependentRows::Drop,
+            sym_tol: None,
+            factorization: Factorization::SVD
         }
     }
 }
 
+impl Default for PDIPM
+{
+    fn default() -> PDIPM
+    {
+        PDIPM::new()
+    }
+}
+
 impl PDIPM
 {
     /// Creates an instance.
@@ -107,9 +613,24 @@ impl PDIPM
     {
         PDIPM {
             n_m_p: (0, 0, 0),
+            k: 0,
+            iterations: 0,
+            timings: None,
+            warm_dual: None,
+            next_ranges: None,
+            ranges: None,
+            status: SolverStatus::default(),
+            residuals: Residuals::default(),
+            factorizations: 0,
+            corrections: 0,
+            direct: false,
+            dropped_eqs: Vec::new(),
+            step_size: 0.,
+            step_max: 0.,
             a: Mat::new(0, 0),
             b: Mat::new_vec(0),
             y: Mat::new_vec(0),
+            best_y: Mat::new_vec(0),
             kkt: Mat::new(0, 0),
             df_o: Mat::new_vec(0),
             f_i: Mat::new_vec(0),
@@ -120,27 +641,31 @@ impl PDIPM
         }
     }
 
-    fn allocate(&mut self, n: usize, m: usize, p: usize)
+    fn allocate(&mut self, n: usize, m: usize, p: usize, k: usize)
     {
-        if self.n_m_p != (n, m, p) {
+        if (self.n_m_p, self.k) != ((n, m, p), k) {
             self.n_m_p = (n, m, p);
+            self.k = k;
             self.a = Mat::new(p, n);
             self.b = Mat::new_vec(p);
-            self.y = Mat::new_vec(n + m + p);
-            self.kkt = Mat::new(n + m + p, n + m + p);
+            self.y = Mat::new_vec(n + m + p + k);
+            self.best_y = Mat::new_vec(n + m + p + k);
+            self.kkt = Mat::new(n + m + p + k, n + m + p + k);
             self.df_o = Mat::new_vec(n);
             self.f_i = Mat::new_vec(m);
-            self.r_t = Mat::new_vec(n + m + p);
+            self.r_t = Mat::new_vec(n + m + p + k);
             self.df_i = Mat::new(m, n);
             self.ddf = Mat::new(n, n);
-            self.svd = MatSVD::new((n + m + p, n + m + p));
+            self.svd = MatSVD::new((n + m + p + k, n + m + p + k));
         }
     }
 
     /// Starts to solve a optimization problem by primal-dual interior-point method.
     /// 
     /// Returns `Ok` with optimal \\(x, \\lambda, \\nu\\) concatenated vector
-    /// or `Err` with message string.
+    /// or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// When the iteration limit is reached, returns `Ok` with the iterate of the smallest merit
+    /// and [`status`](#method.status) tells `SolverStatus::MaxIterReached`.
     /// * `param` is solver parameters.
     /// * `log` outputs solver progress.
     /// * `n` is \\(n\\), the dimension of the variable \\(x\\).
@@ -168,6 +693,9 @@ impl PDIPM
     ///   **The initial values must satisfy all inequality constraints strictly: \\(f_i(x)<0\\).**
     ///   This may seem a hard requirement, but introducing **slack variables** helps in most cases.
     ///   Refer pre-defined solver implementations for example.
+    ///
+    /// The same solve can be driven one iteration at a time by [`PDIPMState`](struct.PDIPMState.html).
+    #[allow(clippy::too_many_arguments)]
     pub fn solve<L, Fo1, Fo2, Fi0, Fi1, Fi2, Fe, Fs>(
         &mut self, param: &PDIPMParam, log: &mut L,
         n: usize, m: usize, p: usize,
@@ -178,7 +706,7 @@ impl PDIPM
         dd_inequality: Fi2,
         equality: Fe,
         start_point: Fs
-    ) -> Result<&Mat, &'static str>
+    ) -> Result<&Mat, SolverError>
     where L: Write,
           Fo1: Fn(&MatSlice, &mut Mat),
           Fo2: Fn(&MatSlice, &mut Mat),
@@ -188,252 +716,820 @@ impl PDIPM
           Fe: FnOnce(&mut Mat, &mut Mat),
           Fs: FnOnce(MatSliMu)
     {
-        let eps_feas = param.eps;
-        let b_loop = param.n_loop;
+        PDIPMState::init(self, param, log, n, m, p,
+                         d_objective, dd_objective,
+                         inequality, d_inequality, dd_inequality,
+                         equality, start_point)?.finish()?;
+
+        Ok(&self.y)
+    }
+
+    // last solution vector, used by solver modules
+    pub(crate) fn y(&self) -> &Mat
+    {
+        &self.y
+    }
+
+    // initial dual variables of the next solve only, used by solver modules:
+    // ignored if their sizes do not match, and lmd must be positive
+    pub(crate) fn set_warm_dual(&mut self, lmd: Mat, nu: Mat)
+    {
+        self.warm_dual = Some((lmd, nu));
+    }
+
+    // two-sided constraints l <= C x <= u of the next solve only, used by solver modules:
+    // each row is one row of the KKT matrix, whose signed dual variable follows nu in the solution vector.
+    // infinite bounds are dropped, and l < u is required.
+    pub(crate) fn set_ranges(&mut self, mat_c: Mat, vec_l: Mat, vec_u: Mat)
+    {
+        self.next_ranges = Some(RangeRows::new(mat_c, vec_l, vec_u));
+    }
+
+    // signed dual variables of the ranges of the last solve, used by solver modules
+    pub(crate) fn range_dual(&self) -> MatSlice<'_>
+    {
+        let (n, m, p) = self.n_m_p;
+
+        self.y.rows(n + m + p .. n + m + p + self.k)
+    }
+
+    /// Returns number of iterations of the last [`solve`](#method.solve).
+    pub fn iterations(&self) -> usize
+    {
+        self.iterations
+    }
+
+    /// Returns number of KKT matrix factorizations of the last [`solve`](#method.solve).
+    pub fn factorizations(&self) -> usize
+    {
+        self.factorizations
+    }
+
+    /// Returns number of accepted Gondzio correctors of the last [`solve`](#method.solve).
+    pub fn corrections(&self) -> usize
+    {
+        self.corrections
+    }
+
+    /// Returns equality rows dropped by `DependentRows::Drop` in the last [`solve`](#method.solve).
+    pub fn dropped_eqs(&self) -> &[usize]
+    {
+        &self.dropped_eqs
+    }
+
+    /// Returns status of the last [`solve`](#method.solve).
+    pub fn status(&self) -> SolverStatus
+    {
+        self.status
+    }
+
+    /// Returns residuals of the solution of the last [`solve`](#method.solve).
+    pub fn residuals(&self) -> Residuals
+    {
+        self.residuals
+    }
+
+    // used by solver modules which return only x:
+    // the iteration limit is an error for them
+    pub(crate) fn check_converged(&self) -> Result<(), SolverError>
+    {
+        match self.status {
+            SolverStatus::Converged => Ok(()),
+            SolverStatus::MaxIterReached => Err(self.failure("iteration: not converged")),
+            SolverStatus::PrimalInfeasible => Err(SolverError::NotConverged("status: primal infeasible".into())),
+            SolverStatus::DualInfeasible => Err(SolverError::NotConverged("status: dual infeasible".into()))
+        }
+    }
+
+    // SolverError::NotConverged with diagnostics at the current iterate
+    fn failure(&self, reason: &str) -> SolverError
+    {
+        let (n, m, _) = self.n_m_p;
+        let iterated = self.iterations > 0;
+
+        // largest -lmd_i f_i
+        let lmd = self.y.rows(n .. n + m);
+        let worst = (0 .. m).map(|i| -lmd[(i, 0)] * self.f_i[(i, 0)])
+                            .enumerate()
+                            .fold(None, |w: Option<(usize, FP)>, (i, c)| if w.is_none_or(|(_, w)| c > w) {Some((i, c))} else {w});
+
+        SolverError::NotConverged(ConvergenceFailure {
+            reason: reason.into(),
+            residuals: Some(self.residuals),
+            worst_complementarity: worst.map(|(i, _)| i),
+            step_size: Some(self.step_size).filter(|_| iterated),
+            step_max: Some(self.step_max).filter(|_| iterated),
+            kkt_cond: self.kkt_cond()
+        })
+    }
+
+    // used by solver modules:
+    // supplements SolverError::NotConverged of the iteration limit, which is detected only with residuals, by diagnostics
+    pub(crate) fn diagnose(&self, err: SolverError) -> SolverError
+    {
+        match err {
+            SolverError::NotConverged(ConvergenceFailure {reason, residuals: Some(residuals), kkt_cond: None, ..}) => {
+                match self.failure(&reason) {
+                    SolverError::NotConverged(fail) => SolverError::NotConverged(ConvergenceFailure {
+                        residuals: Some(residuals),
+                        .. fail
+                    }),
+                    e => e
+                }
+            },
+            e => e
+        }
+    }
+
+    fn kkt_cond(&self) -> Option<FP>
+    {
+        if self.factorizations == 0 {
+            return None;
+        }
+        if self.direct {
+            // the SVD is not run, so an estimate of another factorization
+            return Some(self.kkt.cond_est_p1());
+        }
+
+        let sv = self.svd.s();
+        let s_max = sv.max().unwrap_or(0.);
+        let s_min = sv.min().unwrap_or(0.);
+
+        Some(if s_min > 0. {s_max / s_min} else {FP::INFINITY})
+    }
+
+    /// Returns durations of phases of the last [`solve`](#method.solve),
+    /// or `None` if `PDIPMParam::timing` is disabled.
+    pub fn timings(&self) -> Option<&Timings>
+    {
+        self.timings.as_ref()
+    }
+}
+
+/// Outcome of a [`PDIPMState::step`](struct.PDIPMState.html#method.step)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome
+{
+    /// One iteration is done and the iterate is updated.
+    Continue,
+    /// The termination criteria are satisfied at the iterate.
+    Converged,
+    /// The iteration limit is reached and the iterate is replaced by the one with the smallest merit.
+    MaxIterReached
+}
+
+/**
+State of a [`PDIPM::solve`](struct.PDIPM.html#method.solve) in progress.
+
+[`init`](#method.init) takes the same arguments as `solve`,
+then [`step`](#method.step) advances one iteration at a time
+and the getters inspect the iterate between steps.
+[`finish`](#method.finish) gives what `solve` returns, which is in fact implemented by `init` and `finish`.
+
+```
+use totsu::prelude::*;
+use totsu::pdipm::{PDIPMState, StepOutcome};
+
+// minimize (x - 2)^2 subject to x <= 1
+let param = PDIPMParam::default();
+let mut solver = PDIPM::new();
+let mut log = std::io::sink();
+let mut state = PDIPMState::init(&mut solver, &param, &mut log, 1, 1, 0,
+                                 |x, df_o| df_o[(0, 0)] = 2. * (x[(0, 0)] - 2.),
+                                 |_, ddf_o| ddf_o[(0, 0)] = 2.,
+                                 |x, f_i| f_i[(0, 0)] = x[(0, 0)] - 1.,
+                                 |_, df_i| df_i[(0, 0)] = 1.,
+                                 |_, ddf_i, _| ddf_i[(0, 0)] = 0.,
+                                 |_, _| {},
+                                 |mut x| x[(0, 0)] = 0.).unwrap();
+
+while state.step().unwrap() == StepOutcome::Continue {
+    println!("x = {:.3e}, step = {:.3e}, cond = {:.3e}",
+             state.x()[(0, 0)], state.step_size(), state.kkt_cond().unwrap());
+}
+let y = state.finish().unwrap();
+assert!((y[(0, 0)] - 1.).abs() < 1e-6);
+```
+ */
+pub struct PDIPMState<'a, L, Fo1, Fo2, Fi0, Fi1, Fi2>
+{
+    solver: &'a mut PDIPM,
+    param: &'a PDIPMParam,
+    log: &'a mut L,
+    n: usize,
+    m: usize,
+    p: usize,
+    d_objective: Fo1,
+    dd_objective: Fo2,
+    inequality: Fi0,
+    d_inequality: Fi1,
+    dd_inequality: Fi2,
+    best: Option<Residuals>,
+    timings: Timings,
+    lap_total: Option<Instant>,
+    outcome: Option<StepOutcome>
+}
+
+impl<'a, L, Fo1, Fo2, Fi0, Fi1, Fi2> PDIPMState<'a, L, Fo1, Fo2, Fi0, Fi1, Fi2>
+where L: Write,
+      Fo1: Fn(&MatSlice, &mut Mat),
+      Fo2: Fn(&MatSlice, &mut Mat),
+      Fi0: Fn(&MatSlice, &mut Mat),
+      Fi1: Fn(&MatSlice, &mut Mat),
+      Fi2: Fn(&MatSlice, &mut Mat, usize)
+{
+    /// Initializes a solve by `solver`.
+    ///
+    /// Returns `Ok` with the state at the start point or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// The arguments are the same as [`PDIPM::solve`](struct.PDIPM.html#method.solve).
+    #[allow(clippy::too_many_arguments)]
+    pub fn init<Fe, Fs>(
+        solver: &'a mut PDIPM, param: &'a PDIPMParam, log: &'a mut L,
+        n: usize, m: usize, p: usize,
+        d_objective: Fo1,
+        dd_objective: Fo2,
+        inequality: Fi0,
+        d_inequality: Fi1,
+        dd_inequality: Fi2,
+        equality: Fe,
+        start_point: Fs
+    ) -> Result<Self, SolverError>
+    where Fe: FnOnce(&mut Mat, &mut Mat),
+          Fs: FnOnce(MatSliMu)
+    {
+        solver.timings = None;
+        let mut timings = Timings::default();
+        let lap_total = Timings::start(param);
+        let lap = Timings::start(param);
 
         // parameter check
-        if n == 0 {return Err("n: 0");}
+        if n == 0 {return Err(SolverError::InvalidProblem("n: 0".into()));}
 
         // allocate matrix
-        self.allocate(n, m, p);
+        solver.ranges = solver.next_ranges.take();
+        if let Some(ranges) = &solver.ranges {
+            check_dim("ranges columns", ranges.mat_c.size().1, "n", n)?;
+        }
+        let k = solver.ranges.as_ref().map_or(0, RangeRows::rows);
+        solver.allocate(n, m, p, k);
+        // warm-start only within this solve, so that a result does not depend on previous solves
+        solver.svd.reset_warm();
 
         // initialize
-        let x = self.y.rows_mut(0 .. n);
+        let x = solver.y.rows_mut(0 .. n);
         start_point(x);
-        let mut lmd = self.y.rows_mut(n .. n + m);
+        let mut lmd = solver.y.rows_mut(n .. n + m);
         lmd.assign_all(param.margin);
-        equality(&mut self.a, &mut self.b);
+        let mut nu = solver.y.rows_mut(n + m .. n + m + p);
+        nu.assign_all(0.);
+        if let Some((w_lmd, w_nu)) = solver.warm_dual.take() {
+            if (w_lmd.size() == (m, 1)) && (w_nu.size() == (p, 1)) {
+                solver.y.rows_mut(n .. n + m).assign(&w_lmd);
+                solver.y.rows_mut(n + m .. n + m + p).assign(&w_nu);
+            }
+        }
+        if let Some(ranges) = &mut solver.ranges {
+            let z = ranges.start(&solver.y.rows(0 .. n), param.margin);
+            solver.y.rows_mut(n + m + p .. n + m + p + k).assign(&z);
+        }
+        equality(&mut solver.a, &mut solver.b);
+
+        // dependent rows are zeroed, which keeps their dual variables as they are
+        solver.dropped_eqs.clear();
+        if (p > 0) && (param.dependent_rows == DependentRows::Drop) {
+            match dependent_rows(&solver.a, &solver.b) {
+                Ok(rows) => {
+                    for &r in &rows {
+                        solver.a.row_mut(r).assign_all(0.);
+                        solver.b[(r, 0)] = 0.;
+                        solver.y[(n + m + r, 0)] = 0.;
+                    }
+                    if !rows.is_empty() {
+                        writeln_or!(log, "warning: equality rows {:?} are linearly dependent and dropped", rows)?;
+                    }
+                    solver.dropped_eqs = rows;
+                },
+                Err(rows) => {
+                    let rows: Vec<String> = rows.iter().map(|r| format!("eq[{}]", r)).collect();
+                    return Err(SolverError::Infeasible(format!("equality: rows {} conflict", rows.join(", "))));
+                }
+            }
+        }
 
         // initial df_o, f_i, df_i
-        let x = self.y.rows(0 .. n);
-        d_objective(&x, &mut self.df_o);
-        inequality(&x, &mut self.f_i);
-        d_inequality(&x, &mut self.df_i);
+        let x = solver.y.rows(0 .. n);
+        d_objective(&x, &mut solver.df_o);
+        inequality(&x, &mut solver.f_i);
+        d_inequality(&x, &mut solver.df_i);
 
         // inequality feasibility check
-        if self.f_i.max().unwrap_or(-1.) >= 0. {return Err("inequality: not feasible at init");}
+        if solver.f_i.max().unwrap_or(-1.) >= 0. {return Err(SolverError::InvalidProblem("inequality: not feasible at init".into()));}
 
         // initial residual - dual and primal
-        let mut r_dual = self.r_t.rows_mut(0 .. n);
-        r_dual.assign(&self.df_o);
+        let mut r_dual = solver.r_t.rows_mut(0 .. n);
+        r_dual.assign(&solver.df_o);
         if m > 0 {
-            let lmd = self.y.rows(n .. n + m);
-            r_dual += self.df_i.t() * lmd;
+            let lmd = solver.y.rows(n .. n + m);
+            r_dual += solver.df_i.t() * lmd;
         }
         if p > 0 {
-            let nu = self.y.rows(n + m .. n + m + p);
-            r_dual += self.a.t() * nu;
+            let nu = solver.y.rows(n + m .. n + m + p);
+            r_dual += solver.a.t() * nu;
+        }
+        if let Some(ranges) = &solver.ranges {
+            r_dual += ranges.mat_c.t() * solver.y.rows(n + m + p .. n + m + p + k);
         }
-        let mut r_pri = self.r_t.rows_mut(n + m .. n + m + p);
+        let mut r_pri = solver.r_t.rows_mut(n + m .. n + m + p);
         if p > 0 {
-            let x = self.y.rows(0 .. n);
-            r_pri.assign(&(&self.a * x - &self.b));
+            let x = solver.y.rows(0 .. n);
+            r_pri.assign(&(&solver.a * x - &solver.b));
+        }
+        if let Some(ranges) = &mut solver.ranges {
+            // the central residuals are updated with t in the loop
+            let (x, z) = (solver.y.rows(0 .. n), solver.y.rows(n + m + p .. n + m + p + k));
+            let mut r_z = solver.r_t.rows_mut(n + m + p .. n + m + p + k);
+            ranges.r = ranges.residual(&ranges.v, &x, &z, 0., &mut r_z);
         }
 
-        //
+        Timings::lap(lap, &mut timings.setup);
+
+        solver.iterations = 0;
+        solver.factorizations = 0;
+        solver.corrections = 0;
+        solver.direct = false;
+        solver.status = SolverStatus::Converged;
+
+        Ok(PDIPMState {
+            solver, param, log, n, m, p,
+            d_objective, dd_objective,
+            inequality, d_inequality, dd_inequality,
+            best: None,
+            timings,
+            lap_total,
+            outcome: None
+        })
+    }
 
-        let mut cnt = 0;
-        while cnt < param.n_loop {
-            writeln_or!(log)?;
-            writeln_or!(log, "===== ===== ===== ===== loop : {}", cnt)?;
+    /// Advances one iteration.
+    ///
+    /// Returns `Ok` with [`StepOutcome`](enum.StepOutcome.html) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// Once the outcome is other than `StepOutcome::Continue`, further calls return the same outcome without iterations.
+    pub fn step(&mut self) -> Result<StepOutcome, SolverError>
+    {
+        if let Some(outcome) = self.outcome {
+            return Ok(outcome);
+        }
 
-            let x = self.y.rows(0 .. n);
-            let lmd = self.y.rows(n .. n + m);
+        let (n, m, p) = (self.n, self.m, self.p);
+        let param = self.param;
+        let eps_feas = param.eps;
+        let b_loop = param.n_loop;
+        let cnt = self.solver.iterations;
+        let sv = &mut *self.solver;
+        let k = sv.k;
+        let sides = sv.ranges.as_ref().map_or(0, RangeRows::sides);
 
-            /***** calc t *****/
+        writeln_or!(self.log)?;
+        writeln_or!(self.log, "===== ===== ===== ===== loop : {}", cnt)?;
 
-            let eta = if m > 0 {
-                -self.f_i.prod(&lmd)
-            }
-            else {
-                param.eps
-            };
+        let x = sv.y.rows(0 .. n);
+        let lmd = sv.y.rows(n .. n + m);
+        let z = sv.y.rows(n + m + p .. n + m + p + k);
 
-            // inequality feasibility check
-            if eta < 0. {return Err("inequality: not feasible in loop");}
+        /***** calc t *****/
 
-            let inv_t = eta / (param.mu * m as FP);
+        // finite bounds of ranges count as inequalities
+        let eta = if m + sides > 0 {
+            -sv.f_i.prod(&lmd) + sv.ranges.as_ref().map_or(0., RangeRows::gap)
+        }
+        else {
+            param.eps
+        };
 
-            /***** update residual - central *****/
+        // inequality feasibility check
+        if eta < 0. {return Err(sv.failure("inequality: not feasible in loop"));}
 
-            if m > 0 {
-                let mut r_cent = self.r_t.rows_mut(n .. n + m);
-                r_cent.assign(&(-lmd.clone_diag() * &self.f_i - inv_t));
-            }
+        let inv_t = eta / (param.mu * (m + sides) as FP);
+
+        /***** update residual - central *****/
+
+        if m > 0 {
+            let mut r_cent = sv.r_t.rows_mut(n .. n + m);
+            r_cent.assign(&(-lmd.clone_diag() * &sv.f_i - inv_t));
+        }
+        if let Some(ranges) = &mut sv.ranges {
+            let mut r_z = sv.r_t.rows_mut(n + m + p .. n + m + p + k);
+            ranges.r = ranges.residual(&ranges.v, &x, &z, inv_t, &mut r_z);
+        }
+
+        /***** termination criteria *****/
+
+        // C x - w of ranges follows the equality residual
+        let r_dual = sv.r_t.rows(0 .. n);
+        let r_pri = sv.r_t.rows(n + m .. n + m + p + k);
 
-            /***** termination criteria *****/
+        let mut r_dual_norm = r_dual.norm_p2_resid();
+        let r_pri_norm = r_pri.norm_p2_resid();
+        if let Some(ranges) = &sv.ranges {
+            r_dual_norm = r_dual_norm.hypot(ranges.r.col(0).norm_p2_resid());
+        }
 
-            let r_dual = self.r_t.rows(0 .. n);
-            let r_pri = self.r_t.rows(n + m .. n + m + p);
+        writeln_or!(self.log, "|| r_dual || : {:.3e}", r_dual_norm)?;
+        writeln_or!(self.log, "|| r_pri  || : {:.3e}", r_pri_norm)?;
+        writeln_or!(self.log, "   eta       : {:.3e}", eta)?;
 
-            let r_dual_norm = r_dual.norm_p2();
-            let r_pri_norm = r_pri.norm_p2();
+        sv.residuals = Residuals {r_dual: r_dual_norm, r_pri: r_pri_norm, gap: eta};
 
-            writeln_or!(log, "|| r_dual || : {:.3e}", r_dual_norm)?;
-            writeln_or!(log, "|| r_pri  || : {:.3e}", r_pri_norm)?;
-            writeln_or!(log, "   eta       : {:.3e}", eta)?;
+        if (r_dual_norm <= eps_feas) && (r_pri_norm <= eps_feas) && (eta <= param.eps) {
+            writeln_or!(self.log, "termination criteria satisfied")?;
+            self.outcome = Some(StepOutcome::Converged);
+            return Ok(StepOutcome::Converged);
+        }
 
-            if (r_dual_norm <= eps_feas) && (r_pri_norm <= eps_feas) && (eta <= param.eps) {
-                writeln_or!(log, "termination criteria satisfied")?;
-                break;
+        // keep the best iterate for the iteration limit
+        if self.best.is_none_or(|b| sv.residuals.merit() < b.merit()) {
+            self.best = Some(sv.residuals);
+            sv.best_y.assign(&sv.y);
+            if let Some(ranges) = &mut sv.ranges {
+                ranges.best_v.assign(&ranges.v);
             }
+        }
 
-            /***** calc kkt matrix *****/
-            
-            let mut kkt_x_dual = self.kkt.slice_mut(0 .. n, 0 .. n);
-            dd_objective(&x, &mut self.ddf);
-            kkt_x_dual.assign(&self.ddf);
-            for i in 0 .. m {
-                dd_inequality(&x, &mut self.ddf, i);
-                kkt_x_dual += lmd[(i, 0)] * &self.ddf;
+        if cnt >= param.max_iter.unwrap_or(param.n_loop) {
+            writeln_or!(self.log, "iteration limit")?;
+            sv.status = SolverStatus::MaxIterReached;
+            sv.residuals = self.best.unwrap_or(sv.residuals);
+            sv.y.assign(&sv.best_y);
+            if let Some(ranges) = &mut sv.ranges {
+                ranges.v.assign(&ranges.best_v);
             }
+            (self.inequality)(&sv.y.rows(0 .. n), &mut sv.f_i);
+            self.outcome = Some(StepOutcome::MaxIterReached);
+            return Ok(StepOutcome::MaxIterReached);
+        }
 
-            if m > 0 {
-                let mut kkt_lmd_dual = self.kkt.slice_mut(0 .. n, n .. n + m);
-                kkt_lmd_dual.assign(&self.df_i.t());
+        /***** calc kkt matrix *****/
 
-                let mut kkt_x_cent = self.kkt.slice_mut(n .. n + m, 0 .. n);
-                kkt_x_cent.assign(&(-lmd.clone_diag() * &self.df_i));
+        let lap = Timings::start(param);
 
-                let mut kkt_lmd_cent = self.kkt.slice_mut(n .. n + m, n .. n + m);
-                kkt_lmd_cent.assign(&(-self.f_i.clone_diag()));
-            }
+        let mut kkt_x_dual = sv.kkt.slice_mut(0 .. n, 0 .. n);
+        (self.dd_objective)(&x, &mut sv.ddf);
+        kkt_x_dual.assign(&sv.ddf);
+        for i in 0 .. m {
+            (self.dd_inequality)(&x, &mut sv.ddf, i);
+            kkt_x_dual += lmd[(i, 0)] * &sv.ddf;
+        }
 
-            if p > 0 {
-                let mut kkt_nu_dual = self.kkt.slice_mut(0 .. n, n + m .. n + m + p);
-                kkt_nu_dual.assign(&self.a.t());
+        if m > 0 {
+            let mut kkt_lmd_dual = sv.kkt.slice_mut(0 .. n, n .. n + m);
+            kkt_lmd_dual.assign(&sv.df_i.t());
 
-                let mut kkt_x_pri = self.kkt.slice_mut(n + m .. n + m + p, 0 .. n);
-                kkt_x_pri.assign(&self.a);
-            }
+            let mut kkt_x_cent = sv.kkt.slice_mut(n .. n + m, 0 .. n);
+            kkt_x_cent.assign(&(-lmd.clone_diag() * &sv.df_i));
 
-            /***** calc search direction *****/
+            let mut kkt_lmd_cent = sv.kkt.slice_mut(n .. n + m, n .. n + m);
+            kkt_lmd_cent.assign(&(-sv.f_i.clone_diag()));
+        }
 
-            if param.log_kkt {
-                writeln_or!(log, "kkt : {}", self.kkt)?;
-            }
+        if p > 0 {
+            let mut kkt_nu_dual = sv.kkt.slice_mut(0 .. n, n + m .. n + m + p);
+            kkt_nu_dual.assign(&sv.a.t());
 
-            if param.svd_warm {
-                self.svd.decomp_warm(&self.kkt);
+            let mut kkt_x_pri = sv.kkt.slice_mut(n + m .. n + m + p, 0 .. n);
+            kkt_x_pri.assign(&sv.a);
+
+            let mut kkt_nu_pri = sv.kkt.slice_mut(n + m .. n + m + p, n + m .. n + m + p);
+            if param.dependent_rows == DependentRows::Regularize {
+                kkt_nu_pri.assign_eye();
+                kkt_nu_pri *= -DEPENDENT_REG;
             }
             else {
-                self.svd.decomp(&self.kkt);
+                kkt_nu_pri.assign_all(0.);
             }
-            
-            let dy = self.svd.solve(&(-&self.r_t));
+        }
 
-            writeln_or!(log, "y : {}", self.y.t())?;
-            writeln_or!(log, "r_t : {}", self.r_t.t())?;
-            writeln_or!(log, "dy : {}", dy.t())?;
+        // w and the dual variables of the bounds are eliminated
+        if let Some(ranges) = &sv.ranges {
+            let mut kkt_z_dual = sv.kkt.slice_mut(0 .. n, n + m + p .. n + m + p + k);
+            kkt_z_dual.assign(&ranges.mat_c.t());
 
-            /***** back tracking line search - from here *****/
+            let mut kkt_x_range = sv.kkt.slice_mut(n + m + p .. n + m + p + k, 0 .. n);
+            kkt_x_range.assign(&ranges.mat_c);
+            kkt_x_range.scale_rows(&ranges.sigmas());
 
-            let mut s_max: FP = 1.;
-            {
-                let dlmd = dy.rows(n .. n + m);
+            let mut kkt_z_range = sv.kkt.slice_mut(n + m + p .. n + m + p + k, n + m + p .. n + m + p + k);
+            kkt_z_range.assign_eye();
+            kkt_z_range *= -1.;
+        }
+
+        Timings::lap(lap, &mut self.timings.kkt_assembly);
+
+        /***** calc search direction *****/
 
+        if param.log_kkt {
+            writeln_or!(self.log, "kkt : {}", sv.kkt)?;
+        }
+
+        let lap = Timings::start(param);
+        let direct = match param.factorization {
+            Factorization::SVD => None,
+            Factorization::Cholesky => {
+                let reg = if param.dependent_rows == DependentRows::Regularize {DEPENDENT_REG} else {0.};
+                ReducedKKT::new(sv, &lmd, reg).ok().map(DirectKKT::Reduced)
+            },
+            Factorization::LU => {
+                // dropped rows are zero, whose nu are kept as they are
+                let lu = if sv.dropped_eqs.is_empty() {
+                    MatLU::new(&sv.kkt)
+                }
+                else {
+                    let mut kkt = sv.kkt.clone_sz();
+                    for &r in &sv.dropped_eqs {
+                        kkt[(n + m + r, n + m + r)] = 1.;
+                    }
+                    MatLU::new(&kkt)
+                };
+                lu.ok().map(DirectKKT::LU)
+            }
+        };
+        if direct.is_none() {
+            if param.svd_warm {
+                sv.svd.decomp_warm(&sv.kkt);
+            }
+            else {
+                sv.svd.decomp(&sv.kkt);
+            }
+        }
+        sv.direct = direct.is_some();
+        sv.factorizations += 1;
+        Timings::lap(lap, &mut self.timings.factorization);
+
+        let kkt_solve = |sv: &PDIPM, rhs: &Mat| match &direct {
+            Some(DirectKKT::Reduced(r)) => r.solve(sv, &lmd, rhs),
+            Some(DirectKKT::LU(lu)) => lu.solve(rhs),
+            None => sv.svd.solve(rhs)
+        };
+
+        let lap = Timings::start(param);
+        let mut rhs = -&sv.r_t;
+        if let Some(ranges) = &sv.ranges {
+            rhs.rows_mut(n + m + p .. n + m + p + k).assign(&ranges.rhs(&sv.r_t.rows(n + m + p .. n + m + p + k)));
+        }
+        let mut dy = kkt_solve(sv, &rhs);
+
+        /***** Gondzio centrality correctors *****/
+
+        if (m > 0) && (param.max_correctors > 0) {
+            let mut s_max = step_max(&lmd, &sv.f_i, &sv.df_i, &dy);
+            for _ in 0 .. param.max_correctors {
+                // complementarity products at an enlarged step, predicted by linearization
+                let s_trial = (s_max + GONDZIO_DELTA).min(1.);
+                let df = &sv.df_i * dy.rows(0 .. n);
+                // ranges are left uncorrected
+                let mut corr = Mat::new_vec(n + m + p + k);
                 for i in 0 .. m {
-                    if dlmd[(i, 0)] < -FP_MINPOS { // to avoid zero-division by Dlmd
-                        s_max = s_max.min(-lmd[(i, 0)] / dlmd[(i, 0)]);
+                    let w = -(lmd[(i, 0)] + s_trial * dy[(n + i, 0)]) * (sv.f_i[(i, 0)] + s_trial * df[(i, 0)]);
+                    // move the outliers into the box around the target
+                    corr[(n + i, 0)] = if w < GONDZIO_BETA_MIN * inv_t {
+                        GONDZIO_BETA_MIN * inv_t - w
+                    }
+                    else if w > GONDZIO_BETA_MAX * inv_t {
+                        (GONDZIO_BETA_MAX * inv_t - w).max(-GONDZIO_BETA_MAX * inv_t)
                     }
+                    else {
+                        0.
+                    };
+                }
+
+                let dy_c = &dy + kkt_solve(sv, &corr);
+                let s_max_c = step_max(&lmd, &sv.f_i, &sv.df_i, &dy_c);
+                if s_max_c < s_max + GONDZIO_GAMMA * GONDZIO_DELTA {
+                    break;
                 }
+                writeln_or!(self.log, "corrector : step {:.3e} -> {:.3e}", s_max, s_max_c)?;
+                dy = dy_c;
+                s_max = s_max_c;
+                sv.corrections += 1;
             }
-            let mut s = param.s_coef * s_max;
+        }
+        Timings::lap(lap, &mut self.timings.solve);
 
-            let mut y_p = &self.y + s * &dy;
+        writeln_or!(self.log, "y : {}", sv.y.t())?;
+        writeln_or!(self.log, "r_t : {}", sv.r_t.t())?;
+        writeln_or!(self.log, "dy : {}", dy.t())?;
 
-            let mut bcnt = 0;
-            while bcnt < b_loop {
-                let x_p = y_p.rows(0 .. n);
-                let lmd_p = y_p.rows(n .. n + m);
-                
-                // update f_i
-                inequality(&x_p, &mut self.f_i);
+        // direction of w and the dual variables of the bounds
+        let dv = sv.ranges.as_ref().map(|ranges| ranges.direction(&dy.rows(0 .. n), &sv.r_t.rows(n + m + p .. n + m + p + k)));
 
-                if (self.f_i.max().unwrap_or(-1.) < 0.) && (lmd_p.min().unwrap_or(1.) > 0.) {break;}
-                s *= param.beta;
-                y_p = &self.y + s * &dy;
+        /***** back tracking line search - from here *****/
 
-                bcnt += 1;
-            }
+        let lap = Timings::start(param);
 
-            writeln_or!(log, "s : {:.3e}", s)?;
+        let mut s_max: FP = 1.;
+        {
+            let dlmd = dy.rows(n .. n + m);
 
-            if bcnt < b_loop {
-                writeln_or!(log, "feasible points found")?;
-            }
-            else {
-                writeln_or!(log, "infeasible in this direction")?;
+            for i in 0 .. m {
+                if dlmd[(i, 0)] < -FP_MINPOS { // to avoid zero-division by Dlmd
+                    s_max = s_max.min(-lmd[(i, 0)] / dlmd[(i, 0)]);
+                }
             }
+        }
+        if let (Some(ranges), Some(dv)) = (&sv.ranges, &dv) {
+            s_max = s_max.min(ranges.step_max(dv));
+        }
+        let mut s = param.s_coef * s_max;
 
-            let org_r_t_norm = self.r_t.norm_p2();
+        let mut y_p = &sv.y + s * &dy;
 
-            while bcnt < b_loop {
-                let x_p = y_p.rows(0 .. n);
-                let lmd_p = y_p.rows(n .. n + m);
-                let nu_p = y_p.rows(n + m .. n + m + p);
+        let mut bcnt = 0;
+        while bcnt < b_loop {
+            let x_p = y_p.rows(0 .. n);
+            let lmd_p = y_p.rows(n .. n + m);
 
-                // update df_o, f_i, df_i
-                d_objective(&x_p, &mut self.df_o);
-                inequality(&x_p, &mut self.f_i);
-                d_inequality(&x_p, &mut self.df_i);
+            // update f_i
+            (self.inequality)(&x_p, &mut sv.f_i);
 
-                // update residual
-                let mut r_dual = self.r_t.rows_mut(0 .. n);
-                r_dual.assign(&self.df_o);
-                if m > 0 {
-                    r_dual += self.df_i.t() * &lmd_p;
-                }
-                if p > 0 {
-                    r_dual += self.a.t() * nu_p;
-                }
-                if m > 0 {
-                    let mut r_cent = self.r_t.rows_mut(n .. n + m);
-                    r_cent.assign(&(-lmd_p.clone_diag() * &self.f_i - inv_t));
-                }
-                if p > 0 {
-                    let mut r_pri = self.r_t.rows_mut(n + m .. n + m + p);
-                    r_pri.assign(&(&self.a * x_p - &self.b));
-                }
+            if (sv.f_i.max().unwrap_or(-1.) < 0.) && (lmd_p.min().unwrap_or(1.) > 0.) {break;}
+            s *= param.beta;
+            y_p = &sv.y + s * &dy;
 
-                if self.r_t.norm_p2() <= (1. - param.alpha * s) * org_r_t_norm {break;}
-                s *= param.beta;
-                y_p = &self.y + s * &dy;
+            bcnt += 1;
+        }
 
-                bcnt += 1;
-            }
+        writeln_or!(self.log, "s : {:.3e}", s)?;
 
-            writeln_or!(log, "s : {:.3e}", s)?;
+        if bcnt < b_loop {
+            writeln_or!(self.log, "feasible points found")?;
+        }
+        else {
+            writeln_or!(self.log, "infeasible in this direction")?;
+        }
 
-            if (bcnt < b_loop) && ((&y_p - &self.y).norm_p2() >= FP_EPSILON) {
-                writeln_or!(log, "update")?;
-                // update y
-                self.y.assign(&y_p);
+        let range_norm = |r: Option<&Mat>| r.map_or(0., |r| r.norm_p2());
+        let org_r_t_norm = sv.r_t.norm_p2().hypot(range_norm(sv.ranges.as_ref().map(|ranges| &ranges.r)));
+        // w and the dual variables of the bounds with their residuals at y_p
+        let mut v_p = None;
+
+        while bcnt < b_loop {
+            let x_p = y_p.rows(0 .. n);
+            let lmd_p = y_p.rows(n .. n + m);
+            let nu_p = y_p.rows(n + m .. n + m + p);
+            let z_p = y_p.rows(n + m + p .. n + m + p + k);
+
+            // update df_o, f_i, df_i
+            (self.d_objective)(&x_p, &mut sv.df_o);
+            (self.inequality)(&x_p, &mut sv.f_i);
+            (self.d_inequality)(&x_p, &mut sv.df_i);
+
+            // update residual
+            let mut r_dual = sv.r_t.rows_mut(0 .. n);
+            r_dual.assign(&sv.df_o);
+            if m > 0 {
+                r_dual += sv.df_i.t() * &lmd_p;
             }
-            else {
-                writeln_or!(log, "no more improvement")?;
-                return Err("line search: not converged");
+            if p > 0 {
+                r_dual += sv.a.t() * nu_p;
+            }
+            if let Some(ranges) = &sv.ranges {
+                r_dual += ranges.mat_c.t() * &z_p;
+            }
+            if m > 0 {
+                let mut r_cent = sv.r_t.rows_mut(n .. n + m);
+                r_cent.assign(&(-lmd_p.clone_diag() * &sv.f_i - inv_t));
+            }
+            if p > 0 {
+                let mut r_pri = sv.r_t.rows_mut(n + m .. n + m + p);
+                r_pri.assign(&(&sv.a * &x_p - &sv.b));
+            }
+            if let (Some(ranges), Some(dv)) = (&sv.ranges, &dv) {
+                let v = &ranges.v + s * dv;
+                let mut r_z = sv.r_t.rows_mut(n + m + p .. n + m + p + k);
+                let r = ranges.residual(&v, &x_p, &z_p, inv_t, &mut r_z);
+                v_p = Some((v, r));
             }
 
-            /***** back tracking line search - to here *****/
+            if sv.r_t.norm_p2().hypot(range_norm(v_p.as_ref().map(|(_, r)| r))) <= (1. - param.alpha * s) * org_r_t_norm {break;}
+            s *= param.beta;
+            y_p = &sv.y + s * &dy;
 
-            cnt += 1;
+            bcnt += 1;
         }
 
-        if !(cnt < param.n_loop) {
-            writeln_or!(log, "iteration limit")?;
-            return Err("iteration: not converged");
+        writeln_or!(self.log, "s : {:.3e}", s)?;
+
+        if (bcnt < b_loop) && ((&y_p - &sv.y).norm_p2() >= FP_EPSILON) {
+            writeln_or!(self.log, "update")?;
+            // update y
+            sv.y.assign(&y_p);
+            if let (Some(ranges), Some((v, r))) = (&mut sv.ranges, v_p) {
+                ranges.v = v;
+                ranges.r = r;
+            }
+        }
+        else {
+            writeln_or!(self.log, "no more improvement")?;
+            return Err(sv.failure("line search: not converged"));
         }
 
-        writeln_or!(log)?;
-        writeln_or!(log, "===== ===== ===== ===== result")?;
-        let x = self.y.rows(0 .. n);
-        let lmd = self.y.rows(n .. n + m);
-        let nu = self.y.rows(n + m .. n + m + p);
-        writeln_or!(log, "x : {}", x.t())?;
-        writeln_or!(log, "lmd : {}", lmd.t())?;
-        writeln_or!(log, "nu : {}", nu.t())?;
+        /***** back tracking line search - to here *****/
 
-        Ok(&self.y)
+        Timings::lap(lap, &mut self.timings.line_search);
+
+        sv.step_max = s_max;
+        sv.step_size = s;
+        sv.iterations = cnt + 1;
+
+        Ok(StepOutcome::Continue)
+    }
+
+    /// Finishes the solve, running the remaining iterations if any.
+    ///
+    /// Returns what [`PDIPM::solve`](struct.PDIPM.html#method.solve) returns,
+    /// and the getters of the solver such as [`PDIPM::status`](struct.PDIPM.html#method.status) tell about this solve.
+    pub fn finish(mut self) -> Result<&'a Mat, SolverError>
+    {
+        while self.step()? == StepOutcome::Continue {}
+
+        let (n, m, p) = (self.n, self.m, self.p);
+        let solver = self.solver;
+
+        writeln_or!(self.log)?;
+        writeln_or!(self.log, "===== ===== ===== ===== result")?;
+        let x = solver.y.rows(0 .. n);
+        let lmd = solver.y.rows(n .. n + m);
+        let nu = solver.y.rows(n + m .. n + m + p);
+        writeln_or!(self.log, "x : {}", x.t())?;
+        writeln_or!(self.log, "lmd : {}", lmd.t())?;
+        writeln_or!(self.log, "nu : {}", nu.t())?;
+        if let Some(ranges) = &solver.ranges {
+            writeln_or!(self.log, "z : {}", solver.range_dual().t())?;
+            writeln_or!(self.log, "w : {}", ranges.v.col(0).t())?;
+        }
+
+        if self.param.timing {
+            Timings::lap(self.lap_total, &mut self.timings.total);
+            solver.timings = Some(self.timings);
+        }
+
+        Ok(&solver.y)
+    }
+
+    /// Returns the current primal variables \\(x\\).
+    pub fn x(&self) -> MatSlice<'_>
+    {
+        self.solver.y.rows(0 .. self.n)
+    }
+
+    /// Returns the current dual variables \\(\\lambda\\) of the inequalities.
+    pub fn lmd(&self) -> MatSlice<'_>
+    {
+        self.solver.y.rows(self.n .. self.n + self.m)
+    }
+
+    /// Returns the current dual variables \\(\\nu\\) of the equalities.
+    pub fn nu(&self) -> MatSlice<'_>
+    {
+        self.solver.y.rows(self.n + self.m .. self.n + self.m + self.p)
+    }
+
+    /// Returns the current slacks \\( -f_i(x) \\) of the inequalities, which are positive.
+    pub fn slacks(&self) -> Mat
+    {
+        -&self.solver.f_i
+    }
+
+    /// Returns the residuals evaluated by the last step.
+    pub fn residuals(&self) -> Residuals
+    {
+        self.solver.residuals
+    }
+
+    /// Returns the number of iterations done.
+    pub fn iterations(&self) -> usize
+    {
+        self.solver.iterations
+    }
+
+    /// Returns the condition number of the last factorized KKT matrix,
+    /// the ratio of its largest to smallest singular value, or `None` before the first factorization.
+    ///
+    /// If factorized by `Factorization::Cholesky` or `Factorization::LU`,
+    /// it is instead [`cond_est_p1`](../mat/struct.MatGen.html#method.cond_est_p1), an estimate for p=1 norm.
+    pub fn kkt_cond(&self) -> Option<FP>
+    {
+        self.solver.kkt_cond()
+    }
+
+    /// Returns the max step size of the last step which keeps \\(\\lambda\\) positive.
+    pub fn step_max(&self) -> FP
+    {
+        self.solver.step_max
+    }
+
+    /// Returns the step size taken by the last step after the backtracking line search.
+    pub fn step_size(&self) -> FP
+    {
+        self.solver.step_size
     }
 }
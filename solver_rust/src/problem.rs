@@ -0,0 +1,747 @@
+/*!
+Problem abstraction
+
+Each pre-defined problem class has a struct wrapping its inputs
+([`LPProblem`](../lp/struct.LPProblem.html), [`QPProblem`](../qp/struct.QPProblem.html),
+[`QCQPProblem`](../qcqp/struct.QCQPProblem.html), [`SOCPProblem`](../socp/struct.SOCPProblem.html),
+[`SDPProblem`](../sdp/struct.SDPProblem.html)),
+all of which implement the common [`Problem`](trait.Problem.html) trait.
+This allows generic code to hold and run `Vec<Box<dyn Problem>>`.
+
+```
+use totsu::prelude::*;
+
+let n: usize = 2;
+
+let lp = LPProblem::new(
+    Mat::new_vec(n).set_all(1.),
+    -Mat::new(n, n).set_eye(), Mat::new_vec(n).set_all(-1.),
+    Mat::new(0, n), Mat::new_vec(0)
+);
+let qp = QPProblem::new(
+    Mat::new(n, n).set_eye(), Mat::new_vec(n),
+    Mat::new(0, n), Mat::new_vec(0),
+    Mat::new(0, n), Mat::new_vec(0)
+);
+
+let problems: Vec<Box<dyn Problem>> = vec![Box::new(lp), Box::new(qp)];
+
+let param = PDIPMParam::default();
+for prob in &problems {
+    prob.validate().unwrap();
+    let rslt = prob.solve(&param).unwrap();
+    assert_eq!(rslt.x.size(), (prob.dims().n, 1));
+}
+```
+*/
+
+use super::mat::{Mat, FP};
+use super::pdipm::{PDIPM, PDIPMParam, Timings};
+
+use std::fmt;
+use std::time::Duration;
+
+/// Dimensions of a problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProblemDims
+{
+    /// Number of variables.
+    pub n: usize,
+    /// Number of inequality constraints
+    /// (number of cones for SOCP, size of the linear matrix inequality for SDP).
+    pub m: usize,
+    /// Number of equality constraints.
+    pub p: usize
+}
+
+/// Sense of an objective
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sense
+{
+    /// Minimizes the objective.
+    #[default]
+    Minimize,
+    /// Maximizes the objective, which is minimized internally with its sign negated.
+    Maximize
+}
+
+impl Sense
+{
+    // used by solver modules: coefficient to convert the objective into minimization
+    pub(crate) fn sign(self) -> FP
+    {
+        match self {
+            Sense::Minimize => 1.,
+            Sense::Maximize => -1.
+        }
+    }
+}
+
+/// Result of a solver
+///
+/// Values are in the sense of the original objective.
+/// Dual variables satisfy the stationarity condition
+/// \\( \\nabla f(x) + \\sum_i \\lambda_i \\nabla f_i(x) + A^T \\nu = 0 \\)
+/// for the objective \\( f \\) either minimized or maximized,
+/// hence \\( \\lambda \\succeq 0 \\) for `Sense::Minimize` and \\( \\lambda \\preceq 0 \\) for `Sense::Maximize`.
+#[derive(Debug, Clone)]
+pub struct SolverResult
+{
+    /// Optimal primal variables.
+    pub x: Mat,
+    /// Optimal dual variables of inequality constraints.
+    pub lmd: Mat,
+    /// Optimal dual variables of equality constraints.
+    pub nu: Mat,
+    /// Number of iterations.
+    pub iterations: usize,
+    /// Sense of the objective.
+    pub sense: Sense,
+    /// Optimal objective value.
+    pub primal_objective: FP,
+    /// Dual objective value, the Lagrangian at `x`, `lmd` and `nu`.
+    pub dual_objective: FP,
+    /// Duality gap, primal minus dual objective for `Sense::Minimize` and its negation for `Sense::Maximize`,
+    /// which is nonnegative at a feasible solution.
+    pub duality_gap: FP,
+    /// Quadratic and linear parts of the objective for QP, `None` for the others.
+    pub objective_parts: Option<ObjectiveBreakdown>,
+    /// Durations of solver phases, if `PDIPMParam::timing` is enabled.
+    pub timings: Option<Timings>,
+    /// Total violation of soft constraints absorbed by their slack variables, zero without soft constraints.
+    pub soft_violation: FP,
+    /// Equality rows dropped as linearly dependent, see `PDIPMParam::dependent_rows`.
+    pub dropped_eqs: Vec<usize>,
+    /// Status of termination.
+    pub status: SolverStatus,
+    /// Residuals at `x`, `lmd` and `nu`.
+    pub residuals: Residuals,
+    /// Signed dual variables of two-sided constraints, \\( \\lambda^u - \\lambda^l \\) for each row,
+    /// empty without them.
+    pub range_dual: Mat,
+    /// Certificate of `SolverStatus::PrimalInfeasible` or `SolverStatus::DualInfeasible`,
+    /// `None` for the other statuses.
+    /// See [`selfdual`](../selfdual/index.html) for its form.
+    pub certificate: Option<Mat>
+}
+
+/// Parts of a QP objective
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ObjectiveBreakdown
+{
+    /// Quadratic part \\( {1 \\over 2} x^T P x \\).
+    pub quadratic: FP,
+    /// Linear part \\( q^T x \\).
+    pub linear: FP
+}
+
+/// Status of a solver result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolverStatus
+{
+    /// The termination criteria are satisfied.
+    #[default]
+    Converged,
+    /// The iteration limit `PDIPMParam::max_iter` or `PDIPMParam::n_loop` is reached,
+    /// and the result is the iterate with the smallest [`Residuals::merit`](struct.Residuals.html#method.merit).
+    MaxIterReached,
+    /// The problem is certified infeasible by `Formulation::SelfDual`.
+    PrimalInfeasible,
+    /// The dual problem is certified infeasible, i.e. the problem is unbounded, by `Formulation::SelfDual`.
+    DualInfeasible
+}
+
+/// Residuals of an iterate of the internal problem
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Residuals
+{
+    /// Norm of the dual residual.
+    pub r_dual: FP,
+    /// Norm of the primal residual.
+    pub r_pri: FP,
+    /// Surrogate duality gap \\( \\eta \\).
+    pub gap: FP
+}
+
+impl Residuals
+{
+    /// Returns the merit to compare iterates, \\( \\sqrt{ r_{\\rm dual}^2 + r_{\\rm pri}^2 + \\eta^2 } \\).
+    pub fn merit(&self) -> FP
+    {
+        (self.r_dual * self.r_dual + self.r_pri * self.r_pri + self.gap * self.gap).sqrt()
+    }
+}
+
+/// Names of variables and constraints
+///
+/// Each list is either empty, which leaves its items unnamed, or has one name per item.
+/// Validation errors and violation reports print names when available and indices otherwise.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProblemNames
+{
+    /// Names of variables.
+    pub vars: Vec<String>,
+    /// Names of inequality constraints.
+    pub ineqs: Vec<String>,
+    /// Names of equality constraints.
+    pub eqs: Vec<String>,
+    /// Names of two-sided constraints.
+    pub ranges: Vec<String>
+}
+
+// no names, used in place of None
+static UNNAMED: ProblemNames = ProblemNames {
+    vars: Vec::new(),
+    ineqs: Vec::new(),
+    eqs: Vec::new(),
+    ranges: Vec::new()
+};
+
+impl ProblemNames
+{
+    /// Returns the name of `i`-th variable, or `x[i]` if unnamed.
+    pub fn var(&self, i: usize) -> String
+    {
+        ProblemNames::h_label(&self.vars, "x", i)
+    }
+    /// Returns the name of `i`-th inequality constraint, or `ineq[i]` if unnamed.
+    pub fn ineq(&self, i: usize) -> String
+    {
+        ProblemNames::h_label(&self.ineqs, "ineq", i)
+    }
+    /// Returns the name of `i`-th equality constraint, or `eq[i]` if unnamed.
+    pub fn eq(&self, i: usize) -> String
+    {
+        ProblemNames::h_label(&self.eqs, "eq", i)
+    }
+    /// Returns the name of `i`-th two-sided constraint, or `range[i]` if unnamed.
+    pub fn range(&self, i: usize) -> String
+    {
+        ProblemNames::h_label(&self.ranges, "range", i)
+    }
+
+    fn h_label(names: &[String], kind: &str, i: usize) -> String
+    {
+        match names.get(i) {
+            Some(name) => name.clone(),
+            None => format!("{}[{}]", kind, i)
+        }
+    }
+
+    // used by solver modules:
+    // names of a problem which optionally has them
+    pub(crate) fn of(names: &Option<Box<ProblemNames>>) -> &ProblemNames
+    {
+        names.as_deref().unwrap_or(&UNNAMED)
+    }
+
+    // used by solver modules:
+    // labels of `m` inequality constraints followed by `p` equality constraints
+    pub(crate) fn constraints(&self, m: usize, p: usize) -> Vec<String>
+    {
+        (0 .. m).map(|i| self.ineq(i)).chain((0 .. p).map(|i| self.eq(i))).collect()
+    }
+
+    // used by solver modules:
+    // checks that each non-empty list agrees with the dimensions and `k` two-sided constraints
+    pub(crate) fn validate(&self, dims: ProblemDims, k: usize) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, m, p} = dims;
+
+        for (name, len, rhs, r) in [("vars", self.vars.len(), "variables", n),
+                                    ("ineqs", self.ineqs.len(), "inequality constraints", m),
+                                    ("eqs", self.eqs.len(), "equality constraints", p),
+                                    ("ranges", self.ranges.len(), "two-sided constraints", k)].iter() {
+            if *len > 0 {
+                check_dim(&format!("names.{}", name), *len, rhs, *r)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Two-sided linear constraints
+///
+/// The constraints are \\( l \\preceq C x \\preceq u \\),
+/// where elements of \\(l\\) may be `-FP::INFINITY` and those of \\(u\\) may be `FP::INFINITY` to drop the side.
+/// Each row has one signed dual variable
+/// [`SolverResult::range_dual`](struct.SolverResult.html#structfield.range_dual),
+/// which is positive on the upper side and negative on the lower side for `Sense::Minimize`.
+///
+/// Each row is one row of the KKT matrix rather than two stacked inequality constraints:
+/// the solver keeps the value \\( w_i \\) of \\( C_i x \\) strictly within \\( (l_i, u_i) \\)
+/// with a pair of dual variables of the bounds, and eliminates them into the row of the dual variable of \\( C_i x = w_i \\).
+/// A row must have \\( l_i < u_i \\); one with \\( l_i = u_i \\) is to be given as an equality constraint.
+#[derive(Debug, Clone)]
+pub struct RangeConstraints
+{
+    /// \\(C\\)
+    pub mat_c: Mat,
+    /// \\(l\\)
+    pub vec_l: Mat,
+    /// \\(u\\)
+    pub vec_u: Mat
+}
+
+impl RangeConstraints
+{
+    /// Makes constraints.
+    pub fn new(mat_c: Mat, vec_l: Mat, vec_u: Mat) -> RangeConstraints
+    {
+        RangeConstraints {
+            mat_c, vec_l, vec_u
+        }
+    }
+
+    // used by solver modules:
+    // checks consistency with `n` variables, labeling rows by `names`
+    pub(crate) fn validate(&self, n: usize, names: &ProblemNames) -> Result<(), SolverError>
+    {
+        let k = self.mat_c.size().0;
+
+        check_dim("mat_c columns", self.mat_c.size().1, "variables", n)?;
+        check_dim("vec_l rows", self.vec_l.size().0, "mat_c rows", k)?;
+        check_col_vec("vec_l", &self.vec_l)?;
+        check_dim("vec_u rows", self.vec_u.size().0, "mat_c rows", k)?;
+        check_col_vec("vec_u", &self.vec_u)?;
+
+        for r in 0 .. k {
+            let (l, u) = (self.vec_l[(r, 0)], self.vec_u[(r, 0)]);
+            if l.is_nan() || u.is_nan() || l > u || l == FP::INFINITY || u == FP::NEG_INFINITY {
+                return Err(SolverError::InvalidProblem(format!("{}: [{}, {}] is empty", names.range(r), l, u)));
+            }
+            if l == u {
+                return Err(SolverError::InvalidProblem(format!("{}: [{}, {}] is a point, to be an equality constraint", names.range(r), l, u)));
+            }
+        }
+
+        Ok(())
+    }
+
+    // used by refine module:
+    // appends the finite sides to (G, h) as rows of +C x <= u or -C x <= -l,
+    // and returns the stacked (G, h) and (row, sign) of each appended row
+    pub(crate) fn stack(&self, mat_g: &Mat, vec_h: &Mat) -> (Mat, Mat, Vec<(usize, FP)>)
+    {
+        let (m, n) = mat_g.size();
+        let k = self.mat_c.size().0;
+
+        let mut sides = Vec::new();
+        for r in 0 .. k {
+            if self.vec_l[(r, 0)] > FP::NEG_INFINITY {
+                sides.push((r, -1.));
+            }
+            if self.vec_u[(r, 0)] < FP::INFINITY {
+                sides.push((r, 1.));
+            }
+        }
+
+        let mut stacked_g = Mat::new(m + sides.len(), n);
+        let mut stacked_h = Mat::new_vec(m + sides.len());
+        stacked_g.rows_mut(0 .. m).assign(mat_g);
+        stacked_h.rows_mut(0 .. m).assign(vec_h);
+        for (j, &(r, sign)) in sides.iter().enumerate() {
+            for c in 0 .. n {
+                stacked_g[(m + j, c)] = sign * self.mat_c[(r, c)];
+            }
+            stacked_h[(m + j, 0)] = if sign > 0. {self.vec_u[(r, 0)]} else {-self.vec_l[(r, 0)]};
+        }
+
+        (stacked_g, stacked_h, sides)
+    }
+
+    // used by refine module:
+    // converts a result of the stacked problem with `m` original inequalities into that of the two-sided one
+    pub(crate) fn unstack(&self, rslt: SolverResult, m: usize, sides: &[(usize, FP)]) -> SolverResult
+    {
+        let mut range_dual = Mat::new_vec(self.mat_c.size().0);
+        for (j, &(r, sign)) in sides.iter().enumerate() {
+            range_dual[(r, 0)] += sign * rslt.lmd[(m + j, 0)];
+        }
+
+        SolverResult {
+            lmd: rslt.lmd.rows(0 .. m).clone_sz(),
+            range_dual,
+            .. rslt
+        }
+    }
+
+    // used by refine module:
+    // converts a result of the two-sided problem into that of the stacked one, the inverse of `unstack`
+    pub(crate) fn restack(&self, rslt: &SolverResult, sides: &[(usize, FP)]) -> SolverResult
+    {
+        let (m, _) = rslt.lmd.size();
+        let (k, _) = rslt.range_dual.size();
+        let s = rslt.sense.sign();
+        let lmd = Mat::new_vec(m + sides.len()).set_by(|r, _| {
+            if r < m {
+                rslt.lmd[(r, 0)]
+            }
+            else {
+                // the side of the sign is active, the other inactive
+                let (i, sign) = sides[r - m];
+                let dual = if i < k {sign * rslt.range_dual[(i, 0)]} else {0.};
+                s * (s * dual).max(0.)
+            }
+        });
+
+        SolverResult {
+            lmd,
+            range_dual: Mat::new_vec(0),
+            .. rslt.clone()
+        }
+    }
+}
+
+/// Error of a solver
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolverError
+{
+    /// Problem inputs are inconsistent.
+    InvalidProblem(String),
+    /// Iteration did not reach the termination criteria.
+    NotConverged(ConvergenceFailure),
+    /// Problem inputs are detected infeasible before iterations.
+    Infeasible(String),
+    /// Log output failed.
+    LogFailure
+}
+
+impl fmt::Display for SolverError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        match self {
+            SolverError::InvalidProblem(s) => write!(f, "invalid problem: {}", s),
+            SolverError::NotConverged(s) => write!(f, "not converged: {}", s),
+            SolverError::Infeasible(s) => write!(f, "infeasible: {}", s),
+            SolverError::LogFailure => write!(f, "log: I/O Error")
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+/// Details of `SolverError::NotConverged`
+///
+/// The fields other than `reason` are `None` if the failure is not attributed to iterations,
+/// and are taken at the last iterate, or at the best one for the iteration limit.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConvergenceFailure
+{
+    /// Short description of where it failed.
+    pub reason: String,
+    /// Primal and dual residuals and the complementarity gap.
+    pub residuals: Option<Residuals>,
+    /// Index of the inequality of the largest complementarity product \\( -\\lambda_i f_i(x) \\).
+    pub worst_complementarity: Option<usize>,
+    /// Last accepted step size after the backtracking line search.
+    pub step_size: Option<FP>,
+    /// Last max step size which keeps dual variables positive.
+    pub step_max: Option<FP>,
+    /// Condition number of the last factorized KKT matrix, estimated for p=1 norm if not by SVD.
+    pub kkt_cond: Option<FP>
+}
+
+impl ConvergenceFailure
+{
+    /// Returns a one-line diagnosis of which of the residuals stalled and why, or `None` without residuals.
+    pub fn diagnosis(&self) -> Option<String>
+    {
+        let r = self.residuals?;
+
+        let (name, value, mut hint) = if (r.r_pri >= r.r_dual) && (r.r_pri >= r.gap) {
+            ("primal residual", r.r_pri, "problem may be infeasible")
+        }
+        else if r.r_dual >= r.gap {
+            ("dual residual", r.r_dual, "problem may be unbounded")
+        }
+        else {
+            ("complementarity gap", r.gap, "iterates may have lost centrality")
+        };
+
+        let mut diag = format!("{} stalled at {:.1e}", name, value);
+        if let Some(s) = self.step_size.filter(|&s| s < DIAGNOSIS_SHORT_STEP) {
+            diag += &format!(" with step lengths {:.1e} < {:.0e}", s, DIAGNOSIS_SHORT_STEP);
+            hint = "problem may be ill-conditioned — consider scaling";
+        }
+        if let Some(c) = self.kkt_cond.filter(|&c| c > DIAGNOSIS_ILL_COND) {
+            diag += &format!(" and KKT condition number {:.1e}", c);
+            hint = "problem may be ill-conditioned — consider scaling";
+        }
+
+        Some(format!("{}; {}", diag, hint))
+    }
+}
+
+// thresholds of ConvergenceFailure::diagnosis
+const DIAGNOSIS_SHORT_STEP: FP = 1e-3;
+const DIAGNOSIS_ILL_COND: FP = 1e12;
+
+impl fmt::Display for ConvergenceFailure
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        match self.diagnosis() {
+            Some(diag) => write!(f, "{} ({})", self.reason, diag),
+            None => write!(f, "{}", self.reason)
+        }
+    }
+}
+
+impl From<&str> for ConvergenceFailure
+{
+    fn from(reason: &str) -> ConvergenceFailure
+    {
+        ConvergenceFailure {
+            reason: reason.into(),
+            .. Default::default()
+        }
+    }
+}
+
+impl From<String> for ConvergenceFailure
+{
+    fn from(reason: String) -> ConvergenceFailure
+    {
+        ConvergenceFailure {
+            reason,
+            .. Default::default()
+        }
+    }
+}
+
+/// Common interface of problems
+pub trait Problem
+{
+    /// Returns dimensions of the problem.
+    fn dims(&self) -> ProblemDims;
+    /// Checks consistency of the problem inputs.
+    fn validate(&self) -> Result<(), SolverError>;
+    /// Solves the problem with given parameters.
+    fn solve(&self, param: &PDIPMParam) -> Result<SolverResult, SolverError>;
+}
+
+impl SolverResult
+{
+    // used by solver modules:
+    // the last solution consists of (x, lmd, nu) of the internal problem, which has n_i variables and m_i inequalities,
+    // and its objective is multiplied by sense.sign()
+    pub(crate) fn from_solver<F>(solver: &PDIPM, dims: ProblemDims, (n_i, m_i): (usize, usize),
+                                 sense: Sense, objective: F) -> SolverResult
+    where F: FnOnce(&Mat) -> FP
+    {
+        let ProblemDims {n, m, p} = dims;
+        let y = solver.y();
+        let x = y.rows(0 .. n).clone_sz();
+
+        SolverResult {
+            lmd: y.rows(n_i .. n_i + m) * sense.sign(),
+            nu: y.rows(n_i + m_i .. n_i + m_i + p) * sense.sign(),
+            iterations: solver.iterations(),
+            sense,
+            primal_objective: objective(&x),
+            dual_objective: FP::NAN,
+            duality_gap: FP::NAN,
+            objective_parts: None,
+            timings: solver.timings().cloned(),
+            soft_violation: 0.,
+            dropped_eqs: solver.dropped_eqs().to_vec(),
+            status: solver.status(),
+            residuals: solver.residuals(),
+            range_dual: Mat::new_vec(0),
+            certificate: None,
+            x
+        }
+    }
+
+    /// Returns the quadratic and linear parts of the objective for QP, `None` for the others.
+    ///
+    /// Their sum is `primal_objective`.
+    pub fn objective_breakdown(&self) -> Option<ObjectiveBreakdown>
+    {
+        self.objective_parts
+    }
+
+    // used by solver modules:
+    // sets the parts of a QP objective at x
+    pub(crate) fn with_qp_parts(mut self, mat_p: &Mat, vec_q: &Mat) -> SolverResult
+    {
+        self.objective_parts = Some(ObjectiveBreakdown {
+            quadratic: self.x.prod(&(mat_p * &self.x)) / 2.,
+            linear: vec_q.prod(&self.x)
+        });
+        self
+    }
+
+    // used by solver modules:
+    // sets the duality gap and the dual objective from the Lagrangian,
+    // where `constraints` returns the inequality values paired with `lmd` and the equality residuals at x
+    pub(crate) fn with_gap<F>(mut self, constraints: F) -> SolverResult
+    where F: FnOnce(&Mat) -> (Mat, Mat)
+    {
+        let sign = self.sense.sign();
+        let (f_i, r_eq) = constraints(&self.x);
+        let dual = self.primal_objective + self.lmd.prod(&f_i) + self.nu.prod(&r_eq);
+
+        self.dual_objective = dual;
+        self.duality_gap = sign * (self.primal_objective - dual);
+        self
+    }
+
+    // used by solver modules:
+    // sets the signed dual variables of `ranges` from the last solution of `solver`,
+    // and adds their terms to the dual objective and the duality gap set by `with_gap`
+    pub(crate) fn with_range_dual(mut self, solver: &PDIPM, ranges: Option<&RangeConstraints>) -> SolverResult
+    {
+        if let Some(ranges) = ranges {
+            let sign = self.sense.sign();
+            let z = solver.range_dual();
+            let cx = &ranges.mat_c * &self.x;
+
+            // z (C x - u) on the upper side and z (C x - l) on the lower side
+            let dual: FP = (0 .. z.size().0).map(|r| {
+                let bound = if z[(r, 0)] > 0. {ranges.vec_u[(r, 0)]} else {ranges.vec_l[(r, 0)]};
+                if bound.is_finite() {sign * z[(r, 0)] * (cx[(r, 0)] - bound)} else {0.}
+            }).sum();
+
+            self.range_dual = z * sign;
+            self.dual_objective += dual;
+            self.duality_gap = sign * (self.primal_objective - self.dual_objective);
+        }
+        self
+    }
+
+    // used by solver modules:
+    // x of a converged result, for interfaces which do not convey the status
+    pub(crate) fn converged_x(self) -> Result<Mat, SolverError>
+    {
+        match self.status {
+            SolverStatus::Converged => Ok(self.x),
+            SolverStatus::MaxIterReached => Err(SolverError::NotConverged(ConvergenceFailure {
+                residuals: Some(self.residuals),
+                .. "iteration: not converged".into()
+            })),
+            SolverStatus::PrimalInfeasible => Err(SolverError::NotConverged("status: primal infeasible".into())),
+            SolverStatus::DualInfeasible => Err(SolverError::NotConverged("status: dual infeasible".into()))
+        }
+    }
+
+    // used by solver modules:
+    // adds a duration of problem validation to the recorded timings
+    pub(crate) fn with_validation(mut self, validation: Duration) -> SolverResult
+    {
+        if let Some(timings) = &mut self.timings {
+            timings.setup += validation;
+            timings.total += validation;
+        }
+        self
+    }
+}
+
+// used by solver modules:
+// checks that a dimension `l` described by `lhs` agrees with `r` described by `rhs`
+pub(crate) fn check_dim(lhs: &str, l: usize, rhs: &str, r: usize) -> Result<(), SolverError>
+{
+    if l == r {
+        Ok(())
+    }
+    else {
+        Err(SolverError::InvalidProblem(format!("{} = {} does not match {} = {}", lhs, l, rhs, r)))
+    }
+}
+
+// used by solver modules:
+// checks that `vec` named `name` is a column vector
+pub(crate) fn check_col_vec(name: &str, vec: &Mat) -> Result<(), SolverError>
+{
+    let (_, ncols) = vec.size();
+
+    if ncols == 1 {
+        Ok(())
+    }
+    else {
+        Err(SolverError::InvalidProblem(format!("{} columns = {} does not match a column vector", name, ncols)))
+    }
+}
+
+// used by solver modules:
+// checks that square `mat` named `name` is symmetric within `tol` if given, reporting the worst pair
+pub(crate) fn check_sym(name: &str, mat: &Mat, tol: Option<FP>) -> Result<(), SolverError>
+{
+    match (tol, mat.sym_violation()) {
+        (Some(tol), Some((r, c, v))) if v.is_nan() || (v > tol) => {
+            Err(SolverError::InvalidProblem(format!("{} is not symmetric: {}[({}, {})] = {} and {}[({}, {})] = {} differ by {:e} > tol {:e}",
+                                                    name, name, r, c, mat[(r, c)], name, c, r, mat[(c, r)], v, tol)))
+        },
+        _ => Ok(())
+    }
+}
+
+// used by solver modules:
+// checks that `mat` named `name` has no infinite or NaN element,
+// so that a bad input is reported at the boundary rather than as non-convergence
+pub(crate) fn check_finite(name: &str, mat: &Mat) -> Result<(), SolverError>
+{
+    match mat.find_non_finite() {
+        None => Ok(()),
+        Some((r, c, e)) => Err(SolverError::InvalidProblem(format!("{}[({}, {})] = {} is not finite", name, r, c, e)))
+    }
+}
+
+#[test]
+fn test_problem_trait()
+{
+    use super::prelude::*;
+    use super::verify::verify_qp_kkt;
+
+    let n: usize = 2;
+    let m: usize = 1;
+    let p: usize = 0;
+
+    let mat_p = Mat::new(n, n).set_eye();
+    let vec_q = Mat::new_vec(n).set_iter(&[
+        1., 2.
+    ]);
+    let mat_g = Mat::new(m, n).set_iter(&[
+        -1. / 2., -1. / 3.
+    ]);
+    let vec_h = Mat::new_vec(m).set_iter(&[
+        -1.
+    ]);
+    let mat_a = Mat::new(p, n);
+    let vec_b = Mat::new_vec(p);
+
+    let param = PDIPMParam::default();
+    let x_fn = PDIPM::new().solve_qp(&param, &mut std::io::sink(),
+                                     &mat_p, &vec_q,
+                                     &mat_g, &vec_h,
+                                     &mat_a, &vec_b).unwrap();
+
+    let prob = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                              mat_g.clone(), vec_h.clone(),
+                              mat_a.clone(), vec_b.clone());
+    let probs: Vec<Box<dyn Problem>> = vec![Box::new(prob)];
+
+    for prob in &probs {
+        assert_eq!(prob.dims(), ProblemDims {n, m, p});
+        let rslt = prob.solve(&param).unwrap();
+        println!("x = {}", rslt.x);
+        println!("lmd = {}", rslt.lmd);
+        assert_eq!(rslt.x, x_fn);
+        assert!(rslt.iterations > 0);
+
+        let report = verify_qp_kkt(&rslt, &mat_p, &vec_q,
+                                   &mat_g, &vec_h,
+                                   &mat_a, &vec_b, param.eps.sqrt());
+        println!("report = {:?}", report);
+        assert!(report.is_optimal());
+    }
+
+    let bad = QPProblem::new(Mat::new(2, 2), Mat::new_vec(3),
+                             Mat::new(0, 2), Mat::new_vec(0),
+                             Mat::new(0, 2), Mat::new_vec(0));
+    assert!(matches!(bad.validate(), Err(SolverError::InvalidProblem(_))));
+    assert!(matches!(bad.solve(&param), Err(SolverError::InvalidProblem(_))));
+}
@@ -1,6 +1,8 @@
 //! Quadratically constrained quadratic program
 
 use super::prelude::*;
+use super::problem::{Problem, ProblemDims, SolverResult, SolverError, Sense};
+use super::problem::{check_dim, check_col_vec};
 
 use std::io::Write;
 
@@ -31,62 +33,75 @@ use std::io::Write;
 /// \\end{array}
 /// \\]
 pub trait QCQP {
+    #[allow(clippy::too_many_arguments)]
     fn solve_qcqp<L>(&mut self, param: &PDIPMParam, log: &mut L,
                      mat_p: &[Mat], vec_q: &[Mat], scl_r: &[FP],
                      mat_a: &Mat, vec_b: &Mat)
-                     -> Result<Mat, String>
+                     -> Result<Mat, SolverError>
     where L: Write;
 }
 
-fn check_param(mat_p: &[Mat], vec_q: &[Mat], scl_r: &[FP],
-               mat_a: &Mat, vec_b: &Mat)
-               -> Result<(usize, usize, usize), String>
+/// Quadratically constrained quadratic program inputs
+/// 
+/// See [`QCQP`](trait.QCQP.html) for the problem formulation.
+#[derive(Debug, Clone)]
+pub struct QCQPProblem
 {
-        if mat_p.len() == 0 {return Err("mat_p: 0 length".into());}
-
-        let (n, _) = mat_p[0].size();
-        let m = mat_p.len() - 1;
-        let (p, _) = mat_a.size();
-
-        if n == 0 {return Err("mat_p[0]: 0 rows".into());}
-        // m = 0 means NO inequality constraints
-        // p = 0 means NO equality constraints
-
-        if vec_q.len() != m + 1 {return Err(format!("vec_q: length {} must be {}", vec_q.len(), m + 1));}
-        if scl_r.len() != m + 1 {return Err(format!("scl_r: length {} must be {}", scl_r.len(), m + 1));}
-
-        for i in 0 ..= m {
-            if mat_p[i].size() != (n, n) {return Err(format!("mat_p[{}]: size {:?} must be {:?}", i, mat_p[i].size(), (n, n)));}
-            if vec_q[i].size() != (n, 1) {return Err(format!("vec_q[{}]: size {:?} must be {:?}", i, vec_q[i].size(), (n, 1)));}
-        }
-
-        if mat_a.size() != (p, n) {return Err(format!("mat_a: size {:?} must be {:?}", mat_a.size(), (p, n)));}
-        if vec_b.size() != (p, 1) {return Err(format!("vec_b: size {:?} must be {:?}", vec_b.size(), (p, 1)));}
-
-        Ok((n, m, p))
+    /// \\(P_0, \\ldots, P_m\\)
+    pub mat_p: Vec<Mat>,
+    /// \\(q_0, \\ldots, q_m\\)
+    pub vec_q: Vec<Mat>,
+    /// \\(r_0, \\ldots, r_m\\)
+    pub scl_r: Vec<FP>,
+    /// \\(A\\)
+    pub mat_a: Mat,
+    /// \\(b\\)
+    pub vec_b: Mat,
+    /// Sense of the objective, `Sense::Minimize` by default.
+    pub objective_sense: Sense
 }
 
-impl QCQP for PDIPM
+impl QCQPProblem
 {
-    /// Runs the solver with given parameters.
+    /// Makes a problem.
+    pub fn new(mat_p: Vec<Mat>, vec_q: Vec<Mat>, scl_r: Vec<FP>,
+               mat_a: Mat, vec_b: Mat) -> QCQPProblem
+    {
+        QCQPProblem {
+            mat_p, vec_q, scl_r,
+            mat_a, vec_b,
+            objective_sense: Sense::default()
+        }
+    }
+    /// Sets the sense of the objective.
+    pub fn with_sense(mut self, sense: Sense) -> QCQPProblem
+    {
+        self.objective_sense = sense;
+        self
+    }
+
+    /// Runs the solver with given parameters, reusing a solver instance.
     /// 
-    /// Returns `Ok` with optimal \\(x\\) or `Err` with message string.
+    /// Returns `Ok` with [`SolverResult`](../problem/struct.SolverResult.html)
+    /// or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `solver` is a solver instance.
     /// * `param` is solver parameters.
     /// * `log` outputs solver progress.
-    /// * `mat_p` is \\(P_0, \\ldots, P_m\\).
-    /// * `vec_q` is \\(q_0, \\ldots, q_m\\).
-    /// * `scl_r` is \\(r_0, \\ldots, r_m\\).
-    /// * `mat_a` is \\(A\\).
-    /// * `vec_b` is \\(b\\).
-    fn solve_qcqp<L>(&mut self, param: &PDIPMParam, log: &mut L,
-                     mat_p: &[Mat], vec_q: &[Mat], scl_r: &[FP],
-                     mat_a: &Mat, vec_b: &Mat)
-                     -> Result<Mat, String>
+    pub fn solve_with<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                         -> Result<SolverResult, SolverError>
     where L: Write
     {
         // ----- parameter check
 
-        let (n, m, p) = check_param(mat_p, vec_q, scl_r, mat_a, vec_b)?;
+        let lap = Timings::start(param);
+        self.validate()?;
+        let validation = Timings::elapsed(lap);
+        let ProblemDims {n, m, p} = self.dims();
+
+        let (mat_p, vec_q, scl_r) = (&self.mat_p, &self.vec_q, &self.scl_r);
+        let sign = self.objective_sense.sign();
+        let (mat_p0, vec_q0) = (&(sign * &mat_p[0]), &(sign * &vec_q[0]));
+        let (mat_a, vec_b) = (&self.mat_a, &self.vec_b);
 
         // ----- initial value of a slack variable
 
@@ -100,17 +115,17 @@ impl QCQP for PDIPM
 
         // ----- start to solve
 
-        let rslt = self.solve(param, log,
+        let rslt = solver.solve(param, log,
             n + 1, m, p + 1, // '+ 1' is for a slack variable
             |x, df_o| {
                 df_o.rows_mut(0 .. n).assign(&(
-                    &mat_p[0] * x.rows(0 .. n) + &vec_q[0]
+                    mat_p0 * x.rows(0 .. n) + vec_q0
                 ));
                 // for a slack variable
                 df_o[(n, 0)] = 0.;
             },
             |_, ddf_o| {
-                ddf_o.slice_mut(0 .. n, 0 .. n).assign(&mat_p[0]);
+                ddf_o.slice_mut(0 .. n, 0 .. n).assign(mat_p0);
                 // for a slack variable
                 ddf_o.row_mut(n).assign_all(0.);
                 ddf_o.col_mut(n).assign_all(0.);
@@ -155,9 +170,91 @@ impl QCQP for PDIPM
             }
         );
 
-        match rslt {
-            Ok(y) => Ok(y.rows(0 .. n).clone_sz()),
-            Err(s) => Err(s.into())
+        rslt?;
+
+        let rslt = SolverResult::from_solver(solver, self.dims(), (n + 1, m),
+                                             self.objective_sense, |x| {
+            x.prod(&(&self.mat_p[0] * x)) / 2. + self.vec_q[0].prod(x) + self.scl_r[0]
+        });
+        let rslt = rslt.with_gap(|x| {
+            let f_i = Mat::new_vec(m).set_by(|r, _| {
+                x.prod(&(&self.mat_p[r + 1] * x)) / 2. + self.vec_q[r + 1].prod(x) + self.scl_r[r + 1]
+            });
+            (f_i, &self.mat_a * x - &self.vec_b)
+        });
+
+        Ok(rslt.with_validation(validation))
+    }
+}
+
+impl Problem for QCQPProblem
+{
+    fn dims(&self) -> ProblemDims
+    {
+        ProblemDims {
+            n: self.mat_p.first().map_or(0, |p0| p0.size().0),
+            m: self.mat_p.len().max(1) - 1,
+            p: self.mat_a.size().0
+        }
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let (mat_p, vec_q, scl_r) = (&self.mat_p, &self.vec_q, &self.scl_r);
+        let (mat_a, vec_b) = (&self.mat_a, &self.vec_b);
+
+        if mat_p.is_empty() {return Err(SolverError::InvalidProblem("mat_p: 0 length".into()));}
+
+        let ProblemDims {n, m, p} = self.dims();
+
+        if n == 0 {return Err(SolverError::InvalidProblem("mat_p[0]: 0 rows".into()));}
+        // m = 0 means NO inequality constraints
+        // p = 0 means NO equality constraints
+
+        check_dim("vec_q length", vec_q.len(), "mat_p length", m + 1)?;
+        check_dim("scl_r length", scl_r.len(), "mat_p length", m + 1)?;
+
+        for i in 0 ..= m {
+            check_dim(&format!("mat_p[{}] rows", i), mat_p[i].size().0, "mat_p[0] rows", n)?;
+            check_dim(&format!("mat_p[{}] columns", i), mat_p[i].size().1, "mat_p[0] rows", n)?;
+            check_dim(&format!("vec_q[{}] rows", i), vec_q[i].size().0, "mat_p[0] rows", n)?;
+            check_col_vec(&format!("vec_q[{}]", i), &vec_q[i])?;
         }
+
+        check_dim("mat_a columns", mat_a.size().1, "mat_p[0] rows", n)?;
+        check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+        check_col_vec("vec_b", vec_b)?;
+
+        Ok(())
+    }
+
+    fn solve(&self, param: &PDIPMParam) -> Result<SolverResult, SolverError>
+    {
+        self.solve_with(&mut PDIPM::new(), param, &mut std::io::sink())
+    }
+}
+
+impl QCQP for PDIPM
+{
+    /// Runs the solver with given parameters.
+    /// 
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    /// * `mat_p` is \\(P_0, \\ldots, P_m\\).
+    /// * `vec_q` is \\(q_0, \\ldots, q_m\\).
+    /// * `scl_r` is \\(r_0, \\ldots, r_m\\).
+    /// * `mat_a` is \\(A\\).
+    /// * `vec_b` is \\(b\\).
+    fn solve_qcqp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                     mat_p: &[Mat], vec_q: &[Mat], scl_r: &[FP],
+                     mat_a: &Mat, vec_b: &Mat)
+                     -> Result<Mat, SolverError>
+    where L: Write
+    {
+        let prob = QCQPProblem::new(mat_p.to_vec(), vec_q.to_vec(), scl_r.to_vec(),
+                                    mat_a.clone(), vec_b.clone());
+
+        prob.solve_with(self, param, log).and_then(|rslt| rslt.converged_x()).map_err(|e| self.diagnose(e))
     }
 }
@@ -1,8 +1,18 @@
 //! Quadratic program
 
 use super::prelude::*;
+use super::problem::{Problem, ProblemDims, SolverResult, SolverError, Sense, RangeConstraints, ProblemNames};
+use super::verify::{violation_qp, ViolationReport};
+use super::problem::{check_dim, check_col_vec, check_finite, check_sym};
+use super::stats::ProblemStats;
 
 use std::io::Write;
+use std::borrow::Cow;
+macro_rules! writeln_or {
+    ( $( $arg: expr ),* ) => {
+        writeln!( $( $arg ),* ).or(Err(SolverError::LogFailure))
+    };
+}
 
 /// Quadratic program
 /// 
@@ -34,74 +44,369 @@ use std::io::Write;
 /// 
 /// In the following, \\( r \\) does not appear since it does not matter.
 pub trait QP {
+    #[allow(clippy::too_many_arguments)]
     fn solve_qp<L>(&mut self, param: &PDIPMParam, log: &mut L,
                    mat_p: &Mat, vec_q: &Mat,
                    mat_g: &Mat, vec_h: &Mat,
                    mat_a: &Mat, vec_b: &Mat)
-                   -> Result<Mat, String>
+                   -> Result<Mat, SolverError>
     where L: Write;
 }
 
-fn check_param(mat_p: &Mat, vec_q: &Mat,
-               mat_g: &Mat, vec_h: &Mat,
-               mat_a: &Mat, vec_b: &Mat)
-               -> Result<(usize, usize, usize), String>
+/// Quadratic program inputs
+/// 
+/// See [`QP`](trait.QP.html) for the problem formulation.
+#[derive(Debug, Clone)]
+pub struct QPProblem
 {
-    let (n, _) = mat_p.size();
-    let (m, _) = mat_g.size();
-    let (p, _) = mat_a.size();
-
-    if n == 0 {return Err("mat_p: 0 rows".into());}
-    // m = 0 means NO inequality constraints
-    // p = 0 means NO equality constraints
+    /// \\(P\\)
+    pub mat_p: Mat,
+    /// \\(q\\)
+    pub vec_q: Mat,
+    /// \\(G\\)
+    pub mat_g: Mat,
+    /// \\(h\\)
+    pub vec_h: Mat,
+    /// \\(A\\)
+    pub mat_a: Mat,
+    /// \\(b\\)
+    pub vec_b: Mat,
+    /// Sense of the objective, `Sense::Minimize` by default.
+    pub objective_sense: Sense,
+    /// Softened inequality constraints, none by default.
+    pub soft_constraints: Vec<SoftConstraint>,
+    /// Two-sided constraints in addition to \\( G x \\preceq h \\), none by default.
+    pub ranges: Option<RangeConstraints>,
+    /// Names of variables and constraints, none by default.
+    pub names: Option<Box<ProblemNames>>
+}
 
-    if mat_p.size() != (n, n) {return Err(format!("mat_p: size {:?} must be {:?}", mat_p.size(), (n, n)));}
-    if vec_q.size() != (n, 1) {return Err(format!("vec_q: size {:?} must be {:?}", vec_q.size(), (n, 1)));}
-    if mat_g.size() != (m, n) {return Err(format!("mat_g: size {:?} must be {:?}", mat_g.size(), (m, n)));}
-    if vec_h.size() != (m, 1) {return Err(format!("vec_h: size {:?} must be {:?}", vec_h.size(), (m, 1)));}
-    if mat_a.size() != (p, n) {return Err(format!("mat_a: size {:?} must be {:?}", mat_a.size(), (p, n)));}
-    if vec_b.size() != (p, 1) {return Err(format!("vec_b: size {:?} must be {:?}", vec_b.size(), (p, 1)));}
+/// Penalty of a soft constraint violation \\( t \\ge 0 \\)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoftNorm
+{
+    /// \\( w t \\), an exact penalty which keeps the constraint satisfied when possible with a large enough \\(w\\).
+    L1,
+    /// \\( {1 \\over 2} w t^2 \\)
+    L2
+}
 
-    Ok((n, m, p))
+/// Soft inequality constraint
+///
+/// The \\(i\\)-th constraint \\( (G x)_i \\le h_i \\) is relaxed into \\( (G x)_i \\le h_i + t \\)
+/// with a slack variable \\( t \\ge 0 \\), whose penalty of weight \\(w\\) is added to the objective.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoftConstraint
+{
+    /// Index \\(i\\) of the inequality constraint.
+    pub index: usize,
+    /// Weight \\(w\\) of the penalty.
+    pub weight: FP,
+    /// Type of the penalty.
+    pub norm: SoftNorm
 }
 
-impl QP for PDIPM
+impl QPProblem
 {
-    /// Runs the solver with given parameters.
+    /// Makes a problem.
+    pub fn new(mat_p: Mat, vec_q: Mat,
+               mat_g: Mat, vec_h: Mat,
+               mat_a: Mat, vec_b: Mat) -> QPProblem
+    {
+        QPProblem {
+            mat_p, vec_q,
+            mat_g, vec_h,
+            mat_a, vec_b,
+            objective_sense: Sense::default(),
+            soft_constraints: Vec::new(),
+            ranges: None,
+            names: None
+        }
+    }
+    /// Sets the sense of the objective.
+    pub fn with_sense(mut self, sense: Sense) -> QPProblem
+    {
+        self.objective_sense = sense;
+        self
+    }
+    /// Softens the `index`-th inequality constraint with a penalty of `weight` and `norm`.
+    ///
+    /// See [`SoftConstraint`](struct.SoftConstraint.html).
+    /// The slack variables do not appear in the result,
+    /// and [`SolverResult::soft_violation`](../problem/struct.SolverResult.html#structfield.soft_violation)
+    /// reports their sum.
+    /// The dual objective and the duality gap are those of the internal problem including the penalties.
+    pub fn soft(mut self, index: usize, weight: FP, norm: SoftNorm) -> QPProblem
+    {
+        self.soft_constraints.push(SoftConstraint {index, weight, norm});
+        self
+    }
+
+    /// Adds two-sided constraints \\( l \\preceq C x \\preceq u \\).
+    ///
+    /// See [`RangeConstraints`](../problem/struct.RangeConstraints.html), each row of which is one row of the KKT matrix.
+    /// Their dual variables are reported in
+    /// [`SolverResult::range_dual`](../problem/struct.SolverResult.html#structfield.range_dual).
+    pub fn with_ranges(mut self, mat_c: Mat, vec_l: Mat, vec_u: Mat) -> QPProblem
+    {
+        self.ranges = Some(RangeConstraints::new(mat_c, vec_l, vec_u));
+        self
+    }
+
+    /// Names variables and constraints.
+    ///
+    /// See [`ProblemNames`](../problem/struct.ProblemNames.html).
+    pub fn with_names(mut self, names: ProblemNames) -> QPProblem
+    {
+        self.names = Some(Box::new(names));
+        self
+    }
+
+    /// Returns statistics of the inputs, which may be examined before solving.
+    ///
+    /// See [`ProblemStats`](../stats/struct.ProblemStats.html).
+    /// The condition number of \\(P\\) is estimated by singular value decomposition.
+    pub fn analyze(&self) -> ProblemStats
+    {
+        self.h_stats(true)
+    }
+
+    fn h_stats(&self, with_cond: bool) -> ProblemStats
+    {
+        self.h_inputs().stats(with_cond)
+    }
+
+    fn h_inputs(&self) -> QPInputs<'_>
+    {
+        QPInputs {
+            mat_p: &self.mat_p, vec_q: &self.vec_q,
+            mat_g: &self.mat_g, vec_h: &self.vec_h,
+            mat_a: &self.mat_a, vec_b: &self.vec_b,
+            ranges: self.ranges.as_ref()
+        }
+    }
+
+    // the hard problem with slack variables of soft constraints appended to x:
+    // used by refine module
+    pub(crate) fn h_soften(&self) -> QPProblem
+    {
+        let ProblemDims {n, m, p} = self.dims();
+        let k = self.soft_constraints.len();
+        let sign = self.objective_sense.sign();
+
+        let mut mat_p = Mat::new(n + k, n + k);
+        let mut vec_q = Mat::new_vec(n + k);
+        let mut mat_g = Mat::new(m + k, n + k);
+        let mut vec_h = Mat::new_vec(m + k);
+        let mut mat_a = Mat::new(p, n + k);
+
+        mat_p.slice_mut(0 .. n, 0 .. n).assign(&self.mat_p);
+        vec_q.rows_mut(0 .. n).assign(&self.vec_q);
+        mat_g.slice_mut(0 .. m, 0 .. n).assign(&self.mat_g);
+        vec_h.rows_mut(0 .. m).assign(&self.vec_h);
+        mat_a.cols_mut(0 .. n).assign(&self.mat_a);
+
+        for (j, soft) in self.soft_constraints.iter().enumerate() {
+            // a penalty against the sense of the objective
+            match soft.norm {
+                SoftNorm::L1 => vec_q[(n + j, 0)] = sign * soft.weight,
+                SoftNorm::L2 => mat_p[(n + j, n + j)] = sign * soft.weight
+            }
+            mat_g[(soft.index, n + j)] = -1.;
+            mat_g[(m + j, n + j)] = -1.;
+        }
+
+        // two-sided constraints do not involve the slack variables
+        let ranges = self.ranges.as_ref().map(|ranges| {
+            let mut mat_c = Mat::new(ranges.mat_c.size().0, n + k);
+            mat_c.cols_mut(0 .. n).assign(&ranges.mat_c);
+            RangeConstraints::new(mat_c, ranges.vec_l.clone(), ranges.vec_u.clone())
+        });
+
+        QPProblem {
+            ranges,
+            .. QPProblem::new(mat_p, vec_q, mat_g, vec_h, mat_a, self.vec_b.clone())
+                        .with_sense(self.objective_sense)
+        }
+    }
+
+    /// Reports constraint violations of `x`, listing at most `k` worst violators.
+    ///
+    /// See [`violation_qp`](../verify/fn.violation_qp.html).
+    /// Constraints are labeled by their names if given. Two-sided constraints are not included.
+    pub fn violation(&self, x: &Mat, k: usize) -> ViolationReport
+    {
+        let report = violation_qp(x, &self.mat_g, &self.vec_h, &self.mat_a, &self.vec_b, k);
+
+        match &self.names {
+            Some(names) => {
+                let ProblemDims {m, p, ..} = self.dims();
+                report.with_names(names.constraints(m, p))
+            },
+            None => report
+        }
+    }
+
+    /// Runs the solver with given parameters, reusing a solver instance.
     /// 
-    /// Returns `Ok` with optimal \\(x\\) or `Err` with message string.
+    /// Returns `Ok` with [`SolverResult`](../problem/struct.SolverResult.html)
+    /// or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `solver` is a solver instance.
     /// * `param` is solver parameters.
     /// * `log` outputs solver progress.
-    /// * `mat_p` is \\(P\\).
-    /// * `vec_q` is \\(q\\).
-    /// * `mat_g` is \\(G\\).
-    /// * `vec_h` is \\(h\\).
-    /// * `mat_a` is \\(A\\).
-    /// * `vec_b` is \\(b\\).
-    fn solve_qp<L>(&mut self, param: &PDIPMParam, log: &mut L,
-                   mat_p: &Mat, vec_q: &Mat,
-                   mat_g: &Mat, vec_h: &Mat,
-                   mat_a: &Mat, vec_b: &Mat)
-                   -> Result<Mat, String>
+    pub fn solve_with<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                         -> Result<SolverResult, SolverError>
+    where L: Write
+    {
+        self.solve_warm(solver, param, log, None)
+    }
+
+    // starts from `warm`, a result of a similar problem of the same dimensions, if given:
+    // used by solver modules
+    pub(crate) fn solve_warm<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L,
+                                warm: Option<&SolverResult>)
+                                -> Result<SolverResult, SolverError>
     where L: Write
     {
         // ----- parameter check
 
-        let (n, m, p) = check_param(mat_p, vec_q, mat_g, vec_h, mat_a, vec_b)?;
+        let lap = Timings::start(param);
+        self.validate()?;
+        check_sym("mat_p", &self.mat_p, param.sym_tol)?;
+        let validation = Timings::elapsed(lap);
+        let ProblemDims {n, m, ..} = self.dims();
+
+        if !self.soft_constraints.is_empty() {
+            let rslt = self.h_soften().solve_warm(solver, param, log, warm)?;
+            let x = rslt.x.rows(0 .. n).clone_sz();
+
+            return Ok(SolverResult {
+                lmd: rslt.lmd.rows(0 .. m).clone_sz(),
+                primal_objective: x.prod(&(&self.mat_p * &x)) / 2. + self.vec_q.prod(&x),
+                soft_violation: (n .. n + self.soft_constraints.len()).map(|j| rslt.x[(j, 0)]).sum(),
+                x,
+                .. rslt
+            }.with_qp_parts(&self.mat_p, &self.vec_q).with_validation(validation));
+        }
+
+        let rslt = self.h_inputs().solve_warm(self.objective_sense, solver, param, log, warm)?;
+
+        Ok(rslt.with_validation(validation))
+    }
+}
+
+// inputs of QPProblem without its options, borrowed so that QP::solve_qp does not copy them
+#[derive(Clone, Copy)]
+struct QPInputs<'a>
+{
+    mat_p: &'a Mat,
+    vec_q: &'a Mat,
+    mat_g: &'a Mat,
+    vec_h: &'a Mat,
+    mat_a: &'a Mat,
+    vec_b: &'a Mat,
+    ranges: Option<&'a RangeConstraints>
+}
+
+impl QPInputs<'_>
+{
+    fn dims(&self) -> ProblemDims
+    {
+        ProblemDims {
+            n: self.mat_p.size().0,
+            m: self.mat_g.size().0,
+            p: self.mat_a.size().0
+        }
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, m, p} = self.dims();
+        let (mat_p, vec_q) = (self.mat_p, self.vec_q);
+        let (mat_g, vec_h) = (self.mat_g, self.vec_h);
+        let (mat_a, vec_b) = (self.mat_a, self.vec_b);
+
+        if n == 0 {return Err(SolverError::InvalidProblem("mat_p: 0 rows".into()));}
+        // m = 0 means NO inequality constraints
+        // p = 0 means NO equality constraints
+
+        check_dim("mat_p columns", mat_p.size().1, "mat_p rows", n)?;
+        check_dim("vec_q rows", vec_q.size().0, "mat_p rows", n)?;
+        check_col_vec("vec_q", vec_q)?;
+        check_dim("mat_g columns", mat_g.size().1, "mat_p rows", n)?;
+        check_dim("vec_h rows", vec_h.size().0, "mat_g rows", m)?;
+        check_col_vec("vec_h", vec_h)?;
+        check_dim("mat_a columns", mat_a.size().1, "mat_p rows", n)?;
+        check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+        check_col_vec("vec_b", vec_b)?;
+        for (name, mat) in [("mat_p", mat_p), ("vec_q", vec_q), ("mat_g", mat_g), ("vec_h", vec_h), ("mat_a", mat_a), ("vec_b", vec_b)] {
+            check_finite(name, mat)?;
+        }
+
+        Ok(())
+    }
+
+    fn stats(&self, with_cond: bool) -> ProblemStats
+    {
+        ProblemStats::new(self.dims(), Some(self.mat_p),
+                          vec![("mat_p", self.mat_p), ("vec_q", self.vec_q), ("mat_g", self.mat_g), ("vec_h", self.vec_h), ("mat_a", self.mat_a), ("vec_b", self.vec_b)],
+                          (self.mat_g, self.vec_h), self.mat_a, with_cond)
+    }
+
+    fn solve_warm<L>(&self, sense: Sense, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L,
+                     warm: Option<&SolverResult>)
+                     -> Result<SolverResult, SolverError>
+    where L: Write
+    {
+        if param.log_stats {
+            writeln_or!(log, "problem: {}", self.stats(false))?;
+        }
+
+        let ProblemDims {n, m, p} = self.dims();
+        let sign = sense.sign();
+        // negated into copies only for maximization
+        let (mat_p, vec_q) = match sense {
+            Sense::Minimize => (Cow::Borrowed(self.mat_p), Cow::Borrowed(self.vec_q)),
+            Sense::Maximize => (Cow::Owned(-self.mat_p), Cow::Owned(-self.vec_q))
+        };
+        let (mat_p, vec_q) = (&*mat_p, &*vec_q);
+        let (mat_g, vec_h) = (self.mat_g, self.vec_h);
+        let (mat_a, vec_b) = (self.mat_a, self.vec_b);
 
         // ----- initial value of a slack variable
 
-        let s = -vec_h.min().unwrap_or(0.);
-        let mut margin = param.margin;
+        let warm = warm.filter(|w| (w.x.size(), w.lmd.size(), w.nu.size()) == ((n, 1), (m, 1), (p, 1)));
+        let (x_initial, s, mut margin) = match warm {
+            // close to the previous solution, which satisfies the inequalities almost tightly
+            Some(w) => (w.x.clone(),
+                        (mat_g * &w.x - vec_h).max().unwrap_or(0.) / (m.max(1) as FP),
+                        param.eps.sqrt()),
+            None => (Mat::new_vec(n), -vec_h.min().unwrap_or(0.), param.margin)
+        };
         let mut s_initial = s + margin;
         while s_initial <= s {
             margin *= 2.;
             s_initial = s + margin;
         }
 
+        // dual variables from the previous solution, kept off the boundary
+        if let Some(w) = warm {
+            let lmd_floor = param.eps.sqrt();
+            let lmd = Mat::new_vec(m).set_by(|r, _| (sign * w.lmd[(r, 0)]).max(lmd_floor));
+            let nu = Mat::new_vec(p + 1).set_by(|r, _| if r < p {sign * w.nu[(r, 0)]} else {0.});
+            solver.set_warm_dual(lmd, nu);
+        }
+
+        // two-sided constraints, which do not involve the slack variable
+        if let Some(ranges) = self.ranges {
+            let mut mat_c = Mat::new(ranges.mat_c.size().0, n + 1);
+            mat_c.cols_mut(0 .. n).assign(&ranges.mat_c);
+            solver.set_ranges(mat_c, ranges.vec_l.clone(), ranges.vec_u.clone());
+        }
+
         // ----- start to solve
 
-        let rslt = self.solve(param, log,
+        let rslt = solver.solve(param, log,
             n + 1, m, p + 1, // '+ 1' is for a slack variable
             |x, df_o| {
                 df_o.rows_mut(0 .. n).assign(&(
@@ -123,7 +428,7 @@ impl QP for PDIPM
                 ))
             },
             |_, df_i| {
-                df_i.cols_mut(0 .. n).assign(&mat_g);
+                df_i.cols_mut(0 .. n).assign(mat_g);
                 // for a slack variable
                 df_i.col_mut(n).assign_all(-(m as FP));
             },
@@ -139,14 +444,589 @@ impl QP for PDIPM
                 a[(p, n)] = 1.;
             },
             |mut x| {
-                x.assign_all(0.);
+                x.rows_mut(0 .. n).assign(&x_initial);
                 x[(n, 0)] = s_initial;
             }
         );
 
-        match rslt {
-            Ok(y) => Ok(y.rows(0 .. n).clone_sz()),
-            Err(s) => Err(s.into())
+        rslt?;
+
+        let rslt = SolverResult::from_solver(solver, self.dims(), (n + 1, m),
+                                             sense, |x| {
+            x.prod(&(self.mat_p * x)) / 2. + self.vec_q.prod(x)
+        });
+        let rslt = rslt.with_qp_parts(self.mat_p, self.vec_q)
+                       .with_gap(|x| (self.mat_g * x - self.vec_h, self.mat_a * x - self.vec_b))
+                       .with_range_dual(solver, self.ranges);
+
+        Ok(rslt)
+    }
+}
+
+
+impl Problem for QPProblem
+{
+    fn dims(&self) -> ProblemDims
+    {
+        self.h_inputs().dims()
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, m, ..} = self.dims();
+
+        self.h_inputs().validate()?;
+        let names = ProblemNames::of(&self.names);
+        names.validate(self.dims(), self.ranges.as_ref().map_or(0, |r| r.mat_c.size().0))?;
+        if let Some(ranges) = &self.ranges {
+            ranges.validate(n, names)?;
         }
+
+        for (j, soft) in self.soft_constraints.iter().enumerate() {
+            if soft.index >= m {
+                return Err(SolverError::InvalidProblem(format!("soft_constraints[{}] index = {} is out of mat_g rows = {}", j, soft.index, m)));
+            }
+            if soft.weight.is_nan() || soft.weight <= 0. {
+                return Err(SolverError::InvalidProblem(format!("soft_constraints[{}] on {}: weight = {} is not positive", j, names.ineq(soft.index), soft.weight)));
+            }
+            if self.soft_constraints[.. j].iter().any(|s| s.index == soft.index) {
+                return Err(SolverError::InvalidProblem(format!("soft_constraints[{}] on {} is duplicated", j, names.ineq(soft.index))));
+            }
+        }
+
+        Ok(())
     }
+
+    fn solve(&self, param: &PDIPMParam) -> Result<SolverResult, SolverError>
+    {
+        self.solve_with(&mut PDIPM::new(), param, &mut std::io::sink())
+    }
+}
+
+impl QP for PDIPM
+{
+    /// Runs the solver with given parameters.
+    /// 
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    /// * `mat_p` is \\(P\\).
+    /// * `vec_q` is \\(q\\).
+    /// * `mat_g` is \\(G\\).
+    /// * `vec_h` is \\(h\\).
+    /// * `mat_a` is \\(A\\).
+    /// * `vec_b` is \\(b\\).
+    fn solve_qp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                   mat_p: &Mat, vec_q: &Mat,
+                   mat_g: &Mat, vec_h: &Mat,
+                   mat_a: &Mat, vec_b: &Mat)
+                   -> Result<Mat, SolverError>
+    where L: Write
+    {
+        let inputs = QPInputs {
+            mat_p, vec_q,
+            mat_g, vec_h,
+            mat_a, vec_b,
+            ranges: None
+        };
+
+        inputs.validate()
+              .and_then(|_| check_sym("mat_p", mat_p, param.sym_tol))
+              .and_then(|_| inputs.solve_warm(Sense::Minimize, self, param, log, None))
+              .and_then(|rslt| rslt.converged_x())
+              .map_err(|e| self.diagnose(e))
+    }
+}
+
+#[test]
+fn test_qp_validate()
+{
+    let qp = |np: (usize, usize), nq: (usize, usize), ng: (usize, usize), nh: (usize, usize), na: (usize, usize), nb: (usize, usize)| {
+        QPProblem::new(Mat::new(np.0, np.1), Mat::new(nq.0, nq.1),
+                       Mat::new(ng.0, ng.1), Mat::new(nh.0, nh.1),
+                       Mat::new(na.0, na.1), Mat::new(nb.0, nb.1))
+    };
+
+    assert_eq!(qp((2, 2), (2, 1), (3, 2), (3, 1), (1, 2), (1, 1)).validate(), Ok(()));
+
+    let cases = [
+        (qp((2, 3), (2, 1), (3, 2), (3, 1), (1, 2), (1, 1)), ["mat_p columns = 3", "mat_p rows = 2"]),
+        (qp((2, 2), (3, 1), (3, 2), (3, 1), (1, 2), (1, 1)), ["vec_q rows = 3", "mat_p rows = 2"]),
+        (qp((2, 2), (2, 2), (3, 2), (3, 1), (1, 2), (1, 1)), ["vec_q columns = 2", "column vector"]),
+        (qp((2, 2), (2, 1), (3, 1), (3, 1), (1, 2), (1, 1)), ["mat_g columns = 1", "mat_p rows = 2"]),
+        (qp((2, 2), (2, 1), (3, 2), (2, 1), (1, 2), (1, 1)), ["vec_h rows = 2", "mat_g rows = 3"]),
+        (qp((2, 2), (2, 1), (3, 2), (3, 2), (1, 2), (1, 1)), ["vec_h columns = 2", "column vector"]),
+        (qp((2, 2), (2, 1), (3, 2), (3, 1), (1, 3), (1, 1)), ["mat_a columns = 3", "mat_p rows = 2"]),
+        (qp((2, 2), (2, 1), (3, 2), (3, 1), (1, 2), (2, 1)), ["vec_b rows = 2", "mat_a rows = 1"]),
+        (qp((2, 2), (2, 1), (3, 2), (3, 1), (1, 2), (1, 0)), ["vec_b columns = 0", "column vector"]),
+        (QPProblem {mat_g: Mat::new(3, 2).set_by(|r, c| if (r, c) == (2, 1) {FP::NAN} else {0.}),
+                    ..qp((2, 2), (2, 1), (3, 2), (3, 1), (1, 2), (1, 1))}, ["mat_g[(2, 1)] = NaN", "not finite"]),
+    ];
+
+    for (prob, msgs) in cases.iter() {
+        match prob.validate() {
+            Err(SolverError::InvalidProblem(s)) => {
+                println!("{}", s);
+                for msg in msgs {
+                    assert!(s.contains(msg));
+                }
+            },
+            r => panic!("{:?}", r)
+        }
+    }
+}
+
+#[test]
+fn test_qp_sym_tol()
+{
+    let n = 2;
+    let mat_p = Mat::new(n, n).set_iter(&[
+        2., 1.,
+        1. + 1e-3, 2.
+    ]);
+    let prob = QPProblem::new(mat_p, Mat::new_vec(n),
+                              Mat::new(0, n), Mat::new_vec(0),
+                              Mat::new(0, n), Mat::new_vec(0));
+
+    // not checked by default
+    assert!(prob.solve(&PDIPMParam::default()).is_ok());
+
+    let param = PDIPMParam {sym_tol: Some(1e-6), .. Default::default()};
+    match prob.solve(&param) {
+        Err(SolverError::InvalidProblem(s)) => {
+            println!("{}", s);
+            assert!(s.starts_with("mat_p is not symmetric: mat_p[(0, 1)] = 1 and mat_p[(1, 0)] = 1.001"));
+        },
+        r => panic!("{:?}", r)
+    }
+
+    let param = PDIPMParam {sym_tol: Some(1e-3), .. Default::default()};
+    assert!(prob.solve(&param).is_ok());
+}
+
+#[test]
+fn test_qp_cholesky()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let mut r = XOR64_INIT;
+    let n = 8;
+    let m = 6;
+    let p = 2;
+
+    let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let prob = QPProblem::new(
+        mat_b.t() * &mat_b + Mat::new(n, n).set_eye(),
+        Mat::new_vec(n).set_by(|_, _| xor64(&mut r) - 0.5),
+        Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5),
+        Mat::new_vec(m).set_all(0.1),
+        Mat::new(p, n).set_by(|_, _| xor64(&mut r) - 0.5),
+        Mat::new_vec(p)
+    );
+
+    let rslt_svd = prob.solve(&PDIPMParam::default()).unwrap();
+
+    let param = PDIPMParam {factorization: Factorization::Cholesky, .. Default::default()};
+    let mut solver = PDIPM::new();
+    let rslt = prob.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+    println!("x = {}", rslt.x.t());
+    assert_eq!(rslt.status, SolverStatus::Converged);
+    assert!((&rslt.x - &rslt_svd.x).norm_p2() < 1e-6);
+    assert!((&rslt.nu - &rslt_svd.nu).norm_p2() < 1e-6);
+
+    // falls back to SVD without inequalities, where the slack variable makes the Hessian singular
+    let eq_only = QPProblem::new(
+        Mat::new(2, 2).set_eye(), Mat::new_vec(2).set_all(-1.),
+        Mat::new(0, 2), Mat::new_vec(0),
+        Mat::new(1, 2).set_all(1.), Mat::new_vec(1)
+    );
+    let rslt = eq_only.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+    assert!(rslt.x.norm_p2() < 1e-6);
+}
+
+#[test]
+fn test_qp_lu()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let mut r = XOR64_INIT;
+    let n = 8;
+    let m = 6;
+
+    let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mat_a = Mat::new(1, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let prob = QPProblem::new(
+        mat_b.t() * &mat_b,
+        Mat::new_vec(n).set_by(|_, _| xor64(&mut r) - 0.5),
+        Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5),
+        Mat::new_vec(m).set_all(0.1),
+        // duplicated row to be dropped
+        Mat::vstack(&[&mat_a, &mat_a]),
+        Mat::new_vec(2).set_all(0.1)
+    );
+
+    let rslt_svd = prob.solve(&PDIPMParam::default()).unwrap();
+
+    let param = PDIPMParam {factorization: Factorization::LU, .. Default::default()};
+    let mut solver = PDIPM::new();
+    let rslt = prob.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+    println!("x = {}", rslt.x.t());
+    assert_eq!(rslt.status, SolverStatus::Converged);
+    assert_eq!(solver.dropped_eqs(), &[0]);
+    assert!((&rslt.x - &rslt_svd.x).norm_p2() < 1e-6);
+    assert!((&rslt.lmd - &rslt_svd.lmd).norm_p2() < 1e-6);
+}
+
+#[test]
+fn test_qp_soft()
+{
+    let param = PDIPMParam::default();
+
+    // x0 >= 1 and x0 <= 0 conflict
+    let hard = QPProblem::new(
+        Mat::new(2, 2).set_eye(), Mat::new_vec(2),
+        Mat::new(2, 2).set_iter(&[
+            -1., 0.,
+            1., 0.
+        ]),
+        Mat::new_vec(2).set_iter(&[
+            -1., 0.
+        ]),
+        Mat::new(0, 2), Mat::new_vec(0)
+    );
+    assert!(!matches!(hard.solve(&param), Ok(SolverResult {status: SolverStatus::Converged, ..})));
+
+    let rslt = hard.clone().soft(1, 10., SoftNorm::L1).solve(&param).unwrap();
+    println!("x = {}", rslt.x);
+    assert_eq!(rslt.x.size(), (2, 1));
+    assert_eq!(rslt.lmd.size(), (2, 1));
+    assert!((rslt.x[(0, 0)] - 1.).abs() < 1e-6);
+    assert!((rslt.soft_violation - 1.).abs() < 1e-6);
+
+    // nearest point to (2, 2) in x0 + x1 <= 1
+    let feasible = QPProblem::new(
+        Mat::new(2, 2).set_eye(), Mat::new_vec(2).set_all(-2.),
+        Mat::new(1, 2).set_all(1.), Mat::new_vec(1).set_all(1.),
+        Mat::new(0, 2), Mat::new_vec(0)
+    );
+    let rslt_hard = feasible.solve(&param).unwrap();
+
+    for &(weight, norm) in &[(1e3, SoftNorm::L1), (1e8, SoftNorm::L2)] {
+        let rslt = feasible.clone().soft(0, weight, norm).solve(&param).unwrap();
+        println!("{:?}: x = {} violation = {:e}", norm, rslt.x, rslt.soft_violation);
+        assert!((&rslt.x - &rslt_hard.x).norm_p2() < 1e-6);
+        assert!(rslt.soft_violation.abs() < 1e-6);
+        assert!((rslt.primal_objective - rslt_hard.primal_objective).abs() < 1e-6);
+    }
+
+    let bad = feasible.clone().soft(1, 1., SoftNorm::L1);
+    assert!(bad.validate().is_err());
+    let bad = feasible.clone().soft(0, 0., SoftNorm::L1);
+    assert!(bad.validate().is_err());
+    let bad = feasible.soft(0, 1., SoftNorm::L1).soft(0, 1., SoftNorm::L2);
+    assert!(bad.validate().is_err());
+}
+
+#[test]
+fn test_qp_ranges()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let n: usize = 4;
+    let k: usize = 5;
+    let param = PDIPMParam::default();
+
+    let mut r = XOR64_INIT;
+    let sqrt_p = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mat_p = sqrt_p.t() * &sqrt_p + Mat::new(n, n).set_eye();
+    let vec_q = Mat::new_vec(n).set_by(|_, _| (xor64(&mut r) - 0.5) * 10.);
+    let mat_g = Mat::new(1, n).set_all(1.);
+    let vec_h = Mat::new_vec(1).set_all(1.);
+    let mat_c = Mat::new(k, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let vec_l = Mat::new_vec(k).set_iter(&[-0.1, FP::NEG_INFINITY, -0.2, -0.3, -0.1]);
+    let vec_u = Mat::new_vec(k).set_iter(&[0.1, 0.2, FP::INFINITY, 0.3, 0.1]);
+
+    let ranged = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                                mat_g.clone(), vec_h.clone(),
+                                Mat::new(0, n), Mat::new_vec(0))
+                           .with_ranges(mat_c.clone(), vec_l.clone(), vec_u.clone());
+    let rslt = ranged.solve(&param).unwrap();
+    println!("x = {} range_dual = {}", rslt.x, rslt.range_dual);
+    assert_eq!(rslt.lmd.size(), (1, 1));
+    assert_eq!(rslt.range_dual.size(), (k, 1));
+
+    // equivalent stacked formulation: G; -C for finite l; C for finite u
+    let lower = [0, 2, 3, 4];
+    let upper = [0, 1, 3, 4];
+    let m = 1 + lower.len() + upper.len();
+    let mut stacked_g = Mat::new(m, n);
+    let mut stacked_h = Mat::new_vec(m);
+    stacked_g.row_mut(0).assign(&mat_g);
+    stacked_h[(0, 0)] = vec_h[(0, 0)];
+    for (j, &i) in lower.iter().enumerate() {
+        stacked_g.row_mut(1 + j).assign(&-mat_c.row(i));
+        stacked_h[(1 + j, 0)] = -vec_l[(i, 0)];
+    }
+    for (j, &i) in upper.iter().enumerate() {
+        stacked_g.row_mut(1 + lower.len() + j).assign(&mat_c.row(i));
+        stacked_h[(1 + lower.len() + j, 0)] = vec_u[(i, 0)];
+    }
+    let stacked = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                                 stacked_g, stacked_h,
+                                 Mat::new(0, n), Mat::new_vec(0));
+    let rslt_stacked = stacked.solve(&param).unwrap();
+
+    let mut range_dual = Mat::new_vec(k);
+    for (j, &i) in lower.iter().enumerate() {
+        range_dual[(i, 0)] -= rslt_stacked.lmd[(1 + j, 0)];
+    }
+    for (j, &i) in upper.iter().enumerate() {
+        range_dual[(i, 0)] += rslt_stacked.lmd[(1 + lower.len() + j, 0)];
+    }
+    assert!((&rslt.x - &rslt_stacked.x).norm_p2() < 1e-6);
+    assert!((&rslt.range_dual - range_dual).norm_p2() < 1e-6);
+    assert!((rslt.lmd[(0, 0)] - rslt_stacked.lmd[(0, 0)]).abs() < 1e-6);
+
+    // infinite lower sides degrade to one-sided G x <= h
+    let one_sided = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                                   mat_c.clone(), vec_u.clone().set_by(|r, _| vec_u[(r, 0)].min(1e3)),
+                                   Mat::new(0, n), Mat::new_vec(0));
+    let rslt_one = one_sided.solve(&param).unwrap();
+    let rslt_inf = QPProblem::new(mat_p, vec_q,
+                                  Mat::new(0, n), Mat::new_vec(0),
+                                  Mat::new(0, n), Mat::new_vec(0))
+                            .with_ranges(mat_c, Mat::new_vec(k).set_all(FP::NEG_INFINITY), vec_u)
+                            .solve(&param).unwrap();
+    println!("x = {} range_dual = {}", rslt_inf.x, rslt_inf.range_dual);
+    assert!((&rslt_inf.x - &rslt_one.x).norm_p2() < 1e-6);
+    assert!((&rslt_inf.range_dual - &rslt_one.lmd).norm_p2() < 1e-6);
+}
+
+#[test]
+fn test_qp_ranges_kkt()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let n: usize = 5;
+    let m: usize = 2;
+    let k: usize = 6;
+    let mut r = XOR64_INIT;
+
+    for _ in 0 .. 4 {
+        let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let mat_p = mat_b.t() * &mat_b + Mat::new(n, n).set_eye();
+        let vec_q = Mat::new_vec(n).set_by(|_, _| (xor64(&mut r) - 0.5) * 10.);
+        let mat_g = Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let vec_h = Mat::new_vec(m).set_all(0.5);
+        let mat_a = Mat::new(1, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let vec_b = Mat::new_vec(1).set_all(0.1);
+        let mat_c = Mat::new(k, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        // each third row without the lower or the upper side, and the last one with neither
+        let vec_l = Mat::new_vec(k).set_by(|i, _| {
+            let l = -0.1 - xor64(&mut r);
+            if (i % 3 == 1) || (i == k - 1) {FP::NEG_INFINITY} else {l}
+        });
+        let vec_u = Mat::new_vec(k).set_by(|i, _| {
+            let u = 0.1 + xor64(&mut r);
+            if (i % 3 == 2) || (i == k - 1) {FP::INFINITY} else {u}
+        });
+
+        let ranged = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                                    mat_g.clone(), vec_h.clone(),
+                                    mat_a.clone(), vec_b.clone())
+                               .with_ranges(mat_c.clone(), vec_l.clone(), vec_u.clone());
+
+        // stacked by hand with (row, sign) of each finite side
+        let sides: Vec<(usize, FP)> = (0 .. k).flat_map(|i| {
+            let lower = Some((i, -1.)).filter(|_| vec_l[(i, 0)].is_finite());
+            let upper = Some((i, 1.)).filter(|_| vec_u[(i, 0)].is_finite());
+            lower.into_iter().chain(upper)
+        }).collect();
+        let stacked_g = Mat::new(m + sides.len(), n).set_by(|i, j| {
+            if i < m {mat_g[(i, j)]} else {sides[i - m].1 * mat_c[(sides[i - m].0, j)]}
+        });
+        let stacked_h = Mat::new_vec(m + sides.len()).set_by(|i, _| {
+            if i < m {vec_h[(i, 0)]}
+            else if sides[i - m].1 > 0. {vec_u[(sides[i - m].0, 0)]}
+            else {-vec_l[(sides[i - m].0, 0)]}
+        });
+        let stacked = QPProblem::new(mat_p, vec_q, stacked_g, stacked_h, mat_a, vec_b);
+        let rslt_stacked = stacked.solve(&PDIPMParam::default()).unwrap();
+        let mut range_dual = Mat::new_vec(k);
+        for (j, &(i, sign)) in sides.iter().enumerate() {
+            range_dual[(i, 0)] += sign * rslt_stacked.lmd[(m + j, 0)];
+        }
+
+        for &factorization in &[Factorization::SVD, Factorization::Cholesky, Factorization::LU] {
+            let param = PDIPMParam {factorization, .. Default::default()};
+            let mut solver = PDIPM::new();
+            let rslt = ranged.solve_with(&mut solver, &param, &mut std::io::sink()).unwrap();
+            println!("{:?}: x = {} range_dual = {}", factorization, rslt.x.t(), rslt.range_dual.t());
+            assert_eq!(rslt.status, SolverStatus::Converged);
+            // one row per range, while stacking adds one per finite side
+            assert_eq!(solver.y().size(), (n + 1 + m + 1 + 1 + k, 1));
+            assert!((&rslt.x - &rslt_stacked.x).norm_p2() < 1e-6);
+            assert!((&rslt.lmd - rslt_stacked.lmd.rows(0 .. m)).norm_p2() < 1e-6);
+            assert!((&rslt.nu - &rslt_stacked.nu).norm_p2() < 1e-6);
+            assert!((&rslt.range_dual - &range_dual).norm_p2() < 1e-6);
+            assert!(rslt.range_dual[(k - 1, 0)].abs() < 1e-6);
+            assert!(rslt.duality_gap.abs() < 1e-6);
+        }
+
+        // with soft constraints, whose slack variables the ranges do not involve
+        let rslt = ranged.clone().soft(0, 1e3, SoftNorm::L1).solve(&PDIPMParam::default()).unwrap();
+        let rslt_soft = stacked.soft(0, 1e3, SoftNorm::L1).solve(&PDIPMParam::default()).unwrap();
+        assert!((&rslt.x - &rslt_soft.x).norm_p2() < 1e-6);
+        assert_eq!(rslt.range_dual.size(), (k, 1));
+    }
+
+    let point = QPProblem::new(Mat::new(2, 2).set_eye(), Mat::new_vec(2),
+                               Mat::new(0, 2), Mat::new_vec(0),
+                               Mat::new(0, 2), Mat::new_vec(0))
+                          .with_ranges(Mat::new(1, 2).set_all(1.), Mat::new_vec(1).set_all(1.), Mat::new_vec(1).set_all(1.));
+    assert!(matches!(point.validate(), Err(SolverError::InvalidProblem(_))));
+}
+
+#[test]
+fn test_qp_names()
+{
+    let names = ProblemNames {
+        vars: vec!["x".into(), "y".into()],
+        ineqs: vec!["budget".into(), "cap_x".into()],
+        eqs: vec!["balance".into()],
+        ranges: Vec::new()
+    };
+
+    let qp = QPProblem::new(Mat::new(2, 2).set_eye(), Mat::new_vec(2),
+                            Mat::new(2, 2).set_iter(&[
+                                1., 1.,
+                                1., 0.
+                            ]),
+                            Mat::new_vec(2).set_iter(&[
+                                1., 0.5
+                            ]),
+                            Mat::new(1, 2).set_iter(&[
+                                1., -1.
+                            ]),
+                            Mat::new_vec(1))
+                       .with_names(names.clone());
+    assert_eq!(qp.validate(), Ok(()));
+
+    // dimension error of names
+    let mut short = names.clone();
+    short.ineqs.pop();
+    let bad = qp.clone().with_names(short);
+    match bad.validate() {
+        Err(SolverError::InvalidProblem(s)) => {
+            println!("{}", s);
+            assert!(s.contains("names.ineqs = 1"));
+        },
+        r => panic!("{:?}", r)
+    }
+
+    // errors of rows
+    let mut with_range = names.clone();
+    with_range.ranges.push("band".into());
+    let bad = qp.clone().with_names(with_range)
+                .with_ranges(Mat::new(1, 2).set_all(1.), Mat::new_vec(1).set_all(1.), Mat::new_vec(1));
+    match bad.validate() {
+        Err(SolverError::InvalidProblem(s)) => {
+            println!("{}", s);
+            assert!(s.contains("band"));
+        },
+        r => panic!("{:?}", r)
+    }
+    let bad = qp.clone().soft(1, 1., SoftNorm::L1).soft(1, 2., SoftNorm::L2);
+    match bad.validate() {
+        Err(SolverError::InvalidProblem(s)) => {
+            println!("{}", s);
+            assert!(s.contains("cap_x"));
+        },
+        r => panic!("{:?}", r)
+    }
+
+    // violation report
+    let x = Mat::new_vec(2).set_iter(&[
+        1., 0.5
+    ]);
+    let report = qp.violation(&x, 3);
+    let text = format!("{}", report);
+    println!("{}", text);
+    assert!(text.contains("budget"));
+    assert!(text.contains("cap_x"));
+    assert!(text.contains("balance"));
+
+    let mut unnamed = qp;
+    unnamed.names = None;
+    let text = format!("{}", unnamed.violation(&x, 3));
+    assert!(text.contains("ineq[0]") && text.contains("eq[0]"));
+    assert_eq!(names.var(1), "y");
+    assert_eq!(ProblemNames::default().var(1), "x[1]");
+}
+
+#[test]
+fn test_qp_objective()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let n: usize = 4;
+    let m: usize = 3;
+    let p: usize = 1;
+    let param = PDIPMParam::default();
+
+    let mut r = XOR64_INIT;
+    let sqrt_p = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mat_p = sqrt_p.t() * &sqrt_p + Mat::new(n, n).set_eye();
+    let vec_q = Mat::new_vec(n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let mat_g = Mat::new(m, n).set_by(|_, _| xor64(&mut r) - 0.5);
+    let vec_h = Mat::new_vec(m).set_by(|_, _| xor64(&mut r) * 0.1);
+    let mat_a = Mat::new(p, n).set_all(1.);
+    let vec_b = Mat::new_vec(p).set_all(0.5);
+
+    // independent evaluation of 1/2 x^T P x + q^T x
+    let eval = |mat_p: &Mat, vec_q: &Mat, x: &Mat| {
+        let mut quad = 0.;
+        let mut lin = 0.;
+        for i in 0 .. n {
+            lin += vec_q[(i, 0)] * x[(i, 0)];
+            for j in 0 .. n {
+                quad += x[(i, 0)] * mat_p[(i, j)] * x[(j, 0)] / 2.;
+            }
+        }
+        (quad, lin)
+    };
+
+    for &(scale, sense) in &[(1., Sense::Minimize), (1e3, Sense::Minimize), (1., Sense::Maximize), (1e3, Sense::Maximize)] {
+        let sign = sense.sign();
+        let (mat_p, vec_q) = (sign * scale * &mat_p, scale * &vec_q);
+
+        let qp = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                                mat_g.clone(), vec_h.clone(),
+                                mat_a.clone(), vec_b.clone())
+                           .with_sense(sense);
+        let rslt = qp.solve(&param).unwrap();
+        println!("{:?} x {}: primal {} dual {} gap {:e}", sense, scale, rslt.primal_objective, rslt.dual_objective, rslt.duality_gap);
+
+        let (quad, lin) = eval(&mat_p, &vec_q, &rslt.x);
+        assert!((rslt.primal_objective - (quad + lin)).abs() < 1e-10);
+
+        let parts = rslt.objective_breakdown().unwrap();
+        assert!((parts.quadratic - quad).abs() < 1e-10);
+        assert!((parts.linear - lin).abs() < 1e-10);
+
+        assert!(rslt.duality_gap > -1e-6 * scale);
+        assert!(rslt.duality_gap < 1e-6 * scale);
+        assert!((rslt.primal_objective - rslt.dual_objective - sign * rslt.duality_gap).abs() < 1e-10 * scale);
+    }
+
+    // LP has no breakdown
+    let lp = LPProblem::new(Mat::new_vec(n).set_all(1.),
+                            mat_g.clone(), vec_h.clone(),
+                            mat_a.clone(), vec_b.clone())
+                       .with_ranges(Mat::new(n, n).set_eye(), Mat::new_vec(n).set_all(-1.), Mat::new_vec(n).set_all(1.))
+                       .with_sense(Sense::Maximize);
+    let rslt = lp.solve(&param).unwrap();
+    assert!((rslt.primal_objective - rslt.x.prod(&Mat::new_vec(n).set_all(1.))).abs() < 1e-10);
+    assert!(rslt.duality_gap.abs() < 1e-6);
+    assert_eq!(rslt.objective_breakdown(), None);
 }
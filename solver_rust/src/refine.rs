@@ -0,0 +1,408 @@
+/*!
+Post-solution iterative refinement
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+The interior-point method stops on the central path slightly apart from the exact optimality.
+[`refine_solution`](fn.refine_solution.html) performs a few Newton steps on the full KKT conditions
+\\[
+\\begin{array}{l}
+P x + q + G^T \\lambda + A^T \\nu = 0 \\\\
+\\lambda_i (G x - h)_i = 0 \\\\
+A x - b = 0
+\\end{array}
+\\]
+at the returned point, evaluating the residuals with compensated summation.
+Each step is guarded so that the KKT residual never gets worse.
+*/
+
+use super::prelude::*;
+use super::matsvd::MatSVD;
+use super::mat::KahanSum;
+
+// KKT residuals of the minimization form: (r_dual, r_cent, r_pri, slack G x - h)
+fn h_residual(prob: &QPProblem, sign: FP, x: &Mat, lmd: &Mat, nu: &Mat) -> (Mat, Mat, Mat, Mat)
+{
+    let ProblemDims {n, m, p} = prob.dims();
+
+    let mut slack = Mat::new_vec(m);
+    for k in 0 .. m {
+        let mut acc = KahanSum::default();
+        for j in 0 .. n {
+            acc.add(prob.mat_g[(k, j)] * x[(j, 0)]);
+        }
+        acc.add(-prob.vec_h[(k, 0)]);
+        slack[(k, 0)] = acc.value();
+    }
+
+    let mut r_dual = Mat::new_vec(n);
+    for i in 0 .. n {
+        let mut acc = KahanSum::default();
+        for j in 0 .. n {
+            acc.add(sign * prob.mat_p[(i, j)] * x[(j, 0)]);
+        }
+        acc.add(sign * prob.vec_q[(i, 0)]);
+        for k in 0 .. m {
+            acc.add(prob.mat_g[(k, i)] * lmd[(k, 0)]);
+        }
+        for k in 0 .. p {
+            acc.add(prob.mat_a[(k, i)] * nu[(k, 0)]);
+        }
+        r_dual[(i, 0)] = acc.value();
+    }
+
+    let mut r_cent = Mat::new_vec(m);
+    for k in 0 .. m {
+        r_cent[(k, 0)] = lmd[(k, 0)] * slack[(k, 0)];
+    }
+
+    let mut r_pri = Mat::new_vec(p);
+    for k in 0 .. p {
+        let mut acc = KahanSum::default();
+        for j in 0 .. n {
+            acc.add(prob.mat_a[(k, j)] * x[(j, 0)]);
+        }
+        acc.add(-prob.vec_b[(k, 0)]);
+        r_pri[(k, 0)] = acc.value();
+    }
+
+    (r_dual, r_cent, r_pri, slack)
+}
+
+// norm of KKT residuals including primal and dual infeasibility
+fn h_merit(prob: &QPProblem, sign: FP, x: &Mat, lmd: &Mat, nu: &Mat) -> FP
+{
+    let (r_dual, r_cent, r_pri, slack) = h_residual(prob, sign, x, lmd, nu);
+    let (m, _) = slack.size();
+
+    let mut acc = KahanSum::default();
+    for r in [&r_dual, &r_cent, &r_pri].iter() {
+        let (len, _) = r.size();
+        for i in 0 .. len {
+            acc.add(r[(i, 0)] * r[(i, 0)]);
+        }
+    }
+    for k in 0 .. m {
+        let s = slack[(k, 0)].max(0.);
+        let l = (-lmd[(k, 0)]).max(0.);
+        acc.add(s * s);
+        acc.add(l * l);
+    }
+
+    acc.value().sqrt()
+}
+
+// the hard one-sided problem equivalent to the original,
+// with two-sided constraints stacked into G and slack variables of soft constraints appended to x,
+// whose KKT conditions are those of the original problem
+struct Lowered
+{
+    prob: QPProblem,
+    // (row, sign) of each stacked side of two-sided constraints
+    sides: Vec<(usize, FP)>
+}
+
+impl Lowered
+{
+    fn new(orig: &QPProblem) -> Lowered
+    {
+        let (stacked, sides) = match &orig.ranges {
+            Some(ranges) => {
+                let (mat_g, vec_h, sides) = ranges.stack(&orig.mat_g, &orig.vec_h);
+                let stacked = QPProblem {
+                    mat_g, vec_h,
+                    ranges: None,
+                    names: None,
+                    .. orig.clone()
+                };
+                (stacked, sides)
+            },
+            None => (orig.clone(), Vec::new())
+        };
+
+        let prob = if orig.soft_constraints.is_empty() {
+            stacked
+        }
+        else {
+            stacked.h_soften()
+        };
+
+        Lowered {prob, sides}
+    }
+
+    // a result of `orig` as that of the lowered problem
+    fn lift(&self, orig: &QPProblem, rslt: &SolverResult) -> SolverResult
+    {
+        let rslt = match &orig.ranges {
+            Some(ranges) => ranges.restack(rslt, &self.sides),
+            None => rslt.clone()
+        };
+
+        if orig.soft_constraints.is_empty() {
+            return rslt;
+        }
+
+        let ProblemDims {n, ..} = orig.dims();
+        let (m, _) = rslt.lmd.size();
+        let k = orig.soft_constraints.len();
+        let sign = rslt.sense.sign();
+        let slack = self.prob.mat_g.slice(0 .. m, 0 .. n) * &rslt.x - self.prob.vec_h.rows(0 .. m);
+
+        // the least violations, and the multipliers of t >= 0 which make t stationary
+        let mut x = Mat::new_vec(n + k);
+        let mut lmd = Mat::new_vec(m + k);
+        x.rows_mut(0 .. n).assign(&rslt.x);
+        lmd.rows_mut(0 .. m).assign(&rslt.lmd);
+        for (j, soft) in orig.soft_constraints.iter().enumerate() {
+            let t = slack[(soft.index, 0)].max(0.);
+            let penalty = match soft.norm {
+                SoftNorm::L1 => soft.weight,
+                SoftNorm::L2 => soft.weight * t
+            };
+            x[(n + j, 0)] = t;
+            lmd[(m + j, 0)] = sign * (penalty - sign * rslt.lmd[(soft.index, 0)]).max(0.);
+        }
+
+        SolverResult {
+            x, lmd,
+            .. rslt
+        }
+    }
+
+    // a result of the lowered problem as that of `orig`, the inverse of `lift`
+    fn unlift(&self, orig: &QPProblem, rslt: SolverResult) -> SolverResult
+    {
+        let rslt = if orig.soft_constraints.is_empty() {
+            rslt
+        }
+        else {
+            let ProblemDims {n, ..} = orig.dims();
+            let m = self.prob.mat_g.size().0 - orig.soft_constraints.len();
+            let x = rslt.x.rows(0 .. n).clone_sz();
+
+            SolverResult {
+                lmd: rslt.lmd.rows(0 .. m).clone_sz(),
+                primal_objective: x.prod(&(&orig.mat_p * &x)) / 2. + orig.vec_q.prod(&x),
+                soft_violation: (n .. n + orig.soft_constraints.len()).map(|j| rslt.x[(j, 0)]).sum(),
+                x,
+                .. rslt
+            }.with_qp_parts(&orig.mat_p, &orig.vec_q)
+        };
+
+        match &orig.ranges {
+            Some(ranges) => ranges.unstack(rslt, orig.mat_g.size().0, &self.sides),
+            None => rslt
+        }
+    }
+}
+
+/// Evaluates the KKT residual norm of a QP solution with compensated summation.
+///
+/// The residual includes stationarity, complementary slackness, equality residuals
+/// and violations of inequalities and of the sign of \\(\\lambda\\).
+/// Two-sided constraints are included as a pair of inequalities of their finite sides,
+/// and soft constraints with their least violations and penalties.
+/// * `prob` is the problem.
+/// * `rslt` is a solver result of `prob`.
+pub fn kkt_residual(prob: &QPProblem, rslt: &SolverResult) -> FP
+{
+    let lowered = Lowered::new(prob);
+    let rslt = lowered.lift(prob, rslt);
+    let sign = rslt.sense.sign();
+
+    h_merit(&lowered.prob, sign, &rslt.x, &(sign * &rslt.lmd), &(sign * &rslt.nu))
+}
+
+/// Refines a QP solution by Newton steps on the KKT conditions.
+///
+/// Returns a refined result, which is never worse than `rslt` in terms of
+/// [`kkt_residual`](fn.kkt_residual.html).
+/// * `prob` is the problem.
+/// * `rslt` is a solver result of `prob`.
+/// * `n_steps` is the maximum number of refinement steps.
+pub fn refine_solution(prob: &QPProblem, rslt: &SolverResult, n_steps: usize) -> SolverResult
+{
+    let lowered = Lowered::new(prob);
+    let refined = h_refine(&lowered.prob, &lowered.lift(prob, rslt), n_steps);
+
+    lowered.unlift(prob, refined)
+}
+
+fn h_refine(prob: &QPProblem, rslt: &SolverResult, n_steps: usize) -> SolverResult
+{
+    let ProblemDims {n, m, p} = prob.dims();
+    let sign = rslt.sense.sign();
+
+    // dual variables of the minimization form
+    let mut x = rslt.x.clone();
+    let mut lmd = sign * &rslt.lmd;
+    let mut nu = sign * &rslt.nu;
+    let mut merit = h_merit(prob, sign, &x, &lmd, &nu);
+
+    let mut kkt = Mat::new(n + m + p, n + m + p);
+    let mut svd = MatSVD::new(kkt.size());
+    let mut r_t = Mat::new_vec(n + m + p);
+
+    for _ in 0 .. n_steps {
+        if merit == 0. {
+            break;
+        }
+
+        let (r_dual, r_cent, r_pri, slack) = h_residual(prob, sign, &x, &lmd, &nu);
+
+        r_t.rows_mut(0 .. n).assign(&r_dual);
+        r_t.rows_mut(n .. n + m).assign(&r_cent);
+        r_t.rows_mut(n + m .. n + m + p).assign(&r_pri);
+
+        kkt.assign_all(0.);
+        kkt.slice_mut(0 .. n, 0 .. n).assign(&(sign * &prob.mat_p));
+        kkt.slice_mut(0 .. n, n .. n + m).assign(&prob.mat_g.t());
+        kkt.slice_mut(0 .. n, n + m .. n + m + p).assign(&prob.mat_a.t());
+        kkt.slice_mut(n .. n + m, 0 .. n).assign(&(lmd.clone_diag() * &prob.mat_g));
+        kkt.slice_mut(n .. n + m, n .. n + m).assign(&slack.clone_diag());
+        kkt.slice_mut(n + m .. n + m + p, 0 .. n).assign(&prob.mat_a);
+
+        svd.decomp(&kkt);
+        let dy = svd.solve(&(-&r_t));
+
+        // guarded step: halve until the residual decreases
+        let mut s: FP = 1.;
+        let mut accepted = false;
+        for _ in 0 .. 16 {
+            let x_p = &x + s * dy.rows(0 .. n);
+            // keep lmd nonnegative
+            let lmd_p = Mat::new_vec(m).set_by(|r, _| (lmd[(r, 0)] + s * dy[(n + r, 0)]).max(0.));
+            let nu_p = &nu + s * dy.rows(n + m .. n + m + p);
+
+            let merit_p = h_merit(prob, sign, &x_p, &lmd_p, &nu_p);
+            if merit_p < merit {
+                x = x_p;
+                lmd = lmd_p;
+                nu = nu_p;
+                merit = merit_p;
+                accepted = true;
+                break;
+            }
+            s /= 2.;
+        }
+
+        if !accepted {
+            break;
+        }
+    }
+
+    let primal_objective = x.prod(&(&prob.mat_p * &x)) / 2. + prob.vec_q.prod(&x);
+
+    SolverResult {
+        lmd: sign * lmd,
+        nu: sign * nu,
+        primal_objective,
+        x,
+        .. rslt.clone()
+    }.with_qp_parts(&prob.mat_p, &prob.vec_q)
+     .with_gap(|x| (&prob.mat_g * x - &prob.vec_h, &prob.mat_a * x - &prob.vec_b))
+}
+
+#[test]
+fn test_refine_solution()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let n: usize = 4;
+    let m: usize = 3;
+    let p: usize = 1;
+
+    let mut r = XOR64_INIT;
+    let mat_b = Mat::new(n, n).set_by(|_, _| xor64(&mut r));
+    let mat_p = mat_b.t() * &mat_b + Mat::new(n, n).set_eye();
+    let vec_q = Mat::new_vec(n).set_by(|_, _| xor64(&mut r) * 4.);
+    let mat_g = Mat::new(m, n).set_by(|_, _| xor64(&mut r));
+    let vec_h = Mat::new_vec(m).set_by(|_, _| xor64(&mut r) * 0.1);
+    let mat_a = Mat::new(p, n).set_all(1.);
+    let vec_b = Mat::new_vec(p).set_all(1.);
+
+    let probs = vec![
+        QPProblem::new(mat_p.clone(), vec_q.clone(), mat_g.clone(), vec_h.clone(), mat_a.clone(), vec_b.clone()),
+        QPProblem::new(-&mat_p, -&vec_q, mat_g, vec_h, mat_a, vec_b).with_sense(Sense::Maximize),
+    ];
+
+    let param = PDIPMParam::default();
+    for prob in &probs {
+        let rslt = prob.solve(&param).unwrap();
+        let refined = refine_solution(prob, &rslt, 3);
+        let refined2 = refine_solution(prob, &refined, 3);
+
+        let (r0, r1, r2) = (kkt_residual(prob, &rslt), kkt_residual(prob, &refined), kkt_residual(prob, &refined2));
+        println!("residual: {:.3e} -> {:.3e} -> {:.3e}", r0, r1, r2);
+        assert!(r1 < r0);
+        assert!(r2 <= r1);
+        assert!((&refined.x - &rslt.x).norm_p2() < param.eps.sqrt());
+    }
+}
+
+#[test]
+fn test_refine_ranges()
+{
+    // nearest point to (3, 3) in the box 0 <= x <= 0.5
+    let prob = QPProblem::new(Mat::new(2, 2).set_eye(), Mat::new_vec(2).set_all(-3.),
+                              Mat::new(0, 2), Mat::new_vec(0),
+                              Mat::new(0, 2), Mat::new_vec(0))
+                         .with_ranges(Mat::new(2, 2).set_eye(), Mat::new_vec(2), Mat::new_vec(2).set_all(0.5));
+
+    let param = PDIPMParam::default();
+    let rslt = prob.solve(&param).unwrap();
+    let refined = refine_solution(&prob, &rslt, 3);
+
+    let (r0, r1) = (kkt_residual(&prob, &rslt), kkt_residual(&prob, &refined));
+    println!("residual: {:.3e} -> {:.3e}", r0, r1);
+    println!("x = {}, range_dual = {}", refined.x, refined.range_dual);
+    assert!(r0 < param.eps.sqrt());
+    assert!(r1 <= r0);
+
+    let exp = Mat::new_vec(2).set_all(0.5);
+    assert!((&refined.x - &exp).norm_p2() < param.eps.sqrt());
+    assert_eq!(refined.lmd.size(), (0, 1));
+    assert!((&refined.range_dual - Mat::new_vec(2).set_all(2.5)).norm_p2() < param.eps.sqrt());
+
+    // the unconstrained optimum violates the ranges
+    let unconstrained = SolverResult {
+        x: Mat::new_vec(2).set_all(3.),
+        range_dual: Mat::new_vec(2),
+        .. rslt
+    };
+    assert!(kkt_residual(&prob, &unconstrained) > 1.);
+}
+
+#[test]
+fn test_refine_soft()
+{
+    // x0 >= 1 and x0 <= 0 conflict, and the latter is softened
+    let prob = QPProblem::new(Mat::new(2, 2).set_eye(), Mat::new_vec(2),
+                              Mat::new(2, 2).set_iter(&[
+                                  -1., 0.,
+                                  1., 0.
+                              ]),
+                              Mat::new_vec(2).set_iter(&[
+                                  -1., 0.
+                              ]),
+                              Mat::new(0, 2), Mat::new_vec(0));
+
+    let param = PDIPMParam::default();
+    for &(weight, norm) in &[(10., SoftNorm::L1), (10., SoftNorm::L2)] {
+        let prob = prob.clone().soft(1, weight, norm);
+        let rslt = prob.solve(&param).unwrap();
+        let refined = refine_solution(&prob, &rslt, 3);
+
+        let (r0, r1) = (kkt_residual(&prob, &rslt), kkt_residual(&prob, &refined));
+        println!("{:?}: residual: {:.3e} -> {:.3e}, x = {}", norm, r0, r1, refined.x.t());
+        assert!(r0 < param.eps.sqrt());
+        assert!(r1 <= r0);
+
+        // the hard constraint holds tightly
+        assert_eq!(refined.x.size(), (2, 1));
+        assert_eq!(refined.lmd.size(), (2, 1));
+        assert!((refined.x[(0, 0)] - 1.).abs() < param.eps.sqrt());
+        assert!((refined.soft_violation - 1.).abs() < param.eps.sqrt());
+        assert!((refined.primal_objective - 0.5).abs() < param.eps.sqrt());
+    }
+}
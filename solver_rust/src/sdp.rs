@@ -2,11 +2,13 @@
 
 use super::prelude::*;
 use super::matsvd::MatSVD;
+use super::problem::{Problem, ProblemDims, SolverResult, SolverError, Sense};
+use super::problem::{check_dim, check_col_vec, check_sym};
 
 use std::io::Write;
 macro_rules! writeln_or {
     ( $( $arg: expr ),* ) => {
-        writeln!( $( $arg ),* ).or(Err("log: I/O Error"))
+        writeln!( $( $arg ),* ).or(Err(SolverError::LogFailure))
     };
 }
 
@@ -43,61 +45,87 @@ pub trait SDP {
     fn solve_sdp<L>(&mut self, param: &PDIPMParam, log: &mut L,
                     vec_c: &Mat, mat_f: &[Mat],
                     mat_a: &Mat, vec_b: &Mat)
-                    -> Result<Mat, String>
+                    -> Result<Mat, SolverError>
     where L: Write;
 }
 
-fn check_param(vec_c: &Mat, mat_f: &[Mat],
-               mat_a: &Mat, vec_b: &Mat)
-               -> Result<(usize, usize, usize, usize), String>
+/// Semidefinite program inputs
+/// 
+/// See [`SDP`](trait.SDP.html) for the problem formulation.
+#[derive(Debug, Clone)]
+pub struct SDPProblem
 {
-        let (n, _) = vec_c.size();
-        let m = 0;
-        let (p, _) = mat_a.size();
-
-        if n == 0 {return Err("vec_c: 0 rows".into());}
-        // m = 0 means NO inequality constraints
-        // p = 0 means NO equality constraints
-
-        if vec_c.size() != (n, 1) {return Err(format!("vec_c: size {:?} must be {:?}", vec_c.size(), (n, 1)));}
-
-        if mat_f.len() != n + 1 {return Err(format!("mat_f: length {} must be {}", mat_f.len(), n + 1));}
+    /// \\(c\\)
+    pub vec_c: Mat,
+    /// \\(F_0, \\ldots, F_n\\)
+    pub mat_f: Vec<Mat>,
+    /// \\(A\\)
+    pub mat_a: Mat,
+    /// \\(b\\)
+    pub vec_b: Mat,
+    /// Sense of the objective, `Sense::Minimize` by default.
+    pub objective_sense: Sense
+}
 
-        let (k, _) = mat_f[0].size();
-        for i in 0 ..= n {
-            if mat_f[i].size() != (k, k) {return Err(format!("mat_f[{}]: size {:?} must be {:?}", i, mat_f[i].size(), (k, k)));}
+impl SDPProblem
+{
+    /// Makes a problem.
+    pub fn new(vec_c: Mat, mat_f: Vec<Mat>,
+               mat_a: Mat, vec_b: Mat) -> SDPProblem
+    {
+        SDPProblem {
+            vec_c, mat_f,
+            mat_a, vec_b,
+            objective_sense: Sense::default()
         }
+    }
+    /// Makes a problem from \\(F_0, \\ldots, F_n\\) in the scaled symmetric vectorization,
+    /// each of which is [`Mat::svec`](../mat/struct.MatGen.html#method.svec) of a \\(k \\times k\\) matrix.
+    ///
+    /// They are unpacked by [`Mat::smat`](../mat/struct.MatGen.html#method.smat) into full symmetric matrices.
+    pub fn new_svec(vec_c: Mat, svec_f: &[Mat], k: usize,
+                    mat_a: Mat, vec_b: Mat) -> SDPProblem
+    {
+        let mat_f = svec_f.iter().map(|v| Mat::smat(v, k)).collect();
 
-        if mat_a.size() != (p, n) {return Err(format!("mat_a: size {:?} must be {:?}", mat_a.size(), (p, n)));}
-        if vec_b.size() != (p, 1) {return Err(format!("vec_b: size {:?} must be {:?}", vec_b.size(), (p, 1)));}
-
-        Ok((n, m, p, k))
-}
+        SDPProblem::new(vec_c, mat_f, mat_a, vec_b)
+    }
+    /// Sets the sense of the objective.
+    pub fn with_sense(mut self, sense: Sense) -> SDPProblem
+    {
+        self.objective_sense = sense;
+        self
+    }
 
-impl SDP for PDIPM
-{
-    /// Runs the solver with given parameters.
+    /// Runs the solver with given parameters, reusing a solver instance.
     /// 
-    /// Returns `Ok` with optimal \\(x\\) or `Err` with message string.
+    /// Returns `Ok` with [`SolverResult`](../problem/struct.SolverResult.html)
+    /// or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// `lmd` of the result is a \\(k \\times k\\) dual matrix of the linear matrix inequality.
+    /// * `solver` is a solver instance.
     /// * `param` is solver parameters.
     ///   *NOTE: Current implementation is not so accurate.*
     ///   *You may need increase `eps` parameter.*
     /// * `log` outputs solver progress.
-    /// * `vec_c` is \\(c\\).
-    /// * `mat_f` is \\(F_0, \\ldots, F_n\\).
-    /// * `mat_a` is \\(A\\).
-    /// * `vec_b` is \\(b\\).
-    fn solve_sdp<L>(&mut self, param: &PDIPMParam, log: &mut L,
-                    vec_c: &Mat, mat_f: &[Mat],
-                    mat_a: &Mat, vec_b: &Mat)
-                    -> Result<Mat, String>
+    pub fn solve_with<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                         -> Result<SolverResult, SolverError>
     where L: Write
     {
         // TODO: improve accuracy
 
         // ----- parameter check
 
-        let (n, m, p, k) = check_param(vec_c, mat_f, mat_a, vec_b)?;
+        let lap_total = Timings::start(param);
+        self.validate()?;
+        for (i, mat_fi) in self.mat_f.iter().enumerate() {
+            check_sym(&format!("mat_f[{}]", i), mat_fi, param.sym_tol)?;
+        }
+        let ProblemDims {n, m: k, p} = self.dims();
+        let m = 0;
+
+        let sign = self.objective_sense.sign();
+        let (vec_c, mat_f) = (&(sign * &self.vec_c), &self.mat_f);
+        let (mat_a, vec_b) = (&self.mat_a, &self.vec_b);
 
         // ----- initial value of a slack variable
 
@@ -125,7 +153,7 @@ impl SDP for PDIPM
 
         let mut mat_p = Mat::new(n, p + 1);
         mat_p.cols_mut(0 .. p).assign(&mat_a.t());
-        mat_p.col_mut(p).assign(&vec_c);
+        mat_p.col_mut(p).assign(vec_c);
 
         let mut svd_np1 = MatSVD::new(mat_p.size());
         svd_np1.decomp(&mat_p);
@@ -137,6 +165,12 @@ impl SDP for PDIPM
 
         let mut vec_xs = Mat::new_vec(n + 1);
         vec_xs[(n, 0)] = s_initial;
+        let mut vec_nu = Mat::new_vec(p);
+        let mut t_last = t;
+        let mut iterations = 0;
+        let mut inner_timings = Timings::default();
+        let mut status = SolverStatus::Converged;
+        let mut residuals = Residuals::default();
 
         let svd_cell = RefCell::new(svd_kk);
 
@@ -145,7 +179,7 @@ impl SDP for PDIPM
             writeln_or!(log, "===== ===== ===== ===== barrier loop")?;
             writeln_or!(log, "t = {}", t)?;
 
-            let rslt = self.solve(param, log,
+            let rslt = solver.solve(param, log,
                 n + 1, m, p + 1, // '+ 1' is for a slack variable
                 |x, df_o| {
                     let mut fx = - x[(n, 0)] * &eye;
@@ -210,10 +244,137 @@ impl SDP for PDIPM
 
             let rslt = rslt?;
             vec_xs.assign(&rslt.rows(0 .. n + 1));
+            // dual variables of the barrier subproblem are scaled by t
+            vec_nu.assign(&(rslt.rows(n + 1 .. n + 1 + p) / t));
+            t_last = t;
+            iterations += solver.iterations();
+            // the result is not converged if any of the barrier subproblems is not
+            if solver.status() == SolverStatus::MaxIterReached {
+                status = SolverStatus::MaxIterReached;
+            }
+            residuals = solver.residuals();
+            if let Some(t) = solver.timings() {
+                inner_timings.kkt_assembly += t.kkt_assembly;
+                inner_timings.factorization += t.factorization;
+                inner_timings.solve += t.solve;
+                inner_timings.line_search += t.line_search;
+            }
 
             t *= param.mu;
         }
 
-        Ok(vec_xs.rows(0 .. n).clone_sz())
+        // dual matrix on the central path: -F(x)^-1 / t
+        let mut fx = -vec_xs[(n, 0)] * &eye;
+        fx += &mat_f[n];
+        for i in 0 .. n {
+            fx += &mat_f[i] * vec_xs[(i, 0)];
+        }
+        let mut svd = svd_cell.borrow_mut();
+        svd.decomp(&fx);
+        let mat_z = -svd.solve(&eye) / t_last;
+
+        let x = vec_xs.rows(0 .. n).clone_sz();
+
+        // setup includes validation and the outer barrier loop
+        let timings = if param.timing {
+            inner_timings.total = Timings::elapsed(lap_total);
+            inner_timings.setup = inner_timings.total.saturating_sub(inner_timings.sum_phases());
+            Some(inner_timings)
+        }
+        else {
+            None
+        };
+
+        Ok(SolverResult {
+            lmd: mat_z * sign,
+            nu: vec_nu * sign,
+            iterations,
+            sense: self.objective_sense,
+            primal_objective: self.vec_c.prod(&x),
+            timings,
+            soft_violation: 0.,
+            dropped_eqs: solver.dropped_eqs().to_vec(),
+            status,
+            residuals,
+            range_dual: Mat::new_vec(0),
+            certificate: None,
+            dual_objective: FP::NAN,
+            duality_gap: FP::NAN,
+            objective_parts: None,
+            x
+        }.with_gap(|x| {
+            let mut fx = mat_f[n].clone();
+            for i in 0 .. n {
+                fx += &mat_f[i] * x[(i, 0)];
+            }
+            (fx, mat_a * x - vec_b)
+        }))
+    }
+}
+
+impl Problem for SDPProblem
+{
+    fn dims(&self) -> ProblemDims
+    {
+        ProblemDims {
+            n: self.vec_c.size().0,
+            m: self.mat_f.first().map_or(0, |f0| f0.size().0),
+            p: self.mat_a.size().0
+        }
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, m: k, p} = self.dims();
+        let (vec_c, mat_f) = (&self.vec_c, &self.mat_f);
+        let (mat_a, vec_b) = (&self.mat_a, &self.vec_b);
+
+        if n == 0 {return Err(SolverError::InvalidProblem("vec_c: 0 rows".into()));}
+        // p = 0 means NO equality constraints
+
+        check_col_vec("vec_c", vec_c)?;
+        check_dim("mat_f length", mat_f.len(), "vec_c rows + 1", n + 1)?;
+
+        for (i, mat_fi) in mat_f.iter().enumerate() {
+            check_dim(&format!("mat_f[{}] rows", i), mat_fi.size().0, "mat_f[0] rows", k)?;
+            check_dim(&format!("mat_f[{}] columns", i), mat_fi.size().1, "mat_f[0] rows", k)?;
+        }
+
+        check_dim("mat_a columns", mat_a.size().1, "vec_c rows", n)?;
+        check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+        check_col_vec("vec_b", vec_b)?;
+
+        Ok(())
+    }
+
+    fn solve(&self, param: &PDIPMParam) -> Result<SolverResult, SolverError>
+    {
+        self.solve_with(&mut PDIPM::new(), param, &mut std::io::sink())
+    }
+}
+
+impl SDP for PDIPM
+{
+    /// Runs the solver with given parameters.
+    /// 
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    ///   *NOTE: Current implementation is not so accurate.*
+    ///   *You may need increase `eps` parameter.*
+    /// * `log` outputs solver progress.
+    /// * `vec_c` is \\(c\\).
+    /// * `mat_f` is \\(F_0, \\ldots, F_n\\).
+    /// * `mat_a` is \\(A\\).
+    /// * `vec_b` is \\(b\\).
+    fn solve_sdp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                    vec_c: &Mat, mat_f: &[Mat],
+                    mat_a: &Mat, vec_b: &Mat)
+                    -> Result<Mat, SolverError>
+    where L: Write
+    {
+        let prob = SDPProblem::new(vec_c.clone(), mat_f.to_vec(),
+                                   mat_a.clone(), vec_b.clone());
+
+        prob.solve_with(self, param, log).and_then(|rslt| rslt.converged_x()).map_err(|e| self.diagnose(e))
     }
 }
@@ -0,0 +1,515 @@
+/*!
+Homogeneous self-dual embedding
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+[`LPProblem`](../lp/struct.LPProblem.html) and [`SOCPProblem`](../socp/struct.SOCPProblem.html)
+are solved through this formulation when `PDIPMParam::formulation` is `Formulation::SelfDual`.
+
+A problem is regarded as a conic program
+\\[
+\\begin{array}{ll}
+{\\rm minimize} & f^T x \\\\
+{\\rm subject \\ to} & F x + g \\in {\\cal K} \\\\
+& A x = b,
+\\end{array}
+\\]
+where \\( {\\cal K} \\) is a product of \\(\\nu\\) cones, nonnegative rays for LP (\\( F = -G \\), \\( g = h \\))
+and second-order cones for SOCP (\\( F_i = [c_i^T; G_i] \\), \\( g_i = [d_i; h_i] \\)).
+Its dual is to maximize \\( -g^T z - b^T y \\) subject to \\( F^T z - A^T y = f \\), \\( z \\in {\\cal K} \\).
+
+The homogeneous model with \\( \\tau, \\kappa \\ge 0 \\)
+\\[
+\\left[\\matrix{0 \\\\ 0 \\\\ s \\\\ \\kappa}\\right] =
+\\left[\\matrix{0 & A^T & -F^T & f \\\\ -A & 0 & 0 & b \\\\ F & 0 & 0 & g \\\\ -f^T & -b^T & -g^T & 0}\\right]
+\\left[\\matrix{x \\\\ y \\\\ z \\\\ \\tau}\\right],
+\\quad s, z \\in {\\cal K}
+\\]
+is embedded with an artificial variable \\(\\theta\\) into a problem minimizing \\( (\\nu + 1) \\theta \\),
+whose initial point \\( x = 0, y = 0, z = s = e, \\tau = \\kappa = \\theta = 1 \\) is strictly feasible.
+The embedding always has an optimal solution with \\( \\theta = 0 \\), from which
+- \\( \\tau > \\kappa \\) gives an optimal solution \\( x / \\tau \\), \\( z / \\tau \\), \\( y / \\tau \\),
+- otherwise \\( g^T z + b^T y < 0 \\) certifies primal infeasibility
+  and \\( f^T x < 0 \\) certifies dual infeasibility, i.e. unboundedness.
+
+```
+use totsu::prelude::*;
+
+// x <= -5 and x >= 10 conflict
+let lp = LPProblem::new(Mat::new_vec(1).set_all(1.),
+                        Mat::new(2, 1).set_iter(&[1., -1.]), Mat::new_vec(2).set_iter(&[-5., -10.]),
+                        Mat::new(0, 1), Mat::new_vec(0));
+
+let param = PDIPMParam {
+    formulation: Formulation::SelfDual,
+    .. Default::default()
+};
+let rslt = lp.solve(&param).unwrap();
+assert_eq!(rslt.status, SolverStatus::PrimalInfeasible);
+```
+*/
+
+use super::prelude::*;
+use super::pdipm::Formulation;
+use super::problem::{SolverResult, SolverError, SolverStatus, Sense};
+use super::lp::LPInputs;
+
+use std::io::Write;
+
+// conic data and the embedding residuals r = v0 - M u0
+struct Embedding
+{
+    n: usize,
+    p: usize,
+    k: usize,
+    // (start, length) of each cone in z and s
+    cones: Vec<(usize, usize)>,
+    vec_f: Mat,
+    mat_f: Mat,
+    vec_g: Mat,
+    mat_a: Mat,
+    vec_b: Mat,
+    r_x: Mat,
+    r_y: Mat,
+    r_z: Mat,
+    r_tau: FP
+}
+
+// a solution of the embedding
+struct Embedded
+{
+    x: Mat,
+    y: Mat,
+    z: Mat,
+    tau: FP,
+    kappa: FP
+}
+
+impl Embedding
+{
+    fn new(vec_f: Mat, mat_f: Mat, vec_g: Mat, cones: Vec<(usize, usize)>, mat_a: Mat, vec_b: Mat) -> Embedding
+    {
+        let (k, n) = mat_f.size();
+        let (p, _) = mat_a.size();
+
+        // identity of the cones
+        let mut vec_e = Mat::new_vec(k);
+        for &(start, _) in &cones {
+            vec_e[(start, 0)] = 1.;
+        }
+
+        let r_x = mat_f.t() * &vec_e - &vec_f;
+        let r_y = -&vec_b;
+        let r_z = &vec_e - &vec_g;
+        let r_tau = 1. + vec_g.prod(&vec_e);
+
+        Embedding {
+            n, p, k, cones,
+            vec_f, mat_f, vec_g, mat_a, vec_b,
+            r_x, r_y, r_z, r_tau
+        }
+    }
+
+    // number of variables (x, y, z, tau, theta)
+    fn size(&self) -> usize
+    {
+        self.n + self.p + self.k + 2
+    }
+
+    fn idx_z(&self) -> usize
+    {
+        self.n + self.p
+    }
+
+    fn idx_tau(&self) -> usize
+    {
+        self.n + self.p + self.k
+    }
+
+    fn idx_theta(&self) -> usize
+    {
+        self.n + self.p + self.k + 1
+    }
+
+    // minimizes (nu + 1) theta
+    fn objective(&self) -> Mat
+    {
+        let mut vec_c = Mat::new_vec(self.size());
+        vec_c[(self.idx_theta(), 0)] = (self.cones.len() + 1) as FP;
+        vec_c
+    }
+
+    // rows of x and y, and the normalizing row of theta
+    fn equality(&self) -> (Mat, Mat)
+    {
+        let (n, p, k) = (self.n, self.p, self.k);
+        let (iz, it, ith) = (self.idx_z(), self.idx_tau(), self.idx_theta());
+
+        let mut mat_a = Mat::new(n + p + 1, self.size());
+        let mut vec_b = Mat::new_vec(n + p + 1);
+
+        mat_a.slice_mut(0 .. n, n .. n + p).assign(&self.mat_a.t());
+        mat_a.slice_mut(0 .. n, iz .. iz + k).assign(&-self.mat_f.t());
+        mat_a.slice_mut(0 .. n, it ..= it).assign(&self.vec_f);
+        mat_a.slice_mut(0 .. n, ith ..= ith).assign(&self.r_x);
+
+        mat_a.slice_mut(n .. n + p, 0 .. n).assign(&-&self.mat_a);
+        mat_a.slice_mut(n .. n + p, it ..= it).assign(&self.vec_b);
+        mat_a.slice_mut(n .. n + p, ith ..= ith).assign(&self.r_y);
+
+        mat_a.slice_mut(n + p ..= n + p, 0 .. n).assign(&-self.r_x.t());
+        mat_a.slice_mut(n + p ..= n + p, n .. n + p).assign(&-self.r_y.t());
+        mat_a.slice_mut(n + p ..= n + p, iz .. iz + k).assign(&-self.r_z.t());
+        mat_a[(n + p, it)] = -self.r_tau;
+        vec_b[(n + p, 0)] = -((self.cones.len() + 1) as FP);
+
+        (mat_a, vec_b)
+    }
+
+    // s = F x + g tau + r_z theta
+    fn slack_s(&self) -> Mat
+    {
+        let (n, k) = (self.n, self.k);
+        let (it, ith) = (self.idx_tau(), self.idx_theta());
+
+        let mut mat_s = Mat::new(k, self.size());
+        mat_s.slice_mut(0 .. k, 0 .. n).assign(&self.mat_f);
+        mat_s.slice_mut(0 .. k, it ..= it).assign(&self.vec_g);
+        mat_s.slice_mut(0 .. k, ith ..= ith).assign(&self.r_z);
+        mat_s
+    }
+
+    // kappa = -f^T x - b^T y - g^T z + r_tau theta
+    fn slack_kappa(&self) -> Mat
+    {
+        let (n, p, k) = (self.n, self.p, self.k);
+        let iz = self.idx_z();
+
+        let mut row = Mat::new(1, self.size());
+        row.slice_mut(0 ..= 0, 0 .. n).assign(&-self.vec_f.t());
+        row.slice_mut(0 ..= 0, n .. n + p).assign(&-self.vec_b.t());
+        row.slice_mut(0 ..= 0, iz .. iz + k).assign(&-self.vec_g.t());
+        row[(0, self.idx_theta())] = self.r_tau;
+        row
+    }
+
+    fn extract(&self, w: &Mat) -> Embedded
+    {
+        let (n, p, k) = (self.n, self.p, self.k);
+        let iz = self.idx_z();
+
+        Embedded {
+            x: w.rows(0 .. n).clone_sz(),
+            y: w.rows(n .. n + p).clone_sz(),
+            z: w.rows(iz .. iz + k).clone_sz(),
+            tau: w[(self.idx_tau(), 0)],
+            kappa: (self.slack_kappa() * w)[(0, 0)]
+        }
+    }
+
+    // solves the embedding as LP, for cones of length one
+    fn solve_lp<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                   -> Result<(Embedded, SolverResult), SolverError>
+    where L: Write
+    {
+        let k = self.k;
+        let (iz, it) = (self.idx_z(), self.idx_tau());
+        let (mat_a, vec_b) = self.equality();
+
+        // -z <= 0, -tau <= 0, -s <= 0, -kappa <= 0
+        let mut mat_g = Mat::new(k + 1 + k + 1, self.size());
+        mat_g.slice_mut(0 .. k, iz .. iz + k).assign(&-Mat::new(k, k).set_eye());
+        mat_g[(k, it)] = -1.;
+        mat_g.rows_mut(k + 1 .. k + 1 + k).assign(&-self.slack_s());
+        mat_g.rows_mut(k + 1 + k ..).assign(&-self.slack_kappa());
+        let (m_g, _) = mat_g.size();
+
+        let lp = LPProblem::new(self.objective(), mat_g, Mat::new_vec(m_g), mat_a, vec_b);
+        let rslt = lp.solve_with(solver, &h_standard(param), log)?;
+
+        Ok((self.extract(&rslt.x), rslt))
+    }
+
+    // solves the embedding as SOCP
+    fn solve_socp<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                     -> Result<(Embedded, SolverResult), SolverError>
+    where L: Write
+    {
+        let nw = self.size();
+        let (iz, it) = (self.idx_z(), self.idx_tau());
+        let (mat_a, vec_b) = self.equality();
+        let mat_s = self.slack_s();
+
+        let mut mat_g = Vec::new();
+        let mut vec_c = Vec::new();
+        for &(start, len) in &self.cones {
+            // z in the cone
+            let mut g_z = Mat::new(len - 1, nw);
+            g_z.slice_mut(0 .. len - 1, iz + start + 1 .. iz + start + len).assign_eye();
+            mat_g.push(g_z);
+            vec_c.push(Mat::new_vec(nw).set_by(|r, _| if r == iz + start {1.} else {0.}));
+
+            // s in the cone
+            mat_g.push(mat_s.rows(start + 1 .. start + len).clone_sz());
+            vec_c.push(mat_s.row(start).t().clone_sz());
+        }
+        // tau >= 0, kappa >= 0
+        mat_g.push(Mat::new(0, nw));
+        vec_c.push(Mat::new_vec(nw).set_by(|r, _| if r == it {1.} else {0.}));
+        mat_g.push(Mat::new(0, nw));
+        vec_c.push(self.slack_kappa().t().clone_sz());
+
+        let vec_h = mat_g.iter().map(|g| Mat::new_vec(g.size().0)).collect();
+        let scl_d = vec![0.; mat_g.len()];
+
+        let socp = SOCPProblem::new(self.objective(), mat_g, vec_h, vec_c, scl_d, mat_a, vec_b);
+        let rslt = socp.solve_with(solver, &h_standard(param), log)?;
+
+        Ok((self.extract(&rslt.x), rslt))
+    }
+
+    // converts a solution of the embedding into the result of the original problem:
+    // `lmd` converts optimal z into dual variables of the original inequalities in the sense of minimization
+    fn result<F, D>(&self, emb: Embedded, inner: SolverResult, sense: Sense, objective: F, lmd: D)
+                    -> Result<SolverResult, SolverError>
+    where F: FnOnce(&Mat) -> FP, D: Fn(&Mat) -> Mat
+    {
+        let (n, p) = (self.n, self.p);
+        let sign = sense.sign();
+        let Embedded {x, y, z, tau, kappa} = emb;
+
+        let base = SolverResult {
+            x: Mat::new_vec(n),
+            lmd: lmd(&Mat::new_vec(self.k)),
+            nu: Mat::new_vec(p),
+            primal_objective: FP::NAN,
+            dual_objective: FP::NAN,
+            duality_gap: FP::NAN,
+            range_dual: Mat::new_vec(0),
+            certificate: None,
+            // rows of y in the embedding
+            dropped_eqs: inner.dropped_eqs.iter().filter(|&&r| (n .. n + p).contains(&r)).map(|r| r - n).collect(),
+            .. inner
+        };
+
+        if tau > kappa {
+            let x = x / tau;
+            let y = y / tau;
+            let z = z / tau;
+            let primal = self.vec_f.prod(&x);
+            let dual = -self.vec_g.prod(&z) - self.vec_b.prod(&y);
+
+            Ok(SolverResult {
+                primal_objective: objective(&x),
+                dual_objective: sign * dual,
+                duality_gap: primal - dual,
+                lmd: sign * lmd(&z),
+                nu: sign * y,
+                x,
+                .. base
+            })
+        }
+        else {
+            let farkas = self.vec_g.prod(&z) + self.vec_b.prod(&y);
+            let ray = self.vec_f.prod(&x);
+
+            if farkas < 0. {
+                let mut cert = Mat::new_vec(self.k + p);
+                cert.rows_mut(0 .. self.k).assign(&z);
+                cert.rows_mut(self.k ..).assign(&y);
+
+                Ok(SolverResult {
+                    status: SolverStatus::PrimalInfeasible,
+                    primal_objective: sign * FP::INFINITY,
+                    certificate: Some(cert / -farkas),
+                    .. base
+                })
+            }
+            else if ray < 0. {
+                Ok(SolverResult {
+                    status: SolverStatus::DualInfeasible,
+                    primal_objective: -sign * FP::INFINITY,
+                    certificate: Some(x / -ray),
+                    .. base
+                })
+            }
+            else {
+                Err(SolverError::NotConverged(format!("self-dual: tau = {:.3e} and kappa = {:.3e} are indistinguishable", tau, kappa).into()))
+            }
+        }
+    }
+}
+
+// parameters to solve the embedding itself
+fn h_standard(param: &PDIPMParam) -> PDIPMParam
+{
+    PDIPMParam {
+        formulation: Formulation::Standard,
+        .. param.clone()
+    }
+}
+
+// used by solver modules
+pub(crate) fn solve_lp<L>(prob: LPInputs, sense: Sense, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                          -> Result<SolverResult, SolverError>
+where L: Write
+{
+    let (m, _) = prob.mat_g.size();
+    let sign = sense.sign();
+
+    let emb = Embedding::new(sign * prob.vec_c, -prob.mat_g, prob.vec_h.clone(),
+                             (0 .. m).map(|i| (i, 1)).collect(),
+                             prob.mat_a.clone(), prob.vec_b.clone());
+    let (sol, inner) = emb.solve_lp(solver, param, log)?;
+
+    emb.result(sol, inner, sense, |x| prob.vec_c.prod(x), |z| z.clone())
+}
+
+// used by solver modules
+pub(crate) fn solve_socp<L>(prob: &SOCPProblem, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                            -> Result<SolverResult, SolverError>
+where L: Write
+{
+    let n = prob.vec_f.size().0;
+    let m = prob.mat_g.len();
+    let sign = prob.objective_sense.sign();
+
+    let mut cones = Vec::new();
+    let mut k = 0;
+    for g in &prob.mat_g {
+        let len = g.size().0 + 1;
+        cones.push((k, len));
+        k += len;
+    }
+
+    let mut mat_f = Mat::new(k, n);
+    let mut vec_g = Mat::new_vec(k);
+    for (i, &(start, len)) in cones.iter().enumerate() {
+        mat_f.row_mut(start).assign(&prob.vec_c[i].t());
+        mat_f.rows_mut(start + 1 .. start + len).assign(&prob.mat_g[i]);
+        vec_g[(start, 0)] = prob.scl_d[i];
+        vec_g.rows_mut(start + 1 .. start + len).assign(&prob.vec_h[i]);
+    }
+
+    let emb = Embedding::new(sign * &prob.vec_f, mat_f, vec_g, cones.clone(),
+                             prob.mat_a.clone(), prob.vec_b.clone());
+    let (sol, inner) = emb.solve_socp(solver, param, log)?;
+
+    // multipliers of the internal constraints ||G_i x + h_i||^2 / s_i <= s_i are halves of the cone heads
+    emb.result(sol, inner, prob.objective_sense, |x| prob.vec_f.prod(x), |z| {
+        Mat::new_vec(m).set_by(|i, _| z[(cones[i].0, 0)] / 2.)
+    })
+}
+
+#[test]
+fn test_selfdual_lp()
+{
+    let param = PDIPMParam {
+        formulation: Formulation::SelfDual,
+        .. Default::default()
+    };
+    let param_cap = PDIPMParam {
+        max_iter: Some(64),
+        .. Default::default()
+    };
+
+    // x <= -5 and x >= 10 conflict
+    let infeas = LPProblem::new(Mat::new_vec(1).set_all(1.),
+                                Mat::new(2, 1).set_iter(&[1., -1.]), Mat::new_vec(2).set_iter(&[-5., -10.]),
+                                Mat::new(0, 1), Mat::new_vec(0));
+    assert!(!matches!(infeas.solve(&param_cap), Ok(SolverResult {status: SolverStatus::Converged, ..})));
+
+    let rslt = infeas.solve(&param).unwrap();
+    println!("{:?} in {} iterations, certificate = {}", rslt.status, rslt.iterations, rslt.certificate.as_ref().unwrap());
+    assert_eq!(rslt.status, SolverStatus::PrimalInfeasible);
+    assert!(rslt.iterations < 64);
+    // G^T z = 0, z >= 0, h^T z = -1
+    let z = rslt.certificate.unwrap();
+    assert!((infeas.mat_g.t() * &z).norm_p2() < 1e-6);
+    assert!(z.min().unwrap() > -1e-6);
+    assert!((infeas.vec_h.prod(&z) + 1.).abs() < 1e-6);
+
+    // x0 - x1 <= 1 and x1 >= 0, minimizing -x0 - x1
+    let unbdd = LPProblem::new(Mat::new_vec(2).set_all(-1.),
+                               Mat::new(2, 2).set_iter(&[1., -1., 0., -1.]), Mat::new_vec(2).set_iter(&[1., 0.]),
+                               Mat::new(0, 2), Mat::new_vec(0));
+    assert!(!matches!(unbdd.solve(&param_cap), Ok(SolverResult {status: SolverStatus::Converged, ..})));
+
+    let rslt = unbdd.solve(&param).unwrap();
+    println!("{:?} in {} iterations, certificate = {}", rslt.status, rslt.iterations, rslt.certificate.as_ref().unwrap());
+    assert_eq!(rslt.status, SolverStatus::DualInfeasible);
+    assert!(rslt.iterations < 64);
+    // G x <= 0, c^T x = -1
+    let x = rslt.certificate.unwrap();
+    assert!((&unbdd.mat_g * &x).max().unwrap() < 1e-6);
+    assert!((unbdd.vec_c.prod(&x) + 1.).abs() < 1e-6);
+    assert!(PDIPM::new().solve_lp(&param, &mut std::io::sink(),
+                                  &unbdd.vec_c, &unbdd.mat_g, &unbdd.vec_h, &unbdd.mat_a, &unbdd.vec_b).is_err());
+
+    // feasible problems of both senses
+    let feas = LPProblem::new(Mat::new_vec(2).set_iter(&[1., 2.]),
+                              Mat::new(3, 2).set_iter(&[-1., 0., 0., -1., 1., 1.]), Mat::new_vec(3).set_iter(&[0., 0., 1.]),
+                              Mat::new(1, 2).set_iter(&[1., -1.]), Mat::new_vec(1).set_all(0.2));
+    for &sense in &[Sense::Minimize, Sense::Maximize] {
+        let feas = feas.clone().with_sense(sense);
+        let rslt_std = feas.solve(&PDIPMParam::default()).unwrap();
+        let rslt = feas.solve(&param).unwrap();
+        println!("x = {} lmd = {} nu = {}", rslt.x, rslt.lmd, rslt.nu);
+        assert_eq!(rslt.status, SolverStatus::Converged);
+        assert!((&rslt.x - &rslt_std.x).norm_p2() < 1e-6);
+        assert!((&rslt.lmd - &rslt_std.lmd).norm_p2() < 1e-5);
+        assert!((&rslt.nu - &rslt_std.nu).norm_p2() < 1e-5);
+        assert!((rslt.primal_objective - rslt_std.primal_objective).abs() < 1e-6);
+        assert!(rslt.duality_gap.abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_selfdual_socp()
+{
+    let param = PDIPMParam {
+        formulation: Formulation::SelfDual,
+        .. Default::default()
+    };
+    let n: usize = 2;
+
+    // minimize x0 + x1 subject to ||x|| <= sqrt(2)
+    let feas = SOCPProblem::new(Mat::new_vec(n).set_all(1.),
+                                vec![Mat::new(n, n).set_eye()], vec![Mat::new_vec(n)],
+                                vec![Mat::new_vec(n)], vec![FP::sqrt(2.)],
+                                Mat::new(0, n), Mat::new_vec(0));
+    let rslt_std = feas.solve(&PDIPMParam::default()).unwrap();
+    let rslt = feas.solve(&param).unwrap();
+    println!("x = {} lmd = {} / {}", rslt.x, rslt.lmd, rslt_std.lmd);
+    assert_eq!(rslt.status, SolverStatus::Converged);
+    assert!((&rslt.x - &rslt_std.x).norm_p2() < 1e-6);
+    assert!((&rslt.lmd - &rslt_std.lmd).norm_p2() < 1e-5);
+    assert!((rslt.primal_objective + 2.).abs() < 1e-6);
+
+    // ||x|| <= 1 and x0 >= 2 conflict
+    let infeas = SOCPProblem::new(Mat::new_vec(n).set_all(1.),
+                                  vec![Mat::new(n, n).set_eye(), Mat::new(0, n)], vec![Mat::new_vec(n), Mat::new_vec(0)],
+                                  vec![Mat::new_vec(n), Mat::new_vec(n).set_iter(&[1., 0.])], vec![1., -2.],
+                                  Mat::new(0, n), Mat::new_vec(0));
+    assert!(!matches!(infeas.solve(&PDIPMParam::default()), Ok(SolverResult {status: SolverStatus::Converged, ..})));
+
+    let rslt = infeas.solve(&param).unwrap();
+    println!("{:?} in {} iterations", rslt.status, rslt.iterations);
+    assert_eq!(rslt.status, SolverStatus::PrimalInfeasible);
+    assert!(rslt.iterations < PDIPMParam::default().n_loop);
+
+    // minimizing x1 subject to |x0| <= 2 x0 + x1 is unbounded along x0 = -x1 -> inf
+    let unbdd = SOCPProblem::new(Mat::new_vec(n).set_iter(&[0., 1.]),
+                                 vec![Mat::new(1, n).set_iter(&[1., 0.])], vec![Mat::new_vec(1)],
+                                 vec![Mat::new_vec(n).set_iter(&[2., 1.])], vec![0.],
+                                 Mat::new(0, n), Mat::new_vec(0));
+    assert!(!matches!(unbdd.solve(&PDIPMParam::default()), Ok(SolverResult {status: SolverStatus::Converged, ..})));
+
+    let rslt = unbdd.solve(&param).unwrap();
+    println!("{:?} in {} iterations", rslt.status, rslt.iterations);
+    assert_eq!(rslt.status, SolverStatus::DualInfeasible);
+    assert!(rslt.iterations < PDIPMParam::default().n_loop);
+    let x = rslt.certificate.unwrap();
+    assert!((unbdd.vec_f.prod(&x) + 1.).abs() < 1e-6);
+    assert!(x[(0, 0)].abs() - (2. * x[(0, 0)] + x[(1, 0)]) < 1e-6);
+}
@@ -0,0 +1,234 @@
+/*!
+Sensitivity analysis
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+[`qp_sensitivity`](fn.qp_sensitivity.html) differentiates the solution of a QP
+with respect to its parameters \\(q\\), \\(h\\) and \\(b\\),
+applying the implicit function theorem to the KKT conditions at the solution:
+\\[
+\\begin{array}{l}
+P x + q + G_{\\cal A}^T \\lambda_{\\cal A} + A^T \\nu = 0 \\\\
+G_{\\cal A} x = h_{\\cal A} \\\\
+A x = b,
+\\end{array}
+\\]
+where \\( {\\cal A} \\) is the set of active inequality constraints.
+
+# Active set convention
+
+The interior-point method stops with small but nonzero \\( \\lambda_i \\) and slacks \\( (h - G x)_i \\),
+so a constraint is regarded as active when \\( \\lambda_i > (h - G x)_i \\), and inactive otherwise.
+A weakly active constraint, whose \\( \\lambda_i \\) and slack are both almost zero,
+is thus classified by whichever is smaller; the derivative is then one-sided,
+valid for perturbations which keep the constraint in that state.
+Without weakly active constraints (strict complementarity), the derivative is exact.
+*/
+
+use super::prelude::*;
+use super::matsvd::MatSVD;
+use super::problem::{check_dim, check_col_vec};
+
+/// Derivatives of a QP solution
+///
+/// Made by [`qp_sensitivity`](fn.qp_sensitivity.html).
+#[derive(Debug)]
+pub struct QPSensitivity
+{
+    /// \\( \\partial x / \\partial q \\), an \\( n \\times n \\) matrix.
+    pub dx_dq: Mat,
+    //
+    active: Vec<usize>,
+    n: usize,
+    m: usize,
+    p: usize,
+    svd: MatSVD
+}
+
+impl QPSensitivity
+{
+    // solves the reduced KKT system with a right-hand side of (primal, active rows, equality rows)
+    fn h_solve(&self, rhs: &Mat) -> Mat
+    {
+        self.svd.solve(rhs).rows(0 .. self.n).clone_sz()
+    }
+
+    /// Returns the directional derivative of \\(x\\) along a perturbation `dh` of \\(h\\).
+    ///
+    /// Returns `Err` with [`SolverError::InvalidProblem`](../problem/enum.SolverError.html) if `dh` is not an \\(m\\)-vector.
+    pub fn dx_dh(&self, dh: &Mat) -> Result<Mat, SolverError>
+    {
+        check_dim("dh rows", dh.size().0, "mat_g rows", self.m)?;
+        check_col_vec("dh", dh)?;
+
+        let n_a = self.active.len();
+        let mut rhs = Mat::new_vec(self.n + n_a + self.p);
+
+        for (k, &i) in self.active.iter().enumerate() {
+            rhs[(self.n + k, 0)] = dh[(i, 0)];
+        }
+
+        Ok(self.h_solve(&rhs))
+    }
+
+    /// Returns the directional derivative of \\(x\\) along a perturbation `db` of \\(b\\).
+    ///
+    /// Returns `Err` with [`SolverError::InvalidProblem`](../problem/enum.SolverError.html) if `db` is not a \\(p\\)-vector.
+    pub fn dx_db(&self, db: &Mat) -> Result<Mat, SolverError>
+    {
+        check_dim("db rows", db.size().0, "mat_a rows", self.p)?;
+        check_col_vec("db", db)?;
+
+        let n_a = self.active.len();
+        let mut rhs = Mat::new_vec(self.n + n_a + self.p);
+
+        rhs.rows_mut(self.n + n_a ..).assign(db);
+
+        Ok(self.h_solve(&rhs))
+    }
+
+    /// Returns indices of the inequality constraints regarded as active.
+    pub fn active(&self) -> &[usize]
+    {
+        &self.active
+    }
+}
+
+/// Differentiates a QP solution with respect to its parameters.
+///
+/// Returns `Ok` with [`QPSensitivity`](struct.QPSensitivity.html)
+/// or `Err` with [`SolverError::InvalidProblem`](../problem/enum.SolverError.html) for inconsistent dimensions.
+/// See [`QP`](../qp/trait.QP.html) for the problem formulation,
+/// and [the module documentation](index.html) for the convention of weakly active constraints.
+/// * `rslt` is a solver result of the problem.
+/// * `mat_p` is \\(P\\).
+/// * `vec_q` is \\(q\\).
+/// * `mat_g` is \\(G\\).
+/// * `vec_h` is \\(h\\).
+/// * `mat_a` is \\(A\\).
+/// * `vec_b` is \\(b\\).
+pub fn qp_sensitivity(rslt: &SolverResult,
+                      mat_p: &Mat, vec_q: &Mat,
+                      mat_g: &Mat, vec_h: &Mat,
+                      mat_a: &Mat, vec_b: &Mat)
+                      -> Result<QPSensitivity, SolverError>
+{
+    let (n, _) = mat_p.size();
+    let (m, _) = mat_g.size();
+    let (p, _) = mat_a.size();
+
+    check_dim("mat_p columns", mat_p.size().1, "mat_p rows", n)?;
+    check_dim("vec_q rows", vec_q.size().0, "mat_p rows", n)?;
+    check_col_vec("vec_q", vec_q)?;
+    check_dim("mat_g columns", mat_g.size().1, "mat_p rows", n)?;
+    check_dim("vec_h rows", vec_h.size().0, "mat_g rows", m)?;
+    check_col_vec("vec_h", vec_h)?;
+    check_dim("mat_a columns", mat_a.size().1, "mat_p rows", n)?;
+    check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+    check_col_vec("vec_b", vec_b)?;
+    check_dim("x rows", rslt.x.size().0, "mat_p rows", n)?;
+    check_dim("lmd rows", rslt.lmd.size().0, "mat_g rows", m)?;
+
+    // the minimization form
+    let sign = rslt.sense.sign();
+    let slack = vec_h - mat_g * &rslt.x;
+    let active: Vec<usize> = (0 .. m).filter(|&i| sign * rslt.lmd[(i, 0)] > slack[(i, 0)]).collect();
+    let n_a = active.len();
+
+    let mut kkt = Mat::new(n + n_a + p, n + n_a + p);
+    kkt.slice_mut(0 .. n, 0 .. n).assign(&(sign * mat_p));
+    for (k, &i) in active.iter().enumerate() {
+        kkt.slice_mut(n + k ..= n + k, 0 .. n).assign(&mat_g.row(i));
+        kkt.slice_mut(0 .. n, n + k ..= n + k).assign(&mat_g.row(i).t());
+    }
+    kkt.slice_mut(n + n_a .., 0 .. n).assign(mat_a);
+    kkt.slice_mut(0 .. n, n + n_a ..).assign(&mat_a.t());
+
+    let mut svd = MatSVD::new(kkt.size());
+    svd.decomp(&kkt);
+
+    // d(sign q) enters the stationarity with a negative sign
+    let mut rhs = Mat::new(n + n_a + p, n);
+    rhs.rows_mut(0 .. n).assign(&(-sign * Mat::new(n, n).set_eye()));
+    let dx_dq = svd.solve(&rhs).rows(0 .. n).clone_sz();
+
+    Ok(QPSensitivity {
+        dx_dq,
+        active,
+        n, m, p,
+        svd
+    })
+}
+
+#[test]
+fn test_qp_sensitivity()
+{
+    let n: usize = 3;
+
+    // strictly complementary: the first inequality is active, the second is not
+    let mat_p = Mat::new(n, n).set_iter(&[
+        2., 0.5, 0.,
+        0.5, 1., 0.,
+        0., 0., 3.
+    ]);
+    let vec_q = Mat::new_vec(n).set_iter(&[
+        -4., -1., 1.
+    ]);
+    let mat_g = Mat::new(2, n).set_iter(&[
+        1., 1., 0.,
+        -1., 0., 0.
+    ]);
+    let vec_h = Mat::new_vec(2).set_iter(&[
+        1., 0.
+    ]);
+    let mat_a = Mat::new(1, n).set_iter(&[
+        0., 1., 1.
+    ]);
+    let vec_b = Mat::new_vec(1).set_iter(&[
+        0.5
+    ]);
+
+    let param = PDIPMParam {
+        eps: 1e-12,
+        .. PDIPMParam::default()
+    };
+    let solve = |q: &Mat, h: &Mat, b: &Mat| {
+        QPProblem::new(mat_p.clone(), q.clone(), mat_g.clone(), h.clone(), mat_a.clone(), b.clone())
+                  .solve(&param).unwrap()
+    };
+
+    let rslt = solve(&vec_q, &vec_h, &vec_b);
+    let sens = qp_sensitivity(&rslt, &mat_p, &vec_q, &mat_g, &vec_h, &mat_a, &vec_b).unwrap();
+    println!("x = {}", rslt.x);
+    println!("dx_dq = {}", sens.dx_dq);
+    assert_eq!(sens.active(), &[0]);
+
+    // central differences
+    let delta = 1e-4;
+    let tol = 1e-5;
+
+    for j in 0 .. n {
+        let mut dq = Mat::new_vec(n);
+        dq[(j, 0)] = delta;
+        let fd = (solve(&(&vec_q + &dq), &vec_h, &vec_b).x - solve(&(&vec_q - &dq), &vec_h, &vec_b).x) / (2. * delta);
+        assert!((fd - sens.dx_dq.col(j)).norm_p2() < tol);
+    }
+
+    let dh = Mat::new_vec(2).set_iter(&[
+        1., -0.5
+    ]);
+    let fd = (solve(&vec_q, &(&vec_h + delta * &dh), &vec_b).x - solve(&vec_q, &(&vec_h - delta * &dh), &vec_b).x) / (2. * delta);
+    assert!((fd - sens.dx_dh(&dh).unwrap()).norm_p2() < tol);
+
+    let db = Mat::new_vec(1).set_all(1.);
+    let fd = (solve(&vec_q, &vec_h, &(&vec_b + delta * &db)).x - solve(&vec_q, &vec_h, &(&vec_b - delta * &db)).x) / (2. * delta);
+    assert!((fd - sens.dx_db(&db).unwrap()).norm_p2() < tol);
+
+    assert!(sens.dx_dh(&Mat::new_vec(1)).is_err());
+    assert!(sens.dx_dh(&Mat::new_vec(3)).is_err());
+    assert!(sens.dx_dh(&Mat::new(2, 2)).is_err());
+    assert!(sens.dx_db(&Mat::new_vec(2)).is_err());
+    assert!(sens.dx_db(&Mat::new(1, 2)).is_err());
+
+    assert!(qp_sensitivity(&rslt, &mat_p, &vec_q, &mat_g, &vec_b, &mat_a, &vec_b).is_err());
+}
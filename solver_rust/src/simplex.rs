@@ -0,0 +1,194 @@
+/*!
+Nonnegative least squares and problems over the probability simplex
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+Small QPs which appear often, solved with `PDIPMParam::default()`:
+[`solve_nnls`](fn.solve_nnls.html) and [`solve_simplex_qp`](fn.solve_simplex_qp.html).
+The Euclidean projection onto the probability simplex
+\\( \\Delta = \\lbrace x \\mid x \\succeq 0, {\\bf 1}^T x = 1 \\rbrace \\)
+is computed directly by [`project_simplex`](fn.project_simplex.html) without the solver.
+
+```
+use totsu::prelude::*;
+use totsu::simplex::*;
+
+let y = Mat::new_vec(3).set_iter(&[
+    1., 0.5, -1.
+]);
+let x = project_simplex(&y);
+
+let exp = Mat::new_vec(3).set_iter(&[
+    0.75, 0.25, 0.
+]);
+assert!((&x - exp).norm_p2() < 1e-12);
+```
+*/
+
+use super::prelude::*;
+use super::problem::{check_dim, check_col_vec};
+
+use std::cmp::Ordering;
+
+/// Solves a nonnegative least squares.
+///
+/// The problem is
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize} & \\| A x - b \\|_2^2 \\\\
+/// {\\rm subject \\ to} & x \\succeq 0.
+/// \\end{array}
+/// \\]
+///
+/// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+/// * `mat_a` is \\(A\\).
+/// * `vec_b` is \\(b\\).
+pub fn solve_nnls(mat_a: &Mat, vec_b: &Mat) -> Result<Mat, SolverError>
+{
+    let (k, n) = mat_a.size();
+
+    check_dim("vec_b rows", vec_b.size().0, "mat_a rows", k)?;
+    check_col_vec("vec_b", vec_b)?;
+
+    let prob = QPProblem::new(mat_a.t() * mat_a, -(mat_a.t() * vec_b),
+                              -Mat::new(n, n).set_eye(), Mat::new_vec(n),
+                              Mat::new(0, n), Mat::new_vec(0));
+
+    prob.solve(&PDIPMParam::default())?.converged_x()
+}
+
+/// Solves a QP over the probability simplex.
+///
+/// The problem is
+/// \\[
+/// \\begin{array}{ll}
+/// {\\rm minimize} & {1 \\over 2} x^T P x + q^T x \\\\
+/// {\\rm subject \\ to} & x \\succeq 0 \\\\
+/// & {\\bf 1}^T x = 1.
+/// \\end{array}
+/// \\]
+///
+/// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+/// * `mat_p` is \\(P\\).
+/// * `vec_q` is \\(q\\).
+pub fn solve_simplex_qp(mat_p: &Mat, vec_q: &Mat) -> Result<Mat, SolverError>
+{
+    let (n, _) = mat_p.size();
+
+    let prob = QPProblem::new(mat_p.clone(), vec_q.clone(),
+                              -Mat::new(n, n).set_eye(), Mat::new_vec(n),
+                              Mat::new(1, n).set_all(1.), Mat::new_vec(1).set_all(1.));
+
+    prob.solve(&PDIPMParam::default())?.converged_x()
+}
+
+/// Projects a vector onto the probability simplex.
+///
+/// Returns \\( \\arg\\min_{x \\in \\Delta} \\| x - y \\|_2 \\),
+/// computed in \\( O(n \\log n) \\) by sorting.
+/// * `y` is a column vector \\(y\\).
+pub fn project_simplex(y: &Mat) -> Mat
+{
+    let (n, _) = y.size();
+
+    let mut sorted: Vec<FP> = (0 .. n).map(|r| y[(r, 0)]).collect();
+    sorted.sort_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    // largest rho such that sorted[rho] - (sum of sorted[..= rho] - 1) / (rho + 1) > 0
+    let mut sum = 0.;
+    let mut theta = 0.;
+    for (i, &v) in sorted.iter().enumerate() {
+        sum += v;
+        let t = (sum - 1.) / (i + 1) as FP;
+        if v - t > 0. {
+            theta = t;
+        }
+    }
+
+    y.clone_sz().set_by(|r, _| (y[(r, 0)] - theta).max(0.))
+}
+
+#[test]
+fn test_nnls()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let mat_a = Mat::new(3, 3).set_eye();
+    let vec_b = Mat::new_vec(3).set_iter(&[
+        1., -2., 3.
+    ]);
+    let x = solve_nnls(&mat_a, &vec_b).unwrap();
+    let exp = Mat::new_vec(3).set_iter(&[
+        1., 0., 3.
+    ]);
+    assert!((&x - exp).norm_p2() < 1e-6);
+
+    // random instances against the generic solver, and x >= 0, gradient >= 0, complementarity
+    let mut r = XOR64_INIT;
+    for _ in 0 .. 3 {
+        let mat_a = Mat::new(6, 4).set_by(|_, _| xor64(&mut r) - 0.5);
+        let vec_b = Mat::new_vec(6).set_by(|_, _| xor64(&mut r) - 0.5);
+
+        let x = solve_nnls(&mat_a, &vec_b).unwrap();
+        let grad = mat_a.t() * (&mat_a * &x - &vec_b);
+        println!("x = {}", x);
+
+        let x_qp = PDIPM::new().solve_qp(&PDIPMParam::default(), &mut std::io::sink(),
+                                         &(mat_a.t() * &mat_a), &-(mat_a.t() * &vec_b),
+                                         &-Mat::new(4, 4).set_eye(), &Mat::new_vec(4),
+                                         &Mat::new(0, 4), &Mat::new_vec(0)).unwrap();
+        assert!((&x - x_qp).norm_p2() < 1e-6);
+        for j in 0 .. 4 {
+            assert!(x[(j, 0)] > -1e-6);
+            assert!(grad[(j, 0)] > -1e-6);
+            assert!((x[(j, 0)] * grad[(j, 0)]).abs() < 1e-6);
+        }
+    }
+
+    assert!(solve_nnls(&mat_a, &Mat::new_vec(2)).is_err());
+}
+
+#[test]
+fn test_project_simplex()
+{
+    use super::mat::{xor64, XOR64_INIT};
+
+    let cases: [(&[FP], &[FP]); 4] = [
+        (&[0.5, 0.5], &[0.5, 0.5]),
+        (&[2., 0.], &[1., 0.]),
+        (&[1., 1., 1.], &[1. / 3., 1. / 3., 1. / 3.]),
+        (&[-1., -3., 0.], &[0., 0., 1.]),
+    ];
+    for (y, exp) in cases.iter() {
+        let x = project_simplex(&Mat::new_vec(y.len()).set_iter(*y));
+        println!("x = {}", x);
+        assert!((x - Mat::new_vec(exp.len()).set_iter(*exp)).norm_p2() < 1e-12);
+    }
+
+    // cross-check with the interior-point method
+    let n: usize = 6;
+    let mut r = XOR64_INIT;
+    for _ in 0 .. 5 {
+        let y = Mat::new_vec(n).set_by(|_, _| (xor64(&mut r) - 0.5) * 2.);
+        let x = project_simplex(&y);
+        let x_qp = solve_simplex_qp(&Mat::new(n, n).set_eye(), &-&y).unwrap();
+        println!("x = {}", x);
+        assert!((x.prod(&Mat::new_vec(n).set_all(1.)) - 1.).abs() < 1e-12);
+        assert!((&x - x_qp).norm_p2() < 1e-6);
+    }
+
+    // general P against the generic solver
+    for _ in 0 .. 3 {
+        let sqrt_p = Mat::new(n, n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let mat_p = sqrt_p.t() * &sqrt_p;
+        let vec_q = Mat::new_vec(n).set_by(|_, _| xor64(&mut r) - 0.5);
+        let x = solve_simplex_qp(&mat_p, &vec_q).unwrap();
+        let x_qp = PDIPM::new().solve_qp(&PDIPMParam::default(), &mut std::io::sink(),
+                                         &mat_p, &vec_q,
+                                         &-Mat::new(n, n).set_eye(), &Mat::new_vec(n),
+                                         &Mat::new(1, n).set_all(1.), &Mat::new_vec(1).set_all(1.)).unwrap();
+        assert!((&x - x_qp).norm_p2() < 1e-6);
+    }
+
+    assert_eq!(project_simplex(&Mat::new_vec(0)).size(), (0, 1));
+}
@@ -1,6 +1,9 @@
 //! Second-order cone program
 
 use super::prelude::*;
+use super::problem::{Problem, ProblemDims, SolverResult, SolverError, Sense};
+use super::problem::{check_dim, check_col_vec, check_finite};
+use super::selfdual;
 
 use std::io::Write;
 
@@ -35,80 +38,96 @@ use std::io::Write;
 /// \\]
 /// where \\( \\epsilon_{\\rm bd} > 0 \\) indicates the extent of approximation that excludes \\( c_i^T x + d_i = 0 \\) boundary.
 pub trait SOCP {
+    #[allow(clippy::too_many_arguments)]
     fn solve_socp<L>(&mut self, param: &PDIPMParam, log: &mut L,
                      vec_f: &Mat,
                      mat_g: &[Mat], vec_h: &[Mat], vec_c: &[Mat], scl_d: &[FP],
                      mat_a: &Mat, vec_b: &Mat)
-                     -> Result<Mat, String>
+                     -> Result<Mat, SolverError>
     where L: Write;
 }
 
-fn check_param(vec_f: &Mat,
-               mat_g: &[Mat], vec_h: &[Mat], vec_c: &[Mat], scl_d: &[FP],
-               mat_a: &Mat, vec_b: &Mat)
-               -> Result<(usize, usize, usize), String>
+/// Second-order cone program inputs
+/// 
+/// See [`SOCP`](trait.SOCP.html) for the problem formulation.
+#[derive(Debug, Clone)]
+pub struct SOCPProblem
 {
-        let (n, _) = vec_f.size();
-        let m = mat_g.len();
-        let (p, _) = mat_a.size();
-
-        if n == 0 {return Err("vec_f: 0 rows".into());}
-        // m = 0 means NO inequality constraints
-        // p = 0 means NO equality constraints
-
-        if vec_h.len() != m {return Err(format!("vec_h: length {} must be {}", vec_h.len(), m));}
-        if vec_c.len() != m {return Err(format!("vec_c: length {} must be {}", vec_c.len(), m));}
-        if scl_d.len() != m {return Err(format!("scl_d: length {} must be {}", scl_d.len(), m));}
-
-        if vec_f.size() != (n, 1) {return Err(format!("vec_c: size {:?} must be {:?}", vec_f.size(), (n, 1)));}
-
-        for i in 0 .. m {
-            let (ni, _) = mat_g[i].size();
-            if mat_g[i].size() != (ni, n) {return Err(format!("mat_g[{}]: size {:?} must be {:?}", i, mat_g[i].size(), (ni, n)));}
-            if vec_h[i].size() != (ni, 1) {return Err(format!("vec_h[{}]: size {:?} must be {:?}", i, vec_h[i].size(), (ni, 1)));}
-            if vec_c[i].size() != (n, 1) {return Err(format!("vec_c[{}]: size {:?} must be {:?}", i, vec_c[i].size(), (n, 1)));}
-        }
-
-        if mat_a.size() != (p, n) {return Err(format!("mat_a: size {:?} must be {:?}", mat_a.size(), (p, n)));}
-        if vec_b.size() != (p, 1) {return Err(format!("vec_b: size {:?} must be {:?}", vec_b.size(), (p, 1)));}
-
-        Ok((n, m, p))
+    /// \\(f\\)
+    pub vec_f: Mat,
+    /// \\(G_0, \\ldots, G_{m-1}\\)
+    pub mat_g: Vec<Mat>,
+    /// \\(h_0, \\ldots, h_{m-1}\\)
+    pub vec_h: Vec<Mat>,
+    /// \\(c_0, \\ldots, c_{m-1}\\)
+    pub vec_c: Vec<Mat>,
+    /// \\(d_0, \\ldots, d_{m-1}\\)
+    pub scl_d: Vec<FP>,
+    /// \\(A\\)
+    pub mat_a: Mat,
+    /// \\(b\\)
+    pub vec_b: Mat,
+    /// Sense of the objective, `Sense::Minimize` by default.
+    pub objective_sense: Sense
 }
 
-impl SOCP for PDIPM
+impl SOCPProblem
 {
-    /// Runs the solver with given parameters.
+    /// Makes a problem.
+    pub fn new(vec_f: Mat,
+               mat_g: Vec<Mat>, vec_h: Vec<Mat>, vec_c: Vec<Mat>, scl_d: Vec<FP>,
+               mat_a: Mat, vec_b: Mat) -> SOCPProblem
+    {
+        SOCPProblem {
+            vec_f,
+            mat_g, vec_h, vec_c, scl_d,
+            mat_a, vec_b,
+            objective_sense: Sense::default()
+        }
+    }
+    /// Sets the sense of the objective.
+    pub fn with_sense(mut self, sense: Sense) -> SOCPProblem
+    {
+        self.objective_sense = sense;
+        self
+    }
+
+    /// Runs the solver with given parameters, reusing a solver instance.
     /// 
-    /// Returns `Ok` with optimal \\(x\\) or `Err` with message string.
+    /// Returns `Ok` with [`SolverResult`](../problem/struct.SolverResult.html)
+    /// or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// `lmd` of the result is of the internal constraints \\( {\\| G_i x + h_i \\|_2^2 / s_i} \\le s_i \\).
+    /// * `solver` is a solver instance.
     /// * `param` is solver parameters.
     /// * `log` outputs solver progress.
-    /// * `vec_f` is \\(f\\).
-    /// * `mat_g` is \\(G_0, \\ldots, G_{m-1}\\).
-    /// * `vec_h` is \\(h_0, \\ldots, h_{m-1}\\).
-    /// * `vec_c` is \\(c_0, \\ldots, c_{m-1}\\).
-    /// * `scl_d` is \\(d_0, \\ldots, d_{m-1}\\).
-    /// * `mat_a` is \\(A\\).
-    /// * `vec_b` is \\(b\\).
-    fn solve_socp<L>(&mut self, param: &PDIPMParam, log: &mut L,
-                     vec_f: &Mat,
-                     mat_g: &[Mat], vec_h: &[Mat], vec_c: &[Mat], scl_d: &[FP],
-                     mat_a: &Mat, vec_b: &Mat)
-                     -> Result<Mat, String>
+    pub fn solve_with<L>(&self, solver: &mut PDIPM, param: &PDIPMParam, log: &mut L)
+                         -> Result<SolverResult, SolverError>
     where L: Write
     {
         // ----- parameter check
 
-        let (n, m, p) = check_param(vec_f, mat_g, vec_h, vec_c, scl_d, mat_a, vec_b)?;
+        let lap = Timings::start(param);
+        self.validate()?;
+        let validation = Timings::elapsed(lap);
+        let ProblemDims {n, m, p} = self.dims();
+
+        if param.formulation == Formulation::SelfDual {
+            return Ok(selfdual::solve_socp(self, solver, param, log)?.with_validation(validation));
+        }
+
+        let vec_f = &(self.objective_sense.sign() * &self.vec_f);
+        let (mat_g, vec_h, vec_c, scl_d) = (&self.mat_g, &self.vec_h, &self.vec_c, &self.scl_d);
+        let (mat_a, vec_b) = (&self.mat_a, &self.vec_b);
 
         let eps_div0 = param.eps;
         let eps_bd = param.eps;
 
         // ----- start to solve
 
-        let rslt = self.solve(param, log,
+        let rslt = solver.solve(param, log,
             n + m, m + m, p + m, // '+ m' is for slack variables
             |_, df_o| {
-                df_o.rows_mut(0 .. n).assign(&vec_f);
+                df_o.rows_mut(0 .. n).assign(vec_f);
                 // for slack variables
                 df_o.rows_mut(n .. n + m).assign_all(0.);
             },
@@ -227,9 +246,157 @@ impl SOCP for PDIPM
             }
         );
 
-        match rslt {
-            Ok(y) => Ok(y.rows(0 .. n).clone_sz()),
-            Err(s) => Err(s.into())
+        rslt?;
+
+        let rslt = SolverResult::from_solver(solver, self.dims(), (n + m, m + m),
+                                             self.objective_sense, |x| self.vec_f.prod(x));
+        // inequalities in the internal form ||G x + h||^2 / s - s with s = c^T x + d
+        let rslt = rslt.with_gap(|x| {
+            let f_i = Mat::new_vec(m).set_by(|r, _| {
+                let s = (vec_c[r].prod(x) + scl_d[r]).max(eps_div0);
+                (&mat_g[r] * x + &vec_h[r]).norm_p2sq() / s - s
+            });
+            (f_i, mat_a * x - vec_b)
+        });
+
+        Ok(rslt.with_validation(validation))
+    }
+}
+
+impl Problem for SOCPProblem
+{
+    fn dims(&self) -> ProblemDims
+    {
+        ProblemDims {
+            n: self.vec_f.size().0,
+            m: self.mat_g.len(),
+            p: self.mat_a.size().0
+        }
+    }
+
+    fn validate(&self) -> Result<(), SolverError>
+    {
+        let ProblemDims {n, m, p} = self.dims();
+        let vec_f = &self.vec_f;
+        let (mat_g, vec_h, vec_c, scl_d) = (&self.mat_g, &self.vec_h, &self.vec_c, &self.scl_d);
+        let (mat_a, vec_b) = (&self.mat_a, &self.vec_b);
+
+        if n == 0 {return Err(SolverError::InvalidProblem("vec_f: 0 rows".into()));}
+        // m = 0 means NO inequality constraints
+        // p = 0 means NO equality constraints
+
+        check_col_vec("vec_f", vec_f)?;
+
+        check_dim("vec_h length", vec_h.len(), "mat_g length", m)?;
+        check_dim("vec_c length", vec_c.len(), "mat_g length", m)?;
+        check_dim("scl_d length", scl_d.len(), "mat_g length", m)?;
+
+        for i in 0 .. m {
+            check_dim(&format!("mat_g[{}] columns", i), mat_g[i].size().1, "vec_f rows", n)?;
+            check_dim(&format!("vec_h[{}] rows", i), vec_h[i].size().0, &format!("mat_g[{}] rows", i), mat_g[i].size().0)?;
+            check_col_vec(&format!("vec_h[{}]", i), &vec_h[i])?;
+            check_dim(&format!("vec_c[{}] rows", i), vec_c[i].size().0, "vec_f rows", n)?;
+            check_col_vec(&format!("vec_c[{}]", i), &vec_c[i])?;
+        }
+
+        check_dim("mat_a columns", mat_a.size().1, "vec_f rows", n)?;
+        check_dim("vec_b rows", vec_b.size().0, "mat_a rows", p)?;
+        check_col_vec("vec_b", vec_b)?;
+
+        check_finite("vec_f", vec_f)?;
+        for i in 0 .. m {
+            check_finite(&format!("mat_g[{}]", i), &mat_g[i])?;
+            check_finite(&format!("vec_h[{}]", i), &vec_h[i])?;
+            check_finite(&format!("vec_c[{}]", i), &vec_c[i])?;
+            if !scl_d[i].is_finite() {
+                return Err(SolverError::InvalidProblem(format!("scl_d[{}] = {} is not finite", i, scl_d[i])));
+            }
+        }
+        check_finite("mat_a", mat_a)?;
+        check_finite("vec_b", vec_b)?;
+
+        Ok(())
+    }
+
+    fn solve(&self, param: &PDIPMParam) -> Result<SolverResult, SolverError>
+    {
+        self.solve_with(&mut PDIPM::new(), param, &mut std::io::sink())
+    }
+}
+
+impl SOCP for PDIPM
+{
+    /// Runs the solver with given parameters.
+    /// 
+    /// Returns `Ok` with optimal \\(x\\) or `Err` with [`SolverError`](../problem/enum.SolverError.html).
+    /// * `param` is solver parameters.
+    /// * `log` outputs solver progress.
+    /// * `vec_f` is \\(f\\).
+    /// * `mat_g` is \\(G_0, \\ldots, G_{m-1}\\).
+    /// * `vec_h` is \\(h_0, \\ldots, h_{m-1}\\).
+    /// * `vec_c` is \\(c_0, \\ldots, c_{m-1}\\).
+    /// * `scl_d` is \\(d_0, \\ldots, d_{m-1}\\).
+    /// * `mat_a` is \\(A\\).
+    /// * `vec_b` is \\(b\\).
+    fn solve_socp<L>(&mut self, param: &PDIPMParam, log: &mut L,
+                     vec_f: &Mat,
+                     mat_g: &[Mat], vec_h: &[Mat], vec_c: &[Mat], scl_d: &[FP],
+                     mat_a: &Mat, vec_b: &Mat)
+                     -> Result<Mat, SolverError>
+    where L: Write
+    {
+        let prob = SOCPProblem::new(vec_f.clone(),
+                                    mat_g.to_vec(), vec_h.to_vec(), vec_c.to_vec(), scl_d.to_vec(),
+                                    mat_a.clone(), vec_b.clone());
+
+        prob.solve_with(self, param, log).and_then(|rslt| rslt.converged_x()).map_err(|e| self.diagnose(e))
+    }
+}
+
+#[test]
+fn test_socp_validate()
+{
+    let n: usize = 2;
+    let ok = SOCPProblem::new(Mat::new_vec(n),
+                              vec![Mat::new(3, n), Mat::new(1, n)],
+                              vec![Mat::new_vec(3), Mat::new_vec(1)],
+                              vec![Mat::new_vec(n), Mat::new_vec(n)],
+                              vec![0., 0.],
+                              Mat::new(1, n), Mat::new_vec(1));
+    assert_eq!(ok.validate(), Ok(()));
+
+    let with = |f: &dyn Fn(&mut SOCPProblem)| {
+        let mut prob = ok.clone();
+        f(&mut prob);
+        prob
+    };
+
+    let cases = [
+        (with(&|p| p.vec_f = Mat::new(n, 2)), ["vec_f columns = 2", "column vector"]),
+        (with(&|p| {p.vec_h.pop();}), ["vec_h length = 1", "mat_g length = 2"]),
+        (with(&|p| {p.vec_c.pop();}), ["vec_c length = 1", "mat_g length = 2"]),
+        (with(&|p| {p.scl_d.pop();}), ["scl_d length = 1", "mat_g length = 2"]),
+        (with(&|p| p.mat_g[1] = Mat::new(1, 3)), ["mat_g[1] columns = 3", "vec_f rows = 2"]),
+        (with(&|p| p.vec_h[0] = Mat::new_vec(2)), ["vec_h[0] rows = 2", "mat_g[0] rows = 3"]),
+        (with(&|p| p.vec_h[1] = Mat::new(1, 2)), ["vec_h[1] columns = 2", "column vector"]),
+        (with(&|p| p.vec_c[1] = Mat::new_vec(3)), ["vec_c[1] rows = 3", "vec_f rows = 2"]),
+        (with(&|p| p.vec_c[0] = Mat::new(n, 2)), ["vec_c[0] columns = 2", "column vector"]),
+        (with(&|p| p.mat_a = Mat::new(1, 3)), ["mat_a columns = 3", "vec_f rows = 2"]),
+        (with(&|p| p.vec_b = Mat::new_vec(2)), ["vec_b rows = 2", "mat_a rows = 1"]),
+        (with(&|p| p.vec_b = Mat::new(1, 2)), ["vec_b columns = 2", "column vector"]),
+        (with(&|p| p.vec_h[1][(0, 0)] = FP::INFINITY), ["vec_h[1][(0, 0)] = inf", "not finite"]),
+        (with(&|p| p.scl_d[0] = FP::NAN), ["scl_d[0] = NaN", "not finite"]),
+    ];
+
+    for (prob, msgs) in cases.iter() {
+        match prob.validate() {
+            Err(SolverError::InvalidProblem(s)) => {
+                println!("{}", s);
+                for msg in msgs {
+                    assert!(s.contains(msg));
+                }
+            },
+            r => panic!("{:?}", r)
         }
     }
 }
@@ -0,0 +1,309 @@
+/*!
+Problem statistics
+
+[`QPProblem::analyze`](../qp/struct.QPProblem.html#method.analyze) and
+[`LPProblem::analyze`](../lp/struct.LPProblem.html#method.analyze)
+summarize problem inputs before solving, and detect common modeling issues as [`StatsWarning`](enum.StatsWarning.html).
+*/
+
+use super::mat::{Mat, MatGen, View, FP};
+use super::matsvd::MatSVD;
+use super::problem::ProblemDims;
+
+use std::fmt;
+
+/// Ratio of the max to min absolute nonzero over which a matrix is warned.
+pub const DYNAMIC_RANGE_WARN: FP = 1e8;
+
+/// Statistics of a matrix
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatStats
+{
+    /// Number of rows and columns.
+    pub size: (usize, usize),
+    /// Number of nonzero elements.
+    pub nnz: usize,
+    /// Ratio of nonzero elements, zero for an empty matrix.
+    pub density: FP,
+    /// Minimum absolute nonzero, `None` if all zero.
+    pub min_abs: Option<FP>,
+    /// Maximum absolute nonzero, `None` if all zero.
+    pub max_abs: Option<FP>
+}
+
+impl MatStats
+{
+    /// Makes statistics of `mat`.
+    pub fn new<V: View>(mat: &MatGen<V>) -> MatStats
+    {
+        let (nrows, ncols) = mat.size();
+        let mut nnz = 0;
+        let mut min_abs: Option<FP> = None;
+        let mut max_abs: Option<FP> = None;
+
+        for c in 0 .. ncols {
+            for r in 0 .. nrows {
+                let v = mat[(r, c)].abs();
+                if v != 0. {
+                    nnz += 1;
+                    min_abs = Some(min_abs.map_or(v, |m| m.min(v)));
+                    max_abs = Some(max_abs.map_or(v, |m| m.max(v)));
+                }
+            }
+        }
+
+        let len = nrows * ncols;
+
+        MatStats {
+            size: (nrows, ncols),
+            nnz,
+            density: if len > 0 {nnz as FP / len as FP} else {0.},
+            min_abs,
+            max_abs
+        }
+    }
+    /// Returns the ratio of the max to min absolute nonzero, `None` if all zero.
+    pub fn dynamic_range(&self) -> Option<FP>
+    {
+        match (self.min_abs, self.max_abs) {
+            (Some(min), Some(max)) => Some(max / min),
+            _ => None
+        }
+    }
+}
+
+/// Warning of problem inputs
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsWarning
+{
+    /// A constraint row is all zero.
+    EmptyRow {
+        /// Name of the matrix.
+        matrix: &'static str,
+        /// Index of the row.
+        row: usize
+    },
+    /// Absolute nonzeros of a matrix spread over [`DYNAMIC_RANGE_WARN`](constant.DYNAMIC_RANGE_WARN.html).
+    DynamicRange {
+        /// Name of the matrix.
+        matrix: &'static str,
+        /// Ratio of the max to min absolute nonzero.
+        ratio: FP
+    },
+    /// \\(P\\) is not symmetric.
+    AsymmetricP {
+        /// Maximum absolute difference \\( | P_{ij} - P_{ji} | \\).
+        max_diff: FP
+    }
+}
+
+impl fmt::Display for StatsWarning
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        match self {
+            StatsWarning::EmptyRow {matrix, row} => write!(f, "{} row {} is empty", matrix, row),
+            StatsWarning::DynamicRange {matrix, ratio} => write!(f, "{} dynamic range {:.1e} is huge", matrix, ratio),
+            StatsWarning::AsymmetricP {max_diff} => write!(f, "mat_p is asymmetric by {:.1e}", max_diff)
+        }
+    }
+}
+
+/// Statistics of a problem
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProblemStats
+{
+    /// Dimensions.
+    pub dims: ProblemDims,
+    /// Statistics of each input matrix with its name.
+    pub mats: Vec<(&'static str, MatStats)>,
+    /// Condition number of \\(P\\) estimated by its singular values, `None` for LP or if not estimated.
+    pub cond_p: Option<FP>,
+    /// Number of inequality constraints which bound a single variable.
+    pub n_bounds: usize,
+    /// Minimum width between lower and upper bounds of a variable,
+    /// `None` if no variable has both; negative means infeasible bounds.
+    pub min_bound_width: Option<FP>,
+    /// Detected issues.
+    pub warnings: Vec<StatsWarning>
+}
+
+impl ProblemStats
+{
+    // used by solver modules:
+    // `mat_p` is None for LP, and its condition number is estimated if `with_cond`
+    pub(crate) fn new(dims: ProblemDims, mat_p: Option<&Mat>, mats: Vec<(&'static str, &Mat)>,
+                      (mat_g, vec_h): (&Mat, &Mat), mat_a: &Mat, with_cond: bool) -> ProblemStats
+    {
+        let ProblemDims {n, m, p} = dims;
+        let mut warnings = Vec::new();
+
+        // ----- matrices
+
+        let mats: Vec<(&'static str, MatStats)> = mats.into_iter().map(|(name, mat)| (name, MatStats::new(mat))).collect();
+
+        for (name, stats) in &mats {
+            if let Some(ratio) = stats.dynamic_range() {
+                if ratio > DYNAMIC_RANGE_WARN {
+                    warnings.push(StatsWarning::DynamicRange {matrix: name, ratio});
+                }
+            }
+        }
+
+        for (name, mat, rows) in [("mat_g", mat_g, m), ("mat_a", mat_a, p)].iter() {
+            for r in 0 .. *rows {
+                if (0 .. n).all(|c| mat[(r, c)] == 0.) {
+                    warnings.push(StatsWarning::EmptyRow {matrix: name, row: r});
+                }
+            }
+        }
+
+        // ----- P
+
+        let mut cond_p = None;
+        if let Some(mat_p) = mat_p {
+            let mut max_diff: FP = 0.;
+            for c in 0 .. n {
+                for r in 0 .. c {
+                    max_diff = max_diff.max((mat_p[(r, c)] - mat_p[(c, r)]).abs());
+                }
+            }
+            if max_diff > 0. {
+                warnings.push(StatsWarning::AsymmetricP {max_diff});
+            }
+
+            if with_cond && (n > 0) {
+                let mut svd = MatSVD::new(mat_p.size());
+                svd.decomp(mat_p);
+                let s = svd.s();
+                let (s_min, s_max) = (s.min().unwrap_or(0.), s.max().unwrap_or(0.));
+                cond_p = Some(if s_min > 0. {s_max / s_min} else {FP::INFINITY});
+            }
+        }
+
+        // ----- bounds
+
+        let mut lower: Vec<Option<FP>> = vec![None; n];
+        let mut upper: Vec<Option<FP>> = vec![None; n];
+        let mut n_bounds = 0;
+        for r in 0 .. m {
+            let mut nz = (0 .. n).filter(|&c| mat_g[(r, c)] != 0.);
+            if let (Some(c), None) = (nz.next(), nz.next()) {
+                n_bounds += 1;
+                let g = mat_g[(r, c)];
+                let v = vec_h[(r, 0)] / g;
+                if g > 0. {
+                    upper[c] = Some(upper[c].map_or(v, |u| u.min(v)));
+                }
+                else {
+                    lower[c] = Some(lower[c].map_or(v, |l| l.max(v)));
+                }
+            }
+        }
+        let min_bound_width = lower.iter().zip(&upper)
+                                   .filter_map(|(l, u)| Some((*u)? - (*l)?))
+                                   .fold(None, |acc: Option<FP>, w| Some(acc.map_or(w, |a| a.min(w))));
+
+        ProblemStats {
+            dims,
+            mats,
+            cond_p,
+            n_bounds,
+            min_bound_width,
+            warnings
+        }
+    }
+}
+
+impl fmt::Display for ProblemStats
+{
+    /// One-line summary.
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        let ProblemDims {n, m, p} = self.dims;
+        let nnz: usize = self.mats.iter().map(|(_, s)| s.nnz).sum();
+        let min = self.mats.iter().filter_map(|(_, s)| s.min_abs).fold(FP::INFINITY, FP::min);
+        let max = self.mats.iter().filter_map(|(_, s)| s.max_abs).fold(0., FP::max);
+
+        write!(f, "n = {}, m = {}, p = {}, nnz = {}, |nonzero| in [{:.1e}, {:.1e}]", n, m, p, nnz, min, max)?;
+        if let Some(cond) = self.cond_p {
+            write!(f, ", cond(P) = {:.1e}", cond)?;
+        }
+        write!(f, ", {} bounds, {} warnings", self.n_bounds, self.warnings.len())
+    }
+}
+
+#[test]
+fn test_problem_stats()
+{
+    use super::prelude::*;
+
+    let n: usize = 3;
+
+    let mat_p = Mat::new(n, n).set_iter(&[
+        2., 0., 0.,
+        0., 1., 0.,
+        0., 0., 4.
+    ]);
+    // the third column is planted with a huge range
+    let mat_g = Mat::new(4, n).set_iter(&[
+        1., 0., 0.,
+        -1., 0., 0.,
+        0., 0., 0.,
+        1., 1., 1e12
+    ]);
+    let vec_h = Mat::new_vec(4).set_iter(&[
+        3., 1., 0., 1.
+    ]);
+
+    let qp = QPProblem::new(mat_p.clone(), Mat::new_vec(n),
+                            mat_g.clone(), vec_h.clone(),
+                            Mat::new(1, n).set_all(1.), Mat::new_vec(1));
+    let stats = qp.analyze();
+    println!("{}", stats);
+    for w in &stats.warnings {
+        println!("{}", w);
+    }
+
+    assert_eq!(stats.dims, ProblemDims {n: 3, m: 4, p: 1});
+    assert!(stats.warnings.contains(&StatsWarning::EmptyRow {matrix: "mat_g", row: 2}));
+    assert!(stats.warnings.iter().any(|w| matches!(w, StatsWarning::DynamicRange {matrix: "mat_g", ratio} if *ratio >= 1e12)));
+    assert_eq!(stats.warnings.len(), 2);
+    assert!((stats.cond_p.unwrap() - 4.).abs() < 1e-9);
+    assert_eq!(stats.n_bounds, 2);
+    assert_eq!(stats.min_bound_width, Some(4.));
+
+    // logged only if enabled
+    let log_of = |log_stats| {
+        let mut log = Vec::new();
+        let param = PDIPMParam {
+            log_stats,
+            .. Default::default()
+        };
+        let _ = qp.solve_with(&mut PDIPM::new(), &param, &mut log);
+        String::from_utf8(log).unwrap()
+    };
+    assert!(log_of(true).contains("problem: n = 3, m = 4, p = 1"));
+    assert!(!log_of(false).contains("problem:"));
+
+    let g_stats = &stats.mats.iter().find(|(name, _)| *name == "mat_g").unwrap().1;
+    assert_eq!(g_stats.nnz, 5);
+    assert_eq!(g_stats.min_abs, Some(1.));
+
+    // asymmetric P and a well-scaled LP
+    let mut mat_p2 = mat_p;
+    mat_p2[(0, 1)] = 0.5;
+    let qp = QPProblem::new(mat_p2, Mat::new_vec(n),
+                            Mat::new(0, n), Mat::new_vec(0),
+                            Mat::new(0, n), Mat::new_vec(0));
+    assert_eq!(qp.analyze().warnings, vec![StatsWarning::AsymmetricP {max_diff: 0.5}]);
+
+    let lp = LPProblem::new(Mat::new_vec(n).set_all(1.),
+                            -Mat::new(n, n).set_eye(), Mat::new_vec(n),
+                            Mat::new(0, n), Mat::new_vec(0));
+    let stats = lp.analyze();
+    assert!(stats.warnings.is_empty());
+    assert_eq!(stats.cond_p, None);
+    assert_eq!(stats.n_bounds, 3);
+    assert_eq!(stats.min_bound_width, None);
+}
@@ -0,0 +1,509 @@
+/*!
+Solution verifier
+
+<script src='https://cdnjs.cloudflare.com/ajax/libs/mathjax/2.7.4/MathJax.js?config=TeX-MML-AM_CHTML' async></script>
+
+These functions check a solution independently of the solver,
+for example after loading a cached solution.
+Each returns a [`FeasibilityReport`](struct.FeasibilityReport.html),
+or a [`ViolationReport`](struct.ViolationReport.html) for a per-constraint breakdown.
+*/
+
+use super::mat::{Mat, FP, KahanSum};
+use super::matsvd::MatSVD;
+use super::problem::SolverResult;
+
+use std::fmt;
+
+/// Feasibility and optimality report of a solution
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeasibilityReport
+{
+    /// Minimum slack of the inequality constraints; negative means violation.
+    /// * LP and QP: \\( \\min_i (h - G x)_i \\)
+    /// * SOCP: \\( \\min_i (c_i^T x + d_i - \\| G_i x + h_i \\|_2) \\)
+    /// * SDP: minimum eigenvalue of the slack matrix \\( -(\\sum_i x_i F_i + F_n) \\)
+    ///
+    /// `FP::INFINITY` if there are no inequality constraints.
+    pub ineq_margin: FP,
+    /// Norm of the equality residual \\( \\| A x - b \\|_2 \\).
+    pub eq_residual: FP,
+    /// Norm of the stationarity residual of the Lagrangian, if dual variables are given.
+    pub stationarity: Option<FP>,
+    /// Maximum absolute complementary slackness \\( \\max_i | \\lambda_i (G x - h)_i | \\), if dual variables are given.
+    pub complementarity: Option<FP>,
+    /// Maximum negative part of dual variables of inequality constraints
+    /// (positive part for `Sense::Maximize`), if dual variables are given.
+    pub dual_infeasibility: Option<FP>,
+    /// Tolerance to judge.
+    pub tol: FP
+}
+
+impl FeasibilityReport
+{
+    /// Returns the worst inequality violation, zero if none.
+    pub fn ineq_violation(&self) -> FP
+    {
+        (-self.ineq_margin).max(0.)
+    }
+    /// Checks primal feasibility within the tolerance.
+    pub fn is_feasible(&self) -> bool
+    {
+        (self.ineq_violation() <= self.tol) && (self.eq_residual <= self.tol)
+    }
+    /// Checks primal feasibility and, if dual variables are given, optimality conditions within the tolerance.
+    pub fn is_optimal(&self) -> bool
+    {
+        let within = |v: Option<FP>| v.is_none_or(|v| v <= self.tol);
+
+        self.is_feasible()
+        && within(self.stationarity)
+        && within(self.complementarity)
+        && within(self.dual_infeasibility)
+    }
+}
+
+fn h_eq_residual(x: &Mat, mat_a: &Mat, vec_b: &Mat) -> FP
+{
+    if cfg!(feature = "compensated-sum") {
+        let (p, _) = mat_a.size();
+
+        // cancellation between A x and b is compensated as well
+        Mat::new_vec(p).set_by(|r, _| {
+            let mut acc = KahanSum::default();
+            let a = mat_a.row(r);
+            for c in 0 .. a.size().1 {
+                acc.add_prod(a[(0, c)], x[(c, 0)]);
+            }
+            acc.add(-vec_b[(r, 0)]);
+            acc.value()
+        }).norm_p2_accurate()
+    }
+    else {
+        (mat_a * x - vec_b).norm_p2()
+    }
+}
+
+/// Verifies a QP solution.
+///
+/// See [`QP`](../qp/trait.QP.html) for the problem formulation.
+/// * `x` is a solution \\(x\\).
+/// * `mat_p`, `vec_q`, `mat_g`, `vec_h`, `mat_a`, `vec_b` are the problem inputs.
+/// * `tol` is a tolerance to judge.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_qp(x: &Mat,
+                 mat_p: &Mat, vec_q: &Mat,
+                 mat_g: &Mat, vec_h: &Mat,
+                 mat_a: &Mat, vec_b: &Mat,
+                 tol: FP) -> FeasibilityReport
+{
+    let _ = (mat_p, vec_q);
+
+    FeasibilityReport {
+        ineq_margin: (vec_h - mat_g * x).min().unwrap_or(FP::INFINITY),
+        eq_residual: h_eq_residual(x, mat_a, vec_b),
+        stationarity: None,
+        complementarity: None,
+        dual_infeasibility: None,
+        tol
+    }
+}
+
+/// Verifies a QP solution including its dual variables.
+///
+/// See [`QP`](../qp/trait.QP.html) for the problem formulation.
+/// * `rslt` is a solver result having \\(x\\), \\(\\lambda\\) and \\(\\nu\\).
+/// * `mat_p`, `vec_q`, `mat_g`, `vec_h`, `mat_a`, `vec_b` are the problem inputs.
+/// * `tol` is a tolerance to judge.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_qp_kkt(rslt: &SolverResult,
+                     mat_p: &Mat, vec_q: &Mat,
+                     mat_g: &Mat, vec_h: &Mat,
+                     mat_a: &Mat, vec_b: &Mat,
+                     tol: FP) -> FeasibilityReport
+{
+    let (x, lmd, nu) = (&rslt.x, &rslt.lmd, &rslt.nu);
+
+    let mut report = verify_qp(x, mat_p, vec_q, mat_g, vec_h, mat_a, vec_b, tol);
+
+    let r_dual = mat_p * x + vec_q + mat_g.t() * lmd + mat_a.t() * nu;
+    report.stationarity = Some(r_dual.norm_p2_resid());
+
+    let slack = mat_g * x - vec_h;
+    let (m, _) = slack.size();
+    let mut compl: FP = 0.;
+    for i in 0 .. m {
+        compl = compl.max((lmd[(i, 0)] * slack[(i, 0)]).abs());
+    }
+    report.complementarity = Some(compl);
+    // lmd is nonpositive for maximization
+    let lmd_min = (rslt.sense.sign() * lmd).min().unwrap_or(0.);
+    report.dual_infeasibility = Some((-lmd_min).max(0.));
+
+    report
+}
+
+/// Verifies a LP solution.
+///
+/// See [`LP`](../lp/trait.LP.html) for the problem formulation.
+/// * `x` is a solution \\(x\\).
+/// * `vec_c`, `mat_g`, `vec_h`, `mat_a`, `vec_b` are the problem inputs.
+/// * `tol` is a tolerance to judge.
+pub fn verify_lp(x: &Mat,
+                 vec_c: &Mat,
+                 mat_g: &Mat, vec_h: &Mat,
+                 mat_a: &Mat, vec_b: &Mat,
+                 tol: FP) -> FeasibilityReport
+{
+    let (n, _) = vec_c.size();
+
+    verify_qp(x, &Mat::new(n, n), vec_c, mat_g, vec_h, mat_a, vec_b, tol)
+}
+
+/// Verifies a LP solution including its dual variables.
+///
+/// See [`LP`](../lp/trait.LP.html) for the problem formulation.
+/// * `rslt` is a solver result having \\(x\\), \\(\\lambda\\) and \\(\\nu\\).
+/// * `vec_c`, `mat_g`, `vec_h`, `mat_a`, `vec_b` are the problem inputs.
+/// * `tol` is a tolerance to judge.
+pub fn verify_lp_kkt(rslt: &SolverResult,
+                     vec_c: &Mat,
+                     mat_g: &Mat, vec_h: &Mat,
+                     mat_a: &Mat, vec_b: &Mat,
+                     tol: FP) -> FeasibilityReport
+{
+    let (n, _) = vec_c.size();
+
+    verify_qp_kkt(rslt, &Mat::new(n, n), vec_c, mat_g, vec_h, mat_a, vec_b, tol)
+}
+
+/// Verifies a SOCP solution.
+///
+/// See [`SOCP`](../socp/trait.SOCP.html) for the problem formulation.
+/// * `x` is a solution \\(x\\).
+/// * `vec_f`, `mat_g`, `vec_h`, `vec_c`, `scl_d`, `mat_a`, `vec_b` are the problem inputs.
+/// * `tol` is a tolerance to judge.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_socp(x: &Mat,
+                   vec_f: &Mat,
+                   mat_g: &[Mat], vec_h: &[Mat], vec_c: &[Mat], scl_d: &[FP],
+                   mat_a: &Mat, vec_b: &Mat,
+                   tol: FP) -> FeasibilityReport
+{
+    let _ = vec_f;
+
+    let mut margin = FP::INFINITY;
+    for i in 0 .. mat_g.len() {
+        let norm = (&mat_g[i] * x + &vec_h[i]).norm_p2_resid();
+        margin = margin.min(vec_c[i].prod(x) + scl_d[i] - norm);
+    }
+
+    FeasibilityReport {
+        ineq_margin: margin,
+        eq_residual: h_eq_residual(x, mat_a, vec_b),
+        stationarity: None,
+        complementarity: None,
+        dual_infeasibility: None,
+        tol
+    }
+}
+
+/// Verifies a SDP solution.
+///
+/// See [`SDP`](../sdp/trait.SDP.html) for the problem formulation.
+/// * `x` is a solution \\(x\\).
+/// * `vec_c`, `mat_f`, `mat_a`, `vec_b` are the problem inputs.
+/// * `tol` is a tolerance to judge.
+pub fn verify_sdp(x: &Mat,
+                  vec_c: &Mat, mat_f: &[Mat],
+                  mat_a: &Mat, vec_b: &Mat,
+                  tol: FP) -> FeasibilityReport
+{
+    let (n, _) = vec_c.size();
+
+    let mut slack = -&mat_f[n];
+    for i in 0 .. n {
+        slack -= &mat_f[i] * x[(i, 0)];
+    }
+
+    FeasibilityReport {
+        ineq_margin: min_eig_sym(&slack),
+        eq_residual: h_eq_residual(x, mat_a, vec_b),
+        stationarity: None,
+        complementarity: None,
+        dual_infeasibility: None,
+        tol
+    }
+}
+
+/// Per-constraint violation report of a solution
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViolationReport
+{
+    /// Signed constraint values; inequalities \\( (G x - h)_i \\) (or cone residuals for SOCP) first,
+    /// followed by equalities \\( (A x - b)_i \\).
+    /// Positive inequality values and nonzero equality values are violations.
+    pub values: Mat,
+    /// Number of inequality constraints at the head of `values`.
+    pub m: usize,
+    /// Indices into `values` of the worst violators, in descending order of violation.
+    pub worst: Vec<usize>,
+    /// Norm of the inequality violations.
+    pub norm_ineq: FP,
+    /// Norm of the equality violations.
+    pub norm_eq: FP,
+    /// Maximum violation over all constraints.
+    pub max_violation: FP,
+    /// Optional constraint names, corresponding to `values`.
+    pub names: Option<Vec<String>>
+}
+
+impl ViolationReport
+{
+    /// Makes a report from signed constraint values.
+    /// * `values` are inequality values followed by equality values.
+    /// * `m` is the number of inequality constraints.
+    /// * `k` is the maximum number of worst violators to be listed.
+    pub fn new(values: Mat, m: usize, k: usize) -> ViolationReport
+    {
+        let (len, _) = values.size();
+        assert!(m <= len);
+
+        let viol: Vec<FP> = (0 .. len).map(|i| {
+            if i < m {
+                values[(i, 0)].max(0.)
+            }
+            else {
+                values[(i, 0)].abs()
+            }
+        }).collect();
+
+        let mut worst: Vec<usize> = (0 .. len).filter(|&i| viol[i] > 0.).collect();
+        worst.sort_by(|&a, &b| viol[b].partial_cmp(&viol[a]).unwrap_or(std::cmp::Ordering::Equal));
+        worst.truncate(k);
+
+        let norm = |v: &[FP]| v.iter().map(|e| e * e).sum::<FP>().sqrt();
+
+        ViolationReport {
+            norm_ineq: norm(&viol[.. m]),
+            norm_eq: norm(&viol[m ..]),
+            max_violation: viol.iter().fold(0., |a: FP, &e| a.max(e)),
+            values,
+            m,
+            worst,
+            names: None
+        }
+    }
+    /// Attaches constraint names, inequalities first and then equalities.
+    pub fn with_names(mut self, names: Vec<String>) -> ViolationReport
+    {
+        assert_eq!(names.len(), self.values.size().0);
+
+        self.names = Some(names);
+        self
+    }
+    /// Returns the violation of `i`-th constraint, zero if satisfied.
+    pub fn violation(&self, i: usize) -> FP
+    {
+        if i < self.m {
+            self.values[(i, 0)].max(0.)
+        }
+        else {
+            self.values[(i, 0)].abs()
+        }
+    }
+    /// Returns the name of `i`-th constraint, or its index if unnamed.
+    pub fn label(&self, i: usize) -> String
+    {
+        match &self.names {
+            Some(names) => names[i].clone(),
+            None if i < self.m => format!("ineq[{}]", i),
+            None => format!("eq[{}]", i - self.m)
+        }
+    }
+}
+
+impl fmt::Display for ViolationReport
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error>
+    {
+        writeln!(f, "{:>16} {:>12} {:>12}", "constraint", "value", "violation")?;
+        for &i in &self.worst {
+            writeln!(f, "{:>16} {:>12.3e} {:>12.3e}", self.label(i), self.values[(i, 0)], self.violation(i))?;
+        }
+        writeln!(f, "norm_ineq: {:.3e}, norm_eq: {:.3e}, max: {:.3e}", self.norm_ineq, self.norm_eq, self.max_violation)
+    }
+}
+
+/// Reports constraint violations of a LP or QP solution.
+///
+/// * `x` is a solution \\(x\\), such as `SolverResult::x`.
+/// * `mat_g`, `vec_h`, `mat_a`, `vec_b` are the problem inputs.
+/// * `k` is the maximum number of worst violators to be listed.
+pub fn violation_qp(x: &Mat,
+                    mat_g: &Mat, vec_h: &Mat,
+                    mat_a: &Mat, vec_b: &Mat,
+                    k: usize) -> ViolationReport
+{
+    let (m, _) = mat_g.size();
+    let (p, _) = mat_a.size();
+
+    let mut values = Mat::new_vec(m + p);
+    values.rows_mut(0 .. m).assign(&(mat_g * x - vec_h));
+    values.rows_mut(m .. m + p).assign(&(mat_a * x - vec_b));
+
+    ViolationReport::new(values, m, k)
+}
+
+/// Reports constraint violations of a SOCP solution.
+///
+/// Inequality values are \\( \\| G_i x + h_i \\|_2 - c_i^T x - d_i \\) for each cone.
+/// * `x` is a solution \\(x\\), such as `SolverResult::x`.
+/// * `mat_g`, `vec_h`, `vec_c`, `scl_d`, `mat_a`, `vec_b` are the problem inputs.
+/// * `k` is the maximum number of worst violators to be listed.
+#[allow(clippy::too_many_arguments)]
+pub fn violation_socp(x: &Mat,
+                      mat_g: &[Mat], vec_h: &[Mat], vec_c: &[Mat], scl_d: &[FP],
+                      mat_a: &Mat, vec_b: &Mat,
+                      k: usize) -> ViolationReport
+{
+    let m = mat_g.len();
+    let (p, _) = mat_a.size();
+
+    let mut values = Mat::new_vec(m + p);
+    for i in 0 .. m {
+        values[(i, 0)] = (&mat_g[i] * x + &vec_h[i]).norm_p2_resid() - vec_c[i].prod(x) - scl_d[i];
+    }
+    values.rows_mut(m .. m + p).assign(&(mat_a * x - vec_b));
+
+    ViolationReport::new(values, m, k)
+}
+
+// minimum eigenvalue of a symmetric matrix
+fn min_eig_sym(mat: &Mat) -> FP
+{
+    let (k, _) = mat.size();
+    if k == 0 {
+        return FP::INFINITY;
+    }
+
+    // shift by Frobenius norm, which bounds the spectral radius,
+    // so that singular values equal shifted eigenvalues
+    let shift = mat.norm_p2();
+    let mut shifted = mat.clone();
+    shifted.add_diag(shift);
+
+    let mut svd = MatSVD::new((k, k));
+    svd.decomp(&shifted);
+
+    svd.s().min().unwrap() - shift
+}
+
+#[test]
+fn test_verify_qp()
+{
+    let mat_p = Mat::new(2, 2).set_eye();
+    let vec_q = Mat::new_vec(2);
+    let mat_g = Mat::new(1, 2).set_iter(&[
+        1., 1.
+    ]);
+    let vec_h = Mat::new_vec(1).set_all(1.);
+    let mat_a = Mat::new(0, 2);
+    let vec_b = Mat::new_vec(0);
+
+    let x = Mat::new_vec(2).set_iter(&[
+        0.5, 0.5
+    ]);
+    let rslt = verify_qp(&x, &mat_p, &vec_q, &mat_g, &vec_h, &mat_a, &vec_b, 1e-9);
+    assert!(rslt.is_feasible());
+    assert_eq!(rslt.ineq_violation(), 0.);
+
+    let x = Mat::new_vec(2).set_iter(&[
+        1., 0.5
+    ]);
+    let rslt = verify_qp(&x, &mat_p, &vec_q, &mat_g, &vec_h, &mat_a, &vec_b, 1e-9);
+    assert!(!rslt.is_feasible());
+    assert!((rslt.ineq_violation() - 0.5).abs() < 1e-12);
+
+    // 1e8 x0 + 1e-8 (x1 + ... + x1000) - 1e8 x1001 = 1e-5 is satisfied by ones
+    let n = 1002;
+    let mat_a = Mat::new(1, n).set_by(|_, c| if c == 0 {1e8} else if c == n - 1 {-1e8} else {1e-8});
+    let vec_b = Mat::new_vec(1).set_all(mat_a.t().prod_compensated(&Mat::new_vec(n).set_all(1.)));
+    let x = Mat::new_vec(n).set_all(1.);
+    let rslt = verify_qp(&x, &Mat::new(n, n), &Mat::new_vec(n), &Mat::new(0, n), &Mat::new_vec(0), &mat_a, &vec_b, 1e-9);
+    println!("eq_residual = {:e}", rslt.eq_residual);
+    if cfg!(feature = "compensated-sum") {
+        assert_eq!(rslt.eq_residual, 0.);
+    }
+    else {
+        assert!(rslt.eq_residual > 1e-6);
+    }
+}
+
+#[test]
+fn test_min_eig_sym()
+{
+    let mat = Mat::new(3, 3).set_iter(&[
+        2., 0., 0.,
+        0., -3., 0.,
+        0., 0., 1.
+    ]);
+    assert!((min_eig_sym(&mat) - (-3.)).abs() < 1e-9);
+
+    let mat = Mat::new(2, 2).set_iter(&[
+        2., 1.,
+        1., 2.
+    ]);
+    assert!((min_eig_sym(&mat) - 1.).abs() < 1e-9);
+}
+
+#[test]
+fn test_violation_qp()
+{
+    use super::prelude::*;
+
+    let n: usize = 2;
+
+    let mat_p = Mat::new(n, n).set_eye();
+    let vec_q = Mat::new_vec(n).set_iter(&[
+        -2., -2.
+    ]);
+    // x0 <= 0.5, x1 <= 0.5, x0 + x1 <= 0.8
+    let mat_g = Mat::new(3, n).set_iter(&[
+        1., 0.,
+        0., 1.,
+        1., 1.
+    ]);
+    let vec_h = Mat::new_vec(3).set_iter(&[
+        0.5, 0.5, 0.8
+    ]);
+    // x0 - x1 = 0
+    let mat_a = Mat::new(1, n).set_iter(&[
+        1., -1.
+    ]);
+    let vec_b = Mat::new_vec(1);
+
+    let param = PDIPMParam::default();
+    let rslt = QPProblem::new(mat_p, vec_q, mat_g.clone(), vec_h.clone(), mat_a.clone(), vec_b.clone())
+               .solve(&param).unwrap();
+
+    let report = violation_qp(&rslt.x, &mat_g, &vec_h, &mat_a, &vec_b, 3);
+    println!("{}", report);
+    assert!(report.max_violation < param.eps);
+
+    // perturb the solution: x0_max by 0.1, sum_max and balance by 0.2
+    let mut x = rslt.x.clone();
+    x[(0, 0)] += 0.2;
+
+    let report = violation_qp(&x, &mat_g, &vec_h, &mat_a, &vec_b, 2)
+                 .with_names(vec!["x0_max".into(), "x1_max".into(), "sum_max".into(), "balance".into()]);
+    println!("{}", report);
+    assert_eq!(report.worst.len(), 2);
+    assert!(report.worst.iter().all(|&i| i != 1));
+    assert!((report.max_violation - 0.2).abs() < 1e-6);
+    assert!((report.norm_eq - 0.2).abs() < 1e-6);
+
+    let table = format!("{}", report);
+    assert!(table.contains("sum_max") && table.contains("balance"));
+    assert!(!table.contains("x0_max") && !table.contains("x1_max"));
+}
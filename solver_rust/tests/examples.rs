@@ -0,0 +1,42 @@
+//! Runs the worked examples in `examples/` as integration tests.
+
+#[path = "../examples/svm.rs"]
+mod svm;
+#[path = "../examples/portfolio.rs"]
+mod portfolio;
+#[path = "../examples/robust_ls.rs"]
+mod robust_ls;
+#[path = "../examples/maxcut.rs"]
+mod maxcut;
+#[path = "../examples/mpc_loop.rs"]
+mod mpc_loop;
+
+#[test]
+fn example_svm()
+{
+    svm::main();
+}
+
+#[test]
+fn example_portfolio()
+{
+    portfolio::main();
+}
+
+#[test]
+fn example_robust_ls()
+{
+    robust_ls::main();
+}
+
+#[test]
+fn example_maxcut()
+{
+    maxcut::main();
+}
+
+#[test]
+fn example_mpc_loop()
+{
+    mpc_loop::main();
+}